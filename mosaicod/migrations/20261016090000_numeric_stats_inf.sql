@@ -0,0 +1,4 @@
+-- Flag chunks containing infinite values, which are excluded from min/max
+-- numeric stats to avoid defeating range based pruning
+
+ALTER TABLE column_chunk_numeric_t ADD COLUMN has_inf BOOL NOT NULL DEFAULT FALSE;
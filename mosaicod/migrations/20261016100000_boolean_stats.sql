@@ -0,0 +1,20 @@
+-- Boolean columns have their own statistics table, tracking which values
+-- appear in each chunk instead of casting them to numeric min/max values
+
+CREATE TABLE column_chunk_boolean_t(
+  column_id    INTEGER REFERENCES column_t(column_id) NOT NULL,
+  chunk_id     INTEGER NOT NULL, -- Constraint on chunks defined below
+
+  has_true         BOOL NOT NULL,
+  has_false        BOOL NOT NULL,
+  has_null         BOOL NOT NULL,
+
+  PRIMARY KEY (column_id, chunk_id),
+
+  -- This constraint will cause the deletion of all 
+  -- chunks if the related column chunk entry is deleted.
+  CONSTRAINT fk_chunk
+    FOREIGN KEY (chunk_id)
+    REFERENCES chunk_t(chunk_id)
+    ON DELETE CASCADE
+);
@@ -0,0 +1,11 @@
+-- Track the creation time of each chunk, used to report when a topic last received data.
+-- Existing chunks are assumed to be created together with their topic.
+
+ALTER TABLE chunk_t ADD COLUMN creation_unix_tstamp BIGINT;
+
+UPDATE chunk_t
+SET creation_unix_tstamp = topic_t.creation_unix_tstamp
+FROM topic_t
+WHERE chunk_t.topic_id = topic_t.topic_id;
+
+ALTER TABLE chunk_t ALTER COLUMN creation_unix_tstamp SET NOT NULL;
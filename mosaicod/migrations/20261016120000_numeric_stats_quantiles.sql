@@ -0,0 +1,6 @@
+-- Approximate quantiles of the finite values of numeric columns, used to
+-- prune chunks when most of their values fall below a filter threshold.
+-- Chunks created before this migration have no quantiles (NULL).
+
+ALTER TABLE column_chunk_numeric_t ADD COLUMN p50_value DOUBLE PRECISION;
+ALTER TABLE column_chunk_numeric_t ADD COLUMN p95_value DOUBLE PRECISION;
@@ -0,0 +1,6 @@
+-- Track the timestamp bounds of each chunk (in milliseconds, regardless of the topic
+-- timestamp unit) so that time range queries can discard chunks without opening data files.
+-- Bounds of existing chunks are unknown and left NULL, such chunks are never discarded.
+
+ALTER TABLE chunk_t ADD COLUMN min_unix_tstamp BIGINT;
+ALTER TABLE chunk_t ADD COLUMN max_unix_tstamp BIGINT;
@@ -0,0 +1,22 @@
+-- Membership of sequences in layers, a sequence can belong to any number of layers.
+-- Memberships are removed when the related layer or sequence is deleted.
+
+CREATE TABLE layer_sequence_t(
+  layer_id    INTEGER NOT NULL,
+  sequence_id INTEGER NOT NULL,
+
+  PRIMARY KEY (layer_id, sequence_id),
+
+  CONSTRAINT fk_layer
+    FOREIGN KEY (layer_id)
+    REFERENCES layer_t(layer_id)
+    ON DELETE CASCADE,
+
+  CONSTRAINT fk_sequence
+    FOREIGN KEY (sequence_id)
+    REFERENCES sequence_t(sequence_id)
+    ON DELETE CASCADE
+);
+
+-- Lookup of the layers of a sequence
+CREATE INDEX layer_sequence_sequence_idx ON layer_sequence_t(sequence_id);
@@ -0,0 +1,8 @@
+-- Data contracts of the ontology tags, the contract document constrains the user metadata
+-- of the topics and the fields of the data uploaded for the tag.
+-- Ontology tags without a contract are not validated.
+
+CREATE TABLE ontology_contract_t(
+  ontology_tag TEXT PRIMARY KEY,
+  contract     JSONB NOT NULL
+);
@@ -0,0 +1,5 @@
+-- CRC32 checksum of the data file of each chunk, computed when the chunk is written and
+-- used to detect corrupted data files on read. Checksums of existing chunks are unknown
+-- and left NULL, such chunks are never verified.
+
+ALTER TABLE chunk_t ADD COLUMN checksum BIGINT;
@@ -0,0 +1,16 @@
+-- Audit log of the operations performed on a sequence outside of its normal lifecycle,
+-- such as re-opening a finalized sequence for corrections.
+
+CREATE TABLE sequence_audit_t(
+  sequence_audit_id    SERIAL PRIMARY KEY,
+  sequence_id          INTEGER NOT NULL,
+  action               TEXT NOT NULL,
+  author               TEXT NOT NULL,
+  reason               TEXT,
+  creation_unix_tstamp BIGINT NOT NULL,
+
+  CONSTRAINT fk_sequence
+    FOREIGN KEY (sequence_id)
+    REFERENCES sequence_t(sequence_id)
+    ON DELETE CASCADE
+);
@@ -0,0 +1,6 @@
+-- Number of null values of numeric and literal columns, used to measure the
+-- completeness of the fields of a topic.
+-- Chunks created before this migration have no null count (NULL).
+
+ALTER TABLE column_chunk_numeric_t ADD COLUMN null_count BIGINT;
+ALTER TABLE column_chunk_literal_t ADD COLUMN null_count BIGINT;
@@ -1,8 +1,9 @@
-use std::collections::VecDeque;
+use std::collections::{BTreeSet, HashMap, VecDeque};
 use std::sync::Arc;
 
-use arrow::array::{ArrayRef, AsArray, RecordBatch, StructArray};
-use arrow::datatypes::{DataType, Field, FieldRef, SchemaRef};
+use arrow::array::{Array, ArrayRef, AsArray, RecordBatch, StructArray, make_array};
+use arrow::buffer::NullBuffer;
+use arrow::datatypes::{DataType, Field, FieldRef, Fields, SchemaRef};
 use arrow::error::ArrowError;
 
 use crate::{params, traits::SquashedIterator, types};
@@ -14,6 +15,11 @@ pub enum SchemaError {
     MissingTimestampInSchema,
     #[error("wrong timestamp field type, expected int64")]
     WrongTimestampType,
+    #[error("timestamp unit `{found}` does not match the topic timestamp unit `{expected}`")]
+    TimestampUnitMismatch {
+        expected: types::TimestampUnit,
+        found: String,
+    },
 }
 
 /// Validates that the provided Arrow schema meets certain structural requirements.
@@ -36,6 +42,28 @@ pub fn check_schema(schema: &SchemaRef) -> Result<(), SchemaError> {
     Ok(())
 }
 
+/// Checks that the timestamp unit declared in the schema metadata (if any) matches the
+/// timestamp unit of the topic the data is written to.
+pub fn check_timestamp_unit(
+    schema: &SchemaRef,
+    unit: types::TimestampUnit,
+) -> Result<(), SchemaError> {
+    let Some(declared) = schema
+        .metadata()
+        .get(params::SCHEMA_METADATA_TIMESTAMP_UNIT)
+    else {
+        return Ok(());
+    };
+
+    match declared.parse::<types::TimestampUnit>() {
+        Ok(declared) if declared == unit => Ok(()),
+        _ => Err(SchemaError::TimestampUnitMismatch {
+            expected: unit,
+            found: declared.clone(),
+        }),
+    }
+}
+
 /// Checks if the given Arrow [`DataType`] is considered numeric
 #[must_use]
 pub fn is_numeric(data_type: &DataType) -> bool {
@@ -104,9 +132,29 @@ pub fn cast_array_to_numeric(array: &ArrayRef) -> Result<ArrayRef, ArrowError> {
 /// For example, given a flattened field name like "user.address.street",
 /// this function will traverse the nested structure in the [`RecordBatch`] to
 /// retrieve the corresponding [`ArrayRef`].
+///
+/// The array is returned as stored: values under a null parent struct may not be null
+/// (see [`logical_array_from_flat_field_name`]).
 pub fn array_from_flat_field_name(
     flattened_field_name: &str,
     batch: &RecordBatch,
+) -> Result<ArrayRef, ArrowError> {
+    nested_array(flattened_field_name, batch, false)
+}
+
+/// Like [`array_from_flat_field_name`], but the nulls of the parent structs are combined
+/// with the nulls of the array, so that the values under a null parent are null.
+pub fn logical_array_from_flat_field_name(
+    flattened_field_name: &str,
+    batch: &RecordBatch,
+) -> Result<ArrayRef, ArrowError> {
+    nested_array(flattened_field_name, batch, true)
+}
+
+fn nested_array(
+    flattened_field_name: &str,
+    batch: &RecordBatch,
+    parent_nulls: bool,
 ) -> Result<ArrayRef, ArrowError> {
     let subfields: Vec<&str> = flattened_field_name.split('.').collect();
     if subfields.is_empty() {
@@ -116,9 +164,12 @@ pub fn array_from_flat_field_name(
     }
 
     let top_level_name = subfields[0];
-    let mut current_array = batch.column_by_name(top_level_name).ok_or_else(|| {
-        ArrowError::SchemaError(format!("can't find top level field `{0}`", top_level_name))
-    })?;
+    let mut current_array = batch
+        .column_by_name(top_level_name)
+        .ok_or_else(|| {
+            ArrowError::SchemaError(format!("can't find top level field `{0}`", top_level_name))
+        })?
+        .clone();
 
     // Iterate and traverse the remaining nested path components
     //
@@ -134,15 +185,23 @@ pub fn array_from_flat_field_name(
                 ))
             })?;
 
-        current_array = struct_array.column_by_name(subfield).ok_or_else(|| {
+        let child = struct_array.column_by_name(subfield).ok_or_else(|| {
             ArrowError::SchemaError(format!(
                 "can't find subfield `{0}` for top level field `{1}`",
                 subfield, top_level_name
             ))
         })?;
+
+        current_array = match struct_array.nulls() {
+            Some(nulls) if parent_nulls && nulls.null_count() > 0 => {
+                let nulls = NullBuffer::union(Some(nulls), child.nulls());
+                make_array(child.to_data().into_builder().nulls(nulls).build()?)
+            }
+            _ => Arc::clone(child),
+        };
     }
 
-    Ok(Arc::clone(current_array))
+    Ok(current_array)
 }
 
 pub struct SchemaFlattenerIter {
@@ -208,10 +267,317 @@ impl SquashedIterator for SchemaRef {
     }
 }
 
+/// Compares the fields of a set of named schemas.
+///
+/// Fields are compared using their full nested path (e.g. `pose.position.x`). For each schema
+/// the fields present in at least one of the other schemas but missing from it are returned,
+/// schemas that are not missing any field are not reported.
+pub fn missing_fields(schemas: &[(String, SchemaRef)]) -> Vec<(String, Vec<String>)> {
+    let paths: Vec<BTreeSet<String>> = schemas
+        .iter()
+        .map(|(_, schema)| schema.squashed_iter().map(|(path, _)| path).collect())
+        .collect();
+
+    let all: BTreeSet<&String> = paths.iter().flatten().collect();
+
+    schemas
+        .iter()
+        .zip(&paths)
+        .filter_map(|((name, _), own)| {
+            let missing: Vec<String> = all
+                .iter()
+                .filter(|path| !own.contains(**path))
+                .map(|path| (*path).clone())
+                .collect();
+            (!missing.is_empty()).then(|| (name.clone(), missing))
+        })
+        .collect()
+}
+
+/// Checks whether data with `schema` can be stored along with data with the `established`
+/// schema and read together with it, returning the first incompatible field (by its full
+/// nested path) and the reason of the incompatibility.
+///
+/// The fields in both schemas must have the same type. The fields present in only one of
+/// the schemas are filled with nulls when the data is read together, so they must be
+/// nullable.
+pub fn schema_evolution_violation(
+    established: &SchemaRef,
+    schema: &SchemaRef,
+) -> Option<(String, String)> {
+    let known: HashMap<String, FieldRef> = established.squashed_iter().collect();
+    let incoming: HashMap<String, FieldRef> = schema.squashed_iter().collect();
+
+    let changed = schema
+        .squashed_iter()
+        .find_map(|(path, field)| match known.get(&path) {
+            Some(expected) if expected.data_type() != field.data_type() => Some((
+                path,
+                format!(
+                    "type changed from `{}` to `{}`",
+                    expected.data_type(),
+                    field.data_type()
+                ),
+            )),
+            None if !field.is_nullable() => Some((
+                path,
+                "new fields must be nullable, the stored data has no values for it".to_owned(),
+            )),
+            _ => None,
+        });
+
+    changed.or_else(|| {
+        established.squashed_iter().find_map(|(path, field)| {
+            (!incoming.contains_key(&path) && !field.is_nullable()).then(|| {
+                (
+                    path,
+                    "the field is not nullable, it can't be omitted".to_owned(),
+                )
+            })
+        })
+    })
+}
+
+/// Returns the name of `data_type` reported to clients.
+///
+/// Nested types are named without their children (e.g. `struct` or `list`). View types
+/// (e.g. the strings read by DataFusion) are named as the types they are stored with.
+pub fn data_type_name(data_type: &DataType) -> String {
+    match data_type {
+        DataType::Utf8View => "utf8".to_owned(),
+        DataType::BinaryView => "binary".to_owned(),
+        DataType::Struct(_) => "struct".to_owned(),
+        DataType::List(_) => "list".to_owned(),
+        DataType::LargeList(_) => "large_list".to_owned(),
+        DataType::FixedSizeList(_, size) => format!("fixed_size_list({size})"),
+        DataType::Map(_, _) => "map".to_owned(),
+        other => other.to_string().to_lowercase(),
+    }
+}
+
+/// Compares `schema` with the fields of a [`types::DeclaredSchema`], returning a violation
+/// for each declared field missing or differing (by type or nullability) and for each field
+/// of `schema` that was not declared. The timestamp column does not need to be declared.
+pub fn declared_schema_violations(
+    schema: &SchemaRef,
+    declared: &[types::DeclaredField],
+) -> Vec<types::ContractViolation> {
+    let schema_fields: HashMap<String, FieldRef> = schema.squashed_iter().collect();
+    let nullable = |nullable: bool| if nullable { "nullable" } else { "not nullable" };
+
+    let mut violations: Vec<types::ContractViolation> = declared
+        .iter()
+        .filter_map(|field| {
+            let Some(found) = schema_fields.get(&field.name) else {
+                return Some(types::ContractViolation::new(&field.name, "is missing"));
+            };
+
+            let data_type = data_type_name(found.data_type());
+            if data_type != field.data_type {
+                return Some(types::ContractViolation::new(
+                    &field.name,
+                    format!("is declared as `{}`, found `{data_type}`", field.data_type),
+                ));
+            }
+
+            (found.is_nullable() != field.nullable).then(|| {
+                types::ContractViolation::new(
+                    &field.name,
+                    format!(
+                        "is declared {}, found {}",
+                        nullable(field.nullable),
+                        nullable(found.is_nullable())
+                    ),
+                )
+            })
+        })
+        .collect();
+
+    let names: BTreeSet<&str> = declared.iter().map(|f| f.name.as_str()).collect();
+    violations.extend(
+        schema
+            .squashed_iter()
+            .filter(|(path, _)| {
+                path != params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP && !names.contains(path.as_str())
+            })
+            .map(|(path, _)| types::ContractViolation::new(&path, "is not declared")),
+    );
+
+    violations
+}
+
+/// Builds the arrow fields described by the fields of a [`types::DeclaredSchema`], preceded
+/// by the timestamp column. Nested fields (e.g. `pose.position.x`) are held by nullable
+/// structs, in order of declaration.
+///
+/// Returns [`None`] if a declared type can't be converted back to an arrow type: only the
+/// names of the primitive types are (see [`data_type_name`]), not the ones of nested types.
+pub fn fields_from_declared_schema(declared: &[types::DeclaredField]) -> Option<Fields> {
+    let leaves = declared
+        .iter()
+        .filter(|field| field.name != params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP)
+        .map(|field| {
+            let data_type = primitive_type_from_name(&field.data_type)?;
+            let path: Vec<&str> = field.name.split('.').collect();
+            Some((path, Field::new("", data_type, field.nullable)))
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let timestamp = Field::new(
+        params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+        DataType::Int64,
+        false,
+    );
+    let mut fields = vec![Arc::new(timestamp)];
+    fields.extend(nest_fields(leaves)?.iter().cloned());
+
+    Some(fields.into())
+}
+
+/// Groups the leaf fields by the first component of their path, recursively, building the
+/// structs holding them. Returns [`None`] if a field is declared both as a leaf and as a
+/// struct (e.g. `pose` and `pose.x`).
+fn nest_fields(leaves: Vec<(Vec<&str>, Field)>) -> Option<Fields> {
+    let mut groups: Vec<(&str, Vec<(Vec<&str>, Field)>)> = Vec::new();
+    for (path, field) in leaves {
+        let (first, rest) = path.split_first()?;
+        let (first, rest) = (*first, rest.to_vec());
+        match groups.iter_mut().find(|(name, _)| *name == first) {
+            Some((_, children)) => children.push((rest, field)),
+            None => groups.push((first, vec![(rest, field)])),
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(name, children)| match children.as_slice() {
+            [(rest, field)] if rest.is_empty() => Some(field.clone().with_name(name)),
+            _ => Some(Field::new(
+                name,
+                DataType::Struct(nest_fields(children)?),
+                true,
+            )),
+        })
+        .collect()
+}
+
+/// Returns the primitive type named `name` by [`data_type_name`]
+fn primitive_type_from_name(name: &str) -> Option<DataType> {
+    [
+        DataType::Boolean,
+        DataType::Int8,
+        DataType::Int16,
+        DataType::Int32,
+        DataType::Int64,
+        DataType::UInt8,
+        DataType::UInt16,
+        DataType::UInt32,
+        DataType::UInt64,
+        DataType::Float16,
+        DataType::Float32,
+        DataType::Float64,
+        DataType::Utf8,
+        DataType::LargeUtf8,
+        DataType::Binary,
+        DataType::LargeBinary,
+        DataType::Date32,
+        DataType::Date64,
+    ]
+    .into_iter()
+    .find(|data_type| data_type_name(data_type) == name)
+}
+
+/// Returns the constraints of the contract `fields` not satisfied by `schema`.
+///
+/// Only the presence and the type of the fields are checked here, null values are checked
+/// on each batch (see [`contract_null_violations`]).
+pub fn contract_violations(
+    schema: &SchemaRef,
+    fields: &[types::ContractField],
+) -> Vec<types::ContractViolation> {
+    let schema_fields: HashMap<String, FieldRef> = schema.squashed_iter().collect();
+
+    fields
+        .iter()
+        .filter_map(|field| match schema_fields.get(&field.name) {
+            None if field.nullable => None,
+            None => Some(types::ContractViolation::new(&field.name, "is required")),
+            Some(f) if !matches_contract_type(f.data_type(), field.field_type) => {
+                Some(types::ContractViolation::new(
+                    &field.name,
+                    format!("must be of type `{}`", field.field_type),
+                ))
+            }
+            Some(_) => None,
+        })
+        .collect()
+}
+
+/// Returns a violation for each non nullable field of the contract holding null values in
+/// `batch`. Fields missing from the batch are ignored.
+pub fn contract_null_violations(
+    batch: &RecordBatch,
+    fields: &[types::ContractField],
+) -> Vec<types::ContractViolation> {
+    fields
+        .iter()
+        .filter(|field| !field.nullable)
+        .filter(|field| {
+            array_from_flat_field_name(&field.name, batch).is_ok_and(|array| array.null_count() > 0)
+        })
+        .map(|field| types::ContractViolation::new(&field.name, "can't hold null values"))
+        .collect()
+}
+
+/// Returns a violation for each numeric field of the contract holding values outside the
+/// allowed range in `batch` (see [`types::ContractField::check_range`]). Null values, also
+/// the ones under a null parent struct, and fields missing from the batch are ignored.
+pub fn contract_range_violations(
+    batch: &RecordBatch,
+    fields: &[types::ContractField],
+) -> Result<Vec<types::ContractViolation>, ArrowError> {
+    let mut violations = Vec::new();
+
+    for field in fields.iter().filter(|field| field.range.is_some()) {
+        let Ok(array) = logical_array_from_flat_field_name(&field.name, batch) else {
+            continue;
+        };
+        if !is_numeric(array.data_type()) {
+            continue;
+        }
+
+        let values = cast_array_to_numeric(&array)?;
+        let violation = values
+            .as_primitive::<arrow::datatypes::Float64Type>()
+            .iter()
+            .flatten()
+            .find_map(|value| field.check_range(value));
+        violations.extend(violation);
+    }
+
+    Ok(violations)
+}
+
+fn matches_contract_type(data_type: &DataType, field_type: types::ContractFieldType) -> bool {
+    use types::ContractFieldType;
+
+    match field_type {
+        ContractFieldType::Boolean => matches!(data_type, DataType::Boolean),
+        ContractFieldType::Integer => data_type.is_integer(),
+        ContractFieldType::Float => data_type.is_floating(),
+        ContractFieldType::Text => matches!(
+            data_type,
+            DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View
+        ),
+    }
+}
+
 pub fn stats_from_arrow_field(field: &Field) -> types::Stats {
-    use types::{NumericStats, Stats, TextStats};
+    use types::{BooleanStats, NumericStats, Stats, TextStats};
 
     match field.data_type() {
+        // Booleans are numeric for casting purposes but have their own stats
+        DataType::Boolean => Stats::Boolean(BooleanStats::new()),
         dt if is_numeric(dt) => Stats::Numeric(NumericStats::new()),
         dt if is_literal(dt) => Stats::Text(TextStats::new()),
         _ => Stats::Unsupported,
@@ -220,7 +586,6 @@ pub fn stats_from_arrow_field(field: &Field) -> types::Stats {
 
 /// Inspects an array and updates the provided statistics using SIMD-optimized Arrow compute kernels.
 pub fn stats_inspect_array(stats: &mut types::Stats, array: &ArrayRef) -> Result<(), ArrowError> {
-    use arrow::array::Array;
     use arrow::compute;
     use types::Stats;
 
@@ -229,18 +594,32 @@ pub fn stats_inspect_array(stats: &mut types::Stats, array: &ArrayRef) -> Result
             let narray = cast_array_to_numeric(array)?;
             let primitive = narray.as_primitive::<arrow::datatypes::Float64Type>();
 
-            // Use SIMD-optimized min/max from Arrow compute
-            let min_val = compute::min(primitive);
-            let max_val = compute::max(primitive);
-
-            // Check for nulls (O(1) - Arrow tracks this in metadata)
-            let has_null = primitive.null_count() > 0;
+            // Count nulls (O(1) - Arrow tracks this in metadata)
+            let null_count = primitive.null_count() as u64;
 
-            // Check for NaN values - Arrow's min/max excludes NaN, so we check separately
-            // This is still efficient: single pass, branch-prediction friendly
+            // Check for non-finite values, this is still efficient: single pass,
+            // branch-prediction friendly
             let has_nan = primitive.values().iter().any(|v| v.is_nan());
+            let has_inf = primitive.values().iter().any(|v| v.is_infinite());
+
+            let (min_val, max_val) = if has_nan || has_inf {
+                // Arrow's min/max consider NaN greater than any other value and do not
+                // exclude infinite values, fall back to a scan over the finite (non-null) values
+                let finite = primitive.iter().flatten().filter(|v| v.is_finite());
+                finite.fold((None, None), |(min, max): (Option<f64>, Option<f64>), v| {
+                    (
+                        Some(min.map_or(v, |m| m.min(v))),
+                        Some(max.map_or(v, |m| m.max(v))),
+                    )
+                })
+            } else {
+                // Use SIMD-optimized min/max from Arrow compute
+                (compute::min(primitive), compute::max(primitive))
+            };
+
+            let finite = primitive.iter().flatten().filter(|v| v.is_finite());
 
-            stats.merge(min_val, max_val, has_null, has_nan);
+            stats.merge(min_val, max_val, null_count, has_nan, has_inf, finite);
         }
         Stats::Text(stats) => {
             let sarray = cast_array_to_literal(array)?;
@@ -250,10 +629,20 @@ pub fn stats_inspect_array(stats: &mut types::Stats, array: &ArrayRef) -> Result
             let min_val = compute::min_string(string_array);
             let max_val = compute::max_string(string_array);
 
-            // Check for nulls (O(1))
-            let has_null = string_array.null_count() > 0;
+            // Count nulls (O(1))
+            let null_count = string_array.null_count() as u64;
+
+            stats.merge(min_val, max_val, null_count);
+        }
+        Stats::Boolean(stats) => {
+            let barray = array.as_boolean();
+
+            let has_null = barray.null_count() > 0;
+            // Aggregates consider non-null values only
+            let has_true = compute::bool_or(barray) == Some(true);
+            let has_false = compute::bool_and(barray) == Some(false);
 
-            stats.merge(min_val, max_val, has_null);
+            stats.merge(has_true, has_false, has_null);
         }
         Stats::Unsupported => { /* do nothing */ }
     }
@@ -267,7 +656,8 @@ pub fn column_stats_inspect_record_batch(
     batch: &RecordBatch,
 ) -> Result<(), ArrowError> {
     for (col_name, stats) in &mut cstats.stats {
-        let array = array_from_flat_field_name(col_name, batch)?;
+        // Values under a null parent struct are counted as nulls
+        let array = logical_array_from_flat_field_name(col_name, batch)?;
         stats_inspect_array(stats, &array)?;
     }
     Ok(())
@@ -287,6 +677,80 @@ pub fn column_stats_from_schema(schema: &SchemaRef) -> types::ColumnsStats {
     cs
 }
 
+/// Serializes `batch` as CSV lines, optionally preceded by the header with the column names.
+///
+/// Structs should be flattened first, the values of the remaining nested columns (e.g. lists)
+/// are written as JSON.
+pub fn record_batch_to_csv(
+    batch: &RecordBatch,
+    delimiter: u8,
+    header: bool,
+) -> Result<Vec<u8>, ArrowError> {
+    let batch = json_encode_nested_columns(batch)?;
+
+    let mut writer = arrow::csv::WriterBuilder::new()
+        .with_delimiter(delimiter)
+        .with_header(header)
+        .build(Vec::new());
+    writer.write(&batch)?;
+    Ok(writer.into_inner())
+}
+
+/// Replaces the nested columns of `batch` with text columns holding the JSON encoding of
+/// their values, null values are kept.
+fn json_encode_nested_columns(batch: &RecordBatch) -> Result<RecordBatch, ArrowError> {
+    let schema = batch.schema();
+    if !schema.fields().iter().any(|f| f.data_type().is_nested()) {
+        return Ok(batch.clone());
+    }
+
+    let mut fields = Vec::with_capacity(schema.fields().len());
+    let mut columns = Vec::with_capacity(schema.fields().len());
+    for (field, column) in schema.fields().iter().zip(batch.columns()) {
+        if field.data_type().is_nested() {
+            fields.push(Arc::new(Field::new(field.name(), DataType::Utf8, true)));
+            columns.push(json_encode_column(column)?);
+        } else {
+            fields.push(field.clone());
+            columns.push(column.clone());
+        }
+    }
+
+    RecordBatch::try_new(Arc::new(arrow::datatypes::Schema::new(fields)), columns)
+}
+
+fn json_encode_column(column: &ArrayRef) -> Result<ArrayRef, ArrowError> {
+    if column.is_empty() {
+        return Ok(arrow::array::new_empty_array(&DataType::Utf8));
+    }
+
+    // Values are encoded by the JSON writer as the `value` key of one object per row
+    let field = Field::new("value", column.data_type().clone(), true);
+    let batch = RecordBatch::try_new(
+        Arc::new(arrow::datatypes::Schema::new(vec![field])),
+        vec![column.clone()],
+    )?;
+
+    let mut writer = arrow::json::ArrayWriter::new(Vec::new());
+    writer.write(&batch)?;
+    writer.finish()?;
+
+    let rows: Vec<serde_json::Map<String, serde_json::Value>> =
+        serde_json::from_slice(&writer.into_inner())
+            .map_err(|e| ArrowError::JsonError(e.to_string()))?;
+
+    let values: arrow::array::StringArray = rows
+        .iter()
+        .map(|row| {
+            row.get("value")
+                .filter(|value| !value.is_null())
+                .map(ToString::to_string)
+        })
+        .collect();
+
+    Ok(Arc::new(values))
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -318,6 +782,28 @@ mod tests {
         );
     }
 
+    /// The timestamp unit is validated only if declared in the schema metadata.
+    #[test]
+    fn schema_timestamp_unit() {
+        let fields = vec![Field::new("timestamp_ns", DataType::Int64, false)];
+        let schema = create_schema(fields.clone());
+        assert!(check_timestamp_unit(&schema, types::TimestampUnit::Nanoseconds).is_ok());
+
+        let with_unit = |unit: &str| {
+            Arc::new(Schema::new_with_metadata(
+                fields.clone(),
+                [(
+                    params::SCHEMA_METADATA_TIMESTAMP_UNIT.to_owned(),
+                    unit.to_owned(),
+                )]
+                .into(),
+            ))
+        };
+        assert!(check_timestamp_unit(&with_unit("ns"), types::TimestampUnit::Nanoseconds).is_ok());
+        assert!(check_timestamp_unit(&with_unit("ms"), types::TimestampUnit::Nanoseconds).is_err());
+        assert!(check_timestamp_unit(&with_unit("days"), types::TimestampUnit::Seconds).is_err());
+    }
+
     /// Test case 2: Schema **without** the required 'timestamp' field.
     #[test]
     fn invalid_schema_missing_timestamp() {
@@ -475,4 +961,303 @@ mod tests {
             vec!["list_of_ints".to_owned(), "map_data".to_owned(),]
         );
     }
+
+    #[test]
+    fn numeric_stats_with_infinite_values() {
+        params::load_configurables_from_env();
+
+        use arrow::array::Float64Array;
+
+        let array: ArrayRef = Arc::new(Float64Array::from(vec![
+            Some(2.0),
+            Some(f64::INFINITY),
+            None,
+            Some(-1.5),
+            Some(f64::NEG_INFINITY),
+        ]));
+
+        let mut stats = types::Stats::Numeric(types::NumericStats::new());
+        stats_inspect_array(&mut stats, &array).unwrap();
+
+        if let types::Stats::Numeric(s) = stats {
+            assert_eq!(s.min, -1.5);
+            assert_eq!(s.max, 2.0);
+            assert!(s.has_inf);
+            assert!(s.has_null);
+            assert_eq!(s.null_count, 1);
+            assert!(!s.has_nan);
+        } else {
+            panic!("expecting numeric stats");
+        }
+    }
+
+    #[test]
+    fn numeric_stats_under_null_parent() {
+        params::load_configurables_from_env();
+
+        use arrow::array::Float64Array;
+        use arrow::datatypes::Schema;
+
+        // The value under the null parent is not null in the child array
+        let x = Arc::new(Field::new("x", DataType::Float64, false));
+        let pos = StructArray::new(
+            vec![x.clone()].into(),
+            vec![Arc::new(Float64Array::from(vec![1.0, 100.0, 3.0])) as ArrayRef],
+            Some(NullBuffer::from(vec![true, false, true])),
+        );
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "pos",
+            DataType::Struct(vec![x].into()),
+            true,
+        )]));
+        let batch = RecordBatch::try_new(schema.clone(), vec![Arc::new(pos)]).unwrap();
+
+        assert_eq!(
+            array_from_flat_field_name("pos.x", &batch)
+                .unwrap()
+                .null_count(),
+            0
+        );
+        assert_eq!(
+            logical_array_from_flat_field_name("pos.x", &batch)
+                .unwrap()
+                .null_count(),
+            1
+        );
+
+        let mut cstats = column_stats_from_schema(&schema);
+        column_stats_inspect_record_batch(&mut cstats, &batch).unwrap();
+        let types::Stats::Numeric(s) = &cstats.stats["pos.x"] else {
+            panic!("expecting numeric stats");
+        };
+        assert_eq!((s.min, s.max), (1.0, 3.0));
+        assert!(s.has_null);
+        assert_eq!(s.null_count, 1);
+    }
+
+    #[test]
+    fn boolean_stats() {
+        use arrow::array::BooleanArray;
+
+        let mut stats = stats_from_arrow_field(&Field::new("flag", DataType::Boolean, true));
+
+        let array: ArrayRef = Arc::new(BooleanArray::from(vec![Some(true), None, Some(true)]));
+        stats_inspect_array(&mut stats, &array).unwrap();
+        assert_eq!(
+            stats,
+            types::Stats::Boolean(types::BooleanStats {
+                has_true: true,
+                has_false: false,
+                has_null: true,
+            })
+        );
+
+        let array: ArrayRef = Arc::new(BooleanArray::from(vec![false]));
+        stats_inspect_array(&mut stats, &array).unwrap();
+        assert_eq!(
+            stats,
+            types::Stats::Boolean(types::BooleanStats {
+                has_true: true,
+                has_false: true,
+                has_null: true,
+            })
+        );
+    }
+
+    #[test]
+    fn missing_fields_on_nested_paths() {
+        let position = DataType::Struct(
+            vec![
+                Field::new("x", DataType::Float64, false),
+                Field::new("y", DataType::Float64, false),
+            ]
+            .into(),
+        );
+        let planar = DataType::Struct(vec![Field::new("x", DataType::Float64, false)].into());
+
+        let schemas = vec![
+            (
+                "front".to_owned(),
+                create_schema(vec![
+                    Field::new("timestamp_ns", DataType::Int64, false),
+                    Field::new("position", position.clone(), false),
+                ]),
+            ),
+            (
+                "rear".to_owned(),
+                create_schema(vec![
+                    Field::new("timestamp_ns", DataType::Int64, false),
+                    Field::new("position", planar, false),
+                    Field::new("exposure", DataType::Float64, true),
+                ]),
+            ),
+            (
+                "side".to_owned(),
+                create_schema(vec![
+                    Field::new("timestamp_ns", DataType::Int64, false),
+                    Field::new("position", position, false),
+                    Field::new("exposure", DataType::Float64, true),
+                ]),
+            ),
+        ];
+
+        assert_eq!(
+            missing_fields(&schemas),
+            vec![
+                ("front".to_owned(), vec!["exposure".to_owned()]),
+                ("rear".to_owned(), vec!["position.y".to_owned()]),
+            ]
+        );
+
+        assert!(missing_fields(&schemas[1..2]).is_empty());
+    }
+
+    #[test]
+    fn schema_evolution_on_nested_paths() {
+        let pose = |x: DataType| {
+            DataType::Struct(
+                vec![
+                    Field::new("x", x, false),
+                    Field::new("y", DataType::Float64, false),
+                ]
+                .into(),
+            )
+        };
+        let established = create_schema(vec![
+            Field::new("timestamp_ns", DataType::Int64, false),
+            Field::new("pose", pose(DataType::Float64), false),
+            Field::new("label", DataType::Utf8, true),
+        ]);
+
+        // Nullable fields can be added and omitted
+        let schema = create_schema(vec![
+            Field::new("timestamp_ns", DataType::Int64, false),
+            Field::new("pose", pose(DataType::Float64), false),
+            Field::new("exposure", DataType::Float64, true),
+        ]);
+        assert!(schema_evolution_violation(&established, &schema).is_none());
+
+        let schema = create_schema(vec![
+            Field::new("timestamp_ns", DataType::Int64, false),
+            Field::new("pose", pose(DataType::Float32), false),
+        ]);
+        let (field, _) = schema_evolution_violation(&established, &schema).unwrap();
+        assert_eq!(field, "pose.x");
+
+        let schema = create_schema(vec![
+            Field::new("timestamp_ns", DataType::Int64, false),
+            Field::new("pose", pose(DataType::Float64), false),
+            Field::new("exposure", DataType::Float64, false),
+        ]);
+        let (field, _) = schema_evolution_violation(&established, &schema).unwrap();
+        assert_eq!(field, "exposure");
+
+        let schema = create_schema(vec![Field::new("timestamp_ns", DataType::Int64, false)]);
+        let (field, _) = schema_evolution_violation(&established, &schema).unwrap();
+        assert_eq!(field, "pose.x");
+    }
+
+    #[test]
+    fn declared_schema_on_nested_paths() {
+        let position = DataType::Struct(
+            vec![
+                Field::new("x", DataType::Float64, false),
+                Field::new("y", DataType::Float32, false),
+            ]
+            .into(),
+        );
+        let schema = create_schema(vec![
+            Field::new("timestamp_ns", DataType::Int64, false),
+            Field::new("position", position, false),
+            Field::new("frame_id", DataType::Utf8, true),
+        ]);
+
+        let declared = |name: &str, data_type: &str, nullable: bool| types::DeclaredField {
+            name: name.to_owned(),
+            data_type: data_type.to_owned(),
+            nullable,
+        };
+
+        let matching = vec![
+            declared("position.x", "float64", false),
+            declared("position.y", "float32", false),
+            declared("frame_id", "utf8", true),
+        ];
+        assert!(declared_schema_violations(&schema, &matching).is_empty());
+
+        let drifted = vec![
+            declared("position.x", "float64", false),
+            declared("position.y", "float64", false),
+            declared("position.z", "float64", false),
+        ];
+        let violations: Vec<String> = declared_schema_violations(&schema, &drifted)
+            .iter()
+            .map(|v| v.field.clone())
+            .collect();
+        assert_eq!(violations, vec!["position.y", "position.z", "frame_id"]);
+    }
+
+    #[test]
+    fn contract_violations_on_schema() {
+        let field = |name: &str, field_type, nullable| types::ContractField {
+            name: name.to_owned(),
+            field_type,
+            nullable,
+            range: None,
+        };
+        let contract = vec![
+            field("acc.x", types::ContractFieldType::Float, false),
+            field("frame_id", types::ContractFieldType::Text, false),
+            field("temperature", types::ContractFieldType::Float, true),
+            field("seq", types::ContractFieldType::Integer, false),
+        ];
+
+        let schema = create_schema(vec![
+            Field::new("timestamp_ns", DataType::Int64, false),
+            Field::new_struct("acc", vec![Field::new("x", DataType::Float32, true)], true),
+            Field::new("frame_id", DataType::Int32, true),
+            Field::new("seq", DataType::UInt64, true),
+        ]);
+
+        // Missing nullable fields are accepted
+        assert_eq!(
+            contract_violations(&schema, &contract),
+            vec![types::ContractViolation::new(
+                "frame_id",
+                "must be of type `text`"
+            )]
+        );
+
+        let seq: ArrayRef = Arc::new(arrow::array::UInt64Array::from(vec![Some(1), None]));
+        let batch = RecordBatch::try_from_iter([("seq", seq)]).unwrap();
+        assert_eq!(
+            contract_null_violations(&batch, &contract),
+            vec![types::ContractViolation::new(
+                "seq",
+                "can't hold null values"
+            )]
+        );
+    }
+
+    #[test]
+    fn csv_with_list_field() {
+        let timestamp: ArrayRef = Arc::new(arrow::array::Int64Array::from(vec![0, 1, 2]));
+        let ranges: ArrayRef = Arc::new(arrow::array::ListArray::from_iter_primitive::<
+            arrow::datatypes::Float32Type,
+            _,
+            _,
+        >(vec![
+            Some(vec![Some(1.5), Some(2.0)]),
+            None,
+            Some(vec![]),
+        ]));
+        let batch =
+            RecordBatch::try_from_iter([("timestamp_ns", timestamp), ("ranges", ranges)]).unwrap();
+
+        let csv = record_batch_to_csv(&batch, b';', true).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "timestamp_ns;ranges\n0;[1.5,2.0]\n1;\n2;[]\n"
+        );
+    }
 }
@@ -42,6 +42,7 @@ enum Commands {
 #[derive(Debug)]
 struct Variables {
     repository_db_url: url::Url,
+    repository_replica_db_url: Option<url::Url>,
 }
 
 fn init_logger() {
@@ -58,7 +59,16 @@ fn load_env_variables() -> Result<Variables, Box<dyn std::error::Error>> {
     let repository_db_url: String = params::require_env_var("MOSAICO_REPOSITORY_DB_URL")?;
     let repository_db_url: url::Url = repository_db_url.parse()?;
 
-    let vars = Variables { repository_db_url };
+    // The read replica is optional
+    let repository_replica_db_url = match env::var("MOSAICO_REPOSITORY_REPLICA_DB_URL") {
+        Ok(url) => Some(url.parse()?),
+        Err(_) => None,
+    };
+
+    let vars = Variables {
+        repository_db_url,
+        repository_replica_db_url,
+    };
 
     debug!("{:#?}", params::configurables());
     debug!("{:#?}", vars);
@@ -66,18 +76,42 @@ fn load_env_variables() -> Result<Variables, Box<dyn std::error::Error>> {
     Ok(vars)
 }
 
-fn load_remote_store_vars() -> Result<store::S3Config, Box<dyn std::error::Error>> {
-    let store_endpoint: String = params::require_env_var("MOSAICO_STORE_ENDPOINT")?;
+#[derive(Debug)]
+enum RemoteStoreConfig {
+    S3(store::S3Config),
+    Azure(store::AzureConfig),
+}
+
+/// Loads the remote store configuration, the backend is selected by the scheme of
+/// `MOSAICO_STORE_BUCKET`: `az://<container>` selects Azure Blob Storage, while
+/// `s3://<bucket>` or a plain bucket name select an S3-compatible store.
+///
+/// For Azure the access key is the storage account name, the secret key is the account
+/// key and the endpoint is optional.
+fn load_remote_store_vars() -> Result<RemoteStoreConfig, Box<dyn std::error::Error>> {
     let store_bucket: String = params::require_env_var("MOSAICO_STORE_BUCKET")?;
     let secret_key: String = params::require_env_var("MOSAICO_STORE_SECRET_KEY")?;
     let store_secret_key = params::Hidden::from(secret_key);
     let store_access_key: String = params::require_env_var("MOSAICO_STORE_ACCESS_KEY")?;
 
-    let vars = store::S3Config {
-        endpoint: store_endpoint,
-        bucket: store_bucket,
-        secret_key: store_secret_key,
-        access_key: store_access_key,
+    let vars = if let Some(container) = store_bucket.strip_prefix("az://") {
+        RemoteStoreConfig::Azure(store::AzureConfig {
+            container: container.to_owned(),
+            account: store_access_key,
+            access_key: store_secret_key,
+            endpoint: env::var("MOSAICO_STORE_ENDPOINT").ok(),
+        })
+    } else {
+        let store_endpoint: String = params::require_env_var("MOSAICO_STORE_ENDPOINT")?;
+        RemoteStoreConfig::S3(store::S3Config {
+            endpoint: store_endpoint,
+            bucket: store_bucket
+                .strip_prefix("s3://")
+                .unwrap_or(&store_bucket)
+                .to_owned(),
+            secret_key: store_secret_key,
+            access_key: store_access_key,
+        })
     };
 
     debug!("{:#?}", vars);
@@ -103,6 +137,7 @@ fn run(startup_time: &Instant) -> Result<(), Box<dyn std::error::Error>> {
                 store,
                 repo::Config {
                     db_url: vars.repository_db_url.clone(),
+                    replica_db_url: vars.repository_replica_db_url.clone(),
                 },
             );
 
@@ -131,17 +166,26 @@ fn run(startup_time: &Instant) -> Result<(), Box<dyn std::error::Error>> {
 }
 
 fn get_store(cmds: &CommandRun) -> Result<store::StoreRef, Box<dyn std::error::Error>> {
+    let retry_policy = store::RetryPolicy::from_configurables();
+
     if let Some(path) = &cmds.local_store {
         info!("initializing filesystem store");
-        Ok(Arc::new(store::Store::try_from_filesystem(path)?))
+        Ok(Arc::new(
+            store::Store::try_from_filesystem(path)?.with_retry_policy(retry_policy),
+        ))
     } else {
-        info!("initializing s3-compatible store");
-
-        let s3_config = load_remote_store_vars()?;
-
-        let store = Arc::new(store::Store::try_from_s3_store(s3_config)?);
-
-        Ok(store)
+        let store = match load_remote_store_vars()? {
+            RemoteStoreConfig::S3(config) => {
+                info!("initializing s3-compatible store");
+                store::Store::try_from_s3_store(config)?
+            }
+            RemoteStoreConfig::Azure(config) => {
+                info!("initializing azure blob store");
+                store::Store::try_from_azure_store(config)?
+            }
+        };
+
+        Ok(Arc::new(store.with_retry_policy(retry_policy)))
     }
 }
 
@@ -167,6 +211,16 @@ fn get_store_display_name(store: &store::StoreRef) -> String {
                 "]".dimmed(),
             )
         }
+        store::StoreTarget::Azure(container) => {
+            format!(
+                "{}{} {}{}{}",
+                "az://".yellow(),
+                container.yellow(),
+                "[".dimmed(),
+                "remote".cyan(),
+                "]".dimmed(),
+            )
+        }
     }
 }
 
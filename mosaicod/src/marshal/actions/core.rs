@@ -18,6 +18,14 @@ pub enum ActionError {
     /// Failed to serialize the response.
     #[error("response serialization error: {0}")]
     ResponseSerializationError(String),
+
+    /// The requested response format is not supported.
+    #[error("unsupported response format `{0}`")]
+    UnsupportedResponseFormat(String),
+
+    /// A request field holds a value that can't be used.
+    #[error("invalid value for `{field}` :: {reason}")]
+    InvalidField { field: String, reason: String },
 }
 
 /// Represents the list of actions allowed in the system.
@@ -44,7 +52,10 @@ pub enum ActionRequest {
     SequenceCreate(requests::SequenceCreate),
 
     /// Deletes an unlocked sequence from the system.
-    SequenceDelete(requests::ResourceLocator),
+    ///
+    /// With `dry_run` nothing is deleted, the topics, the number of chunks and the bytes
+    /// that would be removed are returned instead.
+    SequenceDelete(requests::SequenceDelete),
 
     /// Aborts the sequence upload process and deletes all resources associated with the sequence.
     ///
@@ -52,20 +63,43 @@ pub enum ActionRequest {
     /// Calling it on a **locked** sequence will result in an error.
     SequenceAbort(requests::UploadToken),
 
+    /// Reports the chunks of a sequence whose data file is missing from the store and the
+    /// data files not referenced by any chunk, optionally deleting both.
+    SequenceReconcile(requests::SequenceReconcile),
+
+    /// Renames an unlocked sequence along with all its topics.
+    SequenceRename(requests::SequenceRename),
+
+    /// Clones a locked sequence along with all its topics and their data.
+    ///
+    /// The cloned sequence is unlocked, so that new topics can be added to it.
+    SequenceClone(requests::SequenceClone),
+
     /// Ask for system informations about the sequence
     SequenceSystemInfo(requests::ResourceLocator),
 
+    /// Ask for a page of the sequences matching an optional filter
+    SequenceList(requests::SequenceList),
+
     /// Finalizes the upload of a sequence and locks it.
     ///
     /// After this action, the sequence will no longer be editable.  
     /// If there are any unlocked topics in the sequence, the action will fail.
-    SequenceFinalize(requests::UploadToken),
+    /// With `strict` set, the action also fails if any topic holds no data.
+    SequenceFinalize(requests::SequenceFinalize),
+
+    /// Re-opens a finalized sequence, unlocking it and all its topics.
+    ///
+    /// The action is recorded in the audit log of the sequence and is only available
+    /// if enabled in the server configuration.
+    SequenceReopen(requests::SequenceReopen),
 
     /// Creates a notification associated with a sequence.
     SequenceNotifyCreate(requests::NotifyCreate),
 
-    /// Get all nofifications for a given sequence
-    SequenceNotifyList(requests::ResourceLocator),
+    /// Get the nofifications of a given sequence, most recent first, optionally
+    /// filtered by minimum severity
+    SequenceNotifyList(requests::NotifyList),
 
     /// Deletes all notifications associated with a sequence
     SequenceNotifyPurge(requests::ResourceLocator),
@@ -73,23 +107,96 @@ pub enum ActionRequest {
     /// Creates a new topic in the system without any data.
     TopicCreate(requests::TopicCreate),
 
+    /// Creates many topics under the same sequence, either all the topics are
+    /// created or none of them.
+    TopicCreateBulk(requests::TopicCreateBulk),
+
     /// Deletes an unlocked topic from the system.
     TopicDelete(requests::ResourceLocator),
 
+    /// Updates the user metadata of an unlocked topic.
+    TopicUpdate(requests::TopicUpdate),
+
     /// Creates a notification associated with a topic.
     TopicNotifyCreate(requests::NotifyCreate),
 
-    /// Get all nofifications for a given topic
-    TopicNotifyList(requests::ResourceLocator),
+    /// Get the nofifications of a given topic, most recent first, optionally
+    /// filtered by minimum severity
+    TopicNotifyList(requests::NotifyList),
 
     /// Deletes all notifications associated with a topic
     TopicNotifyPurge(requests::ResourceLocator),
 
+    /// Deletes a single notification of a sequence or of a topic
+    NotifyDelete(requests::NotifyDelete),
+
     /// Ask for system informations about the topic
     TopicSystemInfo(requests::ResourceLocator),
 
+    /// Recomputes the column statistics of all the chunks of the topic from their data files
+    TopicRestat(requests::ResourceLocator),
+
+    /// Merges the small chunks of an unlocked topic into larger ones
+    TopicCompact(requests::ResourceLocator),
+
+    /// Ask for the arrow schema of the topic, including the types of nested fields
+    TopicSchema(requests::ResourceLocator),
+
+    /// Returns the most recent row (by timestamp) of a topic
+    TopicLatest(requests::ResourceLocator),
+
+    /// Exports the data of a topic as CSV, nested fields are flattened in columns named
+    /// after their path (e.g. `position.x`). The CSV is streamed in several results.
+    TopicExportCsv(requests::TopicExportCsv),
+
+    /// Prepares a read of several topics merged in a single stream ordered by timestamp,
+    /// the returned ticket needs to be used in a `do_get` call.
+    MultiTopicRead(requests::MultiTopicRead),
+
+    /// Compares the schemas of the topics of a sequence, reporting the fields
+    /// missing from some of them
+    TopicSchemaCompare(requests::TopicSchemaCompare),
+
     Query(requests::Query),
 
+    /// Runs a query and returns, for each matching topic, the number of rows matching
+    /// the ontology filter.
+    ///
+    /// Useful to evaluate the size of a dataset before retrieving the data. Only the
+    /// [`crate::query::Scope::Topic`] scope is supported.
+    QueryCount(requests::Query),
+
+    /// Runs a query and returns, for each matching topic, the distinct values of a field.
+    ///
+    /// The number of values reported for each topic is bounded, see
+    /// [`crate::params::ConfigurablesParams::max_distinct_values`].
+    QueryDistinct(requests::QueryDistinct),
+
+    /// Describes how a query is executed, reporting the generated SQL queries and the
+    /// DataFusion logical plan used to scan the data files. No data is returned.
+    QueryExplain(requests::Query),
+
+    /// Prepares the aggregation (min/max/avg/sum/count) of numeric fields of a topic in
+    /// time windows, the returned ticket needs to be used in a `do_get` call.
+    QueryAggregate(requests::QueryAggregate),
+
+    /// Estimates the number of distinct values of a topic field (HyperLogLog).
+    ///
+    /// The returned value is **approximate**, it is intended to be used to evaluate if a field
+    /// is a good filter candidate.
+    ApproxDistinct(requests::ApproxDistinct),
+
+    /// List the topics of a sequence with their properties and chunk statistics
+    TopicList(requests::TopicList),
+
+    /// List the chunks of a topic holding NaN or null values for a field, using only the
+    /// chunk statistics
+    FieldQualityChunks(requests::FieldQualityChunks),
+
+    /// Returns the value ranges of the numeric and literal fields of a topic, aggregated
+    /// from the chunk statistics
+    TopicColumnStats(requests::TopicColumnStats),
+
     /// Creates a new layer in the repository
     LayerCreate(requests::LayerCreate),
 
@@ -101,6 +208,30 @@ pub enum ActionRequest {
 
     /// Ask for the list of existing layers in the system
     LayerList(requests::Empty),
+
+    /// Adds a sequence to a layer
+    LayerAddSequence(requests::LayerSequence),
+
+    /// Removes a sequence from a layer, the sequence itself is not affected
+    LayerRemoveSequence(requests::LayerSequence),
+
+    /// Registers (or replaces) the data contract of an ontology tag
+    OntologyContractSet(requests::OntologyContractSet),
+
+    /// Removes the data contract of an ontology tag
+    OntologyContractDelete(requests::OntologyContractDelete),
+
+    /// Check if a sequence, topic or layer exists, without loading its metadata
+    ResourceExists(requests::ResourceExists),
+
+    /// Ask for server runtime metrics (e.g. read cache statistics), useful for tuning
+    Metrics(requests::Empty),
+
+    /// Check the health of the server components (store and repository)
+    SystemHealth(requests::Empty),
+
+    /// Ask for the effective (non-secret) configuration of the server
+    ServerConfig(requests::Empty),
 }
 
 /// Internal macro used to parse action requests
@@ -117,24 +248,56 @@ impl ActionRequest {
             "sequence_delete" => parse_action_req!(SequenceDelete, body),
             "sequence_abort" => parse_action_req!(SequenceAbort, body),
             "sequence_finalize" => parse_action_req!(SequenceFinalize, body),
+            "sequence_reopen" => parse_action_req!(SequenceReopen, body),
+            "sequence_rename" => parse_action_req!(SequenceRename, body),
+            "sequence_reconcile" => parse_action_req!(SequenceReconcile, body),
+            "sequence_clone" => parse_action_req!(SequenceClone, body),
             "sequence_system_info" => parse_action_req!(SequenceSystemInfo, body),
+            "sequence_list" => parse_action_req!(SequenceList, body),
             "sequence_notify_create" => parse_action_req!(SequenceNotifyCreate, body),
             "sequence_notify_list" => parse_action_req!(SequenceNotifyList, body),
             "sequence_notify_purge" => parse_action_req!(SequenceNotifyPurge, body),
 
             "topic_create" => parse_action_req!(TopicCreate, body),
+            "topic_create_bulk" => parse_action_req!(TopicCreateBulk, body),
             "topic_delete" => parse_action_req!(TopicDelete, body),
+            "topic_update" => parse_action_req!(TopicUpdate, body),
             "topic_system_info" => parse_action_req!(TopicSystemInfo, body),
+            "topic_restat" => parse_action_req!(TopicRestat, body),
+            "topic_compact" => parse_action_req!(TopicCompact, body),
+            "topic_schema" => parse_action_req!(TopicSchema, body),
+            "topic_schema_compare" => parse_action_req!(TopicSchemaCompare, body),
+            "topic_latest" => parse_action_req!(TopicLatest, body),
+            "topic_export_csv" => parse_action_req!(TopicExportCsv, body),
+            "multi_topic_read" => parse_action_req!(MultiTopicRead, body),
             "topic_notify_create" => parse_action_req!(TopicNotifyCreate, body),
             "topic_notify_list" => parse_action_req!(TopicNotifyList, body),
+            "notify_delete" => parse_action_req!(NotifyDelete, body),
             "topic_notify_purge" => parse_action_req!(TopicNotifyPurge, body),
 
             "layer_create" => parse_action_req!(LayerCreate, body),
             "layer_delete" => parse_action_req!(LayerDelete, body),
             "layer_update" => parse_action_req!(LayerUpdate, body),
             "layer_list" => parse_action_req!(LayerList, body),
+            "layer_add_sequence" => parse_action_req!(LayerAddSequence, body),
+            "layer_remove_sequence" => parse_action_req!(LayerRemoveSequence, body),
+            "resource_exists" => parse_action_req!(ResourceExists, body),
+            "ontology_contract_set" => parse_action_req!(OntologyContractSet, body),
+            "ontology_contract_delete" => parse_action_req!(OntologyContractDelete, body),
 
             "query" => parse_action_req!(Query, body),
+            "query_count" => parse_action_req!(QueryCount, body),
+            "query_distinct" => parse_action_req!(QueryDistinct, body),
+            "query_explain" => parse_action_req!(QueryExplain, body),
+            "query_aggregate" => parse_action_req!(QueryAggregate, body),
+            "approx_distinct" => parse_action_req!(ApproxDistinct, body),
+            "topic_list" => parse_action_req!(TopicList, body),
+            "field_quality_chunks" => parse_action_req!(FieldQualityChunks, body),
+            "topic_column_stats" => parse_action_req!(TopicColumnStats, body),
+
+            "metrics" => parse_action_req!(Metrics, body),
+            "system_health" => parse_action_req!(SystemHealth, body),
+            "server_config" => parse_action_req!(ServerConfig, body),
 
             _ => Err(ActionError::MissingAction(value.to_owned())),
         }
@@ -145,32 +308,132 @@ impl ActionRequest {
 #[serde(tag = "action", content = "response", rename_all = "snake_case")]
 pub enum ActionResponse {
     SequenceCreate(responses::ResourceKey),
+    SequenceClone(responses::ResourceKey),
     SequenceSystemInfo(responses::SequenceSystemInfo),
+    SequenceDeleteSummary(responses::SequenceDeleteSummary),
+    SequenceReconcile(responses::SequenceReconcile),
+    SequenceList(responses::SequenceList),
     SequenceNotifyList(responses::NotifyList),
 
     TopicCreate(responses::ResourceKey),
+    TopicCreateBulk(responses::TopicCreateBulk),
     TopicSystemInfo(responses::TopicSystemInfo),
+    TopicRestat(responses::TopicRestat),
+    TopicCompact(responses::TopicCompact),
+    TopicSchema(responses::TopicSchema),
+    TopicSchemaCompare(responses::TopicSchemaCompare),
+    TopicLatest(responses::TopicLatest),
+    // Streamed as raw bytes, can't be encoded as a single response
+    #[serde(skip_serializing)]
+    TopicExportCsv(responses::CsvStream),
+    MultiTopicRead(responses::FlightTicket),
+    QueryAggregate(responses::FlightTicket),
     TopicNotifyList(responses::NotifyList),
+    ApproxDistinct(responses::ApproxDistinct),
+    TopicList(responses::TopicList),
+    FieldQualityChunks(responses::FieldQualityChunks),
+    TopicColumnStats(responses::TopicColumnStats),
 
     LayerList(responses::LayerList),
+    ResourceExists(responses::ResourceExists),
 
     Query(responses::Query),
+    QueryCount(responses::QueryCount),
+    QueryDistinct(responses::QueryDistinct),
+    QueryExplain(responses::QueryExplain),
+
+    Metrics(responses::Metrics),
+    SystemHealth(responses::SystemHealth),
+    ServerConfig(responses::ServerConfig),
 
     // Empty response, no data to send
     Empty,
 }
 
 impl ActionResponse {
+    /// Name of the header reporting in how many parts the encoded response has been split
+    pub const PARTS_HEADER: &str = "x-mosaico-response-parts";
+
     /// Converts to bytes the action response
     pub fn bytes(&self) -> Result<Vec<u8>, ActionError> {
-        serde_json::to_vec(self).map_err(|e| ActionError::ResponseSerializationError(e.to_string()))
+        self.encode(ResponseFormat::Json)
+    }
+
+    /// Converts to bytes the action response using the provided format
+    pub fn encode(&self, format: ResponseFormat) -> Result<Vec<u8>, ActionError> {
+        match format {
+            ResponseFormat::Json => serde_json::to_vec(self)
+                .map_err(|e| ActionError::ResponseSerializationError(e.to_string())),
+            // Fields are encoded by name, so that the decoded structure is the same as the json one
+            ResponseFormat::MessagePack => rmp_serde::to_vec_named(self)
+                .map_err(|e| ActionError::ResponseSerializationError(e.to_string())),
+        }
+    }
+
+    /// Encodes the action response and splits the bytes in parts of at most `max_part_size`
+    /// bytes, so that each part can be sent as a separate action result.
+    ///
+    /// Responses smaller than `max_part_size` are returned in a single part. Clients rebuild
+    /// the response by concatenating the parts in the order they are received, no state is
+    /// kept on the server between parts.
+    pub fn encode_parts(
+        &self,
+        format: ResponseFormat,
+        max_part_size: usize,
+    ) -> Result<Vec<bytes::Bytes>, ActionError> {
+        let bytes = bytes::Bytes::from(self.encode(format)?);
+
+        if bytes.len() <= max_part_size || max_part_size == 0 {
+            return Ok(vec![bytes]);
+        }
+
+        Ok((0..bytes.len())
+            .step_by(max_part_size)
+            .map(|start| bytes.slice(start..(start + max_part_size).min(bytes.len())))
+            .collect())
+    }
+}
+
+/// Encoding used to serialize an [`ActionResponse`].
+///
+/// Clients can ask for a specific format using the [`ResponseFormat::HEADER`] request header,
+/// the same header is set in the response to state the format used. If the header is missing,
+/// responses are encoded as json.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResponseFormat {
+    #[default]
+    Json,
+    MessagePack,
+}
+
+impl ResponseFormat {
+    /// Name of the header used to negotiate the response format
+    pub const HEADER: &str = "x-mosaico-response-format";
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::MessagePack => "msgpack",
+        }
+    }
+}
+
+impl std::str::FromStr for ResponseFormat {
+    type Err = ActionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "msgpack" => Ok(Self::MessagePack),
+            _ => Err(ActionError::UnsupportedResponseFormat(s.to_owned())),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::ActionRequest;
-    use crate::rw;
+    use crate::{rw, types};
     use serde::Deserialize;
 
     #[derive(Deserialize, Debug)]
@@ -179,6 +442,44 @@ mod tests {
         driver: String,
     }
 
+    /// Ensure that a [`RequestTopicCreate`] without ontology tag is accepted and that the
+    /// tag can be found in the user metadata.
+    #[test]
+    fn request_topic_create_without_tag() {
+        let raw = r#"
+            {
+                "name" : "test_topic",
+                "sequence_key" : "some_uuid",
+                "serialization_format" : "default",
+                "user_metadata" : {
+                    "ontology" : "imu",
+                    "sensor" : { "ontology" : "gps", "rate" : 10 }
+                }
+            }
+        "#;
+
+        let action = ActionRequest::try_new("topic_create", raw.as_bytes())
+            .expect("Problem parsing action request `topic_create`");
+
+        if let ActionRequest::TopicCreate(action) = action {
+            assert!(action.ontology_tag.is_none());
+            assert_eq!(
+                action.ontology_tag_from_metadata("ontology").as_deref(),
+                Some("imu")
+            );
+            assert_eq!(
+                action
+                    .ontology_tag_from_metadata("sensor.ontology")
+                    .as_deref(),
+                Some("gps")
+            );
+            assert!(action.ontology_tag_from_metadata("sensor.rate").is_none());
+            assert!(action.ontology_tag_from_metadata("missing").is_none());
+        } else {
+            panic!("Wrong action request, expecting `topic_create`")
+        }
+    }
+
     /// Ensure that user_metadata field in [`RequestTopicCreate`] is serialized
     /// correctly as a string and can be converted to a parsable json if required.
     #[test]
@@ -203,7 +504,8 @@ mod tests {
             assert_eq!(action.name, "test_topic");
             assert_eq!(action.sequence_key, "some_uuid");
             assert_eq!(action.serialization_format, rw::Format::Default);
-            assert_eq!(action.ontology_tag, "my_sensor");
+            assert_eq!(action.ontology_tag.as_deref(), Some("my_sensor"));
+            assert_eq!(action.timestamp_unit, types::TimestampUnit::Milliseconds);
             let raw_json = action
                 .user_metadata()
                 .expect("Unable to get `user_metadata`");
@@ -217,4 +519,96 @@ mod tests {
             panic!("Wrong action request, expecting `topic_create`")
         }
     }
+
+    #[test]
+    fn response_formats_round_trip() {
+        use super::{ActionResponse, ResponseFormat, responses};
+
+        let response = ActionResponse::ApproxDistinct(responses::ApproxDistinct {
+            field: "pose.position.x".to_owned(),
+            approx_distinct_count: 42,
+        });
+
+        let json: serde_json::Value =
+            serde_json::from_slice(&response.encode(ResponseFormat::Json).unwrap()).unwrap();
+        let msgpack: serde_json::Value =
+            rmp_serde::from_slice(&response.encode(ResponseFormat::MessagePack).unwrap()).unwrap();
+
+        assert_eq!(json, msgpack);
+        assert_eq!(json["action"], "approx_distinct");
+
+        assert_eq!(
+            "msgpack".parse::<ResponseFormat>().unwrap(),
+            ResponseFormat::MessagePack
+        );
+        assert!("xml".parse::<ResponseFormat>().is_err());
+    }
+
+    #[test]
+    fn response_encode_parts() {
+        use super::{ActionResponse, ResponseFormat, responses};
+
+        let response = ActionResponse::SequenceList(responses::SequenceList {
+            sequences: (0..100).map(|i| format!("sequence_{i}")).collect(),
+            total_count: 100,
+        });
+        let bytes = response.encode(ResponseFormat::Json).unwrap();
+
+        let parts = response
+            .encode_parts(ResponseFormat::Json, bytes.len())
+            .unwrap();
+        assert_eq!(parts.len(), 1);
+
+        let parts = response.encode_parts(ResponseFormat::Json, 64).unwrap();
+        assert_eq!(parts.len(), bytes.len().div_ceil(64));
+        assert!(parts.iter().all(|p| p.len() <= 64));
+        assert_eq!(parts.concat(), bytes);
+    }
+
+    #[test]
+    fn response_topic_schema_nesting() {
+        use super::responses;
+        use arrow::datatypes::{DataType, Field, Fields, Schema};
+        use std::sync::Arc;
+
+        let position = Fields::from(vec![
+            Field::new("x", DataType::Float64, false),
+            Field::new("y", DataType::Float64, false),
+        ]);
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("position", DataType::Struct(position), true),
+            Field::new(
+                "ranges",
+                DataType::List(Arc::new(Field::new("item", DataType::Float32, true))),
+                false,
+            ),
+        ]));
+
+        let json = serde_json::to_value(responses::TopicSchema::from(schema)).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "fields": [
+                    {
+                        "name": "position",
+                        "data_type": "struct",
+                        "nullable": true,
+                        "children": [
+                            { "name": "x", "data_type": "float64", "nullable": false },
+                            { "name": "y", "data_type": "float64", "nullable": false },
+                        ]
+                    },
+                    {
+                        "name": "ranges",
+                        "data_type": "list",
+                        "nullable": false,
+                        "children": [
+                            { "name": "item", "data_type": "float32", "nullable": true },
+                        ]
+                    },
+                ]
+            })
+        );
+    }
 }
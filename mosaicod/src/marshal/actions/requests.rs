@@ -1,6 +1,6 @@
 use serde::Deserialize;
 
-use crate::rw;
+use crate::{marshal, query, rw, types};
 
 use super::ActionError;
 
@@ -26,7 +26,22 @@ pub struct TopicCreate {
     pub name: String,
     pub sequence_key: String,
     pub serialization_format: rw::Format,
-    pub ontology_tag: String,
+    /// If missing, the tag is inferred from the user metadata
+    /// (see [`TopicCreate::ontology_tag_from_metadata`])
+    #[serde(default)]
+    pub ontology_tag: Option<String>,
+    /// Unit of the timestamp column, defaults to milliseconds
+    #[serde(default)]
+    pub timestamp_unit: types::TimestampUnit,
+    /// If `true` data with decreasing timestamps is rejected, defaults to `false`
+    #[serde(default)]
+    pub monotonic_timestamps: bool,
+    /// Maximum number of rows of a row group, defaults to the server setting
+    #[serde(default)]
+    pub max_row_group_size: Option<usize>,
+    /// Arrow schema the topic data is expected to match, checked on reads
+    #[serde(default)]
+    pub declared_schema: Option<types::DeclaredSchema>,
 
     user_metadata: serde_json::Value,
 }
@@ -35,6 +50,82 @@ impl TopicCreate {
     pub fn user_metadata(&self) -> Result<String, ActionError> {
         Ok(serde_json::to_string(&self.user_metadata)?)
     }
+
+    /// Returns the text found in the user metadata at the dotted path `field`
+    /// (e.g. `sensor.ontology`), if any.
+    pub fn ontology_tag_from_metadata(&self, field: &str) -> Option<String> {
+        field
+            .split('.')
+            .try_fold(&self.user_metadata, |value, key| value.get(key))?
+            .as_str()
+            .map(ToOwned::to_owned)
+    }
+}
+
+/// Specification of a single topic created by [`TopicCreateBulk`]
+#[derive(Deserialize, Debug)]
+pub struct TopicSpec {
+    pub name: String,
+    pub serialization_format: rw::Format,
+    pub ontology_tag: String,
+    /// Unit of the timestamp column, defaults to milliseconds
+    #[serde(default)]
+    pub timestamp_unit: types::TimestampUnit,
+    /// If `true` data with decreasing timestamps is rejected, defaults to `false`
+    #[serde(default)]
+    pub monotonic_timestamps: bool,
+    /// Maximum number of rows of a row group, defaults to the server setting
+    #[serde(default)]
+    pub max_row_group_size: Option<usize>,
+    /// Arrow schema the topic data is expected to match, checked on reads
+    #[serde(default)]
+    pub declared_schema: Option<types::DeclaredSchema>,
+
+    user_metadata: serde_json::Value,
+}
+
+impl TopicSpec {
+    pub fn user_metadata(&self) -> Result<String, ActionError> {
+        Ok(serde_json::to_string(&self.user_metadata)?)
+    }
+}
+
+/// Creates many topics under the same sequence in a single transaction
+#[derive(Deserialize, Debug)]
+pub struct TopicCreateBulk {
+    pub sequence_key: String,
+    pub topics: Vec<TopicSpec>,
+}
+
+/// Compares the schemas of the topics of a sequence.
+///
+/// Topics can be selected by name and/or by ontology tag, if no selection is
+/// provided all the topics of the sequence are compared.
+#[derive(Deserialize, Debug)]
+pub struct TopicSchemaCompare {
+    pub sequence: String,
+    #[serde(default)]
+    pub topics: Vec<String>,
+    #[serde(default)]
+    pub ontology_tag: Option<String>,
+}
+
+/// Updates the user metadata of an existing (unlocked) topic.
+///
+/// If `merge` is `true` the provided keys are shallow-merged with the existing
+/// metadata, otherwise the existing metadata is replaced.
+#[derive(Deserialize, Debug)]
+pub struct TopicUpdate {
+    pub name: String,
+    user_metadata: serde_json::Value,
+    #[serde(default)]
+    pub merge: bool,
+}
+
+impl TopicUpdate {
+    pub fn user_metadata(&self) -> Result<String, ActionError> {
+        Ok(serde_json::to_string(&self.user_metadata)?)
+    }
 }
 
 /// Request used to locate a specific resource by name.
@@ -43,6 +134,60 @@ pub struct ResourceLocator {
     pub name: String,
 }
 
+/// Request the deletion of the sequence `name`, with `dry_run` nothing is deleted and the
+/// resources that would be removed are reported
+#[derive(Deserialize, Debug)]
+pub struct SequenceDelete {
+    pub name: String,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Request the comparison of the chunks of the sequence `name` with the data files in the
+/// store, inconsistencies are fixed if `repair` is set (only for locked sequences)
+#[derive(Deserialize, Debug)]
+pub struct SequenceReconcile {
+    pub name: String,
+    #[serde(default)]
+    pub repair: bool,
+}
+
+/// Request the CSV export of the topic `name`
+#[derive(Deserialize, Debug)]
+pub struct TopicExportCsv {
+    pub name: String,
+    /// Field delimiter, needs to be an ASCII character
+    #[serde(default = "default_csv_delimiter")]
+    pub delimiter: char,
+    /// If `true` the first line holds the column names
+    #[serde(default = "default_csv_header")]
+    pub header: bool,
+}
+
+fn default_csv_delimiter() -> char {
+    ','
+}
+
+fn default_csv_header() -> bool {
+    true
+}
+
+/// Renames the sequence `name` (and all its topics) to `new_name`
+#[derive(Deserialize, Debug)]
+pub struct SequenceRename {
+    pub name: String,
+    pub new_name: String,
+}
+
+/// Clones the locked sequence `name` (and all its topics) as `new_name`.
+///
+/// All the objects of the sequence are copied, copy-on-write clones are not supported yet.
+#[derive(Deserialize, Debug)]
+pub struct SequenceClone {
+    pub name: String,
+    pub new_name: String,
+}
+
 /// Request used to locate a resource deterministically,
 /// typically by combining the resource name and a unique key.
 /// Used for topics, sequences, or other keyed resources.
@@ -52,6 +197,30 @@ pub struct UploadToken {
     pub key: String,
 }
 
+/// Request to finalize a sequence.
+///
+/// If `strict` is `true` the sequence is finalized only if all of its topics hold some data.
+#[derive(Deserialize, Debug)]
+pub struct SequenceFinalize {
+    pub name: String,
+    pub key: String,
+    #[serde(default)]
+    pub strict: bool,
+}
+
+/// Request to re-open a finalized sequence.
+///
+/// `author` identifies who requested the operation and, along with the optional `reason`,
+/// is recorded in the audit log of the sequence.
+#[derive(Deserialize, Debug)]
+pub struct SequenceReopen {
+    pub name: String,
+    pub key: String,
+    pub author: String,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
 /// Generic request message used to create nofifications
 #[derive(Deserialize, Debug)]
 pub struct NotifyCreate {
@@ -60,6 +229,24 @@ pub struct NotifyCreate {
     pub msg: String,
 }
 
+/// Request the notifications of a resource, most recent first.
+///
+/// Only the notifications with a severity (`notify_type`) of at least `min_severity` are
+/// returned, at most `limit` of them.
+#[derive(Deserialize, Debug)]
+pub struct NotifyList {
+    pub name: String,
+    pub min_severity: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// Delete the notification `id` of the sequence or topic named `name`
+#[derive(Deserialize, Debug)]
+pub struct NotifyDelete {
+    pub name: String,
+    pub id: i32,
+}
+
 /// Creates a new layer
 #[derive(Deserialize, Debug)]
 pub struct LayerCreate {
@@ -73,6 +260,29 @@ pub struct LayerDelete {
     pub name: String,
 }
 
+/// Add (or remove) the sequence `sequence` to the layer `name`
+#[derive(Deserialize, Debug)]
+pub struct LayerSequence {
+    pub name: String,
+    pub sequence: String,
+}
+
+/// Kind of resource checked by a [`ResourceExists`] request
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceKind {
+    Sequence,
+    Topic,
+    Layer,
+}
+
+/// Check if a resource of the given `kind` named `name` exists
+#[derive(Deserialize, Debug)]
+pub struct ResourceExists {
+    pub name: String,
+    pub kind: ResourceKind,
+}
+
 /// Update `name` and `description` on an existing layer
 #[derive(Deserialize, Debug)]
 pub struct LayerUpdate {
@@ -81,8 +291,88 @@ pub struct LayerUpdate {
     pub curr_description: String,
 }
 
+/// Registers the `contract` document (see [`marshal::JsonOntologyContract`]) of `ontology_tag`
+#[derive(Deserialize, Debug)]
+pub struct OntologyContractSet {
+    pub ontology_tag: String,
+    pub contract: marshal::JsonOntologyContract,
+}
+
+/// Removes the contract of `ontology_tag`
+#[derive(Deserialize, Debug)]
+pub struct OntologyContractDelete {
+    pub ontology_tag: String,
+}
+
+/// Request the data of several topics merged in a single stream ordered by timestamp
+#[derive(Deserialize, Debug)]
+pub struct MultiTopicRead {
+    pub topics: Vec<String>,
+}
+
+/// Request the aggregation of the fields of a topic in time windows.
+///
+/// The optional `time_range` (in milliseconds) restricts the aggregated data.
+#[derive(Deserialize, Debug)]
+pub struct QueryAggregate {
+    pub topic: String,
+    pub window_ms: u64,
+    pub fields: Vec<crate::marshal::AggregateField>,
+    pub time_range: Option<[i64; 2]>,
+}
+
+/// Request an approximate count of the distinct values of a topic `field`
+#[derive(Deserialize, Debug)]
+pub struct ApproxDistinct {
+    pub topic: String,
+    pub field: String,
+}
+
+/// Request the chunks of a topic holding NaN or null values for `field`
+#[derive(Deserialize, Debug)]
+pub struct FieldQualityChunks {
+    pub topic: String,
+    pub field: String,
+}
+
+/// Request the value ranges of the fields of a topic
+#[derive(Deserialize, Debug)]
+pub struct TopicColumnStats {
+    pub topic: String,
+}
+
+/// Request the topics of a sequence, optionally restricted by a topic filter
+/// (e.g. `{ "serialization_format": { "$eq": "image" } }`)
+#[derive(Deserialize, Debug)]
+pub struct TopicList {
+    pub name: String,
+    pub filter: Option<serde_json::Value>,
+}
+
+/// Request a page of the sequences matching an (optional) sequence filter.
+///
+/// If `limit` is not provided all the sequences starting from `offset` are returned.
+#[derive(Deserialize, Debug)]
+pub struct SequenceList {
+    pub filter: Option<serde_json::Value>,
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub offset: usize,
+}
+
+/// Request the distinct values of `field` for each topic matching the query
+#[derive(Deserialize, Debug)]
+pub struct QueryDistinct {
+    pub field: String,
+    #[serde(flatten)]
+    pub query: serde_json::Value,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct Query {
+    /// Topics reported for each matching sequence, defaults to [`query::Scope::Topic`]
+    #[serde(default)]
+    pub scope: query::Scope,
     #[serde(flatten)]
     pub query: serde_json::Value,
 }
@@ -1,6 +1,13 @@
+use arrow::array::RecordBatch;
+use arrow::datatypes::{DataType, Field, SchemaRef};
+use arrow::error::ArrowError;
+use futures::stream::BoxStream;
 use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
 
 use crate::types::{self, Resource};
+use crate::utils::size::human_readable_bytes;
+use crate::{params, query, repo, store};
 
 /// Generic response message used to provide to clients the key
 /// of a resource
@@ -17,6 +24,18 @@ impl From<types::ResourceId> for ResourceKey {
     }
 }
 
+/// Key of a topic created by a bulk creation
+#[derive(Serialize, Debug)]
+pub struct TopicKey {
+    pub name: String,
+    pub key: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TopicCreateBulk {
+    pub topics: Vec<TopicKey>,
+}
+
 #[derive(Serialize, Debug)]
 pub struct TopicSystemInfo {
     /// Number of chunks in the topic
@@ -24,10 +43,14 @@ pub struct TopicSystemInfo {
     /// Total size in bytes of the data.
     /// Metadata and other system files are excluded in the count.
     pub total_size_bytes: usize,
+    /// Total size of the data in binary units (e.g. `1.2 GiB`)
+    pub total_size: String,
     /// True if topic is locked
     pub is_locked: bool,
     /// Datetime of the topic creation
     pub created_datetime: String,
+    /// Datetime of the creation of the most recent chunk, `null` if the topic has no chunks
+    pub last_chunk_at: Option<String>,
 }
 
 impl From<types::TopicSystemInfo> for TopicSystemInfo {
@@ -35,8 +58,47 @@ impl From<types::TopicSystemInfo> for TopicSystemInfo {
         Self {
             chunks_number: value.chunks_number,
             total_size_bytes: value.total_size_bytes,
+            total_size: human_readable_bytes(value.total_size_bytes),
             is_locked: value.is_locked,
             created_datetime: value.created_datetime.to_string(),
+            last_chunk_at: value.last_chunk_datetime.map(|dt| dt.to_string()),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct TopicListItem {
+    pub name: String,
+    /// `null` for topics created without data
+    pub serialization_format: Option<String>,
+    pub ontology_tag: Option<String>,
+    pub created_datetime: String,
+    pub total_row_count: i64,
+    pub total_size_bytes: i64,
+}
+
+impl From<types::TopicSummary> for TopicListItem {
+    fn from(value: types::TopicSummary) -> Self {
+        Self {
+            name: value.locator.into(),
+            serialization_format: value.serialization_format.map(|f| f.to_string()),
+            ontology_tag: value.ontology_tag,
+            created_datetime: value.created_datetime.to_string(),
+            total_row_count: value.stats.total_row_count,
+            total_size_bytes: value.stats.total_size_bytes,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct TopicList {
+    pub topics: Vec<TopicListItem>,
+}
+
+impl From<Vec<types::TopicSummary>> for TopicList {
+    fn from(value: Vec<types::TopicSummary>) -> Self {
+        Self {
+            topics: value.into_iter().map(Into::into).collect(),
         }
     }
 }
@@ -46,6 +108,8 @@ pub struct SequenceSystemInfo {
     /// Total size in bytes of the data.
     /// This values includes additional system files.
     pub total_size_bytes: usize,
+    /// Total size of the data in binary units (e.g. `1.2 GiB`)
+    pub total_size: String,
     /// True if sequence is locked
     pub is_locked: bool,
     /// Datetime of the sequence creation
@@ -56,27 +120,90 @@ impl From<types::SequenceSystemInfo> for SequenceSystemInfo {
     fn from(value: types::SequenceSystemInfo) -> Self {
         Self {
             total_size_bytes: value.total_size_bytes,
+            total_size: human_readable_bytes(value.total_size_bytes),
             is_locked: value.is_locked,
             created_datetime: value.created_datetime.to_string(),
         }
     }
 }
 
+/// Resources that would be removed by a sequence deletion
+#[derive(Serialize, Debug)]
+pub struct SequenceDeleteSummary {
+    pub topics: Vec<String>,
+    pub total_chunks: i64,
+    pub total_size_bytes: i64,
+    /// Total size of the chunks in binary units (e.g. `1.2 GiB`)
+    pub total_size: String,
+}
+
+impl From<types::SequenceDeleteSummary> for SequenceDeleteSummary {
+    fn from(value: types::SequenceDeleteSummary) -> Self {
+        Self {
+            topics: value.topics.into_iter().map(|t| t.name().clone()).collect(),
+            total_chunks: value.total_chunks,
+            total_size_bytes: value.total_size_bytes,
+            total_size: human_readable_bytes(value.total_size_bytes.max(0) as usize),
+        }
+    }
+}
+
+/// Inconsistencies between the chunks of a sequence and the data files in the store
+#[derive(Serialize, Debug)]
+pub struct SequenceReconcile {
+    pub missing_data_files: Vec<String>,
+    pub orphan_data_files: Vec<String>,
+    pub repaired: bool,
+}
+
+impl From<types::SequenceReconcileReport> for SequenceReconcile {
+    fn from(value: types::SequenceReconcileReport) -> Self {
+        Self {
+            missing_data_files: value.missing_data_files,
+            orphan_data_files: value.orphan_data_files,
+            repaired: value.repaired,
+        }
+    }
+}
+
+/// A page of sequences, `total_count` holds the number of sequences matching the
+/// request across all pages.
+#[derive(Serialize, Debug)]
+pub struct SequenceList {
+    pub sequences: Vec<String>,
+    pub total_count: usize,
+}
+
+impl From<types::Page<types::SequenceResourceLocator>> for SequenceList {
+    fn from(value: types::Page<types::SequenceResourceLocator>) -> Self {
+        Self {
+            sequences: value.items.into_iter().map(Into::into).collect(),
+            total_count: value.total_count,
+        }
+    }
+}
+
 #[derive(Serialize, Debug)]
 pub struct ResponseNotifyItem {
+    /// Identifier of the notify, unique among the notifies of the same resource
+    pub id: i32,
     pub name: String,
     pub notify_type: String,
     pub msg: String,
     pub created_datetime: String,
+    /// Creation time in milliseconds since the UNIX epoch
+    pub created_unix_timestamp: i64,
 }
 
 impl From<types::Notify> for ResponseNotifyItem {
     fn from(value: types::Notify) -> Self {
         Self {
+            id: value.id,
             name: value.target.name().to_string(),
             notify_type: value.notify_type.to_string(),
             msg: value.msg.unwrap_or_default(),
             created_datetime: value.created_at.to_string(),
+            created_unix_timestamp: value.created_at.unix_timestamp_ms(),
         }
     }
 }
@@ -131,6 +258,16 @@ pub struct ResponseQueryItem {
 #[derive(Serialize, Debug)]
 pub struct Query {
     pub items: Vec<ResponseQueryItem>,
+    /// `true` if the results exceeded the maximum number of topics reported by a query
+    /// and have been truncated, a narrower filter is needed to get all of them
+    pub truncated: bool,
+}
+
+impl Query {
+    pub fn with_truncated(mut self, truncated: bool) -> Self {
+        self.truncated = truncated;
+        self
+    }
 }
 
 impl From<types::SequenceTopicGroup> for ResponseQueryItem {
@@ -151,6 +288,581 @@ impl From<types::SequenceTopicGroups> for Query {
         let vec: Vec<types::SequenceTopicGroup> = value.into();
         Self {
             items: vec.into_iter().map(Into::into).collect(),
+            truncated: false,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct SqlStatement {
+    pub sql: String,
+    /// Values bound to the placeholders of the query, in order
+    pub values: Vec<serde_json::Value>,
+}
+
+impl From<query::SqlStatement> for SqlStatement {
+    fn from(value: query::SqlStatement) -> Self {
+        Self {
+            sql: value.sql,
+            values: value
+                .values
+                .into_iter()
+                .map(|v| match v {
+                    query::Value::Integer(v) => v.into(),
+                    query::Value::Float(v) => v.into(),
+                    query::Value::Text(v) => v.into(),
+                    query::Value::Boolean(v) => v.into(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct OntologyTagExplain {
+    pub ontology_tag: String,
+    pub chunks_sql: SqlStatement,
+    pub candidate_chunks: usize,
+    pub logical_plan: Option<String>,
+    pub explained_chunk: Option<String>,
+}
+
+/// Execution details of a query
+#[derive(Serialize, Debug)]
+pub struct QueryExplain {
+    pub topics_sql: Option<SqlStatement>,
+    pub selected_topics: usize,
+    pub ontology_tags: Vec<OntologyTagExplain>,
+}
+
+impl From<query::Explain> for QueryExplain {
+    fn from(value: query::Explain) -> Self {
+        Self {
+            topics_sql: value.topics_sql.map(Into::into),
+            selected_topics: value.selected_topics,
+            ontology_tags: value
+                .ontology_tags
+                .into_iter()
+                .map(|t| OntologyTagExplain {
+                    ontology_tag: t.ontology_tag,
+                    chunks_sql: t.chunks_sql.into(),
+                    candidate_chunks: t.candidate_chunks,
+                    logical_plan: t.logical_plan,
+                    explained_chunk: t.explained_chunk,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Number of rows matching a query for each topic (indexed by topic name)
+#[derive(Serialize, Debug)]
+pub struct QueryCount {
+    pub topics: BTreeMap<String, usize>,
+}
+
+impl From<HashMap<String, usize>> for QueryCount {
+    fn from(value: HashMap<String, usize>) -> Self {
+        Self {
+            topics: value.into_iter().collect(),
+        }
+    }
+}
+
+/// Distinct values of a field, for each topic matching a query
+#[derive(Serialize, Debug)]
+pub struct QueryDistinct {
+    pub field: String,
+    pub topics: BTreeMap<String, DistinctValues>,
+    /// `true` if the topics matching the query exceeded the maximum number of topics
+    /// reported by a query and have been truncated
+    pub truncated: bool,
+}
+
+/// Distinct values of a field in a topic, sorted in ascending order with `null` last.
+///
+/// `truncated` is `true` if the topic holds more values than the reported ones.
+#[derive(Serialize, Debug)]
+pub struct DistinctValues {
+    pub values: Vec<serde_json::Value>,
+    pub truncated: bool,
+}
+
+impl TryFrom<query::DistinctValues> for DistinctValues {
+    type Error = ArrowError;
+
+    fn try_from(value: query::DistinctValues) -> Result<Self, Self::Error> {
+        let mut writer = arrow::json::ArrayWriter::new(Vec::new());
+        writer.write_batches(&value.batches.iter().collect::<Vec<_>>())?;
+        writer.finish()?;
+
+        let rows: Vec<serde_json::Map<String, serde_json::Value>> =
+            serde_json::from_slice(&writer.into_inner())
+                .map_err(|e| ArrowError::JsonError(e.to_string()))?;
+
+        Ok(Self {
+            // Null values are omitted by the json writer
+            values: rows
+                .into_iter()
+                .map(|mut row| {
+                    row.remove(query::DISTINCT_VALUE_COLUMN)
+                        .unwrap_or(serde_json::Value::Null)
+                })
+                .collect(),
+            truncated: value.truncated,
+        })
+    }
+}
+
+/// Most recent row of a topic, `row` is `null` if the topic has no data.
+///
+/// The row is encoded as a json object, with a field for each column.
+#[derive(Serialize, Debug)]
+pub struct TopicLatest {
+    pub row: Option<serde_json::Value>,
+}
+
+impl TryFrom<Vec<RecordBatch>> for TopicLatest {
+    type Error = ArrowError;
+
+    fn try_from(batches: Vec<RecordBatch>) -> Result<Self, Self::Error> {
+        let mut writer = arrow::json::ArrayWriter::new(Vec::new());
+        writer.write_batches(&batches.iter().collect::<Vec<_>>())?;
+        writer.finish()?;
+
+        let rows: Vec<serde_json::Value> = serde_json::from_slice(&writer.into_inner())
+            .map_err(|e| ArrowError::JsonError(e.to_string()))?;
+
+        Ok(Self {
+            row: rows.into_iter().next(),
+        })
+    }
+}
+
+/// Ticket to be used in a `do_get` call to retrieve the data prepared by an action
+#[derive(Serialize, Debug)]
+pub struct FlightTicket {
+    pub ticket: String,
+}
+
+/// Existence of a resource
+#[derive(Serialize, Debug)]
+pub struct ResourceExists {
+    pub exists: bool,
+}
+
+/// Approximated number of distinct values of a field. The value is an estimate
+/// and it should not be used where an exact count is required.
+#[derive(Serialize, Debug)]
+pub struct ApproxDistinct {
+    pub field: String,
+    pub approx_distinct_count: u64,
+}
+
+/// Number of chunks whose statistics have been recomputed
+#[derive(Serialize, Debug)]
+pub struct TopicRestat {
+    pub chunks: usize,
+}
+
+/// Outcome of the compaction of a topic
+#[derive(Serialize, Debug)]
+pub struct TopicCompact {
+    /// Number of small chunks merged
+    pub merged_chunks: usize,
+    /// Number of chunks created by merging them
+    pub created_chunks: usize,
+}
+
+impl From<types::TopicCompaction> for TopicCompact {
+    fn from(value: types::TopicCompaction) -> Self {
+        Self {
+            merged_chunks: value.merged_chunks,
+            created_chunks: value.created_chunks,
+        }
+    }
+}
+
+/// Chunk holding NaN or null values for a field
+#[derive(Serialize, Debug)]
+pub struct QualityChunk {
+    pub chunk_uuid: String,
+    /// Timestamp bounds (in milliseconds) of the chunk data, `null` if unknown
+    pub min_timestamp: Option<i64>,
+    pub max_timestamp: Option<i64>,
+    pub has_nan: bool,
+    pub has_null: bool,
+}
+
+/// Chunks of a topic holding NaN or null values for a field, ordered by timestamp
+#[derive(Serialize, Debug)]
+pub struct FieldQualityChunks {
+    pub field: String,
+    pub chunks: Vec<QualityChunk>,
+}
+
+impl From<types::ChunkQuality> for QualityChunk {
+    fn from(value: types::ChunkQuality) -> Self {
+        Self {
+            chunk_uuid: value.chunk_uuid.to_string(),
+            min_timestamp: value.timestamp_range.map(|(min, _)| min.into()),
+            max_timestamp: value.timestamp_range.map(|(_, max)| max.into()),
+            has_nan: value.has_nan,
+            has_null: value.has_null,
+        }
+    }
+}
+
+/// Aggregated statistics of a numeric field, `min` and `max` are `null` if the field
+/// holds no finite value. `null_count` and `completeness` are `null` if some chunk was
+/// created before null counts were tracked.
+#[derive(Serialize, Debug)]
+pub struct NumericColumnStats {
+    pub field: String,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub has_null: bool,
+    pub has_nan: bool,
+    pub has_inf: bool,
+    pub null_count: Option<i64>,
+    pub row_count: i64,
+    /// Fraction of non null values, `1 - null_count / row_count`
+    pub completeness: Option<f64>,
+}
+
+/// Aggregated statistics of a literal field, `min` and `max` are `null` if the field
+/// holds only null values. `null_count` and `completeness` are `null` if some chunk was
+/// created before null counts were tracked.
+#[derive(Serialize, Debug)]
+pub struct LiteralColumnStats {
+    pub field: String,
+    pub min: Option<String>,
+    pub max: Option<String>,
+    pub has_null: bool,
+    pub null_count: Option<i64>,
+    pub row_count: i64,
+    /// Fraction of non null values, `1 - null_count / row_count`
+    pub completeness: Option<f64>,
+}
+
+/// Value ranges of the fields of a topic, computed from the chunk statistics
+#[derive(Serialize, Debug)]
+pub struct TopicColumnStats {
+    pub numeric: Vec<NumericColumnStats>,
+    pub literal: Vec<LiteralColumnStats>,
+}
+
+impl From<types::TopicFieldsStats> for TopicColumnStats {
+    fn from(value: types::TopicFieldsStats) -> Self {
+        Self {
+            numeric: value
+                .numeric
+                .into_iter()
+                .map(|s| NumericColumnStats {
+                    completeness: s.completeness(),
+                    field: s.field,
+                    min: s.min,
+                    max: s.max,
+                    has_null: s.has_null,
+                    has_nan: s.has_nan,
+                    has_inf: s.has_inf,
+                    null_count: s.null_count,
+                    row_count: s.row_count,
+                })
+                .collect(),
+            literal: value
+                .literal
+                .into_iter()
+                .map(|s| LiteralColumnStats {
+                    completeness: s.completeness(),
+                    field: s.field,
+                    min: s.min,
+                    max: s.max,
+                    has_null: s.has_null,
+                    null_count: s.null_count,
+                    row_count: s.row_count,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct ReadCacheMetrics {
+    pub hits: u64,
+    pub misses: u64,
+    /// Ratio between hits and total lookups
+    pub hit_rate: f64,
+    pub insertions: u64,
+    pub evictions: u64,
+    /// Number of entries currently cached
+    pub entries: usize,
+    pub used_bytes: usize,
+    pub budget_bytes: usize,
+}
+
+impl From<query::ReadCacheStats> for ReadCacheMetrics {
+    fn from(value: query::ReadCacheStats) -> Self {
+        Self {
+            hits: value.hits,
+            misses: value.misses,
+            hit_rate: value.hit_rate(),
+            insertions: value.insertions,
+            evictions: value.evictions,
+            entries: value.entries,
+            used_bytes: value.used_bytes,
+            budget_bytes: value.budget_bytes,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct SchemaCacheMetrics {
+    pub hits: u64,
+    pub misses: u64,
+    /// Ratio between hits and total lookups
+    pub hit_rate: f64,
+    pub evictions: u64,
+    /// Number of schemas currently cached
+    pub entries: usize,
+    pub capacity: usize,
+}
+
+impl From<query::SchemaCacheStats> for SchemaCacheMetrics {
+    fn from(value: query::SchemaCacheStats) -> Self {
+        Self {
+            hits: value.hits,
+            misses: value.misses,
+            hit_rate: value.hit_rate(),
+            evictions: value.evictions,
+            entries: value.entries,
+            capacity: value.capacity,
+        }
+    }
+}
+
+/// Server runtime metrics
+#[derive(Serialize, Debug)]
+pub struct Metrics {
+    pub read_cache: ReadCacheMetrics,
+    pub schema_cache: SchemaCacheMetrics,
+}
+
+/// Effective configuration of the server.
+///
+/// Only non-secret values are reported: store credentials and database connection
+/// strings are never part of the response.
+#[derive(Serialize, Debug)]
+pub struct ServerConfig {
+    pub store: StoreConfig,
+    pub max_message_size_in_bytes: usize,
+    pub target_message_size_in_bytes: usize,
+    pub max_concurrent_chunk_queries: usize,
+    pub max_distinct_values: usize,
+    pub max_query_results: usize,
+    pub max_db_connections: u32,
+    pub min_db_connections: u32,
+    pub db_acquire_timeout_in_secs: u64,
+    pub read_cache_budget_in_bytes: usize,
+    pub schema_cache_size: usize,
+    pub missing_topic_policy: String,
+    /// Serialization format of automatically created topics, by ontology tag
+    pub default_formats: BTreeMap<String, String>,
+    pub min_notify_severity: String,
+    pub quantile_pruning: bool,
+    pub verify_chunk_checksums: bool,
+    pub allow_sequence_reopen: bool,
+    pub max_chunk_bytes: usize,
+    pub max_row_group_size: usize,
+    pub compaction_target_chunk_bytes: usize,
+    pub compaction_min_chunks: usize,
+    pub stats_copy_threshold: usize,
+    pub numeric_stats_sketch_size: usize,
+    pub ontology_tag_metadata_field: String,
+    pub store_retry_max_attempts: u32,
+    pub store_retry_base_delay_in_ms: u64,
+}
+
+impl ServerConfig {
+    pub fn new(params: &params::ConfigurablesParams, store: &store::StoreTarget) -> Self {
+        Self {
+            store: store.into(),
+            max_message_size_in_bytes: params.max_message_size_in_bytes,
+            target_message_size_in_bytes: params.target_message_size_in_bytes,
+            max_concurrent_chunk_queries: params.max_concurrent_chunk_queries,
+            max_distinct_values: params.max_distinct_values,
+            max_query_results: params.max_query_results,
+            max_db_connections: params.max_db_connections,
+            min_db_connections: params.min_db_connections,
+            db_acquire_timeout_in_secs: params.db_acquire_timeout_in_secs,
+            read_cache_budget_in_bytes: params.read_cache_budget_in_bytes,
+            schema_cache_size: params.schema_cache_size,
+            missing_topic_policy: params.missing_topic_policy.to_string(),
+            default_formats: params
+                .default_formats
+                .iter()
+                .map(|(tag, format)| (tag.clone(), format.to_string()))
+                .collect(),
+            min_notify_severity: params.min_notify_severity.to_string(),
+            quantile_pruning: params.quantile_pruning,
+            verify_chunk_checksums: params.verify_chunk_checksums,
+            allow_sequence_reopen: params.allow_sequence_reopen,
+            max_chunk_bytes: params.max_chunk_bytes,
+            max_row_group_size: params.max_row_group_size,
+            compaction_target_chunk_bytes: params.compaction_target_chunk_bytes,
+            compaction_min_chunks: params.compaction_min_chunks,
+            stats_copy_threshold: params.stats_copy_threshold,
+            numeric_stats_sketch_size: params.numeric_stats_sketch_size,
+            ontology_tag_metadata_field: params.ontology_tag_metadata_field.clone(),
+            store_retry_max_attempts: params.store_retry_max_attempts,
+            store_retry_base_delay_in_ms: params.store_retry_base_delay_in_ms,
+        }
+    }
+}
+
+/// Store backend in use, with the directory, bucket or container holding the data
+#[derive(Serialize, Debug)]
+pub struct StoreConfig {
+    pub backend: String,
+    pub location: String,
+}
+
+impl From<&store::StoreTarget> for StoreConfig {
+    fn from(value: &store::StoreTarget) -> Self {
+        let (backend, location) = match value {
+            store::StoreTarget::Filesystem(path) => ("filesystem", path),
+            store::StoreTarget::S3Compatible(bucket) => ("s3", bucket),
+            store::StoreTarget::Azure(container) => ("azure", container),
+        };
+        Self {
+            backend: backend.to_owned(),
+            location: location.clone(),
+        }
+    }
+}
+
+/// Arrow schema of a topic, represented as a tree of fields
+#[derive(Serialize, Debug)]
+pub struct TopicSchema {
+    pub fields: Vec<SchemaField>,
+}
+
+/// Field of a topic schema.
+///
+/// Nested types (structs, lists and maps) report their inner fields as `children`,
+/// e.g. a list reports a single child describing its items.
+#[derive(Serialize, Debug)]
+pub struct SchemaField {
+    pub name: String,
+    pub data_type: String,
+    pub nullable: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<SchemaField>,
+}
+
+impl From<&Field> for SchemaField {
+    fn from(field: &Field) -> Self {
+        let children: Vec<&Field> = match field.data_type() {
+            DataType::Struct(fields) => fields.iter().map(|f| f.as_ref()).collect(),
+            DataType::List(item) | DataType::LargeList(item) | DataType::FixedSizeList(item, _) => {
+                vec![item.as_ref()]
+            }
+            DataType::Map(entries, _) => vec![entries.as_ref()],
+            _ => Vec::new(),
+        };
+
+        Self {
+            name: field.name().clone(),
+            data_type: crate::arrow::data_type_name(field.data_type()),
+            nullable: field.is_nullable(),
+            children: children.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// Fields of the compared topics missing from a topic
+#[derive(Serialize, Debug)]
+pub struct TopicMissingFields {
+    pub topic: String,
+    /// Full nested paths of the missing fields (e.g. `pose.position.x`)
+    pub missing_fields: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TopicSchemaCompare {
+    /// Names of the compared topics
+    pub topics: Vec<String>,
+    /// `true` if all the compared topics share the same fields
+    pub consistent: bool,
+    /// Topics missing some of the fields found in the other topics
+    pub inconsistencies: Vec<TopicMissingFields>,
+}
+
+impl From<SchemaRef> for TopicSchema {
+    fn from(value: SchemaRef) -> Self {
+        Self {
+            fields: value.fields().iter().map(|f| f.as_ref().into()).collect(),
+        }
+    }
+}
+
+/// CSV data of a topic, each item holds a chunk of CSV lines (the header is
+/// in the first chunk, if requested)
+pub struct CsvStream(pub BoxStream<'static, Result<Vec<u8>, super::ActionError>>);
+
+/// Health status of a single server component
+#[derive(Serialize, Debug)]
+pub struct ComponentHealth {
+    pub healthy: bool,
+    /// Reason of the failure, if the component is not healthy
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl<E: std::fmt::Display> From<Result<(), E>> for ComponentHealth {
+    fn from(value: Result<(), E>) -> Self {
+        match value {
+            Ok(()) => Self {
+                healthy: true,
+                error: None,
+            },
+            Err(e) => Self {
+                healthy: false,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+}
+
+/// Component-by-component health status of the server
+#[derive(Serialize, Debug)]
+pub struct SystemHealth {
+    /// True only if all the components are healthy
+    pub healthy: bool,
+    pub store: ComponentHealth,
+    pub repository: ComponentHealth,
+    /// Usage of the primary database connection pool
+    pub database_pool: DatabasePool,
+    /// Usage of the read replica connection pool, if configured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replica_pool: Option<DatabasePool>,
+}
+
+/// Usage of a database connection pool
+#[derive(Serialize, Debug)]
+pub struct DatabasePool {
+    /// Number of open connections, both idle and in use
+    pub size: u32,
+    pub idle: usize,
+    pub in_use: usize,
+}
+
+impl From<repo::PoolStats> for DatabasePool {
+    fn from(value: repo::PoolStats) -> Self {
+        Self {
+            size: value.size,
+            idle: value.idle,
+            in_use: value.in_use,
         }
     }
 }
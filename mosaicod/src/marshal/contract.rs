@@ -0,0 +1,226 @@
+use crate::types::{self, MetadataError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::JsonMetadataBlob;
+
+/// Contract document of an ontology tag, e.g.
+/// `{ "metadata": { "rate_hz": { "type": "float", "range": [0, 1000] } }, "data": { "acc.x": { "type": "float", "range": [-160, 160] } } }`.
+///
+/// Fields are identified by their full (dotted) path and are required unless `nullable`.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct JsonOntologyContract {
+    #[serde(default)]
+    metadata: BTreeMap<String, JsonContractField>,
+    #[serde(default)]
+    data: BTreeMap<String, JsonContractField>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct JsonContractField {
+    #[serde(rename = "type")]
+    field_type: types::ContractFieldType,
+    #[serde(default)]
+    nullable: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    range: Option<[f64; 2]>,
+}
+
+impl JsonOntologyContract {
+    /// Validates the contract document and converts it to the contract of `ontology_tag`
+    pub fn into_types(
+        self,
+        ontology_tag: String,
+    ) -> Result<types::OntologyContract, MetadataError> {
+        Ok(types::OntologyContract {
+            ontology_tag,
+            metadata: into_contract_fields(self.metadata)?,
+            data: into_contract_fields(self.data)?,
+        })
+    }
+}
+
+fn into_contract_fields(
+    fields: BTreeMap<String, JsonContractField>,
+) -> Result<Vec<types::ContractField>, MetadataError> {
+    fields
+        .into_iter()
+        .map(|(name, field)| {
+            let range = match field.range {
+                Some([min, max]) if !field.field_type.is_numeric() || min > max => {
+                    return Err(MetadataError::DeserializationError(format!(
+                        "invalid range for field `{name}`"
+                    )));
+                }
+                Some([min, max]) => Some((min, max)),
+                None => None,
+            };
+
+            Ok(types::ContractField {
+                name,
+                field_type: field.field_type,
+                nullable: field.nullable,
+                range,
+            })
+        })
+        .collect()
+}
+
+fn from_contract_fields(fields: Vec<types::ContractField>) -> BTreeMap<String, JsonContractField> {
+    fields
+        .into_iter()
+        .map(|field| {
+            (
+                field.name,
+                JsonContractField {
+                    field_type: field.field_type,
+                    nullable: field.nullable,
+                    range: field.range.map(|(min, max)| [min, max]),
+                },
+            )
+        })
+        .collect()
+}
+
+impl From<types::OntologyContract> for JsonOntologyContract {
+    fn from(value: types::OntologyContract) -> Self {
+        Self {
+            metadata: from_contract_fields(value.metadata),
+            data: from_contract_fields(value.data),
+        }
+    }
+}
+
+impl TryFrom<serde_json::Value> for JsonOntologyContract {
+    type Error = MetadataError;
+
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        serde_json::from_value(value)
+            .map_err(|e| MetadataError::DeserializationError(e.to_string()))
+    }
+}
+
+impl TryFrom<JsonOntologyContract> for serde_json::Value {
+    type Error = MetadataError;
+
+    fn try_from(value: JsonOntologyContract) -> Result<Self, Self::Error> {
+        serde_json::to_value(value).map_err(|e| MetadataError::SerializationError(e.to_string()))
+    }
+}
+
+impl JsonMetadataBlob {
+    /// Returns the constraints of `fields` not satisfied by this metadata.
+    ///
+    /// Fields are looked up by their dotted path (e.g. `sensor.rate_hz`), a missing field
+    /// is treated as a null value.
+    pub fn contract_violations(
+        &self,
+        fields: &[types::ContractField],
+    ) -> Vec<types::ContractViolation> {
+        let mut violations = Vec::new();
+
+        for field in fields {
+            let value = field
+                .name
+                .split('.')
+                .try_fold(&self.0, |value, key| value.get(key))
+                .filter(|v| !v.is_null());
+
+            let Some(value) = value else {
+                if !field.nullable {
+                    violations.push(types::ContractViolation::new(&field.name, "is required"));
+                }
+                continue;
+            };
+
+            let type_matches = match field.field_type {
+                types::ContractFieldType::Boolean => value.is_boolean(),
+                types::ContractFieldType::Integer => value.is_i64() || value.is_u64(),
+                types::ContractFieldType::Float => value.is_number(),
+                types::ContractFieldType::Text => value.is_string(),
+            };
+            if !type_matches {
+                violations.push(types::ContractViolation::new(
+                    &field.name,
+                    format!("must be of type `{}`", field.field_type),
+                ));
+                continue;
+            }
+
+            if let Some(violation) = value.as_f64().and_then(|v| field.check_range(v)) {
+                violations.push(violation);
+            }
+        }
+
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::MetadataBlob;
+
+    fn contract(doc: &str) -> Result<types::OntologyContract, MetadataError> {
+        let doc: JsonOntologyContract = serde_json::from_str(doc).unwrap();
+        doc.into_types("imu".to_owned())
+    }
+
+    #[test]
+    fn metadata_contract() {
+        let contract = contract(
+            r#"{
+                "metadata": {
+                    "sensor.rate_hz": { "type": "float", "range": [0, 1000] },
+                    "sensor.model": { "type": "text" },
+                    "calibrated": { "type": "boolean", "nullable": true }
+                },
+                "data": { "acc.x": { "type": "float" } }
+            }"#,
+        )
+        .unwrap();
+
+        let mdata = JsonMetadataBlob::try_from_str(
+            r#"{ "sensor": { "rate_hz": 100, "model": "xsens" }, "calibrated": null }"#,
+        )
+        .unwrap();
+        assert!(mdata.contract_violations(&contract.metadata).is_empty());
+
+        let mdata =
+            JsonMetadataBlob::try_from_str(r#"{ "sensor": { "rate_hz": 2000, "model": 1 } }"#)
+                .unwrap();
+        let violations = mdata.contract_violations(&contract.metadata);
+        assert_eq!(
+            violations,
+            vec![
+                types::ContractViolation::new("sensor.model", "must be of type `text`"),
+                types::ContractViolation::new("sensor.rate_hz", "must be within [0, 1000]"),
+            ]
+        );
+
+        let mdata = JsonMetadataBlob::try_from_str("{}").unwrap();
+        assert_eq!(mdata.contract_violations(&contract.metadata).len(), 2);
+    }
+
+    #[test]
+    fn invalid_contract() {
+        // Ranges are only supported on numeric fields
+        assert!(
+            contract(r#"{ "metadata": { "a": { "type": "text", "range": [0, 1] } } }"#).is_err()
+        );
+        assert!(
+            contract(r#"{ "metadata": { "a": { "type": "float", "range": [1, 0] } } }"#).is_err()
+        );
+        assert!(contract(r#"{ "data": { "a": { "type": "text", "range": [0, 1] } } }"#).is_err());
+        assert!(contract(r#"{ "data": { "a": { "type": "float", "range": [0, 1] } } }"#).is_ok());
+
+        assert!(
+            serde_json::from_str::<JsonOntologyContract>(
+                r#"{ "data": { "a": { "type": "date" } } }"#
+            )
+            .is_err()
+        );
+    }
+}
@@ -1,12 +1,12 @@
-use crate::rw;
 use crate::types::{self, MetadataBlob, MetadataError};
+use crate::{params, rw};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 type Error = MetadataError;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct JsonMetadataBlob(serde_json::Value);
+pub struct JsonMetadataBlob(pub(super) serde_json::Value);
 
 impl MetadataBlob for JsonMetadataBlob {
     fn try_to_string(&self) -> Result<String, Error> {
@@ -25,12 +25,33 @@ impl MetadataBlob for JsonMetadataBlob {
     }
 }
 
+impl JsonMetadataBlob {
+    /// Shallow-merges `other` into this blob: top-level keys of `other` overwrite
+    /// the existing ones. If any of the two blobs is not a JSON object, `other`
+    /// replaces this blob.
+    pub fn merge(self, other: JsonMetadataBlob) -> JsonMetadataBlob {
+        match (self.0, other.0) {
+            (serde_json::Value::Object(mut current), serde_json::Value::Object(other)) => {
+                current.extend(other);
+                JsonMetadataBlob(serde_json::Value::Object(current))
+            }
+            (_, other) => JsonMetadataBlob(other),
+        }
+    }
+}
+
 impl From<JsonMetadataBlob> for serde_json::Value {
     fn from(value: JsonMetadataBlob) -> Self {
         value.0
     }
 }
 
+impl From<serde_json::Value> for JsonMetadataBlob {
+    fn from(value: serde_json::Value) -> Self {
+        JsonMetadataBlob(value)
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct JsonSequenceMetadata {
     pub user_metadata: JsonMetadataBlob,
@@ -95,6 +116,10 @@ impl JsonTopicMetadata {
                 "mosaico:context".to_owned(), //
                 "topic".to_owned(),
             ),
+            (
+                params::SCHEMA_METADATA_TIMESTAMP_UNIT.to_owned(),
+                self.properties.timestamp_unit.to_string(),
+            ),
             (
                 "mosaico:properties".to_owned(),
                 serde_json::to_string(&self.properties)
@@ -142,7 +167,17 @@ impl TryInto<Vec<u8>> for JsonTopicMetadata {
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct JsonTopicProperties {
     pub serialization_format: rw::Format,
-    pub ontology_tag: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ontology_tag: Option<String>,
+    /// Missing in topics created before the unit was recorded, which use milliseconds
+    #[serde(default)]
+    pub timestamp_unit: types::TimestampUnit,
+    #[serde(default)]
+    pub monotonic_timestamps: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_row_group_size: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub declared_schema: Option<types::DeclaredSchema>,
 }
 
 impl From<JsonTopicProperties> for types::TopicProperties {
@@ -150,6 +185,10 @@ impl From<JsonTopicProperties> for types::TopicProperties {
         Self {
             serialization_format: value.serialization_format,
             ontology_tag: value.ontology_tag,
+            timestamp_unit: value.timestamp_unit,
+            monotonic_timestamps: value.monotonic_timestamps,
+            max_row_group_size: value.max_row_group_size,
+            declared_schema: value.declared_schema,
         }
     }
 }
@@ -159,6 +198,10 @@ impl From<types::TopicProperties> for JsonTopicProperties {
         Self {
             serialization_format: value.serialization_format,
             ontology_tag: value.ontology_tag,
+            timestamp_unit: value.timestamp_unit,
+            monotonic_timestamps: value.monotonic_timestamps,
+            max_row_group_size: value.max_row_group_size,
+            declared_schema: value.declared_schema,
         }
     }
 }
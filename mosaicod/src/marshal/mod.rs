@@ -9,5 +9,14 @@ pub use actions::*;
 mod query;
 pub use query::*;
 
+mod ticket;
+pub use ticket::*;
+
+mod put;
+pub use put::*;
+
+mod contract;
+pub use contract::*;
+
 mod errors;
 pub use errors::*;
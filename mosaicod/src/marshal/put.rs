@@ -0,0 +1,92 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{rw, types};
+
+/// Report of a dry run write, sent back as metadata of the `do_put` result.
+///
+/// Each entry describes a chunk that the same write would have created, in order.
+#[derive(Serialize, Debug, Default)]
+pub struct DryRunReport {
+    pub chunks: Vec<DryRunChunk>,
+}
+
+/// Size and statistics of a chunk produced by a dry run write
+#[derive(Serialize, Debug)]
+pub struct DryRunChunk {
+    pub size_bytes: usize,
+    pub row_count: usize,
+    /// Timestamp bounds of the chunk, in the timestamp unit of the topic
+    pub min_timestamp: Option<i64>,
+    pub max_timestamp: Option<i64>,
+    /// Statistics of the columns, indexed by the (flattened) column name. Columns
+    /// without statistics are not reported.
+    pub columns: BTreeMap<String, DryRunColumnStats>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DryRunColumnStats {
+    Numeric {
+        /// [`None`] if the chunk holds no finite value
+        min: Option<f64>,
+        max: Option<f64>,
+        has_null: bool,
+        has_nan: bool,
+        has_inf: bool,
+    },
+    Text {
+        min: String,
+        max: String,
+        has_null: bool,
+    },
+    Boolean {
+        has_true: bool,
+        has_false: bool,
+        has_null: bool,
+    },
+}
+
+impl DryRunChunk {
+    pub fn new(stats: types::ColumnsStats, metadata: rw::ChunkMetadata) -> Self {
+        let columns = stats
+            .stats
+            .into_iter()
+            .filter_map(|(name, stats)| {
+                let stats = match stats {
+                    types::Stats::Numeric(s) => {
+                        // Min and max keep their placeholders until a finite value is found
+                        let finite = s.min <= s.max;
+                        DryRunColumnStats::Numeric {
+                            min: finite.then_some(s.min),
+                            max: finite.then_some(s.max),
+                            has_null: s.has_null,
+                            has_nan: s.has_nan,
+                            has_inf: s.has_inf,
+                        }
+                    }
+                    types::Stats::Text(s) => {
+                        let (min, max, has_null) = s.into_owned();
+                        DryRunColumnStats::Text { min, max, has_null }
+                    }
+                    types::Stats::Boolean(s) => DryRunColumnStats::Boolean {
+                        has_true: s.has_true,
+                        has_false: s.has_false,
+                        has_null: s.has_null,
+                    },
+                    types::Stats::Unsupported => return None,
+                };
+                Some((name, stats))
+            })
+            .collect();
+
+        Self {
+            size_bytes: metadata.size_bytes,
+            row_count: metadata.row_count,
+            min_timestamp: metadata.timestamp_bounds.map(|(min, _)| min),
+            max_timestamp: metadata.timestamp_bounds.map(|(_, max)| max),
+            columns,
+        }
+    }
+}
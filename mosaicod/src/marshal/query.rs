@@ -1,7 +1,40 @@
-use crate::query;
+use crate::{params, query, rw};
 use serde::Deserialize;
+use serde::de::DeserializeOwned;
 use std::collections::HashMap;
 
+type JsonObject = serde_json::Map<String, serde_json::Value>;
+
+/// Builds the error reported for a malformed value found at `path` in the query document
+fn bad_query(path: &str, err: impl std::fmt::Display) -> query::Error {
+    if path.is_empty() {
+        return query::Error::DeserializationError(err.to_string());
+    }
+    query::Error::DeserializationError(format!("at `{path}` :: {err}"))
+}
+
+/// Deserializes the value found at `path` in the query document
+fn from_value_at<T: DeserializeOwned>(
+    value: serde_json::Value,
+    path: &str,
+) -> Result<T, query::Error> {
+    serde_json::from_value(value).map_err(|e| bad_query(path, e))
+}
+
+fn into_object(value: serde_json::Value, path: &str) -> Result<JsonObject, query::Error> {
+    match value {
+        serde_json::Value::Object(object) => Ok(object),
+        _ => Err(bad_query(path, "expected an object")),
+    }
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        return key.to_owned();
+    }
+    format!("{path}.{key}")
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
 enum Value {
@@ -52,11 +85,27 @@ impl TryInto<query::Integer> for Value {
     }
 }
 
+/// Largest integer that a JSON number parsed as `f64` represents exactly (2^53)
+const MAX_EXACT_FLOAT_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+/// Converts a float to an integer timestamp, failing if the conversion would lose precision.
+///
+/// JSON numbers that are not valid `i64` (e.g. `1.5` or values above `i64::MAX`)
+/// are parsed as `f64`, that can't hold large timestamps (e.g. nanoseconds since epoch).
+fn exact_timestamp(v: f64) -> Result<i64, query::OpError> {
+    if v.fract() == 0.0 && v.abs() <= MAX_EXACT_FLOAT_INTEGER {
+        Ok(v as i64)
+    } else {
+        Err(query::OpError::TimestampNotRepresentable(v.to_string()))
+    }
+}
+
 impl TryInto<query::Timestamp> for Value {
     type Error = query::OpError;
     fn try_into(self) -> Result<query::Timestamp, Self::Error> {
         match self {
             Value::Integer(v) => Ok(v.into()),
+            Value::Float(v) => Ok(exact_timestamp(v)?.into()),
             _ => Err(Self::Error::WrongType),
         }
     }
@@ -82,10 +131,65 @@ enum Op {
     Nex,
     #[serde(rename = "$between")]
     Between([Value; 2]),
+    #[serde(rename = "$between_exclusive_end")]
+    BetweenExclusiveEnd([Value; 2]),
     #[serde(rename = "$in")]
     In(Vec<Value>),
+    #[serde(rename = "$not_in")]
+    NotIn(Vec<Value>),
     #[serde(rename = "$match")]
     Match(Value),
+    #[serde(rename = "$imatch")]
+    IMatch(Value),
+    #[serde(rename = "$regex")]
+    Regex(Value),
+    #[serde(rename = "$starts_with")]
+    StartsWith(Value),
+    #[serde(rename = "$ends_with")]
+    EndsWith(Value),
+    #[serde(rename = "$is_nan")]
+    IsNan,
+    #[serde(rename = "$is_not_nan")]
+    IsNotNan,
+    #[serde(rename = "$has_nan")]
+    HasNan,
+    #[serde(rename = "$has_null")]
+    HasNull,
+}
+
+impl Op {
+    /// Ensures that the values compared with a timestamp column are exact integers
+    fn into_timestamp_op(self) -> Result<Self, query::OpError> {
+        let ts = |v: Value| -> Result<Value, query::OpError> {
+            match v {
+                Value::Float(v) => Ok(Value::Integer(exact_timestamp(v)?)),
+                v => Ok(v),
+            }
+        };
+        let ts_all = |values: Vec<Value>| values.into_iter().map(ts).collect::<Result<_, _>>();
+
+        Ok(match self {
+            Op::Eq(v) => Op::Eq(ts(v)?),
+            Op::Neq(v) => Op::Neq(ts(v)?),
+            Op::Leq(v) => Op::Leq(ts(v)?),
+            Op::Geq(v) => Op::Geq(ts(v)?),
+            Op::Lt(v) => Op::Lt(ts(v)?),
+            Op::Gt(v) => Op::Gt(ts(v)?),
+            Op::Between([min, max]) => Op::Between([ts(min)?, ts(max)?]),
+            Op::BetweenExclusiveEnd([min, max]) => Op::BetweenExclusiveEnd([ts(min)?, ts(max)?]),
+            Op::In(values) => Op::In(ts_all(values)?),
+            Op::NotIn(values) => Op::NotIn(ts_all(values)?),
+            op => op,
+        })
+    }
+}
+
+/// Ensures that set based operations are not called with an empty set of values
+fn non_empty(values: Vec<Value>) -> Result<Vec<Value>, query::OpError> {
+    if values.is_empty() {
+        return Err(query::OpError::EmptySet);
+    }
+    Ok(values)
 }
 
 impl TryInto<query::Op<query::Text>> for Op {
@@ -99,15 +203,35 @@ impl TryInto<query::Op<query::Text>> for Op {
             Op::Geq(_) => return Err(query::OpError::UnsupportedOperation),
             Op::Lt(_) => return Err(query::OpError::UnsupportedOperation),
             Op::Gt(_) => return Err(query::OpError::UnsupportedOperation),
-            Op::Between(_) => return Err(query::OpError::UnsupportedOperation),
+            Op::Between(_) | Op::BetweenExclusiveEnd(_) => {
+                return Err(query::OpError::UnsupportedOperation);
+            }
             Op::Ex => query::Op::Ex,
             Op::Nex => query::Op::Nex,
             Op::In(vec) => query::Op::In(
-                vec.into_iter()
+                non_empty(vec)?
+                    .into_iter()
+                    .map(|v| v.try_into())
+                    .collect::<Result<_, _>>()?,
+            ),
+            Op::NotIn(vec) => query::Op::NotIn(
+                non_empty(vec)?
+                    .into_iter()
                     .map(|v| v.try_into())
                     .collect::<Result<_, _>>()?,
             ),
             Op::Match(v) => query::Op::Match(v.try_into()?),
+            Op::IMatch(v) => query::Op::IMatch(v.try_into()?),
+            Op::Regex(v) => {
+                let pattern: query::Text = v.try_into()?;
+                query::validate_regex(&pattern)?;
+                query::Op::Regex(pattern)
+            }
+            Op::StartsWith(v) => query::Op::StartsWith(v.try_into()?),
+            Op::EndsWith(v) => query::Op::EndsWith(v.try_into()?),
+            Op::IsNan | Op::IsNotNan | Op::HasNan | Op::HasNull => {
+                return Err(query::OpError::UnsupportedOperation);
+            }
         })
     }
 }
@@ -127,12 +251,32 @@ impl TryInto<query::Op<query::Timestamp>> for Op {
             Op::Between([min, max]) => {
                 query::Op::Between(query::Range::try_new(min.try_into()?, max.try_into()?)?)
             }
+            Op::BetweenExclusiveEnd([min, max]) => {
+                query::Op::BetweenHalfOpen(query::Range::try_new(min.try_into()?, max.try_into()?)?)
+            }
             Op::In(vec) => query::Op::In(
-                vec.into_iter()
+                non_empty(vec)?
+                    .into_iter()
                     .map(|v| v.try_into())
                     .collect::<Result<_, _>>()?,
             ),
-            Op::Match(_) => return Err(Self::Error::UnsupportedOperation),
+            Op::NotIn(vec) => query::Op::NotIn(
+                non_empty(vec)?
+                    .into_iter()
+                    .map(|v| v.try_into())
+                    .collect::<Result<_, _>>()?,
+            ),
+            Op::Match(_)
+            | Op::IMatch(_)
+            | Op::Regex(_)
+            | Op::StartsWith(_)
+            | Op::EndsWith(_)
+            | Op::IsNan
+            | Op::IsNotNan
+            | Op::HasNan
+            | Op::HasNull => {
+                return Err(Self::Error::UnsupportedOperation);
+            }
         })
     }
 }
@@ -152,17 +296,126 @@ impl TryInto<query::Op<query::Value>> for Op {
             Op::Between([min, max]) => {
                 query::Op::Between(query::Range::try_new(min.into(), max.into())?)
             }
-            Op::In(vec) => query::Op::In(vec.into_iter().map(Into::into).collect()),
+            Op::BetweenExclusiveEnd([min, max]) => {
+                query::Op::BetweenHalfOpen(query::Range::try_new(min.into(), max.into())?)
+            }
+            Op::In(vec) => query::Op::In(non_empty(vec)?.into_iter().map(Into::into).collect()),
+            Op::NotIn(vec) => {
+                query::Op::NotIn(non_empty(vec)?.into_iter().map(Into::into).collect())
+            }
             Op::Match(v) => query::Op::Match(v.into()),
+            Op::IMatch(v) => query::Op::IMatch(v.into()),
+            Op::Regex(v) => {
+                let v: query::Value = v.into();
+                if let query::Value::Text(pattern) = &v {
+                    query::validate_regex(pattern)?;
+                }
+                query::Op::Regex(v)
+            }
+            Op::StartsWith(v) => query::Op::StartsWith(v.into()),
+            Op::EndsWith(v) => query::Op::EndsWith(v.into()),
+            Op::IsNan => query::Op::IsNan,
+            Op::IsNotNan => query::Op::IsNotNan,
+            Op::HasNan => query::Op::HasNan,
+            Op::HasNull => query::Op::HasNull,
         })
     }
 }
 
-#[derive(Debug, Deserialize)]
+/// A set of field expressions combined in `AND`, optionally holding nested groups.
+///
+/// Nested groups are expressed with the `any_of` (`OR`) and `all_of` (`AND`) keys, e.g.
+/// `{ "imu.acc.x": { "$gt": 0 }, "any_of": [ { "gps.lat": { "$gt": 10 } }, { "gps.lat": { "$lt": -10 } } ] }`.
+///
+/// Groups are parsed recursively (see [`ExprGroup::parse`]) so that errors report the
+/// path of the offending value, e.g. `ontology.any_of[1].gps.lat`.
+#[derive(Debug, Default)]
+struct ExprGroup {
+    any_of: Option<Vec<ExprGroup>>,
+    all_of: Option<Vec<ExprGroup>>,
+    fields: HashMap<String, Op>,
+}
+
+impl ExprGroup {
+    /// Parses the group found at `path` in the query document
+    fn parse(value: serde_json::Value, path: &str) -> Result<Self, query::Error> {
+        let mut group = Self::default();
+
+        for (key, value) in into_object(value, path)? {
+            let key_path = join_path(path, &key);
+
+            match key.as_str() {
+                "any_of" | "all_of" => {
+                    let serde_json::Value::Array(items) = value else {
+                        return Err(bad_query(&key_path, "expected an array of groups"));
+                    };
+                    let groups = items
+                        .into_iter()
+                        .enumerate()
+                        .map(|(idx, item)| Self::parse(item, &format!("{key_path}[{idx}]")))
+                        .collect::<Result<_, _>>()?;
+
+                    if key == "any_of" {
+                        group.any_of = Some(groups);
+                    } else {
+                        group.all_of = Some(groups);
+                    }
+                }
+                _ => {
+                    let op = from_value_at(value, &key_path)?;
+                    group.fields.insert(key, op);
+                }
+            }
+        }
+
+        Ok(group)
+    }
+}
+
+#[derive(Debug, Default)]
 struct Query {
     sequence: Option<Sequence>,
     topic: Option<Topic>,
-    ontology: Option<HashMap<String, Op>>,
+    layer: Option<Layer>,
+    ontology: Option<ExprGroup>,
+    /// Inclusive window (in milliseconds) of the data evaluated by the ontology filter
+    time_range: Option<[Value; 2]>,
+}
+
+const QUERY_KEYS: [&str; 5] = ["sequence", "topic", "layer", "ontology", "time_range"];
+
+impl Query {
+    /// Parses a query document, unknown keys are reported as errors so that typos
+    /// (e.g. `ontolgy`) do not silently widen the query
+    fn parse(value: serde_json::Value) -> Result<Self, query::Error> {
+        let mut query = Self::default();
+
+        for (key, value) in into_object(value, "")? {
+            // Null sections are equivalent to missing ones
+            if value.is_null() {
+                continue;
+            }
+
+            match key.as_str() {
+                "sequence" => query.sequence = Some(from_value_at(value, &key)?),
+                "topic" => query.topic = Some(from_value_at(value, &key)?),
+                "layer" => query.layer = Some(from_value_at(value, &key)?),
+                "ontology" => query.ontology = Some(ExprGroup::parse(value, &key)?),
+                "time_range" => query.time_range = Some(from_value_at(value, &key)?),
+                _ => {
+                    return Err(bad_query(
+                        &key,
+                        format!(
+                            "unknown field, expected one of {}",
+                            QUERY_KEYS.map(|k| format!("`{k}`")).join(", ")
+                        ),
+                    ));
+                }
+            }
+        }
+
+        Ok(query)
+    }
 }
 
 impl TryInto<query::Filter> for Query {
@@ -171,37 +424,111 @@ impl TryInto<query::Filter> for Query {
         Ok(query::Filter {
             sequence: self.sequence.map(|v| v.try_into()).transpose()?,
             topic: self.topic.map(|v| v.try_into()).transpose()?,
+            layer: self.layer.map(|v| v.try_into()).transpose()?,
             ontology: self.ontology.map(|v| v.try_into()).transpose()?,
+            time_range: self
+                .time_range
+                .map(
+                    |[start, end]| -> Result<query::Range<query::Timestamp>, _> {
+                        query::Range::try_new(start.try_into()?, end.try_into()?)
+                    },
+                )
+                .transpose()
+                .map_err(|e| Self::Error::OpError {
+                    field: "time_range".to_owned(),
+                    err: e,
+                })?,
+        })
+    }
+}
+
+fn ontology_fields_into_exprs(
+    fields: HashMap<String, Op>,
+) -> Result<Vec<query::Expr<query::Value>>, query::Error> {
+    fields
+        .into_iter()
+        .map(|(col, op)| {
+            let op_err = |e: query::OpError| query::Error::OpError {
+                field: col.clone(),
+                err: e,
+            };
+
+            let field = query::OntologyField::try_new(col.clone())?;
+
+            // Timestamps are compared as integers, so they must be exact
+            let op = if col.split_once('.').map(|(_, f)| f)
+                == Some(params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP)
+            {
+                op.into_timestamp_op().map_err(op_err)?
+            } else {
+                op
+            };
+            let op: query::Op<query::Value> = op.try_into().map_err(op_err)?;
+
+            Ok::<query::Expr<query::Value>, query::Error>((field, op).into())
         })
+        .collect()
+}
+
+impl ExprGroup {
+    /// Converts the nested groups into a list of [`query::ExprGroup`]
+    fn try_into_subgroups(
+        any_of: Option<Vec<ExprGroup>>,
+        all_of: Option<Vec<ExprGroup>>,
+    ) -> Result<Vec<query::ExprGroup<query::Value>>, query::Error> {
+        let mut subgroups = Vec::new();
+
+        for (groups, connector) in [
+            (any_of, query::Connector::Or),
+            (all_of, query::Connector::And),
+        ] {
+            if let Some(groups) = groups {
+                let groups = groups
+                    .into_iter()
+                    .map(|g| g.try_into())
+                    .collect::<Result<Vec<query::ExprGroup<query::Value>>, query::Error>>()?;
+
+                subgroups.push(
+                    query::ExprGroup::new(Vec::new())
+                        .with_subgroups(groups)
+                        .with_connector(connector),
+                );
+            }
+        }
+
+        Ok(subgroups)
     }
 }
 
-impl TryInto<query::OntologyFilter> for HashMap<String, Op> {
+impl TryInto<query::ExprGroup<query::Value>> for ExprGroup {
+    type Error = query::Error;
+    fn try_into(self) -> Result<query::ExprGroup<query::Value>, Self::Error> {
+        Ok(
+            query::ExprGroup::new(ontology_fields_into_exprs(self.fields)?)
+                .with_subgroups(Self::try_into_subgroups(self.any_of, self.all_of)?),
+        )
+    }
+}
+
+impl TryInto<query::OntologyFilter> for ExprGroup {
     type Error = query::Error;
     fn try_into(self) -> Result<query::OntologyFilter, Self::Error> {
-        let map = self
+        let map = ontology_fields_into_exprs(self.fields)?
             .into_iter()
-            .map(|(col, op)| {
-                let op = op.try_into().map_err(|e| query::Error::OpError {
-                    field: col.clone(),
-                    err: e,
-                })?;
+            .map(|e| e.into_parts())
+            .collect();
 
-                let col = query::OntologyField::try_new(col)?;
-
-                Ok::<(query::OntologyField, query::Op<query::Value>), Self::Error>((col, op))
-            })
-            .collect::<Result<_, _>>()?;
-
-        Ok(query::OntologyFilter::new(map))
+        Ok(query::OntologyFilter::new(map)
+            .with_groups(Self::try_into_subgroups(self.any_of, self.all_of)?))
     }
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct Sequence {
     name: Option<Op>,
     created_timestamp: Option<Op>,
-    user_metadata: Option<HashMap<String, Op>>,
+    user_metadata: Option<JsonObject>,
 }
 
 impl TryInto<query::SequenceFilter> for Sequence {
@@ -222,18 +549,22 @@ impl TryInto<query::SequenceFilter> for Sequence {
                     field: "sequence.created_timestamp".to_owned(),
                     err: e,
                 })?,
-            user_metadata: self.user_metadata.map(|v| v.try_into()).transpose()?,
+            user_metadata: self
+                .user_metadata
+                .map(|v| ExprGroup::parse(v.into(), "sequence.user_metadata")?.try_into())
+                .transpose()?,
         })
     }
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Topic {
     name: Option<Op>,
     created_timestamp: Option<Op>,
     ontology_tag: Option<Op>,
     serialization_format: Option<Op>,
-    user_metadata: Option<HashMap<String, Op>>,
+    user_metadata: Option<JsonObject>,
 }
 
 impl TryInto<query::TopicFilter> for Topic {
@@ -268,32 +599,236 @@ impl TryInto<query::TopicFilter> for Topic {
 
             serialization_format: self
                 .serialization_format
-                .map(|v| v.try_into())
+                .map(|v| v.try_into().and_then(validate_serialization_format))
                 .transpose()
                 .map_err(|e| Self::Error::OpError {
                     field: "topic.serialization_format".to_owned(),
                     err: e,
                 })?,
 
-            user_metadata: self.user_metadata.map(|v| v.try_into()).transpose()?,
+            user_metadata: self
+                .user_metadata
+                .map(|v| ExprGroup::parse(v.into(), "topic.user_metadata")?.try_into())
+                .transpose()?,
         })
     }
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Layer {
+    name: Option<Op>,
+}
+
+impl TryInto<query::LayerFilter> for Layer {
+    type Error = query::Error;
+
+    fn try_into(self) -> Result<query::LayerFilter, Self::Error> {
+        Ok(query::LayerFilter {
+            name: self.name.map(|v| v.try_into()).transpose().map_err(|e| {
+                Self::Error::OpError {
+                    field: "layer.name".to_owned(),
+                    err: e,
+                }
+            })?,
+        })
+    }
+}
+
+/// Ensures that the values compared with the serialization format are valid formats,
+/// so that a misspelled format is reported instead of silently matching nothing.
+fn validate_serialization_format(
+    op: query::Op<query::Text>,
+) -> Result<query::Op<query::Text>, query::OpError> {
+    let values = match &op {
+        query::Op::Eq(v) | query::Op::Neq(v) => std::slice::from_ref(v),
+        query::Op::In(v) | query::Op::NotIn(v) => v.as_slice(),
+        _ => &[],
+    };
+
+    if let Some(invalid) = values.iter().find(|v| v.parse::<rw::Format>().is_err()) {
+        return Err(query::OpError::InvalidValue(invalid.clone()));
+    }
+
+    Ok(op)
+}
+
 pub fn query_filter_from_string(s: &str) -> Result<query::Filter, super::Error> {
-    let query: Query =
+    let value: serde_json::Value =
         serde_json::from_str(s).map_err(|e| super::Error::DeserializationError(e.to_string()))?;
-    let query: query::Filter = query
-        .try_into()
+    query_filter_from_serde_value(value)
+}
+
+pub fn sequence_filter_from_serde_value(
+    v: serde_json::Value,
+) -> Result<query::SequenceFilter, super::Error> {
+    let filter: query::SequenceFilter = from_value_at::<Sequence>(v, "sequence")
+        .and_then(TryInto::try_into)
         .map_err(|e: query::Error| super::Error::DeserializationError(e.to_string()))?;
-    Ok(query)
+    Ok(filter)
+}
+
+pub fn topic_filter_from_serde_value(
+    v: serde_json::Value,
+) -> Result<query::TopicFilter, super::Error> {
+    let filter: query::TopicFilter = from_value_at::<Topic>(v, "topic")
+        .and_then(TryInto::try_into)
+        .map_err(|e: query::Error| super::Error::DeserializationError(e.to_string()))?;
+    Ok(filter)
 }
 
 pub fn query_filter_from_serde_value(v: serde_json::Value) -> Result<query::Filter, super::Error> {
-    let query: Query =
-        serde_json::from_value(v).map_err(|e| super::Error::DeserializationError(e.to_string()))?;
-    let query: query::Filter = query
-        .try_into()
+    let query: query::Filter = Query::parse(v)
+        .and_then(TryInto::try_into)
         .map_err(|e: query::Error| super::Error::DeserializationError(e.to_string()))?;
     Ok(query)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn topic_serialization_format() {
+        let filter = query_filter_from_string(
+            r#"{ "topic": { "serialization_format": { "$in": ["image", "ragged"] } } }"#,
+        )
+        .unwrap();
+        assert!(filter.topic.unwrap().serialization_format.is_some());
+
+        // Misspelled formats are reported
+        let filter = query_filter_from_string(
+            r#"{ "topic": { "serialization_format": { "$eq": "Image" } } }"#,
+        );
+        assert!(filter.is_err());
+    }
+
+    #[test]
+    fn ontology_with_time_range() {
+        let filter = query_filter_from_string(
+            r#"{
+                "topic": { "user_metadata": { "driver": { "$eq": "jon" } } },
+                "ontology": { "imu.acc.x": { "$gt": 1.0 } },
+                "time_range": [1000, 2000]
+            }"#,
+        )
+        .unwrap();
+        assert!(filter.topic.is_some());
+        assert!(filter.ontology.is_some());
+        let range = filter.time_range.unwrap();
+        assert_eq!(range.min, 1000i64.into());
+        assert_eq!(range.max, 2000i64.into());
+
+        let filter = query_filter_from_string(r#"{ "time_range": [2000, 1000] }"#);
+        assert!(filter.is_err());
+    }
+
+    #[test]
+    fn timestamp_precision() {
+        let filter = query_filter_from_string(r#"{ "time_range": [1000.0, 2000] }"#).unwrap();
+        assert_eq!(filter.time_range.unwrap().min, 1000i64.into());
+
+        // Nanoseconds since epoch are above 2^53 and can't be exact as floats
+        let err = query_filter_from_string(r#"{ "time_range": [0, 1.7e18] }"#).unwrap_err();
+        assert!(err.to_string().contains("2^53"));
+
+        // Values above `i64::MAX` fall back to floats
+        let filter = query_filter_from_string(r#"{ "time_range": [0, 10000000000000000000] }"#);
+        assert!(filter.is_err());
+
+        let filter = query_filter_from_string(
+            r#"{ "ontology": { "imu.timestamp_ns": { "$between": [1000, 1500.5] } } }"#,
+        );
+        assert!(filter.is_err());
+    }
+
+    #[test]
+    fn between_exclusive_end() {
+        let filter = query_filter_from_string(
+            r#"{ "ontology": { "imu.acc.x": { "$between_exclusive_end": [1.0, 2.0] } } }"#,
+        )
+        .unwrap();
+        let group = filter.ontology.unwrap().into_expr_group();
+        let (_, op) = group.group.into_iter().next().unwrap().into_parts();
+        assert!(matches!(op, query::Op::BetweenHalfOpen(_)));
+
+        // Ranges are still required to be non decreasing
+        let filter = query_filter_from_string(
+            r#"{ "ontology": { "imu.acc.x": { "$between_exclusive_end": [2.0, 1.0] } } }"#,
+        );
+        assert!(filter.is_err());
+    }
+
+    #[test]
+    fn regex() {
+        let filter = query_filter_from_string(
+            r#"{ "topic": { "name": { "$regex": "^seq_[0-9]+/imu$" } }, "ontology": { "image.encoding": { "$regex": "rgb|bgr" } } }"#,
+        )
+        .unwrap();
+        let topic = filter.topic.unwrap();
+        assert!(matches!(topic.name, Some(query::Op::Regex(p)) if p == "^seq_[0-9]+/imu$"));
+
+        let err = query_filter_from_string(r#"{ "topic": { "name": { "$regex": "seq_(" } } }"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("invalid regex"), "{err}");
+
+        let err = query_filter_from_string(
+            r#"{ "ontology": { "image.encoding": { "$regex": "[rgb" } } }"#,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("invalid regex"), "{err}");
+
+        // Word boundaries are not matched in the same way by the catalog
+        let err = query_filter_from_string(r#"{ "topic": { "name": { "$regex": "\bimu\b" } } }"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("unsupported assertion"), "{err}");
+    }
+
+    #[test]
+    fn layer() {
+        let filter = query_filter_from_string(
+            r#"{ "layer": { "name": { "$eq": "curated" } }, "ontology": { "imu.acc.x": { "$gt": 1.0 } } }"#,
+        )
+        .unwrap();
+        let layer = filter.layer.unwrap();
+        assert!(matches!(layer.name, Some(query::Op::Eq(name)) if name == "curated"));
+
+        let filter = query_filter_from_string(r#"{ "layer": { "name": { "$gt": 1.0 } } }"#);
+        assert!(filter.is_err());
+    }
+
+    #[test]
+    fn errors_report_json_path() {
+        let err = query_filter_from_string(r#"{ "ontolgy": { "imu.acc.x": { "$gt": 1.0 } } }"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("`ontolgy`"), "{err}");
+        assert!(err.contains("unknown field"), "{err}");
+
+        let err = query_filter_from_string(r#"{ "topic": { "nmae": { "$eq": "a" } } }"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("at `topic`") && err.contains("nmae"), "{err}");
+
+        let err = query_filter_from_string(
+            r#"{ "ontology": { "any_of": [ { "gps.lat": { "$gt": 10 } }, { "gps.lat": { "$gtt": 1 } } ] } }"#,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("at `ontology.any_of[1].gps.lat`"), "{err}");
+
+        let err = query_filter_from_string(
+            r#"{ "sequence": { "user_metadata": { "all_of": { "a.b": { "$eq": 1 } } } } }"#,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("at `sequence.user_metadata.all_of`"), "{err}");
+
+        // Null sections are still accepted
+        let filter = query_filter_from_string(r#"{ "topic": null, "time_range": null }"#).unwrap();
+        assert!(filter.topic.is_none());
+    }
+}
@@ -0,0 +1,322 @@
+use super::Error;
+use crate::{query, types};
+use serde::{Deserialize, Serialize};
+
+/// Data request carried by a flight ticket in a `do_get` call.
+///
+/// A ticket can contain just the topic name, in this case the whole topic is returned.
+/// Otherwise the ticket is a json object that allows to restrict the returned data
+/// to a (inclusive) time window and/or to a subset of columns, e.g.
+///
+/// ```json
+/// { "topic": "my_sequence/my_topic", "time_range": [1000, 2000], "projection": ["pose"] }
+/// ```
+///
+/// The timestamp column is always returned, even if not listed in the projection.
+///
+/// Data can also be restricted to the rows matching exactly a set of timestamps, e.g.
+///
+/// ```json
+/// { "topic": "my_sequence/my_topic", "timestamps": [1000, 1500, 2000] }
+/// ```
+///
+/// Rows are returned in ascending timestamp order, unless a different `order` is requested.
+/// Rows can also be sorted by the value of a (possibly nested) field, e.g.
+///
+/// ```json
+/// { "topic": "my_sequence/my_topic", "order": "desc", "order_by": "pose.position.x" }
+/// ```
+///
+/// High rate topics can be decimated, keeping one row every `decimate` rows. Decimation is
+/// applied after the timestamp ordering and before sorting by a field, e.g.
+///
+/// ```json
+/// { "topic": "my_sequence/my_topic", "decimate": 1000 }
+/// ```
+///
+/// A `row_number` column, holding the position of each row in timestamp order
+/// (starting from 0), can be added to the returned data, e.g.
+///
+/// ```json
+/// { "topic": "my_sequence/my_topic", "time_range": [1000, 2000], "row_number": true }
+/// ```
+///
+/// The duration of a read can be bounded with `max_duration_ms`, when the time budget is
+/// exhausted the stream ends early and a final message with `{"truncated": true}` as
+/// application metadata is sent, e.g.
+///
+/// ```json
+/// { "topic": "my_sequence/my_topic", "max_duration_ms": 500 }
+/// ```
+///
+/// Rows can be paginated with `offset` and `limit`, pages are taken after all the other
+/// options (including the ordering) are applied. The total number of rows of the read is
+/// reported in the `mosaico:total_rows` metadata field of the returned schema, e.g.
+///
+/// ```json
+/// { "topic": "my_sequence/my_topic", "offset": 200, "limit": 100 }
+/// ```
+///
+/// Numeric fields can be aggregated in time windows (see [`TicketAggregate`]), e.g.
+///
+/// ```json
+/// { "topic": "my_sequence/my_topic", "aggregate": { "window_ms": 1000, "fields": [{ "field": "acc.x", "function": "avg" }] } }
+/// ```
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TopicTicket {
+    pub topic: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    time_range: Option<[i64; 2]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub projection: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timestamps: Option<Vec<i64>>,
+    #[serde(default, skip_serializing_if = "query::SortOrder::is_asc")]
+    pub order: query::SortOrder,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decimate: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aggregate: Option<TicketAggregate>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub row_number: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_duration_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+/// Time bucketed aggregation of the fields of a topic
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TicketAggregate {
+    /// Length of the time windows, in milliseconds
+    pub window_ms: u64,
+    pub fields: Vec<AggregateField>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AggregateField {
+    pub field: String,
+    pub function: query::AggFn,
+}
+
+impl TopicTicket {
+    pub fn new(topic: String) -> Self {
+        Self {
+            topic,
+            time_range: None,
+            projection: None,
+            timestamps: None,
+            order: query::SortOrder::Asc,
+            order_by: None,
+            decimate: None,
+            aggregate: None,
+            row_number: false,
+            max_duration_ms: None,
+            offset: None,
+            limit: None,
+        }
+    }
+
+    pub fn with_time_range(mut self, start: types::Timestamp, end: types::Timestamp) -> Self {
+        self.time_range = Some([start.into(), end.into()]);
+        self
+    }
+
+    pub fn with_projection(mut self, columns: Vec<String>) -> Self {
+        self.projection = Some(columns);
+        self
+    }
+
+    pub fn with_timestamps(mut self, timestamps: Vec<types::Timestamp>) -> Self {
+        self.timestamps = Some(timestamps.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn with_order(mut self, order: query::SortOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn with_order_by(mut self, field: String) -> Self {
+        self.order_by = Some(field);
+        self
+    }
+
+    pub fn with_decimate(mut self, n: usize) -> Self {
+        self.decimate = Some(n);
+        self
+    }
+
+    pub fn with_aggregate(mut self, aggregate: TicketAggregate) -> Self {
+        self.aggregate = Some(aggregate);
+        self
+    }
+
+    pub fn with_row_number(mut self) -> Self {
+        self.row_number = true;
+        self
+    }
+
+    pub fn with_max_duration(mut self, duration: std::time::Duration) -> Self {
+        self.max_duration_ms = Some(duration.as_millis() as u64);
+        self
+    }
+
+    pub fn with_page(mut self, offset: usize, limit: usize) -> Self {
+        self.offset = Some(offset);
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Returns `true` if only a page of the rows is requested
+    pub fn is_paginated(&self) -> bool {
+        self.offset.is_some() || self.limit.is_some()
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        serde_json::to_vec(self).map_err(|e| Error::SerializationError(e.to_string()))
+    }
+
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let ticket =
+            std::str::from_utf8(bytes).map_err(|e| Error::DeserializationError(e.to_string()))?;
+
+        if ticket.trim_start().starts_with('{') {
+            return serde_json::from_str(ticket)
+                .map_err(|e| Error::DeserializationError(e.to_string()));
+        }
+
+        Ok(Self::new(ticket.to_owned()))
+    }
+
+    /// Returns the requested time window, if any
+    pub fn time_range(&self) -> Result<Option<query::Range<types::Timestamp>>, query::OpError> {
+        self.time_range
+            .map(|[start, end]| query::Range::try_new(start.into(), end.into()))
+            .transpose()
+    }
+
+    /// Returns the requested set of timestamps, if any
+    pub fn timestamps(&self) -> Option<Vec<types::Timestamp>> {
+        self.timestamps
+            .as_ref()
+            .map(|ts| ts.iter().map(|t| types::Timestamp::from(*t)).collect())
+    }
+}
+
+/// Data request carried by a flight ticket in a `do_get` call, asking for the rows of
+/// several topics merged in a single stream ordered by timestamp, e.g.
+///
+/// ```json
+/// { "topics": ["my_sequence/imu", "my_sequence/gps"] }
+/// ```
+///
+/// The topics are required to share the same timestamp unit. Each row holds the timestamp
+/// and one struct column per topic, named after the topic: the struct of the topic the row
+/// was read from holds its fields, the structs of the other topics are null.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MultiTopicTicket {
+    pub topics: Vec<String>,
+}
+
+impl MultiTopicTicket {
+    pub fn new(topics: Vec<String>) -> Self {
+        Self { topics }
+    }
+
+    /// Returns [`None`] if the bytes do not represent a multi topic ticket
+    pub fn try_from_bytes(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice(bytes).ok()
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        serde_json::to_vec(self).map_err(|e| Error::SerializationError(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_and_json_tickets() {
+        let ticket = TopicTicket::try_from_bytes(b"my_sequence/my_topic").unwrap();
+        assert_eq!(ticket.topic, "my_sequence/my_topic");
+        assert!(ticket.time_range().unwrap().is_none());
+        assert!(ticket.projection.is_none());
+
+        let ticket = TopicTicket::try_from_bytes(
+            br#"{ "topic": "my_sequence/my_topic", "time_range": [1000, 2000] }"#,
+        )
+        .unwrap();
+        assert_eq!(ticket.topic, "my_sequence/my_topic");
+        let range = ticket.time_range().unwrap().unwrap();
+        assert_eq!(range.min, 1000i64.into());
+        assert_eq!(range.max, 2000i64.into());
+
+        let ticket =
+            TopicTicket::try_from_bytes(br#"{ "topic": "t", "projection": ["pose", "twist"] }"#)
+                .unwrap();
+        assert_eq!(
+            ticket.projection,
+            Some(vec!["pose".to_owned(), "twist".to_owned()])
+        );
+
+        let ticket =
+            TopicTicket::try_from_bytes(br#"{ "topic": "t", "time_range": [2000, 1000] }"#)
+                .unwrap();
+        assert!(ticket.time_range().is_err());
+
+        let ticket = TopicTicket::try_from_bytes(b"t").unwrap();
+        assert_eq!(ticket.order, query::SortOrder::Asc);
+
+        let ticket = TopicTicket::try_from_bytes(
+            br#"{ "topic": "t", "order": "desc", "order_by": "pose.position.x" }"#,
+        )
+        .unwrap();
+        assert_eq!(ticket.order, query::SortOrder::Desc);
+        assert_eq!(ticket.order_by.as_deref(), Some("pose.position.x"));
+
+        let ticket = TopicTicket::try_from_bytes(br#"{ "topic": "t", "decimate": 100 }"#).unwrap();
+        assert_eq!(ticket.decimate, Some(100));
+        assert!(!ticket.row_number);
+
+        let ticket =
+            TopicTicket::try_from_bytes(br#"{ "topic": "t", "row_number": true }"#).unwrap();
+        assert!(ticket.row_number);
+        assert!(!ticket.is_paginated());
+
+        let ticket =
+            TopicTicket::try_from_bytes(br#"{ "topic": "t", "offset": 20, "limit": 10 }"#).unwrap();
+        assert!(ticket.is_paginated());
+        assert_eq!((ticket.offset, ticket.limit), (Some(20), Some(10)));
+
+        let bytes = TopicTicket::new("t".to_owned())
+            .with_aggregate(TicketAggregate {
+                window_ms: 1000,
+                fields: vec![AggregateField {
+                    field: "acc.x".to_owned(),
+                    function: query::AggFn::Avg,
+                }],
+            })
+            .to_bytes()
+            .unwrap();
+        let ticket = TopicTicket::try_from_bytes(&bytes).unwrap();
+        let aggregate = ticket.aggregate.unwrap();
+        assert_eq!(aggregate.window_ms, 1000);
+        assert_eq!(aggregate.fields[0].function, query::AggFn::Avg);
+    }
+
+    #[test]
+    fn multi_topic_ticket() {
+        let ticket = MultiTopicTicket::try_from_bytes(br#"{ "topics": ["a/x", "a/y"] }"#).unwrap();
+        assert_eq!(ticket.topics, vec!["a/x".to_owned(), "a/y".to_owned()]);
+
+        assert!(MultiTopicTicket::try_from_bytes(br#"{ "topic": "a/x" }"#).is_none());
+        assert!(MultiTopicTicket::try_from_bytes(b"a/x").is_none());
+    }
+}
@@ -8,6 +8,23 @@
 /// Defines the name of the `timestamp` column in the arrow schema
 pub const ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP: &str = "timestamp_ns";
 
+/// Defines the name of the optional column holding the position of each row in a read
+pub const ARROW_SCHEMA_COLUMN_NAME_ROW_NUMBER: &str = "row_number";
+
+/// Arrow schema metadata key holding the unit of the timestamp column (see [`crate::types::TimestampUnit`])
+pub const SCHEMA_METADATA_TIMESTAMP_UNIT: &str = "mosaico:timestamp_unit";
+
+/// Flight schema metadata key holding the total number of rows of a paginated read
+/// (see [`crate::marshal::TopicTicket`])
+pub const SCHEMA_METADATA_TOTAL_ROWS: &str = "mosaico:total_rows";
+
+/// Flight schema metadata key holding the differences between the data of a topic and the
+/// schema declared by the topic (see [`crate::types::DeclaredSchema`])
+pub const SCHEMA_METADATA_SCHEMA_WARNING: &str = "mosaico:schema_warning";
+
+/// Maximum number of values in a single `IN` list when filtering data by a set of timestamps
+pub const MAX_TIMESTAMPS_PER_IN_LIST: usize = 1024;
+
 /// Internal resolution for floating point comparisons
 pub const EPSILON: f64 = 1.0e-06;
 
@@ -21,9 +38,13 @@ pub mod ext {
     /// Json file extension
     pub const JSON: &str = "json";
     pub const PARQUET: &str = "parquet";
+    /// Arrow IPC (Feather v2) file extension
+    pub const ARROW: &str = "arrow";
 }
 
-use std::{env, str::FromStr, sync::OnceLock};
+use std::{collections::HashMap, env, str::FromStr, sync::OnceLock};
+
+use crate::{rw, types};
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -39,8 +60,143 @@ pub struct ConfigurablesParams {
     pub target_message_size_in_bytes: usize,
     /// Maximum number of concurrent chunk queries during data catalog filtering
     pub max_concurrent_chunk_queries: usize,
-    /// Maximum number of database connections in the pool
+    /// Maximum number of distinct values of a field reported for each topic by
+    /// `query_distinct`
+    pub max_distinct_values: usize,
+    /// Maximum number of topics reported by a query, the results of broader queries are
+    /// truncated
+    pub max_query_results: usize,
+    /// Maximum number of database connections in the pool.
+    ///
+    /// Each concurrent chunk query holds a connection, so the pool is never sized below
+    /// `max_concurrent_chunk_queries` (see [`crate::repo::Repository::try_new`])
     pub max_db_connections: u32,
+    /// Number of idle database connections kept open in the pool
+    pub min_db_connections: u32,
+    /// Maximum time (in seconds) to wait for a database connection to become available
+    pub db_acquire_timeout_in_secs: u64,
+    /// Global memory budget for decoded topic data kept in the read cache (0 disables the cache)
+    pub read_cache_budget_in_bytes: usize,
+    /// Maximum number of schemas resolved for the read paths kept in the schema cache
+    /// (0 disables the cache)
+    pub schema_cache_size: usize,
+    /// Behavior when data is written to a topic that does not exist
+    pub missing_topic_policy: MissingTopicPolicy,
+    /// Serialization format used for automatically created topics, by ontology tag
+    pub default_formats: OntologyFormats,
+    /// Notifications with a lower severity are discarded without being persisted
+    pub min_notify_severity: types::NotifyType,
+    /// Enables chunk pruning based on the approximate `p95` of numeric columns.
+    ///
+    /// Since quantiles are estimated, chunks with a few values above the `p95`
+    /// may be discarded by `>` filters, so this trades recall for speed.
+    pub quantile_pruning: bool,
+    /// If `true` the data files of the chunks are checked against the checksum computed when
+    /// they were written before a read (or a query) scans them, at the cost of downloading
+    /// them twice. Reads only verify the chunks overlapping their time range. This detects
+    /// data files corrupted in the store, the bytes downloaded again by the scan are not
+    /// verified
+    pub verify_chunk_checksums: bool,
+    /// Allows finalized sequences to be re-opened for corrections (`sequence_reopen`),
+    /// should be disabled where sequences are required to be immutable
+    pub allow_sequence_reopen: bool,
+    /// Memory usage (in bytes) above which a chunk being written is finalized, the following
+    /// data of the write is stored in a new chunk
+    pub max_chunk_bytes: usize,
+    /// Maximum number of rows of a parquet row group of the topics not setting their own
+    /// limit, 0 uses the default of each serialization format
+    pub max_row_group_size: usize,
+    /// Size (in bytes) of the chunks produced by the compaction, smaller chunks are merged
+    /// together up to this size
+    pub compaction_target_chunk_bytes: usize,
+    /// Number of chunks smaller than `compaction_target_chunk_bytes` that triggers the
+    /// compaction of a topic at the end of a write (before the topic is locked), 0 disables
+    /// the automatic compaction
+    pub compaction_min_chunks: usize,
+    /// Minimum number of numeric column statistics of a chunk loaded with a binary `COPY`
+    /// instead of a multi-row `INSERT`
+    pub stats_copy_threshold: usize,
+    /// Number of samples retained by the quantile sketch of numeric column statistics.
+    /// Larger sketches give more accurate `p50`/`p95` estimates at the cost of memory while
+    /// writing chunks (the sketch holds at most twice this number of samples)
+    pub numeric_stats_sketch_size: usize,
+    /// Dotted path of the user metadata field holding the ontology tag of topics
+    /// created without an explicit tag (e.g. `ontology` or `sensor.ontology`)
+    pub ontology_tag_metadata_field: String,
+    /// Maximum number of attempts of store reads failing with transient errors
+    pub store_retry_max_attempts: u32,
+    /// Delay (in milliseconds) before the first retry of a store read, doubled at each retry
+    pub store_retry_base_delay_in_ms: u64,
+}
+
+/// Defines how writes to a non-existent topic are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissingTopicPolicy {
+    /// The write is rejected
+    Reject,
+    /// The topic is created with default properties, the parent sequence must exist
+    AutoCreate,
+}
+
+impl FromStr for MissingTopicPolicy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "reject" => Ok(Self::Reject),
+            "auto_create" => Ok(Self::AutoCreate),
+            _ => Err(Error::UnableToParse(s.to_owned())),
+        }
+    }
+}
+
+impl std::fmt::Display for MissingTopicPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Reject => write!(f, "reject"),
+            Self::AutoCreate => write!(f, "auto_create"),
+        }
+    }
+}
+
+/// Mapping between ontology tags and serialization formats, parsed from a
+/// comma separated list of `tag=format` pairs (e.g. `camera=image,lidar=ragged`).
+///
+/// Ontology tags not in the mapping use [`rw::Format::Default`].
+#[derive(Debug, Clone, Default)]
+pub struct OntologyFormats(HashMap<String, rw::Format>);
+
+impl OntologyFormats {
+    pub fn format_for(&self, ontology_tag: &str) -> rw::Format {
+        self.0
+            .get(ontology_tag)
+            .copied()
+            .unwrap_or(rw::Format::Default)
+    }
+
+    /// Iterates over the ontology tags with an explicit format
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &rw::Format)> {
+        self.0.iter()
+    }
+}
+
+impl FromStr for OntologyFormats {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut formats = HashMap::new();
+        for pair in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (tag, format) = pair
+                .split_once('=')
+                .ok_or_else(|| Error::UnableToParse(pair.to_owned()))?;
+            let format = format
+                .trim()
+                .parse()
+                .map_err(|_| Error::UnableToParse(pair.to_owned()))?;
+            formats.insert(tag.trim().to_owned(), format);
+        }
+        Ok(Self(formats))
+    }
 }
 
 static ENV: OnceLock<ConfigurablesParams> = OnceLock::new();
@@ -60,7 +216,40 @@ pub fn load_configurables_from_env() {
             25 * 1024 * 1024,
         ),
         max_concurrent_chunk_queries: cast_env_var("MOSAICO_MAX_CONCURRENT_CHUNK_QUERIES", 4),
+        max_distinct_values: cast_env_var("MOSAICO_MAX_DISTINCT_VALUES", 1000),
+        max_query_results: cast_env_var("MOSAICO_MAX_QUERY_RESULTS", 10000),
         max_db_connections: cast_env_var("MOSAICO_MAX_DB_CONNECTIONS", 10),
+        min_db_connections: cast_env_var("MOSAICO_MIN_DB_CONNECTIONS", 0),
+        db_acquire_timeout_in_secs: cast_env_var("MOSAICO_DB_ACQUIRE_TIMEOUT_IN_SECS", 30),
+        read_cache_budget_in_bytes: cast_env_var(
+            "MOSAICO_READ_CACHE_BUDGET_IN_BYTES",
+            256 * 1024 * 1024,
+        ),
+        schema_cache_size: cast_env_var("MOSAICO_SCHEMA_CACHE_SIZE", 1024),
+        missing_topic_policy: cast_env_var(
+            "MOSAICO_MISSING_TOPIC_POLICY",
+            MissingTopicPolicy::Reject,
+        ),
+        default_formats: cast_env_var("MOSAICO_DEFAULT_FORMATS", OntologyFormats::default()),
+        min_notify_severity: cast_env_var("MOSAICO_MIN_NOTIFY_SEVERITY", types::NotifyType::Info),
+        quantile_pruning: cast_env_var("MOSAICO_QUANTILE_PRUNING", false),
+        verify_chunk_checksums: cast_env_var("MOSAICO_VERIFY_CHUNK_CHECKSUMS", false),
+        allow_sequence_reopen: cast_env_var("MOSAICO_ALLOW_SEQUENCE_REOPEN", false),
+        max_chunk_bytes: cast_env_var("MOSAICO_MAX_CHUNK_BYTES", 256 * 1024 * 1024),
+        max_row_group_size: cast_env_var("MOSAICO_MAX_ROW_GROUP_SIZE", 0),
+        compaction_target_chunk_bytes: cast_env_var(
+            "MOSAICO_COMPACTION_TARGET_CHUNK_BYTES",
+            64 * 1024 * 1024,
+        ),
+        compaction_min_chunks: cast_env_var("MOSAICO_COMPACTION_MIN_CHUNKS", 16),
+        stats_copy_threshold: cast_env_var("MOSAICO_STATS_COPY_THRESHOLD", 256),
+        numeric_stats_sketch_size: cast_env_var("MOSAICO_NUMERIC_STATS_SKETCH_SIZE", 128),
+        ontology_tag_metadata_field: cast_env_var(
+            "MOSAICO_ONTOLOGY_TAG_METADATA_FIELD",
+            "ontology".to_owned(),
+        ),
+        store_retry_max_attempts: cast_env_var("MOSAICO_STORE_RETRY_MAX_ATTEMPTS", 4),
+        store_retry_base_delay_in_ms: cast_env_var("MOSAICO_STORE_RETRY_BASE_DELAY_IN_MS", 100),
     };
 
     let _ = ENV.set(ev);
@@ -125,3 +314,19 @@ impl std::fmt::Display for Hidden {
         write!(f, "**********")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ontology_formats() {
+        let formats: OntologyFormats = "camera=image, lidar = ragged".parse().unwrap();
+        assert_eq!(formats.format_for("camera"), rw::Format::Image);
+        assert_eq!(formats.format_for("lidar"), rw::Format::Ragged);
+        assert_eq!(formats.format_for("imu"), rw::Format::Default);
+
+        assert!("camera".parse::<OntologyFormats>().is_err());
+        assert!("camera=jpeg".parse::<OntologyFormats>().is_err());
+    }
+}
@@ -1,6 +1,6 @@
 use crate::query::OntologyField;
 
-use super::{Error, ExprGroup, IsSupportedOp, Op, Value};
+use super::{Connector, Error, ExprGroup, IsSupportedOp, Op, Value};
 
 const EMPTY_CLAUSE: &str = "()";
 
@@ -34,6 +34,14 @@ pub trait CompileClause {
     fn compile_clause<V>(&mut self, field: &str, op: Op<V>) -> Result<CompiledClause, Error>
     where
         V: Into<Value> + IsSupportedOp;
+
+    /// Returns the string used to join clauses combined with the given [`Connector`].
+    fn connector_fmt(&self, connector: Connector) -> &'static str {
+        match connector {
+            Connector::And => " AND ",
+            Connector::Or => " OR ",
+        }
+    }
 }
 
 /// Specify how a given ontology field needs to be formatted
@@ -104,6 +112,9 @@ impl ClausesCompiler {
     }
 
     // es: field = topic.user_metadata
+    //
+    // Expressions of groups combined in `AND` are appended as separate clauses, while groups
+    // combined in `OR` are compiled into a single clause.
     pub fn filter<F, V>(mut self, filter: ExprGroup<V>, formatter: &mut F) -> Self
     where
         V: Into<Value> + IsSupportedOp,
@@ -113,15 +124,75 @@ impl ClausesCompiler {
             return self;
         }
 
-        for expr in filter.into_iter() {
+        if filter.connector == Connector::Or {
+            match Self::compile_group(filter, formatter) {
+                Ok(Some(clause)) => {
+                    let (clause, mut values) = clause.into_parts();
+                    self.result.clauses.push(clause);
+                    self.result.values.append(&mut values);
+                }
+                Ok(None) => {}
+                Err(err) => self.error = Some(err),
+            }
+            return self;
+        }
+
+        for expr in filter.group {
             let (ontology_field, op) = expr.into_parts();
             let field = formatter.ontology_column_fmt(&ontology_field);
             self = self.expr(&field, op, formatter);
         }
 
+        for subgroup in filter.subgroups {
+            self = self.filter(subgroup, formatter);
+        }
+
         self
     }
 
+    /// Compiles a whole group into a single clause, joining its members with the
+    /// group connector. Values are returned in the same order placeholders are consumed.
+    ///
+    /// Returns [`None`] if the group produces no clauses.
+    fn compile_group<F, V>(
+        filter: ExprGroup<V>,
+        formatter: &mut F,
+    ) -> Result<Option<CompiledClause>, Error>
+    where
+        V: Into<Value> + IsSupportedOp,
+        F: CompileClause + OntologyFieldFmt,
+    {
+        let mut clauses = Vec::new();
+        let mut values = Vec::new();
+
+        for expr in filter.group {
+            let (ontology_field, op) = expr.into_parts();
+            let field = formatter.ontology_column_fmt(&ontology_field);
+            let (clause, mut v) = formatter.compile_clause(&field, op)?.into_parts();
+            clauses.push(clause);
+            values.append(&mut v);
+        }
+
+        for subgroup in filter.subgroups {
+            if let Some(clause) = Self::compile_group(subgroup, formatter)? {
+                let (clause, mut v) = clause.into_parts();
+                clauses.push(clause);
+                values.append(&mut v);
+            }
+        }
+
+        if clauses.is_empty() {
+            return Ok(None);
+        }
+
+        let clause = format!(
+            "({})",
+            clauses.join(formatter.connector_fmt(filter.connector))
+        );
+
+        Ok(Some(CompiledClause::new(clause, values)))
+    }
+
     pub fn compile(self) -> Result<CompilerResult, Error> {
         if self.error.is_some() {
             return Err(self.error.unwrap());
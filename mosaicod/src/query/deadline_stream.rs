@@ -0,0 +1,128 @@
+//! Record batch stream bounded by a wall-clock deadline.
+//!
+//! Long reads can tie up server resources for a long time, clients that prefer partial
+//! data over waiting can bound the duration of a read. When the deadline expires the
+//! stream ends gracefully, without returning an error, and the truncation is reported
+//! through a shared flag, so that callers can notify clients once the stream is over.
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use arrow::array::RecordBatch;
+use arrow::datatypes::SchemaRef;
+use datafusion::error::DataFusionError;
+use datafusion::execution::{RecordBatchStream, SendableRecordBatchStream};
+use futures::{FutureExt, Stream, StreamExt};
+use log::trace;
+use tokio::time::Sleep;
+
+/// Stream ending as soon as a deadline expires.
+///
+/// A timer is polled together with the inner stream, so the stream ends when the deadline
+/// expires even while a batch is being produced. Only complete batches are returned, the
+/// batch being produced when the deadline expires is discarded.
+pub struct DeadlineStream {
+    inner: SendableRecordBatchStream,
+    deadline: Pin<Box<Sleep>>,
+    truncated: Arc<AtomicBool>,
+}
+
+impl DeadlineStream {
+    pub fn new(inner: SendableRecordBatchStream, deadline: Instant) -> Self {
+        Self {
+            inner,
+            deadline: Box::pin(tokio::time::sleep_until(deadline.into())),
+            truncated: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Returns a flag set to `true` if the stream has been ended by the deadline
+    pub fn truncated(&self) -> Arc<AtomicBool> {
+        self.truncated.clone()
+    }
+}
+
+impl Stream for DeadlineStream {
+    type Item = Result<RecordBatch, DataFusionError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        if this.truncated.load(Ordering::Relaxed) {
+            return Poll::Ready(None);
+        }
+
+        // The timer is polled first, so that it wakes the task up if the inner stream is
+        // still pending when the deadline expires
+        if this.deadline.poll_unpin(cx).is_ready() {
+            trace!("read deadline expired, truncating stream");
+            this.truncated.store(true, Ordering::Relaxed);
+            return Poll::Ready(None);
+        }
+
+        this.inner.poll_next_unpin(cx)
+    }
+}
+
+impl RecordBatchStream for DeadlineStream {
+    fn schema(&self) -> SchemaRef {
+        self.inner.schema()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::Int64Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+    use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+    use std::time::Duration;
+
+    fn batch_stream(batches: usize) -> SendableRecordBatchStream {
+        let schema = Arc::new(Schema::new(vec![Field::new("v", DataType::Int64, false)]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int64Array::from_iter_values(0..8))],
+        )
+        .unwrap();
+
+        Box::pin(RecordBatchStreamAdapter::new(
+            schema,
+            futures::stream::iter((0..batches).map(move |_| Ok(batch.clone()))),
+        ))
+    }
+
+    #[tokio::test]
+    async fn expired_deadline_truncates() {
+        let stream = DeadlineStream::new(batch_stream(4), Instant::now() + Duration::from_secs(60));
+        let truncated = stream.truncated();
+        assert_eq!(stream.count().await, 4);
+        assert!(!truncated.load(Ordering::Relaxed));
+
+        let stream =
+            DeadlineStream::new(batch_stream(4), Instant::now() - Duration::from_millis(1));
+        let truncated = stream.truncated();
+        assert_eq!(stream.count().await, 0);
+        assert!(truncated.load(Ordering::Relaxed));
+    }
+
+    #[tokio::test]
+    async fn deadline_expires_while_pending() {
+        let schema = Arc::new(Schema::new(vec![Field::new("v", DataType::Int64, false)]));
+        let pending = Box::pin(RecordBatchStreamAdapter::new(
+            schema,
+            futures::stream::pending::<Result<RecordBatch, DataFusionError>>(),
+        ));
+
+        let stream = DeadlineStream::new(pending, Instant::now() + Duration::from_millis(20));
+        let truncated = stream.truncated();
+
+        let count = tokio::time::timeout(Duration::from_secs(10), stream.count())
+            .await
+            .expect("the stream should end once the deadline expires");
+        assert_eq!(count, 0);
+        assert!(truncated.load(Ordering::Relaxed));
+    }
+}
@@ -10,8 +10,38 @@ pub enum Error {
     #[error("operation error :: field `{field}` has {err}")]
     OpError { field: String, err: super::OpError },
 
-    #[error("bad field `{field}`")]
-    BadField { field: String },
+    #[error("bad field `{field}`{}", fmt_available(available))]
+    BadField {
+        field: String,
+        /// Fields that could be used in place of the bad one, if known
+        available: Vec<String>,
+    },
+
+    #[error("unknown column `{column}`")]
+    UnknownColumn { column: String },
+
+    #[error("field `{field}` is not numeric")]
+    NotNumeric { field: String },
+
+    #[error("aggregation window is shorter than the timestamp resolution")]
+    BadWindow,
+
+    #[error("column `{column}` already exists")]
+    DuplicateColumn { column: String },
+
+    #[error("data can't be sorted by timestamp")]
+    NotSortable,
+
+    #[error("no data source to read")]
+    NothingToRead,
+
+    #[error("expression groups combined in `OR` can't refer to different ontology tags")]
+    MixedOntologyTags,
+
+    #[error(
+        "`$has_nan` and `$has_null` can't be combined with row level operations on the same ontology tag"
+    )]
+    MixedChunkLevelOps,
 
     #[error("datafusion backend error :: {0}")]
     DataFusion(#[from] datafusion::error::DataFusionError),
@@ -21,6 +51,46 @@ pub enum Error {
 
     #[error("store error :: {0}")]
     StoreError(#[from] store::Error),
+
+    #[error("{source} (gave up after {attempts} attempts)")]
+    RetriesExhausted { attempts: u32, source: Box<Error> },
+}
+
+impl store::Retryable for Error {
+    /// Data files are read by datafusion, so the object store (or io) error causing the
+    /// failure is searched in the chain of error sources
+    fn is_transient(&self) -> bool {
+        if let Self::RetriesExhausted { .. } = self {
+            return false;
+        }
+
+        let mut source: Option<&(dyn std::error::Error + 'static)> = Some(self);
+        while let Some(err) = source {
+            if let Some(e) = err.downcast_ref::<object_store::Error>() {
+                return store::is_transient_backend_error(e);
+            }
+            if let Some(e) = err.downcast_ref::<std::io::Error>() {
+                return store::is_transient_io_error(e);
+            }
+            source = err.source();
+        }
+        false
+    }
+
+    fn exhausted(self, attempts: u32) -> Self {
+        Self::RetriesExhausted {
+            attempts,
+            source: Box::new(self),
+        }
+    }
+}
+
+fn fmt_available(available: &[String]) -> String {
+    if available.is_empty() {
+        String::new()
+    } else {
+        format!(", available fields are: {}", available.join(", "))
+    }
 }
 
 impl Error {
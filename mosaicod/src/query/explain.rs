@@ -0,0 +1,43 @@
+use super::Value;
+
+/// A compiled SQL statement, along with the values bound to its placeholders (`$1`, `$2`, ...)
+#[derive(Debug, Clone)]
+pub struct SqlStatement {
+    pub sql: String,
+    pub values: Vec<Value>,
+}
+
+impl From<(String, Vec<Value>)> for SqlStatement {
+    fn from((sql, values): (String, Vec<Value>)) -> Self {
+        Self { sql, values }
+    }
+}
+
+/// How the expressions referring to a single ontology tag are evaluated
+#[derive(Debug, Clone)]
+pub struct OntologyTagExplain {
+    pub ontology_tag: String,
+    /// Query used by the data catalog to select the candidate chunks
+    pub chunks_sql: SqlStatement,
+    /// Number of chunks selected by the data catalog, before any time range pruning
+    pub candidate_chunks: usize,
+    /// Logical plan used to scan the data file of the first candidate chunk, the plans of
+    /// the other candidates differ only by the scanned data file.
+    ///
+    /// `None` if there are no candidate chunks or if the expressions are resolved using
+    /// only the chunk statistics (no data file is scanned).
+    pub logical_plan: Option<String>,
+    /// Data file of the chunk whose plan is reported in `logical_plan`
+    pub explained_chunk: Option<String>,
+}
+
+/// Describes how a query is executed, without returning any result
+#[derive(Debug, Clone, Default)]
+pub struct Explain {
+    /// Query selecting the topics using the sequence, topic and layer filters, `None` if no
+    /// such filter is provided
+    pub topics_sql: Option<SqlStatement>,
+    /// Number of topics selected by `topics_sql`
+    pub selected_topics: usize,
+    pub ontology_tags: Vec<OntologyTagExplain>,
+}
@@ -48,6 +48,24 @@ pub enum OpError {
     /// Occurs when constructing a [`Range`] where `min > max`.
     #[error("empty range")]
     EmptyRange,
+
+    /// Occurs when a set based operation (e.g. [`Op::In`]) receives an empty set.
+    #[error("empty set")]
+    EmptySet,
+
+    /// Occurs when a value is not among the allowed ones for the field.
+    #[error("invalid value `{0}`")]
+    InvalidValue(String),
+
+    /// Occurs when a timestamp can't be represented exactly as an `i64` number of milliseconds.
+    #[error(
+        "timestamp `{0}` is not representable, timestamps must be integers and numbers with a fractional part or above 2^53 lose precision"
+    )]
+    TimestampNotRepresentable(String),
+
+    /// Occurs when the pattern of a [`Op::Regex`] is not a valid regular expression.
+    #[error("invalid regex :: {0}")]
+    InvalidRegex(String),
 }
 
 /// A wrapper enum to allow heterogeneous values (Numbers and Strings)
@@ -84,6 +102,12 @@ impl From<i64> for Value {
     }
 }
 
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Boolean(b)
+    }
+}
+
 impl From<Timestamp> for Value {
     fn from(n: Timestamp) -> Self {
         Value::Integer(n.into())
@@ -112,6 +136,21 @@ pub trait IsSupportedOp {
     fn support_match(&self) -> bool {
         false
     }
+    /// NaN checks carry no value, so support depends only on the implementing type
+    fn support_nan() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+    /// Chunk level checks (see [`Op::is_chunk_level`]) carry no value, so support depends
+    /// only on the implementing type
+    fn support_chunk_level() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
 }
 
 impl IsSupportedOp for Value {
@@ -135,6 +174,16 @@ impl IsSupportedOp for Value {
     fn support_match(&self) -> bool {
         matches!(self, Self::Text(_))
     }
+
+    /// Values are used for ontology fields, the numeric type of the field is checked
+    /// against the data when the filter is applied
+    fn support_nan() -> bool {
+        true
+    }
+
+    fn support_chunk_level() -> bool {
+        true
+    }
 }
 
 impl IsSupportedOp for bool {
@@ -165,6 +214,10 @@ impl IsSupportedOp for types::Timestamp {
     fn support_ordering(&self) -> bool {
         true
     }
+
+    fn support_in(&self) -> bool {
+        true
+    }
 }
 
 impl IsSupportedOp for Text {
@@ -209,6 +262,7 @@ impl OntologyField {
     pub fn try_new(v: String) -> Result<Self, super::Error> {
         let ontology_tag = v.split(".").next().ok_or_else(|| super::Error::BadField {
             field: v.to_string(),
+            available: Vec::new(),
         })?;
         let len = ontology_tag.len();
 
@@ -284,21 +338,120 @@ impl<T> From<(OntologyField, Op<T>)> for Expr<T> {
     }
 }
 
+/// Boolean connector used to combine the members of an [`ExprGroup`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Connector {
+    /// All the members need to be satisfied
+    #[default]
+    And,
+    /// At least one of the members needs to be satisfied
+    Or,
+}
+
 /// An expression group is defined as a series of ontology fields
-/// with associated operations.
+/// with associated operations, optionally nested in sub-groups.
+///
+/// All the members of the group (expressions and sub-groups) are combined using
+/// the group [`Connector`], by default [`Connector::And`].
 #[derive(Debug, Clone)]
 pub struct ExprGroup<T> {
     pub group: Vec<Expr<T>>,
+    pub subgroups: Vec<ExprGroup<T>>,
+    pub connector: Connector,
 }
 
 impl<T> ExprGroup<T> {
     pub fn new(group: Vec<Expr<T>>) -> Self {
-        Self { group }
+        Self {
+            group,
+            subgroups: Vec::new(),
+            connector: Connector::And,
+        }
     }
 
-    /// Exports filter data as several expression groupss grouped by ontology tag
-    /// So if the
-    pub fn split_by_ontology_tag(self) -> Vec<ExprGroup<T>> {
+    pub fn with_connector(mut self, connector: Connector) -> Self {
+        self.connector = connector;
+        self
+    }
+
+    pub fn with_subgroups(mut self, subgroups: Vec<ExprGroup<T>>) -> Self {
+        self.subgroups = subgroups;
+        self
+    }
+
+    /// Returns `true` if the group has no expressions, also considering sub-groups.
+    pub fn is_empty(&self) -> bool {
+        self.group.is_empty() && self.subgroups.iter().all(|g| g.is_empty())
+    }
+
+    /// Returns the ontology tag of the first expression found in the group (sub-groups included).
+    pub fn ontology_tag(&self) -> Option<&str> {
+        self.group
+            .first()
+            .map(|e| e.ontology_field().ontology_tag())
+            .or_else(|| self.subgroups.iter().find_map(|g| g.ontology_tag()))
+    }
+
+    /// Returns the ontology fields of all the expressions in the group (sub-groups included).
+    pub fn ontology_fields(&self) -> Vec<&OntologyField> {
+        self.group
+            .iter()
+            .map(|e| e.ontology_field())
+            .chain(self.subgroups.iter().flat_map(|g| g.ontology_fields()))
+            .collect()
+    }
+
+    /// Returns the ontology fields checked by [`Op::IsNan`] or [`Op::IsNotNan`] in the
+    /// group (sub-groups included).
+    pub fn nan_checked_fields(&self) -> Vec<&OntologyField> {
+        self.group
+            .iter()
+            .filter(|e| matches!(e.op(), Op::IsNan | Op::IsNotNan))
+            .map(|e| e.ontology_field())
+            .chain(self.subgroups.iter().flat_map(|g| g.nan_checked_fields()))
+            .collect()
+    }
+
+    /// Returns the ontology fields checked by chunk level operations (see
+    /// [`Op::is_chunk_level`]) in the group (sub-groups included).
+    pub fn chunk_level_fields(&self) -> Vec<&OntologyField> {
+        self.group
+            .iter()
+            .filter(|e| e.op().is_chunk_level())
+            .map(|e| e.ontology_field())
+            .chain(self.subgroups.iter().flat_map(|g| g.chunk_level_fields()))
+            .collect()
+    }
+
+    /// Returns `true` if all the expressions in the group (sub-groups included) refer
+    /// to the same ontology tag.
+    fn has_single_ontology_tag(&self, tag: &str) -> bool {
+        self.group
+            .iter()
+            .all(|e| e.ontology_field().ontology_tag() == tag)
+            && self
+                .subgroups
+                .iter()
+                .all(|g| g.has_single_ontology_tag(tag))
+    }
+
+    /// Exports filter data as several expression groups grouped by ontology tag.
+    ///
+    /// Sub-groups are moved as a whole into the group of their ontology tag, for this reason
+    /// a sub-group can't hold expressions referring to different ontology tags.
+    /// The split is only allowed on [`Connector::And`] groups, since splitting a disjunction
+    /// would change its meaning.
+    pub fn split_by_ontology_tag(self) -> Result<Vec<ExprGroup<T>>, super::Error> {
+        if self.connector == Connector::Or {
+            let Some(tag) = self.ontology_tag().map(ToOwned::to_owned) else {
+                return Ok(Vec::new());
+            };
+            if !self.has_single_ontology_tag(&tag) {
+                return Err(super::Error::MixedOntologyTags);
+            }
+            return Ok(vec![self]);
+        }
+
         let mut map: HashMap<String, ExprGroup<T>> = HashMap::new();
         for expr in self.group {
             let tag = expr.ontology_field().ontology_tag();
@@ -312,13 +465,28 @@ impl<T> ExprGroup<T> {
             }
         }
 
-        map.into_values().collect()
+        for subgroup in self.subgroups {
+            let Some(tag) = subgroup.ontology_tag().map(ToOwned::to_owned) else {
+                continue;
+            };
+
+            if !subgroup.has_single_ontology_tag(&tag) {
+                return Err(super::Error::MixedOntologyTags);
+            }
+
+            map.entry(tag)
+                .or_insert_with(|| Self::new(Vec::new()))
+                .subgroups
+                .push(subgroup);
+        }
+
+        Ok(map.into_values().collect())
     }
 }
 
 impl<T> Default for ExprGroup<T> {
     fn default() -> Self {
-        Self { group: Vec::new() }
+        Self::new(Vec::new())
     }
 }
 
@@ -326,36 +494,51 @@ impl<T> IntoIterator for ExprGroup<T> {
     type Item = Expr<T>;
     type IntoIter = std::vec::IntoIter<Expr<T>>;
 
+    /// Iterates over the expressions of the group, sub-groups are not included.
     fn into_iter(self) -> Self::IntoIter {
         self.group.into_iter()
     }
 }
 
 /// A container for dynamic user-defined expressions mapping to ontology data models.
+///
+/// Top level fields are combined in `AND`, nested groups can be used to express
+/// different boolean combinations (e.g. `OR`).
 #[derive(Debug, Clone)]
-pub struct OntologyFilter(HashMap<OntologyField, Op<Value>>);
+pub struct OntologyFilter {
+    fields: HashMap<OntologyField, Op<Value>>,
+    groups: Vec<ExprGroup<Value>>,
+}
 
 impl OntologyFilter {
     /// Creates a new Metadata instance from a [`HashMap`].
     pub fn new(v: HashMap<OntologyField, Op<Value>>) -> Self {
-        Self(v)
+        Self {
+            fields: v,
+            groups: Vec::new(),
+        }
     }
 
     /// Creates an empty Metadata instance.
     pub fn empty() -> Self {
-        Self(HashMap::new())
+        Self::new(HashMap::new())
+    }
+
+    /// Adds nested expression groups to the filter.
+    pub fn with_groups(mut self, groups: Vec<ExprGroup<Value>>) -> Self {
+        self.groups = groups;
+        self
     }
 
     /// Retrieves the operation associated with a specific metadata field.
     pub fn get_op(&self, field: &str) -> Option<&Op<Value>> {
-        self.0.get(field)
+        self.fields.get(field)
     }
 
     /// Exports filter data as a unique expression group
     pub fn into_expr_group(self) -> ExprGroup<Value> {
-        ExprGroup {
-            group: self.0.into_iter().map(|(o, v)| Expr(o, v)).collect(),
-        }
+        ExprGroup::new(self.fields.into_iter().map(|(o, v)| Expr(o, v)).collect())
+            .with_subgroups(self.groups)
     }
 }
 
@@ -374,16 +557,50 @@ pub enum Op<T> {
     Lt(T),
     /// Greater then
     Gt(T),
-    /// Exists
+    /// Exists, the field holds a (non null) value
     Ex,
-    /// Not exists
+    /// Not exists, the field is null or one of the structs containing it is null
     Nex,
     /// In between a two value range [a, b] with a >= b
     Between(Range<T>),
+    /// In a half open range [a, b), including `a` and excluding `b`, with b >= a
+    BetweenHalfOpen(Range<T>),
     /// Found in a set
     In(Vec<T>),
+    /// Not found in a set
+    NotIn(Vec<T>),
     /// Matches a certain expression
     Match(T),
+    /// Matches a `LIKE` pattern ignoring case, the pattern is searched anywhere in the
+    /// value (see [`imatch_pattern`])
+    IMatch(T),
+    /// Matches a regular expression, the pattern is searched anywhere in the value
+    /// (use `^` and `$` to anchor it). Patterns are restricted to the syntax shared by the
+    /// catalog and the query engine (see [`validate_regex`]).
+    Regex(T),
+    /// Starts with a certain text, the value is matched literally
+    StartsWith(T),
+    /// Ends with a certain text, the value is matched literally
+    EndsWith(T),
+    /// Is NaN, only for numeric fields
+    IsNan,
+    /// Is not NaN, only for numeric fields
+    IsNotNan,
+    /// At least a value of the chunk is NaN, only for numeric fields
+    HasNan,
+    /// At least a value of the chunk is null
+    HasNull,
+}
+
+impl<T> Op<T> {
+    /// Returns `true` for the operations evaluated on a whole chunk rather than on single
+    /// rows ([`Op::HasNan`] and [`Op::HasNull`]).
+    ///
+    /// These operations are resolved on the chunk statistics stored in the data catalog,
+    /// without reading any data file.
+    pub fn is_chunk_level(&self) -> bool {
+        matches!(self, Self::HasNan | Self::HasNull)
+    }
 }
 
 impl<T> Op<T>
@@ -400,32 +617,218 @@ where
             Op::Gt(v) => v.support_ordering(),
             Op::Ex => true,
             Op::Nex => true,
-            Op::Between(range) => range.min.support_ordering(),
-            Op::In(items) => items[0].support_in(),
-            Op::Match(v) => v.support_match(),
+            Op::Between(range) | Op::BetweenHalfOpen(range) => range.min.support_ordering(),
+            Op::In(items) | Op::NotIn(items) => items.first().is_some_and(|v| v.support_in()),
+            Op::Match(v) | Op::IMatch(v) | Op::Regex(v) => v.support_match(),
+            Op::StartsWith(v) | Op::EndsWith(v) => v.support_match(),
+            Op::IsNan | Op::IsNotNan => T::support_nan(),
+            Op::HasNan | Op::HasNull => T::support_chunk_level(),
+        }
+    }
+}
+
+/// Returns the `LIKE` pattern evaluated by [`Op::IMatch`], so that the catalog, the chunk
+/// statistics and the query engine search the pattern anywhere in the value.
+///
+/// Non textual values are returned unchanged.
+pub fn imatch_pattern(value: Value) -> Value {
+    match value {
+        Value::Text(text) => Value::Text(format!("%{text}%")),
+        value => value,
+    }
+}
+
+/// Ensures that `pattern` is a valid regular expression, so that invalid patterns are
+/// reported before reaching the database or the query engine.
+///
+/// Patterns are matched by Postgres (on the catalog) and by the query engine (on the data
+/// files), so only the syntax both interpret in the same way is accepted: literals and
+/// escaped punctuation, `.`, `^` and `$`, bracket expressions (with ranges, negation and
+/// `[:class:]` names), `\d`, `\s`, `\w` and their negations, `\t`, `\n`, `\r`, `\f`,
+/// `\v`, `\a`, groups `(...)` and `(?:...)`, alternations and repetitions bounded by 255.
+///
+/// Constructs with a different meaning in the two dialects (e.g. `\b` is a word boundary
+/// for the query engine and a backspace for Postgres) or supported by only one of them
+/// (named groups, lookarounds, inline flags, unicode classes, hex and octal escapes, nested
+/// or set-operation classes) are rejected. Note that `.` also matches new lines in Postgres.
+pub fn validate_regex(pattern: &str) -> Result<(), OpError> {
+    regex::Regex::new(pattern).map_err(|e| OpError::InvalidRegex(e.to_string()))?;
+
+    let ast = regex_syntax::ast::parse::Parser::new()
+        .parse(pattern)
+        .map_err(|e| OpError::InvalidRegex(e.to_string()))?;
+    regex_syntax::ast::visit(&ast, PortableRegex)
+        .map_err(|construct| OpError::InvalidRegex(format!("unsupported {construct}")))
+}
+
+/// Visitor rejecting the constructs of a regular expression outside of the syntax accepted
+/// by [`validate_regex`], the error describes the construct.
+struct PortableRegex;
+
+impl regex_syntax::ast::Visitor for PortableRegex {
+    type Output = ();
+    type Err = String;
+
+    fn finish(self) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn visit_pre(&mut self, ast: &regex_syntax::ast::Ast) -> Result<(), String> {
+        use regex_syntax::ast::{
+            AssertionKind, Ast, GroupKind, LiteralKind, RepetitionKind, RepetitionRange,
+        };
+
+        match ast {
+            Ast::Flags(_) => Err("inline flags".to_owned()),
+            Ast::ClassUnicode(_) => Err("unicode class".to_owned()),
+            Ast::Assertion(assertion) => match assertion.kind {
+                AssertionKind::StartLine | AssertionKind::EndLine => Ok(()),
+                _ => Err(format!("assertion `{}`", ast)),
+            },
+            Ast::Literal(literal) => match literal.kind {
+                LiteralKind::Octal | LiteralKind::HexFixed(_) | LiteralKind::HexBrace(_) => {
+                    Err(format!("escape `{}`", ast))
+                }
+                _ => Ok(()),
+            },
+            Ast::Group(group) => match &group.kind {
+                GroupKind::CaptureIndex(_) => Ok(()),
+                GroupKind::NonCapturing(flags) if flags.items.is_empty() => Ok(()),
+                GroupKind::NonCapturing(_) => Err("group flags".to_owned()),
+                GroupKind::CaptureName { .. } => Err("named group".to_owned()),
+            },
+            Ast::Repetition(repetition) => match repetition.op.kind {
+                RepetitionKind::Range(
+                    RepetitionRange::Exactly(n)
+                    | RepetitionRange::AtLeast(n)
+                    | RepetitionRange::Bounded(_, n),
+                ) if n > 255 => Err("repetition bound above 255".to_owned()),
+                _ => Ok(()),
+            },
+            Ast::ClassBracketed(class) => match &class.kind {
+                regex_syntax::ast::ClassSet::BinaryOp(_) => Err("class set operation".to_owned()),
+                regex_syntax::ast::ClassSet::Item(_) => Ok(()),
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn visit_class_set_item_pre(
+        &mut self,
+        item: &regex_syntax::ast::ClassSetItem,
+    ) -> Result<(), String> {
+        use regex_syntax::ast::{ClassSetItem, LiteralKind};
+
+        match item {
+            ClassSetItem::Unicode(_) => Err("unicode class".to_owned()),
+            ClassSetItem::Bracketed(_) => Err("nested class".to_owned()),
+            ClassSetItem::Ascii(class) if class.negated => Err("negated class name".to_owned()),
+            ClassSetItem::Perl(class) if class.negated => {
+                Err("negated class escape in brackets".to_owned())
+            }
+            ClassSetItem::Literal(literal) => match literal.kind {
+                LiteralKind::Octal | LiteralKind::HexFixed(_) | LiteralKind::HexBrace(_) => {
+                    Err("hex or octal escape".to_owned())
+                }
+                _ => Ok(()),
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Escapes the wildcards (`%` and `_`) and the escape character (`\`) of a text,
+/// so that it is matched literally in a `LIKE` pattern.
+pub fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Defines which topics are reported for the sequences matching a query.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    /// Only the topics matching the filter are reported
+    #[default]
+    Topic,
+    /// All the topics of a sequence are reported if at least one of its topics
+    /// matches the filter
+    Sequence,
+}
+
+/// Sorting direction of the rows returned by a read.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub fn is_asc(&self) -> bool {
+        matches!(self, Self::Asc)
+    }
+}
+
+/// Aggregate function applied to the values of a field within a time window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AggFn {
+    Min,
+    Max,
+    Avg,
+    Sum,
+    Count,
+}
+
+impl AggFn {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Min => "min",
+            Self::Max => "max",
+            Self::Avg => "avg",
+            Self::Sum => "sum",
+            Self::Count => "count",
         }
     }
 }
 
 /// The root object representing a complete search query.
 ///
-/// A query allows filtering across three distinct domains:
+/// A query allows filtering across four distinct domains:
 /// 1. The sequence, as [`SequenceFilter`]
 /// 2. The topic, as [`TopicFilter`]
-/// 3. The data catalog, represented as [`OntologyFilter`]
+/// 3. The layers containing the sequence, as [`LayerFilter`]
+/// 4. The data catalog, represented as [`OntologyFilter`]
 ///
 /// All fields are optional; [`None`] implies no filtering for that domain.
+///
+/// The ontology filter can be restricted to the data within a (inclusive) `time_range`,
+/// expressed in milliseconds.
 #[derive(Debug, Clone, Default)]
 pub struct Filter {
     pub sequence: Option<SequenceFilter>,
     pub topic: Option<TopicFilter>,
+    pub layer: Option<LayerFilter>,
     pub ontology: Option<OntologyFilter>,
+    pub time_range: Option<Range<Timestamp>>,
 }
 
 impl Filter {
     /// Returns true if there are no filters applied
     pub fn is_empty(&self) -> bool {
-        self.sequence.is_none() && self.topic.is_none() && self.ontology.is_none()
+        self.sequence.is_none()
+            && self.topic.is_none()
+            && self.layer.is_none()
+            && self.ontology.is_none()
+            && self.time_range.is_none()
     }
 
     pub fn into_parts(
@@ -433,9 +836,17 @@ impl Filter {
     ) -> (
         Option<SequenceFilter>,
         Option<TopicFilter>,
+        Option<LayerFilter>,
         Option<OntologyFilter>,
+        Option<Range<Timestamp>>,
     ) {
-        (self.sequence, self.topic, self.ontology)
+        (
+            self.sequence,
+            self.topic,
+            self.layer,
+            self.ontology,
+            self.time_range,
+        )
     }
 }
 
@@ -471,6 +882,19 @@ impl TopicFilter {
     }
 }
 
+/// Restricts the results to the sequences belonging to (at least) one of the layers
+/// matching the filter.
+#[derive(Debug, Clone, Default)]
+pub struct LayerFilter {
+    pub name: Option<Op<Text>>,
+}
+
+impl LayerFilter {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -486,32 +910,30 @@ mod tests {
 
     #[test]
     fn expr_grp_split() {
-        let grp = ExprGroup {
-            group: vec![
-                (
-                    OntologyField::try_new("image.width".into()).unwrap(),
-                    Op::Eq(Value::Integer(1200)),
-                )
-                    .into(),
-                (
-                    OntologyField::try_new("image.height".into()).unwrap(),
-                    Op::Eq(Value::Integer(800)),
-                )
-                    .into(),
-                (
-                    OntologyField::try_new("imu.acceleration.x".into()).unwrap(),
-                    Op::Geq(Value::Float(8.0)),
-                )
-                    .into(),
-                (
-                    OntologyField::try_new("imu.angular_velocity.x".into()).unwrap(),
-                    Op::Leq(Value::Float(3.0)),
-                )
-                    .into(),
-            ],
-        };
-
-        let splits = grp.split_by_ontology_tag();
+        let grp = ExprGroup::new(vec![
+            (
+                OntologyField::try_new("image.width".into()).unwrap(),
+                Op::Eq(Value::Integer(1200)),
+            )
+                .into(),
+            (
+                OntologyField::try_new("image.height".into()).unwrap(),
+                Op::Eq(Value::Integer(800)),
+            )
+                .into(),
+            (
+                OntologyField::try_new("imu.acceleration.x".into()).unwrap(),
+                Op::Geq(Value::Float(8.0)),
+            )
+                .into(),
+            (
+                OntologyField::try_new("imu.angular_velocity.x".into()).unwrap(),
+                Op::Leq(Value::Float(3.0)),
+            )
+                .into(),
+        ]);
+
+        let splits = grp.split_by_ontology_tag().unwrap();
 
         dbg!(&splits);
 
@@ -522,4 +944,110 @@ mod tests {
             assert!(ontology_tag == "image" || ontology_tag == "imu");
         }
     }
+
+    #[test]
+    fn expr_grp_split_with_subgroups() {
+        let or_grp = ExprGroup::new(vec![
+            (
+                OntologyField::try_new("gps.latitude".into()).unwrap(),
+                Op::Gt(Value::Float(10.0)),
+            )
+                .into(),
+            (
+                OntologyField::try_new("gps.latitude".into()).unwrap(),
+                Op::Lt(Value::Float(-10.0)),
+            )
+                .into(),
+        ])
+        .with_connector(Connector::Or);
+
+        let grp = ExprGroup::new(vec![
+            (
+                OntologyField::try_new("imu.acceleration.x".into()).unwrap(),
+                Op::Geq(Value::Float(8.0)),
+            )
+                .into(),
+        ])
+        .with_subgroups(vec![or_grp]);
+
+        let splits = grp.split_by_ontology_tag().unwrap();
+        assert_eq!(splits.len(), 2);
+
+        for split in splits {
+            match split.ontology_tag() {
+                Some("imu") => {
+                    assert_eq!(split.group.len(), 1);
+                    assert!(split.subgroups.is_empty());
+                }
+                Some("gps") => {
+                    assert!(split.group.is_empty());
+                    assert_eq!(split.subgroups.len(), 1);
+                    assert_eq!(split.subgroups[0].connector, Connector::Or);
+                }
+                _ => panic!("unexpected ontology tag"),
+            }
+        }
+
+        // A disjunction across different ontology tags can't be split
+        let mixed = ExprGroup::new(vec![
+            (
+                OntologyField::try_new("gps.latitude".into()).unwrap(),
+                Op::Gt(Value::Float(10.0)),
+            )
+                .into(),
+            (
+                OntologyField::try_new("imu.acceleration.x".into()).unwrap(),
+                Op::Geq(Value::Float(8.0)),
+            )
+                .into(),
+        ])
+        .with_connector(Connector::Or);
+
+        assert!(matches!(
+            ExprGroup::new(Vec::new())
+                .with_subgroups(vec![mixed])
+                .split_by_ontology_tag(),
+            Err(crate::query::Error::MixedOntologyTags)
+        ));
+    }
+
+    #[test]
+    fn portable_regex() {
+        for pattern in [
+            "^seq_[0-9]+/imu$",
+            "rgb|bgr",
+            r"^a\.b\d{2,3}\s*(?:x|y)?$",
+            "[^[:alpha:]_-]+",
+            r"[\d\]]",
+            r"\t\n",
+        ] {
+            assert!(validate_regex(pattern).is_ok(), "{pattern}");
+        }
+
+        for pattern in [
+            // not valid for the query engine
+            "seq_(",
+            "(?=a)",
+            r"(a)\1",
+            // divergent or supported by the query engine only
+            r"\bimu\b",
+            r"\Aimu\z",
+            "(?P<name>a)",
+            "(?<name>a)",
+            "(?i)imu",
+            "(?i:imu)",
+            r"\p{L}",
+            r"\x41",
+            "[a[b]]",
+            "[a-z&&[^x]]",
+            "[[:^alpha:]]",
+            r"[\D]",
+            "a{300}",
+        ] {
+            assert!(
+                matches!(validate_regex(pattern), Err(OpError::InvalidRegex(_))),
+                "{pattern}"
+            );
+        }
+    }
 }
@@ -0,0 +1,185 @@
+//! Least recently used set of entries shared by the caches of the query engine (see
+//! [`ReadCache`](super::ReadCache) and [`SchemaCache`](super::SchemaCache)).
+//!
+//! Each entry has a weight (e.g. its size in bytes, or `1` to bound the number of entries)
+//! and the least recently used entries are evicted when the total weight would exceed the
+//! budget. The set is not synchronized, caches wrap it in their own lock.
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use log::trace;
+
+struct Entry<V> {
+    value: V,
+    weight: usize,
+    last_access: u64,
+}
+
+pub(super) struct Lru<K, V> {
+    /// Name of the cache, used in the logs
+    name: &'static str,
+    entries: HashMap<K, Entry<V>>,
+    /// Monotonic counter used to track entries access order
+    tick: u64,
+    /// Total weight of the entries
+    weight: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub insertions: u64,
+    pub evictions: u64,
+}
+
+impl<K, V> Lru<K, V>
+where
+    K: Eq + Hash + Clone + Debug,
+    V: Clone,
+{
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            entries: HashMap::new(),
+            tick: 0,
+            weight: 0,
+            hits: 0,
+            misses: 0,
+            insertions: 0,
+            evictions: 0,
+        }
+    }
+
+    /// Number of entries in the set
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Total weight of the entries in the set
+    pub fn weight(&self) -> usize {
+        self.weight
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    /// Returns the value associated with `key`, marking it as the most recently used.
+    pub fn get<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let tick = self.next_tick();
+
+        let value = self.entries.get_mut(key).map(|entry| {
+            entry.last_access = tick;
+            entry.value.clone()
+        });
+
+        if value.is_some() {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+
+        value
+    }
+
+    /// Inserts `value` with the given `weight`, replacing any previous value of `key`.
+    ///
+    /// Least recently used entries are evicted until the total weight fits in `budget`.
+    pub fn insert(&mut self, key: K, value: V, weight: usize, budget: usize) {
+        self.remove(&key);
+
+        while self.weight + weight > budget {
+            if !self.evict_lru() {
+                break;
+            }
+        }
+
+        let last_access = self.next_tick();
+        self.weight += weight;
+        self.insertions += 1;
+        self.entries.insert(
+            key,
+            Entry {
+                value,
+                weight,
+                last_access,
+            },
+        );
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let entry = self.entries.remove(key)?;
+        self.weight -= entry.weight;
+        Some(entry.value)
+    }
+
+    /// Keeps only the entries whose key satisfies `keep`.
+    pub fn retain(&mut self, mut keep: impl FnMut(&K) -> bool) {
+        let mut removed = 0;
+        self.entries.retain(|key, entry| {
+            let kept = keep(key);
+            if !kept {
+                removed += entry.weight;
+            }
+            kept
+        });
+        self.weight -= removed;
+    }
+
+    /// Evicts the least recently used entry, returns `false` if the set is empty
+    fn evict_lru(&mut self) -> bool {
+        let lru = self
+            .entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_access)
+            .map(|(key, _)| key.clone());
+
+        if let Some(key) = lru {
+            self.remove(&key);
+            self.evictions += 1;
+            trace!("evicted {:?} from {} cache", key, self.name);
+            return true;
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weighted_eviction() {
+        let mut lru: Lru<String, u32> = Lru::new("test");
+
+        lru.insert("a".to_owned(), 1, 2, 4);
+        lru.insert("b".to_owned(), 2, 2, 4);
+
+        // touch `a` so that `b` becomes the least recently used
+        assert_eq!(lru.get("a"), Some(1));
+
+        lru.insert("c".to_owned(), 3, 1, 4);
+        assert_eq!((lru.len(), lru.weight()), (2, 3));
+        assert_eq!(lru.get("b"), None);
+
+        // replacing an entry updates its weight
+        lru.insert("a".to_owned(), 4, 3, 4);
+        assert_eq!((lru.len(), lru.weight()), (2, 4));
+        assert_eq!(lru.get("a"), Some(4));
+
+        lru.retain(|key| key != "a");
+        assert_eq!((lru.len(), lru.weight()), (1, 1));
+
+        assert_eq!((lru.hits, lru.misses), (2, 1));
+        assert_eq!((lru.insertions, lru.evictions), (4, 1));
+    }
+}
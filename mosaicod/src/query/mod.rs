@@ -9,5 +9,19 @@ pub use builder::*;
 mod timeseries_gw;
 pub use timeseries_gw::*;
 
+mod lru;
+
+mod read_cache;
+pub use read_cache::*;
+
+mod schema_cache;
+pub use schema_cache::*;
+
+mod deadline_stream;
+pub use deadline_stream::*;
+
+mod explain;
+pub use explain::*;
+
 mod error;
 pub use error::*;
@@ -0,0 +1,384 @@
+//! In-memory cache for decoded topic data.
+//!
+//! The cache keeps the record batches (and their schema) of recently read topics,
+//! so that repeated reads of the same topic do not need to hit the object store
+//! and decode the data files again.
+//!
+//! All entries share a single global memory budget (see
+//! [`params::ConfigurablesParams::read_cache_budget_in_bytes`](crate::params::ConfigurablesParams)).
+//! When inserting a new entry would exceed the budget, the least recently used entries
+//! are evicted until the new entry fits.
+//!
+//! Only immutable data (i.e. data of locked topics) should be cached, since the cache
+//! has no way to detect changes in the underlying data files. Operations rewriting the
+//! data of a topic must invalidate its entries, reads started before the invalidation
+//! are not cached (see [`ReadCache::generation`]).
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll};
+
+use arrow::array::RecordBatch;
+use arrow::datatypes::SchemaRef;
+use datafusion::error::DataFusionError;
+use datafusion::execution::{RecordBatchStream, SendableRecordBatchStream};
+use futures::{Stream, StreamExt};
+use log::trace;
+
+use super::lru::Lru;
+
+/// Decoded data of a topic stored in the cache
+#[derive(Debug)]
+pub struct CachedRead {
+    pub schema: SchemaRef,
+    pub batches: Vec<RecordBatch>,
+    size_bytes: usize,
+}
+
+impl CachedRead {
+    /// Memory occupied by the cached batches
+    pub fn size_bytes(&self) -> usize {
+        self.size_bytes
+    }
+}
+
+/// Snapshot of the cache usage statistics
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReadCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub insertions: u64,
+    pub evictions: u64,
+    /// Number of entries currently in the cache
+    pub entries: usize,
+    /// Memory currently occupied by the cached entries
+    pub used_bytes: usize,
+    /// Maximum memory that can be occupied by the cached entries
+    pub budget_bytes: usize,
+}
+
+impl ReadCacheStats {
+    /// Ratio between hits and total lookups, `0.0` if no lookup was performed
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / lookups as f64
+    }
+}
+
+struct State {
+    /// Entries weighted by their size in bytes
+    entries: Lru<String, Arc<CachedRead>>,
+    /// Incremented on every invalidation
+    generation: u64,
+}
+
+/// Thread-safe LRU cache of decoded topic data bounded by a memory budget.
+///
+/// The cache can be cheaply cloned, all clones share the same entries.
+/// A cache with a budget of `0` bytes is disabled: lookups always miss and
+/// insertions are discarded.
+#[derive(Clone)]
+pub struct ReadCache {
+    budget_bytes: usize,
+    state: Arc<Mutex<State>>,
+}
+
+impl ReadCache {
+    pub fn new(budget_bytes: usize) -> Self {
+        Self {
+            budget_bytes,
+            state: Arc::new(Mutex::new(State {
+                entries: Lru::new("read"),
+                generation: 0,
+            })),
+        }
+    }
+
+    /// Creates a cache that never stores any entry
+    pub fn disabled() -> Self {
+        Self::new(0)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.budget_bytes > 0
+    }
+
+    pub fn budget_bytes(&self) -> usize {
+        self.budget_bytes
+    }
+
+    /// Returns the cached data associated with `key`, marking it as the most recently used.
+    pub fn get(&self, key: &str) -> Option<Arc<CachedRead>> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        self.lock().entries.get(key)
+    }
+
+    /// Returns the current generation of the cache, which changes on every invalidation.
+    ///
+    /// Reads must take the generation before reading the data and pass it to
+    /// [`ReadCache::insert_at`], so that data read before an invalidation is discarded.
+    pub fn generation(&self) -> u64 {
+        self.lock().generation
+    }
+
+    /// Inserts the data associated with `key`, replacing any previous value.
+    ///
+    /// Least recently used entries are evicted to make room for the new one.
+    /// Returns `false` if the data is larger than the whole budget and was not cached.
+    pub fn insert(&self, key: String, schema: SchemaRef, batches: Vec<RecordBatch>) -> bool {
+        self.insert_inner(None, key, schema, batches)
+    }
+
+    /// Same as [`ReadCache::insert`], but the data is discarded if the cache has been
+    /// invalidated since `generation` was taken.
+    pub fn insert_at(
+        &self,
+        generation: u64,
+        key: String,
+        schema: SchemaRef,
+        batches: Vec<RecordBatch>,
+    ) -> bool {
+        self.insert_inner(Some(generation), key, schema, batches)
+    }
+
+    fn insert_inner(
+        &self,
+        generation: Option<u64>,
+        key: String,
+        schema: SchemaRef,
+        batches: Vec<RecordBatch>,
+    ) -> bool {
+        let size_bytes = batches_size(&batches);
+
+        if size_bytes > self.budget_bytes {
+            trace!(
+                "skipping read cache insertion for `{}`, size {} exceeds budget {}",
+                key, size_bytes, self.budget_bytes
+            );
+            return false;
+        }
+
+        let mut state = self.lock();
+
+        if generation.is_some_and(|generation| generation != state.generation) {
+            trace!(
+                "skipping read cache insertion for `{}`, invalidated while reading",
+                key
+            );
+            return false;
+        }
+
+        let data = Arc::new(CachedRead {
+            schema,
+            batches,
+            size_bytes,
+        });
+        state
+            .entries
+            .insert(key, data, size_bytes, self.budget_bytes);
+
+        true
+    }
+
+    /// Removes the entry associated with `path` and the entries of all the paths below it
+    /// (e.g. invalidating a sequence removes the entries of its topics).
+    pub fn invalidate(&self, path: &str) {
+        let prefix = format!("{}/", path.trim_end_matches('/'));
+
+        let mut state = self.lock();
+        state.generation += 1;
+        state
+            .entries
+            .retain(|key| key != path && !key.starts_with(&prefix));
+    }
+
+    pub fn stats(&self) -> ReadCacheStats {
+        let state = self.lock();
+        let entries = &state.entries;
+        ReadCacheStats {
+            hits: entries.hits,
+            misses: entries.misses,
+            insertions: entries.insertions,
+            evictions: entries.evictions,
+            entries: entries.len(),
+            used_bytes: entries.weight(),
+            budget_bytes: self.budget_bytes,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // The state is always left consistent, so it is safe to recover from a poisoned lock
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn batches_size(batches: &[RecordBatch]) -> usize {
+    batches.iter().map(|b| b.get_array_memory_size()).sum()
+}
+
+/// Record batch stream that collects the streamed batches and stores them into
+/// a [`ReadCache`] once the inner stream has been fully consumed.
+///
+/// Collection is abandoned as soon as the collected data exceeds the cache budget,
+/// or if the inner stream returns an error. The batches are discarded if the cache
+/// has been invalidated after `generation` was taken.
+pub struct CachingStream {
+    inner: SendableRecordBatchStream,
+    cache: ReadCache,
+    key: String,
+    generation: u64,
+    collected: Option<Vec<RecordBatch>>,
+    collected_bytes: usize,
+}
+
+impl CachingStream {
+    pub fn new(
+        inner: SendableRecordBatchStream,
+        cache: ReadCache,
+        key: String,
+        generation: u64,
+    ) -> Self {
+        let collected = cache.is_enabled().then(Vec::new);
+        Self {
+            inner,
+            cache,
+            key,
+            generation,
+            collected,
+            collected_bytes: 0,
+        }
+    }
+}
+
+impl Stream for CachingStream {
+    type Item = Result<RecordBatch, DataFusionError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let poll = this.inner.poll_next_unpin(cx);
+
+        match &poll {
+            Poll::Ready(Some(Ok(batch))) => {
+                if let Some(collected) = &mut this.collected {
+                    this.collected_bytes += batch.get_array_memory_size();
+                    if this.collected_bytes > this.cache.budget_bytes() {
+                        this.collected = None;
+                    } else {
+                        collected.push(batch.clone());
+                    }
+                }
+            }
+            Poll::Ready(Some(Err(_))) => this.collected = None,
+            Poll::Ready(None) => {
+                if let Some(collected) = this.collected.take() {
+                    let schema = this.inner.schema();
+                    this.cache
+                        .insert_at(this.generation, this.key.clone(), schema, collected);
+                }
+            }
+            Poll::Pending => {}
+        }
+
+        poll
+    }
+}
+
+impl RecordBatchStream for CachingStream {
+    fn schema(&self) -> SchemaRef {
+        self.inner.schema()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::Int64Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    fn batch(rows: i64) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![Field::new("v", DataType::Int64, false)]));
+        RecordBatch::try_new(
+            schema,
+            vec![Arc::new(Int64Array::from_iter_values(0..rows))],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn lru_eviction_respects_budget() {
+        let b = batch(128);
+        let size = b.get_array_memory_size();
+
+        // room for two entries only
+        let cache = ReadCache::new(size * 2);
+
+        assert!(cache.insert("a".to_owned(), b.schema(), vec![b.clone()]));
+        assert!(cache.insert("b".to_owned(), b.schema(), vec![b.clone()]));
+
+        // touch `a` so that `b` becomes the least recently used
+        assert!(cache.get("a").is_some());
+
+        assert!(cache.insert("c".to_owned(), b.schema(), vec![b.clone()]));
+
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("c").is_some());
+
+        let stats = cache.stats();
+        assert_eq!(stats.entries, 2);
+        assert_eq!(stats.evictions, 1);
+        assert_eq!(stats.hits, 3);
+        assert_eq!(stats.misses, 1);
+        assert!(stats.used_bytes <= stats.budget_bytes);
+    }
+
+    #[test]
+    fn oversized_entries_are_not_cached() {
+        let b = batch(128);
+        let cache = ReadCache::new(b.get_array_memory_size() - 1);
+
+        assert!(!cache.insert("a".to_owned(), b.schema(), vec![b]));
+        assert!(cache.get("a").is_none());
+        assert_eq!(cache.stats().used_bytes, 0);
+    }
+
+    #[test]
+    fn invalidation() {
+        let b = batch(1);
+        let cache = ReadCache::new(b.get_array_memory_size() * 4);
+
+        assert!(cache.insert("seq/a".to_owned(), b.schema(), vec![b.clone()]));
+        assert!(cache.insert("seq/b".to_owned(), b.schema(), vec![b.clone()]));
+        assert!(cache.insert("seq_2/a".to_owned(), b.schema(), vec![b.clone()]));
+
+        // reads started before an invalidation are not cached
+        let generation = cache.generation();
+        cache.invalidate("seq");
+        assert!(!cache.insert_at(generation, "seq/a".to_owned(), b.schema(), vec![b.clone()]));
+
+        assert!(cache.get("seq/a").is_none());
+        assert!(cache.get("seq/b").is_none());
+        assert!(cache.get("seq_2/a").is_some());
+
+        let generation = cache.generation();
+        assert!(cache.insert_at(generation, "seq/a".to_owned(), b.schema(), vec![b]));
+        assert!(cache.get("seq/a").is_some());
+    }
+
+    #[test]
+    fn disabled_cache() {
+        let b = batch(1);
+        let cache = ReadCache::disabled();
+
+        assert!(!cache.insert("a".to_owned(), b.schema(), vec![b]));
+        assert!(cache.get("a").is_none());
+        assert_eq!(cache.stats().misses, 0);
+    }
+}
@@ -0,0 +1,178 @@
+//! In-memory cache of the schemas resolved for the data files read by the
+//! [`TimeseriesGw`](super::TimeseriesGw).
+//!
+//! Registering a path in the query engine requires resolving its schema, which means listing
+//! the path and reading the footers of its data files from the store. The cache keeps the
+//! schema resolved for each (path, format) pair, so that repeated reads of the same topic or
+//! chunk skip this step.
+//!
+//! The number of entries is bounded (see
+//! [`params::ConfigurablesParams::schema_cache_size`](crate::params::ConfigurablesParams)),
+//! the least recently used entry is evicted when a new entry does not fit.
+//!
+//! The cache has no way to detect changes in the underlying data files, so entries must be
+//! invalidated (see [`SchemaCache::invalidate`]) when data is written to or deleted from a path.
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use arrow::datatypes::SchemaRef;
+
+use super::lru::Lru;
+use crate::rw;
+
+/// Snapshot of the cache usage statistics
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SchemaCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    /// Number of entries currently in the cache
+    pub entries: usize,
+    /// Maximum number of entries held by the cache
+    pub capacity: usize,
+}
+
+impl SchemaCacheStats {
+    /// Ratio between hits and total lookups, `0.0` if no lookup was performed
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / lookups as f64
+    }
+}
+
+type Key = (String, rw::Format);
+
+/// Entries have all the same weight, so that the budget bounds their number
+type State = Lru<Key, SchemaRef>;
+
+/// Thread-safe LRU cache of resolved schemas bounded by a number of entries.
+///
+/// The cache can be cheaply cloned, all clones share the same entries.
+/// A cache with a capacity of `0` entries is disabled: lookups always miss and
+/// insertions are discarded.
+#[derive(Clone)]
+pub struct SchemaCache {
+    capacity: usize,
+    state: Arc<Mutex<State>>,
+}
+
+impl SchemaCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Arc::new(Mutex::new(Lru::new("schema"))),
+        }
+    }
+
+    /// Creates a cache that never stores any entry
+    pub fn disabled() -> Self {
+        Self::new(0)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// Returns the schema resolved for `path` read as `format`, marking it as the most
+    /// recently used.
+    pub fn get(&self, path: &str, format: rw::Format) -> Option<SchemaRef> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        self.lock().get(&(path.to_owned(), format))
+    }
+
+    /// Inserts the schema resolved for `path` read as `format`, replacing any previous value.
+    ///
+    /// The least recently used entry is evicted if the cache is full.
+    pub fn insert(&self, path: String, format: rw::Format, schema: SchemaRef) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        self.lock().insert((path, format), schema, 1, self.capacity);
+    }
+
+    /// Removes the entries of `path` and of all the paths below it (e.g. invalidating a
+    /// topic removes the entries of its chunks), for any format.
+    pub fn invalidate(&self, path: &str) {
+        let prefix = format!("{}/", path.trim_end_matches('/'));
+        self.lock()
+            .retain(|(key, _)| key != path && !key.starts_with(&prefix));
+    }
+
+    pub fn stats(&self) -> SchemaCacheStats {
+        let state = self.lock();
+        SchemaCacheStats {
+            hits: state.hits,
+            misses: state.misses,
+            evictions: state.evictions,
+            entries: state.len(),
+            capacity: self.capacity,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // The state is always left consistent, so it is safe to recover from a poisoned lock
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![Field::new("v", DataType::Int64, false)]))
+    }
+
+    #[test]
+    fn lru_eviction_and_invalidation() {
+        let cache = SchemaCache::new(2);
+        let format = rw::Format::Default;
+
+        cache.insert("seq/a".to_owned(), format, schema());
+        cache.insert("seq/a/data-00000.parquet".to_owned(), format, schema());
+
+        // touch `seq/a` so that its chunk becomes the least recently used
+        assert!(cache.get("seq/a", format).is_some());
+
+        cache.insert("seq/ab".to_owned(), format, schema());
+
+        assert!(cache.get("seq/a", format).is_some());
+        assert!(cache.get("seq/a/data-00000.parquet", format).is_none());
+        assert!(cache.get("seq/ab", format).is_some());
+        assert!(cache.get("seq/a", rw::Format::Ragged).is_none());
+
+        let stats = cache.stats();
+        assert_eq!(stats.entries, 2);
+        assert_eq!(stats.evictions, 1);
+        assert_eq!(stats.hits, 3);
+        assert_eq!(stats.misses, 2);
+
+        // sibling paths sharing the same prefix are not invalidated
+        let cache = SchemaCache::new(8);
+        cache.insert("seq/a".to_owned(), format, schema());
+        cache.insert("seq/a/data-00000.parquet".to_owned(), format, schema());
+        cache.insert("seq/ab".to_owned(), format, schema());
+        cache.invalidate("seq/a");
+        assert!(cache.get("seq/a", format).is_none());
+        assert!(cache.get("seq/a/data-00000.parquet", format).is_none());
+        assert!(cache.get("seq/ab", format).is_some());
+    }
+
+    #[test]
+    fn disabled_cache() {
+        let cache = SchemaCache::disabled();
+
+        cache.insert("seq/a".to_owned(), rw::Format::Default, schema());
+        assert!(cache.get("seq/a", rw::Format::Default).is_none());
+        assert_eq!(cache.stats().misses, 0);
+    }
+}
@@ -1,22 +1,30 @@
 //! This module provides the timeseries gateway, a wrapper around the datafusion
 //! query engine tailored for reading and processing timeseries data files stored in the
-//! application's underlying object store (S3, GCS, etc.).
+//! application's underlying object store (S3, Azure, etc.).
 //!
 //! The engine integrates directly with the configured [`store::Store`] to resolve
 //! paths and access data sources like Parquet files efficiently.
 use log::trace;
 
-use crate::{params, query, rw, store};
-use arrow::datatypes::{Schema, SchemaRef};
+use crate::{params, query, rw, store, traits::SquashedIterator, types};
+use arrow::array::{AsArray, RecordBatch, StructArray};
+use arrow::datatypes::{DataType, Fields, Schema, SchemaRef, UInt64Type};
+use datafusion::common::{DFSchema, ScalarValue};
+use datafusion::datasource::file_format::arrow::ArrowFormat;
 use datafusion::datasource::file_format::parquet::ParquetFormat;
 use datafusion::datasource::listing::ListingOptions;
 use datafusion::execution::SendableRecordBatchStream;
 use datafusion::execution::runtime_env::{RuntimeEnv, RuntimeEnvBuilder};
 use datafusion::functions::core::expr_ext::FieldAccessor;
+use datafusion::functions_aggregate::expr_fn::{approx_distinct, avg, count, max, min, sum};
+use datafusion::functions_window::expr_fn::row_number as row_number_window;
+use datafusion::logical_expr::ExprFunctionExt;
+use datafusion::logical_expr::SortExpr;
 use datafusion::prelude::*;
 use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
 use super::Error;
 
@@ -25,6 +33,8 @@ pub type TimeseriesGwRef = Arc<TimeseriesGw>;
 pub struct TimeseriesGw {
     runtime: Arc<RuntimeEnv>,
     store: Arc<store::Store>,
+    cache: query::ReadCache,
+    schemas: query::SchemaCache,
 }
 
 impl TimeseriesGw {
@@ -38,9 +48,33 @@ impl TimeseriesGw {
         Ok(TimeseriesGw {
             runtime,
             store: store.clone(),
+            cache: query::ReadCache::disabled(),
+            schemas: query::SchemaCache::disabled(),
         })
     }
 
+    /// Sets the cache used to hold decoded data of immutable topics.
+    pub fn with_read_cache(mut self, cache: query::ReadCache) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    pub fn read_cache(&self) -> &query::ReadCache {
+        &self.cache
+    }
+
+    /// Sets the cache used to hold the schemas resolved for the read paths.
+    pub fn with_schema_cache(mut self, schemas: query::SchemaCache) -> Self {
+        self.schemas = schemas;
+        self
+    }
+
+    /// Returns the cache of the resolved schemas, its entries must be invalidated when data
+    /// is written to or deleted from a path.
+    pub fn schema_cache(&self) -> &query::SchemaCache {
+        &self.schemas
+    }
+
     /// Read time-series data from a path.
     ///
     /// All files in the provided path will be included in the read.
@@ -48,52 +82,220 @@ impl TimeseriesGw {
     /// If `batch_size` is provided, the system will use it to configure the batch size
     /// for the query engine. This allows callers to control message sizes based on
     /// pre-computed statistics from the database.
+    ///
+    /// If `time_range` is provided, only the rows with a timestamp within the (inclusive)
+    /// range are returned. The predicate is pushed down into the scan, so the row groups
+    /// outside the range are skipped using the timestamp column statistics.
+    ///
+    /// If `projection` is provided, only the timestamp column and the listed (top-level)
+    /// columns are read. An error is returned if any of the columns does not exist.
+    ///
+    /// Rows are sorted by timestamp according to `order`.
     pub async fn read(
         &self,
         path: impl AsRef<Path>,
         format: rw::Format,
         batch_size: Option<usize>,
+        time_range: Option<query::Range<query::Timestamp>>,
+        projection: Option<Vec<String>>,
+        order: query::SortOrder,
     ) -> Result<TimeseriesGwResult, Error> {
-        let listing_options = get_listing_options(format);
+        let ctx = self.session_context(batch_size);
+
+        // we use `data` as internal reference for this context
+        self.register_listing_table(&ctx, "data", path, format)
+            .await?;
+
+        let ts = params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP;
+
+        let columns = if let Some(projection) = projection {
+            let schema = ctx.table("data").await?.schema().clone();
+
+            let mut columns = vec![ts.to_owned()];
+            for column in projection {
+                if !schema.has_column_with_unqualified_name(&column) {
+                    return Err(Error::UnknownColumn { column });
+                }
+                if !columns.contains(&column) {
+                    columns.push(column);
+                }
+            }
+
+            columns
+                .iter()
+                .map(|c| quote_identifier(c))
+                .collect::<Vec<_>>()
+                .join(", ")
+        } else {
+            "*".to_owned()
+        };
+
+        let direction = if order.is_asc() { "ASC" } else { "DESC" };
+
+        let select = if let Some(range) = time_range {
+            let (start, end): (i64, i64) = (range.min.into(), range.max.into());
+            format!(
+                "SELECT {columns} FROM data WHERE {ts} BETWEEN {start} AND {end} ORDER BY {ts} {direction}"
+            )
+        } else {
+            format!("SELECT {columns} FROM data ORDER BY {ts} {direction}")
+        };
+
+        let df = ctx.sql(&select).await?;
+
+        Ok(TimeseriesGwResult {
+            data_frame: df,
+            sort: vec![col(ts).sort(order.is_asc(), false)],
+        })
+    }
+
+    /// Reads the data of several paths, merged in a single result ordered by timestamp.
+    ///
+    /// Each path is given the name of its column in the result: the rows hold the timestamp
+    /// column and one struct column per path, holding the other columns of the path (see
+    /// [`struct_type`]). The struct column of a path is set only on the rows read from that
+    /// path and is null on the others, so the columns of different paths are never merged
+    /// and their schemas don't need to match.
+    pub async fn read_many<P: AsRef<Path>>(
+        &self,
+        paths: &[(&str, P, rw::Format)],
+        batch_size: Option<usize>,
+    ) -> Result<TimeseriesGwResult, Error> {
+        let ctx = self.session_context(batch_size);
+
+        let mut frames = Vec::with_capacity(paths.len());
+        for (idx, (name, path, format)) in paths.iter().enumerate() {
+            let table = format!("data_{idx}");
+            self.register_listing_table(&ctx, &table, path, *format)
+                .await?;
+
+            let df = ctx.table(&table).await?;
+            let data_type = struct_type(df.schema());
+            frames.push((*name, df, data_type));
+        }
+
+        let ts = params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP;
+
+        let mut data_frame: Option<DataFrame> = None;
+        for (idx, (_, df, _)) in frames.iter().enumerate() {
+            let mut columns = vec![ident(ts)];
+            for (other, (name, _, data_type)) in frames.iter().enumerate() {
+                let DataType::Struct(fields) = data_type else {
+                    unreachable!("struct_type returns struct types");
+                };
+
+                let value = if other != idx {
+                    lit(ScalarValue::try_from(data_type)?)
+                } else if fields.is_empty() {
+                    // Paths holding only the timestamp column are marked by an empty struct
+                    lit(ScalarValue::Struct(Arc::new(
+                        StructArray::new_empty_fields(1, None),
+                    )))
+                } else {
+                    let args = fields
+                        .iter()
+                        .flat_map(|field| [lit(field.name().as_str()), ident(field.name())])
+                        .collect();
+                    cast(named_struct(args), data_type.clone())
+                };
+                columns.push(value.alias(*name));
+            }
+            let df = df.clone().select(columns)?;
 
+            data_frame = Some(match data_frame {
+                Some(acc) => acc.union(df)?,
+                None => df,
+            });
+        }
+
+        let data_frame = data_frame.ok_or(Error::NothingToRead)?;
+
+        let sort = vec![col(ts).sort(true, false)];
+
+        Ok(TimeseriesGwResult {
+            data_frame: data_frame.sort(sort.clone())?,
+            sort,
+        })
+    }
+
+    /// Creates a new session context configured to exploit the timestamp column statistics.
+    ///
+    /// Predicates are pushed down into the parquet scan, so that row groups and pages falling
+    /// outside the requested time window are skipped using the min/max statistics (and the bloom
+    /// filter) written for the timestamp column, instead of being decoded and filtered afterwards.
+    fn session_context(&self, batch_size: Option<usize>) -> SessionContext {
         let mut conf = SessionConfig::new();
         if let Some(batch_size) = batch_size {
             conf = conf.with_batch_size(batch_size);
         }
 
-        let ctx = SessionContext::new_with_config_rt(conf, self.runtime.clone());
+        let parquet = &mut conf.options_mut().execution.parquet;
+        parquet.pruning = true;
+        parquet.enable_page_index = true;
+        parquet.bloom_filter_on_read = true;
+        parquet.pushdown_filters = true;
+        parquet.reorder_filters = true;
+
+        SessionContext::new_with_config_rt(conf, self.runtime.clone())
+    }
+
+    /// Registers the data files in `path` as `table`.
+    ///
+    /// The schema of the data files is resolved from the store only if it is not in the
+    /// schema cache, otherwise the cached schema is provided to the listing table.
+    async fn register_listing_table(
+        &self,
+        ctx: &SessionContext,
+        table: &str,
+        path: impl AsRef<Path>,
+        format: rw::Format,
+    ) -> Result<(), Error> {
+        let key = path.as_ref().to_string_lossy().into_owned();
+        let cached = self.schemas.get(&key, format);
+        let resolve = cached.is_none();
 
-        // we use `data` as internal reference for this context
         ctx.register_listing_table(
-            "data",
-            self.datafile_url(path)?,
-            listing_options,
-            None,
+            table,
+            self.datafile_url(&path)?,
+            get_listing_options(format),
+            cached,
             None,
         )
         .await?;
 
-        let select = format!(
-            "SELECT * FROM data ORDER BY {}",
-            params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP
-        );
-
-        let df = ctx.sql(&select).await?;
+        if resolve {
+            let schema = ctx.table(table).await?.schema().inner().clone();
+            // Paths without data files have no schema to cache
+            if !schema.fields().is_empty() {
+                trace!("caching schema of `{}`", key);
+                self.schemas.insert(key, format, schema);
+            }
+        }
 
-        Ok(TimeseriesGwResult { data_frame: df })
+        Ok(())
     }
 
     fn datafile_url(&self, path: impl AsRef<Path>) -> Result<url::Url, Error> {
-        Ok(self
-            .store
-            .as_ref()
-            .url_schema
-            .join(&path.as_ref().to_string_lossy())?)
+        Ok(self.store.url(path)?)
     }
 }
 
+/// Name of the column holding the values returned by [`TimeseriesGwResult::distinct`]
+pub const DISTINCT_VALUE_COLUMN: &str = "value";
+
+/// Distinct values of a field, in the [`DISTINCT_VALUE_COLUMN`] column of the batches
+#[derive(Debug, Default)]
+pub struct DistinctValues {
+    pub batches: Vec<RecordBatch>,
+    /// `true` if the field takes more values than the returned ones
+    pub truncated: bool,
+}
+
+#[derive(Clone)]
 pub struct TimeseriesGwResult {
     data_frame: DataFrame,
+    /// Ordering of the rows, restored by the operations that do not preserve it
+    sort: Vec<SortExpr>,
 }
 
 impl TimeseriesGwResult {
@@ -104,10 +306,41 @@ impl TimeseriesGwResult {
         ))
     }
 
+    /// Returns the optimized logical plan used to compute the result, as printed by
+    /// `EXPLAIN`. No data is read.
+    pub fn logical_plan(&self) -> Result<String, Error> {
+        let plan = self.data_frame.clone().into_optimized_plan()?;
+        Ok(plan.display_indent().to_string())
+    }
+
+    /// Keeps the rows matching `filter`.
+    ///
+    /// An error listing the available fields is returned if the filter refers to a field
+    /// that does not exist in the data.
     pub fn filter<V>(self, filter: query::ExprGroup<V>) -> Result<Self, Error>
     where
         V: Into<query::Value>,
     {
+        let schema = self.data_frame.schema();
+        if let Some(field) = filter
+            .ontology_fields()
+            .into_iter()
+            .find(|f| !has_field(schema, f.field()))
+        {
+            return Err(Error::BadField {
+                field: field.value().to_owned(),
+                available: field_paths(schema),
+            });
+        }
+
+        if let Some(field) = filter.nan_checked_fields().into_iter().find(|f| {
+            !field_data_type(schema, f.field()).is_some_and(|data_type| data_type.is_numeric())
+        }) {
+            return Err(Error::NotNumeric {
+                field: field.value().to_owned(),
+            });
+        }
+
         let expr = expr_group_to_df_expr(filter);
 
         let data_frame = if let Some(expr) = expr {
@@ -117,7 +350,254 @@ impl TimeseriesGwResult {
             self.data_frame
         };
 
-        Ok(TimeseriesGwResult { data_frame })
+        Ok(TimeseriesGwResult {
+            data_frame,
+            sort: self.sort,
+        })
+    }
+
+    /// Sorts the rows by the value of `field`, using the timestamp to order the rows
+    /// sharing the same value. Null values are placed last.
+    ///
+    /// Nested fields can be addressed using dots (e.g. `pose.position.x`).
+    /// An error is returned if the field does not exist.
+    pub fn order_by_field(self, field: &str, order: query::SortOrder) -> Result<Self, Error> {
+        if !has_field(self.data_frame.schema(), field) {
+            return Err(Error::UnknownColumn {
+                column: field.to_owned(),
+            });
+        }
+
+        let ts = params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP;
+        let sort = vec![
+            unfold_column(field).sort(order.is_asc(), false),
+            col(ts).sort(order.is_asc(), false),
+        ];
+
+        Ok(TimeseriesGwResult {
+            data_frame: self.data_frame.sort(sort.clone())?,
+            sort,
+        })
+    }
+
+    /// Keeps one row every `n` rows, useful to reduce the amount of data returned to
+    /// clients that do not need the full resolution (e.g. for visualization).
+    ///
+    /// Rows are numbered following the timestamp ordering, and the first row of each group
+    /// of `n` rows is kept. Decimation is applied after the timestamp ordering, so the result
+    /// is deterministic and keeps the current order. A value of `n` lower than 2 keeps all the rows.
+    pub fn decimate(self, n: usize) -> Result<Self, Error> {
+        if n < 2 {
+            return Ok(self);
+        }
+
+        let ts = params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP;
+        let sortable = self
+            .data_frame
+            .schema()
+            .field_with_unqualified_name(ts)
+            .is_ok_and(|field| field.data_type().is_integer());
+        if !sortable {
+            return Err(Error::NotSortable);
+        }
+
+        let row_number = "__row_number__";
+        let numbering = row_number_window()
+            .order_by(vec![col(ts).sort(true, false)])
+            .build()?;
+
+        let data_frame = self
+            .data_frame
+            .with_column(row_number, numbering)?
+            .filter(((col(row_number) - lit(1u64)) % lit(n as u64)).eq(lit(0u64)))?
+            .drop_columns(&[row_number])?
+            .sort(self.sort.clone())?;
+
+        Ok(TimeseriesGwResult {
+            data_frame,
+            sort: self.sort,
+        })
+    }
+
+    /// Replaces struct columns with a column for each of their (possibly nested) fields,
+    /// named after the dotted path of the field (e.g. `position.x`).
+    pub fn flatten(self) -> Result<Self, Error> {
+        let columns: Vec<Expr> = field_paths(self.data_frame.schema())
+            .into_iter()
+            .map(|path| unfold_column(&path).alias(path))
+            .collect();
+
+        Ok(TimeseriesGwResult {
+            data_frame: self.data_frame.select(columns)?,
+            sort: self.sort,
+        })
+    }
+
+    /// Adds a column (see [`params::ARROW_SCHEMA_COLUMN_NAME_ROW_NUMBER`]) holding the
+    /// position of each row following the timestamp ordering, starting from 0.
+    ///
+    /// Rows are numbered after all the previous operations (e.g. filtering or decimation),
+    /// so the numbering refers to the rows of this read. The current order is kept.
+    pub fn with_row_number(self) -> Result<Self, Error> {
+        let ts = params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP;
+        let row_number = params::ARROW_SCHEMA_COLUMN_NAME_ROW_NUMBER;
+
+        if has_field(self.data_frame.schema(), row_number) {
+            return Err(Error::DuplicateColumn {
+                column: row_number.to_owned(),
+            });
+        }
+
+        let numbering = row_number_window()
+            .order_by(vec![col(ts).sort(true, false)])
+            .build()?;
+
+        let data_frame = self
+            .data_frame
+            .with_column(row_number, numbering - lit(1u64))?
+            .sort(self.sort.clone())?;
+
+        Ok(TimeseriesGwResult {
+            data_frame,
+            sort: self.sort,
+        })
+    }
+
+    /// Groups the rows in consecutive time windows of length `window` and applies the
+    /// aggregate functions to the listed fields, e.g. the average of a field for each second.
+    ///
+    /// Windows are computed as `floor(timestamp / window)`, where `window` is converted to
+    /// the timestamp `unit` of the data, so that timestamps before the epoch are assigned to
+    /// the window starting before them. The result holds a row for each window with at least a
+    /// value, with the timestamp column reporting the start of the window and a column named
+    /// `fn(field)` for each aggregation (e.g. `avg(imu.acc.x)`). Windows are sorted by timestamp.
+    ///
+    /// Nested fields can be addressed using dots, all the fields are required to be numeric.
+    pub fn aggregate(
+        self,
+        window: Duration,
+        unit: types::TimestampUnit,
+        aggs: Vec<(String, query::AggFn)>,
+    ) -> Result<Self, Error> {
+        let window = unit.duration_in_units(window);
+        if window <= 0 {
+            return Err(Error::BadWindow);
+        }
+
+        let schema = self.data_frame.schema();
+        let mut aggr_exprs = Vec::with_capacity(aggs.len());
+        for (field, agg) in aggs {
+            match field_data_type(schema, &field) {
+                None => return Err(Error::UnknownColumn { column: field }),
+                Some(data_type) if !data_type.is_numeric() => {
+                    return Err(Error::NotNumeric { field });
+                }
+                Some(_) => {}
+            }
+
+            let column = unfold_column(&field);
+            let expr = match agg {
+                query::AggFn::Min => min(column),
+                query::AggFn::Max => max(column),
+                query::AggFn::Avg => avg(column),
+                query::AggFn::Sum => sum(column),
+                query::AggFn::Count => count(column),
+            };
+            aggr_exprs.push(expr.alias(format!("{}({})", agg.as_str(), field)));
+        }
+
+        let ts = params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP;
+        // `%` truncates toward zero, negative remainders are moved to the previous window
+        let rem = col(ts) % lit(window);
+        let bucket = when(rem.clone().lt(lit(0)), col(ts) - rem.clone() - lit(window))
+            .otherwise(col(ts) - rem)?
+            .alias(ts);
+        let sort = vec![col(ts).sort(true, false)];
+
+        Ok(TimeseriesGwResult {
+            data_frame: self
+                .data_frame
+                .aggregate(vec![bucket], aggr_exprs)?
+                .sort(sort.clone())?,
+            sort,
+        })
+    }
+
+    /// Restricts the result to the rows whose timestamp exactly matches one of `timestamps`.
+    ///
+    /// To avoid unbounded `IN` lists, the (sorted) timestamps are split in chunks of at most
+    /// [`params::MAX_TIMESTAMPS_PER_IN_LIST`] values. Each chunk is matched with its own `IN`
+    /// list, paired with the range of its values so that the scan can be pruned using the
+    /// timestamp column statistics, and the results of all the chunks are concatenated.
+    pub fn at_timestamps(self, mut timestamps: Vec<query::Timestamp>) -> Result<Self, Error> {
+        timestamps.sort_unstable();
+        timestamps.dedup();
+
+        let ts = params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP;
+
+        let mut data_frame: Option<DataFrame> = None;
+        for chunk in timestamps.chunks(params::MAX_TIMESTAMPS_PER_IN_LIST) {
+            // chunks are never empty
+            let (first, last): (i64, i64) = (chunk[0].into(), chunk[chunk.len() - 1].into());
+            let list = chunk
+                .iter()
+                .map(|t| value_to_df_expr((*t).into()))
+                .collect();
+
+            let expr = col(ts)
+                .between(lit(first), lit(last))
+                .and(col(ts).in_list(list, false));
+            let chunk_frame = self.data_frame.clone().filter(expr)?;
+
+            data_frame = Some(match data_frame {
+                Some(df) => df.union(chunk_frame)?,
+                None => chunk_frame,
+            });
+        }
+
+        let data_frame = match data_frame {
+            Some(df) => df.sort(self.sort.clone())?,
+            None => self.data_frame.filter(lit(false))?,
+        };
+
+        Ok(TimeseriesGwResult {
+            data_frame,
+            sort: self.sort,
+        })
+    }
+
+    /// Keeps only the first `n` rows of the result.
+    ///
+    /// Since the result is sorted, the limit is executed as a top-k: row groups that can't
+    /// contain any of the first `n` rows are skipped using the timestamp column statistics.
+    pub fn limit(self, n: usize) -> Result<Self, Error> {
+        Ok(TimeseriesGwResult {
+            data_frame: self.data_frame.limit(0, Some(n))?,
+            sort: self.sort,
+        })
+    }
+
+    /// Skips the first `offset` rows and keeps at most `limit` of the following ones, all the
+    /// remaining rows are kept if no limit is provided.
+    ///
+    /// The rows are sorted again before being paginated, so that consecutive pages of the
+    /// same read do not overlap. Rows sharing the same timestamp (and value of the sorting
+    /// field, if any) are ordered by the values of their other fields (see
+    /// [`tie_breaker_sort`]).
+    pub fn paginate(self, offset: usize, limit: Option<usize>) -> Result<Self, Error> {
+        let mut sort = self.sort.clone();
+        sort.extend(tie_breaker_sort(self.data_frame.schema()));
+
+        let data_frame = self.data_frame.sort(sort)?.limit(offset, limit)?;
+
+        Ok(TimeseriesGwResult {
+            data_frame,
+            sort: self.sort,
+        })
+    }
+
+    pub async fn collect(self) -> Result<Vec<RecordBatch>, Error> {
+        Ok(self.data_frame.collect().await?)
     }
 
     pub async fn stream(self) -> Result<SendableRecordBatchStream, Error> {
@@ -128,6 +608,86 @@ impl TimeseriesGwResult {
         Ok(self.data_frame.count().await?)
     }
 
+    /// Returns an **approximate** count of the distinct values of `field`, estimated
+    /// using HyperLogLog.
+    ///
+    /// Nested fields can be addressed using dots (e.g. `pose.position.x`).
+    pub async fn approx_distinct(self, field: &str) -> Result<u64, Error> {
+        let batches = self
+            .data_frame
+            .aggregate(
+                vec![],
+                vec![approx_distinct(unfold_column(field)).alias("approx_distinct")],
+            )?
+            .collect()
+            .await?;
+
+        let count = batches
+            .first()
+            .filter(|batch| batch.num_rows() > 0)
+            .and_then(|batch| batch.column(0).as_primitive_opt::<UInt64Type>())
+            .map(|array| array.value(0))
+            .unwrap_or_default();
+
+        Ok(count)
+    }
+
+    /// Returns the distinct values of `field`, sorted in ascending order with the null value
+    /// last, in a column named `value`.
+    ///
+    /// Nested fields can be addressed using dots (e.g. `pose.position.x`). At most `limit`
+    /// values are returned, see [`DistinctValues::truncated`]. An error is returned if the
+    /// field does not exist.
+    pub async fn distinct(self, field: &str, limit: usize) -> Result<DistinctValues, Error> {
+        if !has_field(self.data_frame.schema(), field) {
+            return Err(Error::UnknownColumn {
+                column: field.to_owned(),
+            });
+        }
+
+        let values = self
+            .data_frame
+            .select(vec![unfold_column(field).alias(DISTINCT_VALUE_COLUMN)])?;
+
+        // Strings are read as views, values are returned with the types they are stored with
+        let stored_type = match values.schema().field(0).data_type() {
+            DataType::Utf8View => Some(DataType::Utf8),
+            DataType::BinaryView => Some(DataType::Binary),
+            _ => None,
+        };
+        let values = match stored_type {
+            Some(data_type) => values.select(vec![
+                cast(col(DISTINCT_VALUE_COLUMN), data_type).alias(DISTINCT_VALUE_COLUMN),
+            ])?,
+            None => values,
+        };
+
+        // One more value is requested to detect if the values are truncated
+        let batches = values
+            .distinct()?
+            .sort(vec![col(DISTINCT_VALUE_COLUMN).sort(true, false)])?
+            .limit(0, Some(limit + 1))?
+            .collect()
+            .await?;
+
+        let total: usize = batches.iter().map(|b| b.num_rows()).sum();
+
+        let mut remaining = limit;
+        let batches = batches
+            .into_iter()
+            .filter_map(|batch| {
+                let keep = batch.num_rows().min(remaining);
+                remaining -= keep;
+                (keep > 0).then(|| batch.slice(0, keep))
+            })
+            .collect();
+
+        Ok(DistinctValues {
+            batches,
+            truncated: total > limit,
+        })
+    }
+
     /// Checks if there are any rows matching the current query.
     /// This is more efficient than `count()` when you only need to know if results exist,
     /// as it stops after finding the first matching row.
@@ -138,12 +698,43 @@ impl TimeseriesGwResult {
     }
 }
 
-fn get_listing_options(_format: rw::Format) -> ListingOptions {
-    ListingOptions::new(Arc::new(ParquetFormat::default())).with_file_extension(".parquet")
+/// Returns the type of the struct column holding the columns of a path read together with
+/// other paths (see [`TimeseriesGw::read_many`]), i.e. all the columns but the timestamp.
+///
+/// Fields are nullable, since the struct is null on the rows of the other paths.
+fn struct_type(schema: &DFSchema) -> DataType {
+    let fields: Fields = schema
+        .as_arrow()
+        .fields()
+        .iter()
+        .filter(|field| field.name() != params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP)
+        .map(|field| Arc::new(field.as_ref().clone().with_nullable(true)))
+        .collect();
+
+    DataType::Struct(fields)
+}
+
+/// Quotes a column name, so that it is not normalized (lowercased) by the sql parser
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn get_listing_options(format: rw::Format) -> ListingOptions {
+    match format.container() {
+        rw::Container::Parquet => ListingOptions::new(Arc::new(ParquetFormat::default()))
+            .with_file_extension(format!(".{}", params::ext::PARQUET)),
+        rw::Container::Ipc => ListingOptions::new(Arc::new(ArrowFormat))
+            .with_file_extension(format!(".{}", params::ext::ARROW)),
+    }
 }
 
 fn unfold_field(field: &query::OntologyField) -> Expr {
-    let mut fields = field.field().split(".");
+    unfold_column(field.field())
+}
+
+/// Builds a column expression from a dotted path, accessing nested struct fields
+fn unfold_column(path: &str) -> Expr {
+    let mut fields = path.split(".");
     // By construction fields needs to have at least a value
     let mut col = col(fields.next().unwrap());
     for s in fields {
@@ -152,13 +743,84 @@ fn unfold_field(field: &query::OntologyField) -> Expr {
     col
 }
 
+/// Returns the ascending sort of all the leaf fields of `schema`, used to order the rows
+/// sharing the same sorting keys.
+///
+/// Lists, maps and binary fields (e.g. images) are skipped, comparing them would be
+/// expensive. Rows only differing in these fields have an undefined order.
+fn tie_breaker_sort(schema: &DFSchema) -> Vec<SortExpr> {
+    schema
+        .inner()
+        .squashed_iter()
+        .filter(|(_, field)| {
+            let data_type = field.data_type();
+            !data_type.is_nested()
+                && !matches!(
+                    data_type,
+                    DataType::Binary
+                        | DataType::LargeBinary
+                        | DataType::BinaryView
+                        | DataType::FixedSizeBinary(_)
+                )
+        })
+        .map(|(path, _)| unfold_column(&path).sort(true, false))
+        .collect()
+}
+
+/// Checks if the dotted `path` addresses an existing (possibly nested) field of `schema`
+fn has_field(schema: &DFSchema, path: &str) -> bool {
+    field_data_type(schema, path).is_some()
+}
+
+/// Returns the dotted paths of all the leaf fields of `schema`, nested fields included
+fn field_paths(schema: &DFSchema) -> Vec<String> {
+    schema
+        .inner()
+        .squashed_iter()
+        .map(|(path, _)| path)
+        .collect()
+}
+
+/// Returns the type of the (possibly nested) field addressed by the dotted `path`
+fn field_data_type<'a>(schema: &'a DFSchema, path: &str) -> Option<&'a DataType> {
+    let mut fields = path.split(".");
+    // By construction fields needs to have at least a value
+    let mut field = schema
+        .field_with_unqualified_name(fields.next().unwrap())
+        .ok()?;
+
+    for name in fields {
+        let DataType::Struct(children) = field.data_type() else {
+            return None;
+        };
+        field = children.find(name)?.1.as_ref();
+    }
+
+    Some(field.data_type())
+}
+
 fn expr_group_to_df_expr<V>(filter: query::ExprGroup<V>) -> Option<Expr>
 where
     V: Into<query::Value>,
 {
     let mut ret: Option<Expr> = None;
+    let connector = filter.connector;
 
-    for expr in filter.into_iter() {
+    let combine = |ret: Option<Expr>, expr: Expr| match ret {
+        None => Some(expr),
+        Some(ret) => match connector {
+            query::Connector::And => Some(ret.and(expr)),
+            query::Connector::Or => Some(ret.or(expr)),
+        },
+    };
+
+    for subgroup in filter.subgroups {
+        if let Some(expr) = expr_group_to_df_expr(subgroup) {
+            ret = combine(ret, expr);
+        }
+    }
+
+    for expr in filter.group {
         let (field, op) = expr.into_parts();
         let expr = match op {
             query::Op::Eq(v) => Some(unfold_field(&field).eq(value_to_df_expr(v.into()))),
@@ -167,8 +829,13 @@ where
             query::Op::Geq(v) => Some(unfold_field(&field).gt_eq(value_to_df_expr(v.into()))),
             query::Op::Lt(v) => Some(unfold_field(&field).lt(value_to_df_expr(v.into()))),
             query::Op::Gt(v) => Some(unfold_field(&field).gt(value_to_df_expr(v.into()))),
-            query::Op::Ex => None,  // no-op
-            query::Op::Nex => None, // no-op
+            query::Op::Ex => Some(unfold_field(&field).is_not_null()),
+            query::Op::Nex => Some(unfold_field(&field).is_null()),
+            // Integers can't be NaN, the cast allows to handle all the numeric types
+            query::Op::IsNan => Some(isnan(cast(unfold_field(&field), DataType::Float64))),
+            query::Op::IsNotNan => Some(not(isnan(cast(unfold_field(&field), DataType::Float64)))),
+            // Resolved on the chunk statistics, data files are never scanned for them
+            query::Op::HasNan | query::Op::HasNull => None,
             query::Op::Between(range) => {
                 let vmin: query::Value = range.min.into();
                 let vmax: query::Value = range.max.into();
@@ -176,6 +843,13 @@ where
                 let emax = unfold_field(&field).gt_eq(value_to_df_expr(vmin));
                 Some(emin.and(emax))
             }
+            query::Op::BetweenHalfOpen(range) => {
+                let vmin: query::Value = range.min.into();
+                let vmax: query::Value = range.max.into();
+                let emin = unfold_field(&field).gt_eq(value_to_df_expr(vmin));
+                let emax = unfold_field(&field).lt(value_to_df_expr(vmax));
+                Some(emin.and(emax))
+            }
             query::Op::In(items) => {
                 let list = items
                     .into_iter()
@@ -183,15 +857,33 @@ where
                     .collect();
                 Some(unfold_field(&field).in_list(list, false))
             }
+            query::Op::NotIn(items) => {
+                let list = items
+                    .into_iter()
+                    .map(|v| value_to_df_expr(v.into()))
+                    .collect();
+                Some(unfold_field(&field).in_list(list, true))
+            }
             query::Op::Match(v) => Some(unfold_field(&field).like(value_to_df_expr(v.into()))),
+            query::Op::IMatch(v) => {
+                Some(unfold_field(&field).ilike(value_to_df_expr(query::imatch_pattern(v.into()))))
+            }
+            query::Op::Regex(v) => Some(regexp_like(
+                unfold_field(&field),
+                value_to_df_expr(v.into()),
+                None,
+            )),
+            query::Op::StartsWith(v) => Some(starts_with(
+                unfold_field(&field),
+                value_to_df_expr(v.into()),
+            )),
+            query::Op::EndsWith(v) => {
+                Some(ends_with(unfold_field(&field), value_to_df_expr(v.into())))
+            }
         };
 
         if let Some(expr) = expr {
-            if ret.is_none() {
-                ret = Some(expr);
-            } else {
-                ret = Some(ret.unwrap().and(expr));
-            }
+            ret = combine(ret, expr);
         }
     }
 
@@ -206,3 +898,655 @@ fn value_to_df_expr(v: query::Value) -> Expr {
         query::Value::Boolean(v) => lit(v),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Float64Array, Int64Array, RecordBatch, StringArray, StructArray};
+    use arrow::buffer::NullBuffer;
+    use arrow::datatypes::{DataType, Field, Fields};
+    use datafusion::physical_plan::{ExecutionPlan, collect};
+
+    #[tokio::test]
+    async fn imatch_is_case_insensitive() {
+        let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Utf8, false)]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![Arc::new(StringArray::from(vec!["IMU_Front", "gps_rear"]))],
+        )
+        .unwrap();
+
+        let field = query::OntologyField::try_new("sensor.id".to_owned()).unwrap();
+        let group = query::ExprGroup::new(vec![
+            (field, query::Op::IMatch(query::Value::from("u_f"))).into(),
+        ]);
+
+        // the pattern is searched anywhere in the value
+        let expr = expr_group_to_df_expr(group).unwrap();
+        let count = SessionContext::new()
+            .read_batch(batch)
+            .unwrap()
+            .filter(expr)
+            .unwrap()
+            .count()
+            .await
+            .unwrap();
+
+        assert_eq!(count, 1);
+    }
+
+    #[tokio::test]
+    async fn existence_on_nested_fields() {
+        let info = Fields::from(vec![Field::new("height", DataType::Int64, true)]);
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "info",
+            DataType::Struct(info.clone()),
+            true,
+        )]));
+
+        // a value, a null leaf and a null struct
+        let heights = Int64Array::from(vec![Some(480), None, None]);
+        let infos = StructArray::new(
+            info,
+            vec![Arc::new(heights)],
+            Some(NullBuffer::from(vec![true, true, false])),
+        );
+        let batch = RecordBatch::try_new(schema, vec![Arc::new(infos)]).unwrap();
+
+        let count = async |op: query::Op<query::Value>| {
+            let field = query::OntologyField::try_new("image.info.height".to_owned()).unwrap();
+            let expr = expr_group_to_df_expr(query::ExprGroup::new(vec![(field, op).into()]));
+            SessionContext::new()
+                .read_batch(batch.clone())
+                .unwrap()
+                .filter(expr.unwrap())
+                .unwrap()
+                .count()
+                .await
+                .unwrap()
+        };
+
+        assert_eq!(count(query::Op::Ex).await, 1);
+        assert_eq!(count(query::Op::Nex).await, 2);
+    }
+
+    #[tokio::test]
+    async fn aggregate_floors_windows() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+                DataType::Int64,
+                false,
+            ),
+            Field::new("value", DataType::Float64, false),
+        ]));
+        let timestamps = vec![-4, -3, -1, 0, 2, 3];
+        let values = timestamps.iter().map(|t| *t as f64).collect::<Vec<_>>();
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(timestamps)),
+                Arc::new(Float64Array::from(values)),
+            ],
+        )
+        .unwrap();
+
+        let result = TimeseriesGwResult {
+            data_frame: SessionContext::new().read_batch(batch).unwrap(),
+            sort: vec![],
+        }
+        .aggregate(
+            Duration::from_millis(3),
+            types::TimestampUnit::Milliseconds,
+            vec![
+                ("value".to_owned(), query::AggFn::Avg),
+                ("value".to_owned(), query::AggFn::Count),
+            ],
+        )
+        .unwrap();
+
+        let batches = result.collect().await.unwrap();
+        let batch = arrow::compute::concat_batches(&batches[0].schema(), &batches).unwrap();
+
+        // -4 belongs to the window starting at -6, -1 to the one starting at -3
+        let column = batch
+            .column_by_name(params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP)
+            .unwrap();
+        assert_eq!(
+            column
+                .as_primitive::<arrow::datatypes::Int64Type>()
+                .values()
+                .to_vec(),
+            vec![-6, -3, 0, 3]
+        );
+
+        let column = batch.column_by_name("avg(value)").unwrap();
+        assert_eq!(
+            column
+                .as_primitive::<arrow::datatypes::Float64Type>()
+                .values()
+                .to_vec(),
+            vec![-4.0, -2.0, 1.0, 3.0]
+        );
+
+        let column = batch.column_by_name("count(value)").unwrap();
+        assert_eq!(
+            column
+                .as_primitive::<arrow::datatypes::Int64Type>()
+                .values()
+                .to_vec(),
+            vec![1, 2, 2, 1]
+        );
+    }
+
+    /// Sums the values of the metric `name` over the whole physical plan
+    fn plan_metric(plan: &dyn ExecutionPlan, name: &str) -> usize {
+        let own = plan
+            .metrics()
+            .and_then(|m| m.sum_by_name(name))
+            .map(|v| v.as_usize())
+            .unwrap_or_default();
+
+        own + plan
+            .children()
+            .into_iter()
+            .map(|child| plan_metric(child.as_ref(), name))
+            .sum::<usize>()
+    }
+
+    /// Runs `sql` against the topic at `path` and returns the number of bytes read from storage
+    async fn scanned_bytes(gw: &TimeseriesGw, path: &str, sql: &str) -> usize {
+        let ctx = gw.session_context(None);
+        ctx.register_listing_table(
+            "data",
+            gw.datafile_url(path).unwrap(),
+            get_listing_options(rw::Format::Ragged),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let plan = ctx
+            .sql(sql)
+            .await
+            .unwrap()
+            .create_physical_plan()
+            .await
+            .unwrap();
+        collect(plan.clone(), ctx.task_ctx()).await.unwrap();
+
+        plan_metric(plan.as_ref(), "bytes_scanned")
+    }
+
+    /// Writes a topic at `path` made of `chunks` ragged chunks, each one holding `rows` rows
+    /// with increasing timestamps.
+    async fn write_topic(store: &store::testing::Store, path: &str, chunks: i64, rows: i64) {
+        params::load_configurables_from_env();
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+                DataType::Int64,
+                false,
+            ),
+            Field::new("value", DataType::Float64, false),
+            Field::new("label", DataType::Utf8, false),
+        ]));
+
+        for chunk in 0..chunks {
+            let batch = RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(Int64Array::from_iter_values(
+                        chunk * rows..(chunk + 1) * rows,
+                    )),
+                    Arc::new(Float64Array::from_iter_values((0..rows).map(|v| v as f64))),
+                    Arc::new(StringArray::from_iter_values(
+                        (0..rows).map(|v| format!("label_{v}")),
+                    )),
+                ],
+            )
+            .unwrap();
+
+            let mut writer = rw::ChunkWriter::try_new(schema.clone(), rw::Format::Ragged).unwrap();
+            writer.write(&batch).unwrap();
+            let (buffer, _, _) = writer.finalize().unwrap();
+
+            store
+                .write_bytes(format!("{path}chunk_{chunk}.parquet"), buffer)
+                .await
+                .unwrap();
+        }
+    }
+
+    /// Reading a narrow time window of a large topic should only touch the row groups
+    /// overlapping the window.
+    #[tokio::test]
+    async fn narrow_time_window_reads_a_fraction_of_data() {
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let gw = TimeseriesGw::try_new(store.clone()).unwrap();
+
+        write_topic(&store, "topic/", 10, 10_000).await;
+
+        let ts = params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP;
+        let full = scanned_bytes(&gw, "topic/", &format!("SELECT * FROM data ORDER BY {ts}")).await;
+        let window = scanned_bytes(
+            &gw,
+            "topic/",
+            &format!("SELECT * FROM data WHERE {ts} BETWEEN 42000 AND 42100 ORDER BY {ts}"),
+        )
+        .await;
+
+        assert!(
+            window * 4 < full,
+            "full scan: {full} bytes, narrow window: {window} bytes"
+        );
+    }
+
+    #[tokio::test]
+    async fn read_with_projection() {
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let gw = TimeseriesGw::try_new(store.clone()).unwrap();
+
+        write_topic(&store, "topic/", 1, 10).await;
+
+        let result = gw
+            .read(
+                "topic/",
+                rw::Format::Ragged,
+                None,
+                None,
+                Some(vec!["value".to_owned()]),
+                query::SortOrder::Asc,
+            )
+            .await
+            .unwrap();
+
+        let fields: Vec<String> = result
+            .schema_with_metadata(HashMap::new())
+            .fields()
+            .iter()
+            .map(|f| f.name().clone())
+            .collect();
+        assert_eq!(
+            fields,
+            vec![params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP, "value"]
+        );
+
+        let result = gw
+            .read(
+                "topic/",
+                rw::Format::Ragged,
+                None,
+                None,
+                Some(vec!["missing".to_owned()]),
+                query::SortOrder::Asc,
+            )
+            .await;
+        assert!(matches!(result, Err(Error::UnknownColumn { column }) if column == "missing"));
+    }
+
+    #[tokio::test]
+    async fn read_at_timestamps() {
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let gw = TimeseriesGw::try_new(store.clone()).unwrap();
+
+        write_topic(&store, "topic/", 2, 3_000).await;
+
+        let read = async |timestamps: Vec<i64>| {
+            gw.read(
+                "topic/",
+                rw::Format::Ragged,
+                None,
+                None,
+                None,
+                query::SortOrder::Asc,
+            )
+            .await
+            .unwrap()
+            .at_timestamps(timestamps.into_iter().map(Into::into).collect())
+            .unwrap()
+            .count()
+            .await
+            .unwrap()
+        };
+
+        // duplicated and missing timestamps are ignored
+        assert_eq!(read(vec![4000, 5, 2999, 5, 10_000]).await, 3);
+
+        // more timestamps than a single `IN` list can hold
+        let every_other: Vec<i64> = (0..6_000).step_by(2).collect();
+        assert!(every_other.len() > params::MAX_TIMESTAMPS_PER_IN_LIST);
+        assert_eq!(read(every_other).await, 3_000);
+
+        assert_eq!(read(vec![]).await, 0);
+    }
+
+    #[tokio::test]
+    async fn distinct_values() {
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let gw = TimeseriesGw::try_new(store.clone()).unwrap();
+
+        // the same 10 labels in each chunk
+        write_topic(&store, "topic/", 2, 10).await;
+
+        let distinct = async |field: &str, limit: usize| {
+            gw.read(
+                "topic/",
+                rw::Format::Ragged,
+                None,
+                None,
+                None,
+                query::SortOrder::Asc,
+            )
+            .await
+            .unwrap()
+            .distinct(field, limit)
+            .await
+        };
+
+        let labels = |values: &DistinctValues| -> Vec<String> {
+            values
+                .batches
+                .iter()
+                .flat_map(|batch| {
+                    batch
+                        .column_by_name(DISTINCT_VALUE_COLUMN)
+                        .unwrap()
+                        .as_string::<i32>()
+                        .iter()
+                        .map(|v| v.unwrap().to_owned())
+                        .collect::<Vec<_>>()
+                })
+                .collect()
+        };
+
+        let values = distinct("label", 100).await.unwrap();
+        assert!(!values.truncated);
+        let expected: Vec<String> = (0..10).map(|v| format!("label_{v}")).collect();
+        assert_eq!(labels(&values), expected);
+
+        let values = distinct("label", 3).await.unwrap();
+        assert!(values.truncated);
+        assert_eq!(labels(&values), expected[..3]);
+
+        assert!(matches!(
+            distinct("missing", 3).await,
+            Err(Error::UnknownColumn { column }) if column == "missing"
+        ));
+    }
+
+    /// Returns the values of the timestamp column in the order they are streamed
+    async fn timestamps(result: TimeseriesGwResult) -> Vec<i64> {
+        result
+            .data_frame
+            .collect()
+            .await
+            .unwrap()
+            .iter()
+            .flat_map(|batch| {
+                batch
+                    .column_by_name(params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP)
+                    .unwrap()
+                    .as_primitive::<arrow::datatypes::Int64Type>()
+                    .values()
+                    .to_vec()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn read_ordering() {
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let gw = TimeseriesGw::try_new(store.clone()).unwrap();
+
+        write_topic(&store, "topic/", 2, 3).await;
+
+        let read = async |order: query::SortOrder| {
+            gw.read("topic/", rw::Format::Ragged, None, None, None, order)
+                .await
+                .unwrap()
+        };
+
+        assert_eq!(
+            timestamps(read(query::SortOrder::Desc).await).await,
+            vec![5, 4, 3, 2, 1, 0]
+        );
+
+        // ordering is preserved when selecting a set of timestamps
+        let result = read(query::SortOrder::Desc)
+            .await
+            .at_timestamps(vec![1.into(), 4.into()])
+            .unwrap();
+        assert_eq!(timestamps(result).await, vec![4, 1]);
+
+        // `value` is repeated in each chunk, ties are ordered by timestamp
+        let result = read(query::SortOrder::Asc)
+            .await
+            .order_by_field("value", query::SortOrder::Desc)
+            .unwrap();
+        assert_eq!(timestamps(result).await, vec![5, 2, 4, 1, 3, 0]);
+
+        let result = read(query::SortOrder::Asc)
+            .await
+            .order_by_field("missing", query::SortOrder::Asc);
+        assert!(matches!(result, Err(Error::UnknownColumn { column }) if column == "missing"));
+
+        let missing = query::OntologyField::try_new("test.missing".to_owned()).unwrap();
+        let result = read(query::SortOrder::Asc)
+            .await
+            .filter(query::ExprGroup::new(vec![
+                (missing, query::Op::Gt(query::Value::Float(1.0))).into(),
+            ]));
+        assert!(matches!(
+            result,
+            Err(Error::BadField { field, available }) if field == "test.missing" && available.contains(&"value".to_owned())
+        ));
+
+        let result = read(query::SortOrder::Desc).await.limit(1).unwrap();
+        assert_eq!(timestamps(result).await, vec![5]);
+
+        // two windows of 3 rows each: values are 0, 1, 2 in both windows
+        let result = read(query::SortOrder::Asc)
+            .await
+            .aggregate(
+                Duration::from_millis(3),
+                types::TimestampUnit::Milliseconds,
+                vec![
+                    ("value".to_owned(), query::AggFn::Avg),
+                    ("value".to_owned(), query::AggFn::Max),
+                ],
+            )
+            .unwrap();
+        assert_eq!(timestamps(result).await, vec![0, 3]);
+
+        let result = read(query::SortOrder::Asc).await.aggregate(
+            Duration::from_millis(3),
+            types::TimestampUnit::Milliseconds,
+            vec![("label".to_owned(), query::AggFn::Sum)],
+        );
+        assert!(matches!(result, Err(Error::NotNumeric { field }) if field == "label"));
+
+        // rows are numbered by timestamp, the current order is kept
+        let result = read(query::SortOrder::Desc).await.decimate(2).unwrap();
+        assert_eq!(timestamps(result).await, vec![4, 2, 0]);
+
+        let batches = read(query::SortOrder::Desc)
+            .await
+            .with_row_number()
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        let numbers: Vec<u64> = batches
+            .iter()
+            .flat_map(|b| {
+                b.column_by_name(params::ARROW_SCHEMA_COLUMN_NAME_ROW_NUMBER)
+                    .unwrap()
+                    .as_primitive::<UInt64Type>()
+                    .values()
+                    .to_vec()
+            })
+            .collect();
+        assert_eq!(numbers, vec![5, 4, 3, 2, 1, 0]);
+
+        // pages follow the current order
+        let result = read(query::SortOrder::Desc)
+            .await
+            .paginate(2, Some(3))
+            .unwrap();
+        assert_eq!(timestamps(result).await, vec![3, 2, 1]);
+
+        let result = read(query::SortOrder::Asc)
+            .await
+            .order_by_field("value", query::SortOrder::Desc)
+            .unwrap()
+            .paginate(4, None)
+            .unwrap();
+        assert_eq!(timestamps(result).await, vec![3, 0]);
+
+        let result = read(query::SortOrder::Asc)
+            .await
+            .paginate(10, Some(2))
+            .unwrap();
+        assert!(timestamps(result).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn read_many_merges_by_timestamp() {
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let gw = TimeseriesGw::try_new(store.clone()).unwrap();
+
+        write_topic(&store, "topic_a/", 2, 2).await;
+        write_topic(&store, "topic_b/", 1, 3).await;
+
+        let result = gw
+            .read_many(
+                &[
+                    ("a", "topic_a/", rw::Format::Ragged),
+                    ("b", "topic_b/", rw::Format::Ragged),
+                ],
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(timestamps(result).await, vec![0, 0, 1, 1, 2, 2, 3]);
+
+        let result = gw.read_many::<&str>(&[], None).await;
+        assert!(matches!(result, Err(Error::NothingToRead)));
+    }
+
+    /// Each topic is read in its own struct column, null on the rows of the other topics:
+    /// columns with the same name are kept apart and their nullability may differ.
+    #[tokio::test]
+    async fn read_many_projects_topics_as_structs() {
+        params::load_configurables_from_env();
+
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let gw = TimeseriesGw::try_new(store.clone()).unwrap();
+
+        let timestamp = Field::new(
+            params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+            DataType::Int64,
+            false,
+        );
+        let with_note = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                timestamp.clone(),
+                Field::new("note", DataType::Utf8, true),
+            ])),
+            vec![
+                Arc::new(Int64Array::from(vec![0, 2])),
+                Arc::new(StringArray::from(vec![Some("a"), None])),
+            ],
+        )
+        .unwrap();
+        let without_note = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![timestamp])),
+            vec![Arc::new(Int64Array::from(vec![1]))],
+        )
+        .unwrap();
+
+        for (path, batch) in [("topic_c/", with_note), ("topic_d/", without_note)] {
+            let mut writer = rw::ChunkWriter::try_new(batch.schema(), rw::Format::Ragged).unwrap();
+            writer.write(&batch).unwrap();
+            let (buffer, _, _) = writer.finalize().unwrap();
+            store
+                .write_bytes(format!("{path}chunk_0.parquet"), buffer)
+                .await
+                .unwrap();
+        }
+
+        // `topic_a` holds non nullable columns, its `label` is a different column than the
+        // `label` of `topic_e`
+        write_topic(&store, "topic_a/", 1, 2).await;
+        write_topic(&store, "topic_e/", 1, 1).await;
+
+        let batches = gw
+            .read_many(
+                &[
+                    ("a", "topic_a/", rw::Format::Ragged),
+                    ("c", "topic_c/", rw::Format::Ragged),
+                    ("d", "topic_d/", rw::Format::Ragged),
+                    ("e", "topic_e/", rw::Format::Ragged),
+                ],
+                None,
+            )
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        let batch = arrow::compute::concat_batches(&batches[0].schema(), &batches).unwrap();
+
+        let names: Vec<&str> = batch
+            .schema_ref()
+            .fields()
+            .iter()
+            .map(|f| f.name().as_str())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+                "a",
+                "c",
+                "d",
+                "e"
+            ]
+        );
+
+        // Each row is read from a single topic
+        let topics = ["a", "c", "d", "e"];
+        let rows = |name: &str| -> Vec<usize> {
+            let column = batch.column_by_name(name).unwrap();
+            (0..batch.num_rows())
+                .filter(|row| column.is_valid(*row))
+                .collect()
+        };
+        let counts: Vec<usize> = topics.iter().map(|name| rows(name).len()).collect();
+        assert_eq!(counts, vec![2, 2, 1, 1]);
+        assert_eq!(counts.iter().sum::<usize>(), batch.num_rows());
+
+        // Values of the string field `name` of the rows of topic `topic`, strings may be read
+        // as views
+        let values = |topic: &str, name: &str| -> Vec<Option<String>> {
+            let column = batch.column_by_name(topic).unwrap().as_struct();
+            let values =
+                arrow::compute::cast(column.column_by_name(name).unwrap(), &DataType::Utf8)
+                    .unwrap();
+            let values = values.as_string::<i32>();
+            rows(topic)
+                .into_iter()
+                .map(|row| values.is_valid(row).then(|| values.value(row).to_owned()))
+                .collect()
+        };
+        assert_eq!(values("c", "note"), vec![Some("a".to_owned()), None]);
+        assert_eq!(
+            values("a", "label"),
+            vec![Some("label_0".to_owned()), Some("label_1".to_owned())]
+        );
+        assert_eq!(values("e", "label"), vec![Some("label_0".to_owned())]);
+    }
+}
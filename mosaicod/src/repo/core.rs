@@ -4,8 +4,18 @@
 //! The central component is the [`Repository`] struct, which holds the connection pool and
 //! methods for interacting with the database. Error handling is unified through the
 //! [`RepositoryError`] enum.
+//!
+//! A read replica can be optionally configured to offload read-only queries from the
+//! primary database. Replicas are updated asynchronously, so they may lag behind the
+//! primary: data written on the primary may not be visible on the replica yet. For this
+//! reason writes (transactions) and the reads used to validate writes always go to the
+//! primary ([`Repository::connection`]), while only reads that tolerate slightly stale data
+//! (catalog listing, queries, statistics) are routed to the replica
+//! ([`Repository::replica_connection`]).
+
+use std::time::Duration;
 
-use log::debug;
+use log::{debug, warn};
 use sqlx::Pool;
 use url::Url;
 
@@ -13,6 +23,11 @@ use super::Error;
 use crate::params;
 
 /// The concrete database type used throughout this module.
+///
+/// Only Postgres is supported. Queries are checked at compile time against Postgres (see
+/// `.sqlx`), chunk statistics are loaded with `COPY` and the query compilers emit
+/// Postgres-only SQL (`ANY($1)` array binds, `ILIKE`, `~` regular expressions, `jsonb`
+/// operators), another backend would need a parallel copy of all of them.
 pub type Database = sqlx::Postgres;
 
 /// If the layer has this id is not registered in the repository
@@ -46,6 +61,12 @@ impl<'a> Tx<'a> {
         self.inner.rollback().await?;
         Ok(())
     }
+
+    /// Returns the connection of the transaction, required by the operations that are not
+    /// available through an executor (e.g. `COPY`)
+    pub(super) fn connection_mut(&mut self) -> &mut sqlx::PgConnection {
+        &mut self.inner
+    }
 }
 
 impl<'a> AsExec for Tx<'a> {
@@ -72,26 +93,78 @@ impl<'a> AsExec for Cx<'a> {
 /// Configuration structure for initializing the [`Repository`].
 pub struct Config {
     pub db_url: Url,
+    /// Optional read replica of the database at `db_url`
+    pub replica_db_url: Option<Url>,
+}
+
+/// Usage of a database connection pool
+#[derive(Debug, Clone, Copy)]
+pub struct PoolStats {
+    /// Number of open connections, both idle and in use
+    pub size: u32,
+    pub idle: usize,
+    pub in_use: usize,
+}
+
+impl From<&Pool<Database>> for PoolStats {
+    fn from(pool: &Pool<Database>) -> Self {
+        let size = pool.size();
+        let idle = pool.num_idle();
+        Self {
+            size,
+            idle,
+            in_use: (size as usize).saturating_sub(idle),
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct Repository {
     pub(super) pool: Pool<Database>,
+    pub(super) replica: Option<Pool<Database>>,
 }
 
 impl Repository {
+    /// Connects to the database and runs the migrations.
+    ///
+    /// The pool is sized at least as large as `max_concurrent_chunk_queries`, otherwise the
+    /// concurrent chunk queries of a single catalog filtering could exhaust it and starve (or
+    /// time out) every other request.
     pub async fn try_new(config: &Config) -> Result<Self, Error> {
         debug!("creating database connection pool");
-        let max_connections = params::configurables().max_db_connections;
-        let pool = sqlx::postgres::PgPoolOptions::new()
+        let params = params::configurables();
+
+        let concurrency = u32::try_from(params.max_concurrent_chunk_queries).unwrap_or(u32::MAX);
+        let mut max_connections = params.max_db_connections;
+        if max_connections < concurrency {
+            warn!(
+                "max db connections ({}) is lower than max concurrent chunk queries ({}), using {} connections",
+                max_connections, concurrency, concurrency
+            );
+            max_connections = concurrency;
+        }
+        let min_connections = params.min_db_connections.min(max_connections);
+
+        let acquire_timeout = Duration::from_secs(params.db_acquire_timeout_in_secs);
+        let pool_options = sqlx::postgres::PgPoolOptions::new()
             .max_connections(max_connections)
-            .connect(config.db_url.as_str())
-            .await?;
+            .min_connections(min_connections)
+            .acquire_timeout(acquire_timeout);
+
+        let pool = pool_options.clone().connect(config.db_url.as_str()).await?;
 
         debug!("running migrations");
         sqlx::migrate!().run(&pool).await?;
 
-        Ok(Self { pool })
+        // Migrations are replicated from the primary
+        let replica = if let Some(url) = &config.replica_db_url {
+            debug!("creating read replica connection pool");
+            Some(pool_options.connect(url.as_str()).await?)
+        } else {
+            None
+        };
+
+        Ok(Self { pool, replica })
     }
 
     /// Builds a transaction.
@@ -107,9 +180,43 @@ impl Repository {
     /// Returns a connection to perform operations on the repository.
     ///
     /// This call should be used when performing **read-only** operations on the repository.
+    /// The connection always refers to the primary database, so it observes all the committed
+    /// writes.
     pub fn connection(&self) -> Cx<'_> {
         Cx { inner: &self.pool }
     }
+
+    /// Checks that the database is reachable by running a trivial query on the primary
+    /// pool and, if configured, on the read replica.
+    pub async fn health_check(&self) -> Result<(), Error> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        if let Some(replica) = &self.replica {
+            sqlx::query("SELECT 1").execute(replica).await?;
+        }
+        Ok(())
+    }
+
+    /// Returns the usage of the primary connection pool.
+    pub fn pool_stats(&self) -> PoolStats {
+        (&self.pool).into()
+    }
+
+    /// Returns the usage of the read replica connection pool, if configured.
+    pub fn replica_pool_stats(&self) -> Option<PoolStats> {
+        self.replica.as_ref().map(PoolStats::from)
+    }
+
+    /// Returns a connection to perform **read-only** operations on the read replica, if
+    /// configured, otherwise on the primary database.
+    ///
+    /// Since the replica may lag behind the primary, recent writes may not be visible yet.
+    /// This call should not be used for reads that validate a write or that need to
+    /// observe a write performed just before.
+    pub fn replica_connection(&self) -> Cx<'_> {
+        Cx {
+            inner: self.replica.as_ref().unwrap_or(&self.pool),
+        }
+    }
 }
 
 /// Testing utilities for the repository module.
@@ -126,7 +233,10 @@ pub mod testing {
         /// Creates a new [`Repository`] instance for testing using the provided database pool.
         pub fn new(pool: sqlx::Pool<super::Database>) -> Self {
             Self {
-                inner: super::Repository { pool },
+                inner: super::Repository {
+                    pool,
+                    replica: None,
+                },
             }
         }
 
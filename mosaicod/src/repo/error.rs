@@ -6,7 +6,11 @@ use crate::query;
 pub enum Error {
     /// An error occurred in the underlying SQL database backend (e.g., connection, query execution).
     #[error("backend error :: {0}")]
-    BackendError(#[from] sqlx::Error),
+    BackendError(sqlx::Error),
+    /// No database connection became available within the configured acquire timeout.
+    /// This is a transient condition, the operation can be retried later.
+    #[error("timed out waiting for a database connection")]
+    PoolTimeout,
     /// An error occurred during database schema migration.
     #[error("migration error :: {0}")]
     MigrationError(#[from] sqlx::migrate::MigrateError),
@@ -26,7 +30,30 @@ pub enum Error {
     // Not found
     #[error("not found")]
     NotFound,
+    /// A generated query refers to a different number of placeholders than the values
+    /// bound to it
+    #[error("query has {placeholders} placeholders but {values} bound values")]
+    BoundValuesMismatch { placeholders: usize, values: usize },
     /// The query received contains an unsupported operation
     #[error("query error :: {0}")]
     QueryError(#[from] query::Error),
 }
+
+impl Error {
+    /// Returns `true` if the error is caused by a missing record
+    pub fn is_not_found(&self) -> bool {
+        matches!(
+            self,
+            Self::NotFound | Self::BackendError(sqlx::Error::RowNotFound)
+        )
+    }
+}
+
+impl From<sqlx::Error> for Error {
+    fn from(value: sqlx::Error) -> Self {
+        match value {
+            sqlx::Error::PoolTimedOut => Self::PoolTimeout,
+            err => Self::BackendError(err),
+        }
+    }
+}
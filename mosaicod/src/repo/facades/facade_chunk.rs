@@ -1,5 +1,10 @@
 use super::FacadeError;
-use crate::{repo, types};
+use crate::{params, repo, rw, store, types};
+use std::collections::HashMap;
+
+/// Maximum number of rows of a multi-row `INSERT`, keeps the number of bind parameters
+/// below the Postgres limit (65535) for all the chunk and stats tables
+const MAX_BATCH_ROWS: usize = 4096;
 
 pub struct FacadeChunk<'a> {
     tx: repo::Tx<'a>,
@@ -7,79 +12,415 @@ pub struct FacadeChunk<'a> {
 }
 
 impl<'a> FacadeChunk<'a> {
+    /// Creates the chunk record, `timestamp_bounds` are the (inclusive) bounds of the chunk
+    /// timestamps in milliseconds, if known. The `checksum` (CRC32) of the data file is used
+    /// to detect corruptions on read (see [`verify_checksum`]).
     pub async fn create(
         topic_id: i32,
         datafile: impl AsRef<std::path::Path>,
         size_bytes: i64,
         row_count: i64,
+        timestamp_bounds: Option<(types::Timestamp, types::Timestamp)>,
+        checksum: Option<u32>,
         repo: &'a repo::Repository,
     ) -> Result<Self, FacadeError> {
         let mut tx = repo.transaction().await?;
 
-        let chunk = repo::chunk_create(
-            &mut tx,
-            &repo::Chunk::new(topic_id, datafile, size_bytes, row_count),
-        )
-        .await?;
+        let mut chunk = repo::Chunk::new(topic_id, datafile, size_bytes, row_count);
+        if let Some((min, max)) = timestamp_bounds {
+            chunk = chunk.with_timestamp_bounds(min, max);
+        }
+        if let Some(checksum) = checksum {
+            chunk = chunk.with_checksum(checksum);
+        }
+
+        let chunk = repo::chunk_create(&mut tx, &chunk).await?;
 
         Ok(Self { tx, chunk })
     }
 
+    /// Creates the records of several chunks of a topic, along with their column statistics,
+    /// in a single transaction.
+    ///
+    /// Chunks are inserted with a single query and so are the statistics of each kind,
+    /// regardless of the number of chunks, reducing the round-trips of writes producing many
+    /// chunks. Returns the created chunks, in the same order of `chunks`.
+    pub async fn create_batch(
+        topic_id: i32,
+        ontology_tag: &str,
+        chunks: Vec<NewChunk>,
+        repo: &repo::Repository,
+    ) -> Result<Vec<repo::Chunk>, FacadeError> {
+        let mut tx = repo.transaction().await?;
+        let records = insert_chunks(&mut tx, topic_id, ontology_tag, chunks).await?;
+        tx.commit().await?;
+
+        Ok(records)
+    }
+
+    /// Replaces the chunks `old` of a topic with the chunks `new` (holding the same data) in
+    /// a single transaction, the statistics of the removed chunks are deleted with them.
+    ///
+    /// The data files of the removed chunks are not deleted, this is left to the caller once
+    /// the replacement has been committed.
+    ///
+    /// The chunks are only replaced while the sequence `sequence_id` of the topic is unlocked,
+    /// the sequence can't be locked until the replacement is committed.
+    pub async fn replace(
+        sequence_id: i32,
+        topic_id: i32,
+        ontology_tag: &str,
+        old: &[repo::Chunk],
+        new: Vec<NewChunk>,
+        repo: &repo::Repository,
+    ) -> Result<Vec<repo::Chunk>, FacadeError> {
+        let mut tx = repo.transaction().await?;
+
+        let sequence = repo::sequence_find_by_id_for_share(&mut tx, sequence_id).await?;
+        if sequence.is_locked() {
+            return Err(FacadeError::SequenceLocked);
+        }
+
+        for chunk in old {
+            repo::chunk_delete(&mut tx, chunk.chunk_id).await?;
+        }
+        let records = insert_chunks(&mut tx, topic_id, ontology_tag, new).await?;
+
+        tx.commit().await?;
+
+        Ok(records)
+    }
+
     /// Push all column statistics using batch inserts for better performance.
     /// This method collects all stats, resolves column IDs, then performs
-    /// two batch INSERT operations (one for numeric, one for literal stats).
+    /// a batch INSERT operation for each kind of stats (numeric, literal and boolean).
     pub async fn push_all_stats(
         &mut self,
         ontology_tag: &str,
         cstats: types::ColumnsStats,
     ) -> Result<(), FacadeError> {
-        let mut numeric_batch: Vec<repo::ColumnChunkNumeric> = Vec::new();
-        let mut literal_batch: Vec<repo::ColumnChunkLiteral> = Vec::new();
+        push_stats(
+            &mut self.tx,
+            ontology_tag,
+            vec![(self.chunk.chunk_id, cstats)],
+        )
+        .await
+    }
 
-        // First pass: resolve column IDs and collect stats for batch insert
-        for (field, stats) in cstats.stats {
-            if stats.is_unsupported() {
-                continue;
+    /// Recomputes the column statistics and the timestamp bounds of `chunk` reading its data
+    /// file, and replaces the stored ones.
+    ///
+    /// The data file is decoded with [`rw::ChunkReader`], so that the statistics are computed
+    /// on the stored column types. Statistics are replaced in a single transaction, so the
+    /// chunk is never left without statistics if something fails.
+    pub async fn restat(
+        chunk: repo::Chunk,
+        format: rw::Format,
+        timestamp_unit: types::TimestampUnit,
+        ontology_tag: &str,
+        store: &store::StoreRef,
+        repo: &'a repo::Repository,
+    ) -> Result<(), FacadeError> {
+        let buffer = store.read_bytes(chunk.data_file()).await?;
+        let reader = rw::ChunkReader::new(format, bytes::Bytes::from_owner(buffer))?;
+
+        let mut cstats = crate::arrow::column_stats_from_schema(&reader.schema());
+        let mut bounds = None;
+        for batch in reader {
+            let batch = batch?;
+            crate::arrow::column_stats_inspect_record_batch(&mut cstats, &batch)
+                .map_err(rw::Error::from)?;
+            bounds = rw::chunk_writer::merge_timestamp_bounds(bounds, &batch);
+        }
+
+        let mut tx = repo.transaction().await?;
+        repo::chunk_stats_delete(&mut tx, chunk.chunk_id).await?;
+        repo::chunk_update_timestamp_bounds(
+            &mut tx,
+            chunk.chunk_id,
+            timestamp_bounds_to_millis(timestamp_unit, bounds),
+        )
+        .await?;
+
+        let mut handle = Self { tx, chunk };
+        handle.push_all_stats(ontology_tag, cstats).await?;
+        handle.finalize().await
+    }
+
+    pub async fn finalize(self) -> Result<(), FacadeError> {
+        self.tx.commit().await?;
+        Ok(())
+    }
+}
+
+/// Data file and statistics of a chunk created by [`FacadeChunk::create_batch`]
+pub struct NewChunk {
+    pub datafile: std::path::PathBuf,
+    pub size_bytes: i64,
+    pub row_count: i64,
+    /// Bounds (in milliseconds) of the chunk timestamps, if known
+    pub timestamp_bounds: Option<(types::Timestamp, types::Timestamp)>,
+    /// CRC32 checksum of the data file
+    pub checksum: Option<u32>,
+    pub stats: types::ColumnsStats,
+}
+
+impl NewChunk {
+    /// Builds the data catalog record of a chunk produced by a [`rw::ChunkedWriter`]
+    pub fn new(
+        timestamp_unit: types::TimestampUnit,
+        datafile: std::path::PathBuf,
+        stats: types::ColumnsStats,
+        chunk_metadata: rw::ChunkMetadata,
+    ) -> Self {
+        Self {
+            datafile,
+            size_bytes: chunk_metadata.size_bytes as i64,
+            row_count: chunk_metadata.row_count as i64,
+            timestamp_bounds: timestamp_bounds_to_millis(
+                timestamp_unit,
+                chunk_metadata.timestamp_bounds,
+            ),
+            checksum: Some(chunk_metadata.checksum),
+            stats,
+        }
+    }
+}
+
+/// Converts the timestamp bounds of a chunk, in the unit of the topic, to the bounds stored in
+/// the repository: milliseconds, rounded outwards so that they always include the chunk
+/// timestamps.
+fn timestamp_bounds_to_millis(
+    unit: types::TimestampUnit,
+    bounds: Option<(i64, i64)>,
+) -> Option<(types::Timestamp, types::Timestamp)> {
+    bounds.map(|(min, max)| (unit.floor_to_millis(min), unit.ceil_to_millis(max)))
+}
+
+/// Creates the records of `chunks`, along with their column statistics, returning them in
+/// the same order of `chunks`.
+///
+/// Chunks are inserted with a single query and so are the statistics of each kind.
+async fn insert_chunks(
+    tx: &mut repo::Tx<'_>,
+    topic_id: i32,
+    ontology_tag: &str,
+    chunks: Vec<NewChunk>,
+) -> Result<Vec<repo::Chunk>, FacadeError> {
+    let (records, stats): (Vec<repo::Chunk>, Vec<types::ColumnsStats>) = chunks
+        .into_iter()
+        .map(|chunk| {
+            let mut record =
+                repo::Chunk::new(topic_id, &chunk.datafile, chunk.size_bytes, chunk.row_count);
+            if let Some((min, max)) = chunk.timestamp_bounds {
+                record = record.with_timestamp_bounds(min, max);
+            }
+            if let Some(checksum) = chunk.checksum {
+                record = record.with_checksum(checksum);
             }
+            (record, chunk.stats)
+        })
+        .unzip();
+
+    let mut created = Vec::with_capacity(records.len());
+    let mut records = records.into_iter().peekable();
+    while records.peek().is_some() {
+        let batch = records.by_ref().take(MAX_BATCH_ROWS).collect();
+        created.extend(repo::chunk_create_batch(tx, batch).await?);
+    }
+    let records = created;
+
+    // Chunks are returned in the same order, so each one keeps its own statistics
+    let chunks_stats = records
+        .iter()
+        .map(|chunk| chunk.chunk_id)
+        .zip(stats)
+        .collect();
+    push_stats(tx, ontology_tag, chunks_stats).await?;
+
+    Ok(records)
+}
+
+/// Inserts the column statistics of several chunks, given as pairs of chunk id and
+/// statistics, with a batch operation for each kind of stats (numeric, literal and boolean).
+///
+/// Each column id is resolved once, even if the column appears in several chunks.
+async fn push_stats(
+    tx: &mut repo::Tx<'_>,
+    ontology_tag: &str,
+    chunks_stats: Vec<(i32, types::ColumnsStats)>,
+) -> Result<(), FacadeError> {
+    let mut numeric_batch: Vec<repo::ColumnChunkNumeric> = Vec::new();
+    let mut literal_batch: Vec<repo::ColumnChunkLiteral> = Vec::new();
+    let mut boolean_batch: Vec<repo::ColumnChunkBoolean> = Vec::new();
+
+    let mut column_ids: HashMap<String, i32> = HashMap::new();
 
-            let column = repo::column_get_or_create(&mut self.tx, &field, ontology_tag).await?;
+    // First pass: resolve column IDs and collect stats for batch insert. Columns without
+    // statistics (e.g. binary fields) are registered too, since the registered columns define
+    // the fields of the ontology tag (see `FacadeTopic::check_ontology_schema`)
+    for (chunk_id, cstats) in chunks_stats {
+        for (field, stats) in cstats.stats {
+            let column_id = match column_ids.get(&field) {
+                Some(column_id) => *column_id,
+                None => {
+                    let column = repo::column_get_or_create(tx, &field, ontology_tag).await?;
+                    column_ids.insert(field, column.column_id);
+                    column.column_id
+                }
+            };
 
             match stats {
                 types::Stats::Text(stats) => {
+                    let null_count = stats.null_count;
                     let (min, max, has_null) = stats.into_owned();
-                    literal_batch.push(repo::ColumnChunkLiteral::try_new(
-                        column.column_id,
-                        self.chunk.chunk_id,
-                        min,
-                        max,
-                        has_null,
-                    )?);
+                    literal_batch.push(
+                        repo::ColumnChunkLiteral::try_new(column_id, chunk_id, min, max, has_null)?
+                            .with_null_count(null_count),
+                    );
                 }
                 types::Stats::Numeric(stats) => {
-                    numeric_batch.push(repo::ColumnChunkNumeric::new(
-                        column.column_id,
-                        self.chunk.chunk_id,
-                        stats.min,
-                        stats.max,
+                    numeric_batch.push(repo::ColumnChunkNumeric::new(column_id, chunk_id, &stats));
+                }
+                types::Stats::Boolean(stats) => {
+                    boolean_batch.push(repo::ColumnChunkBoolean::new(
+                        column_id,
+                        chunk_id,
+                        stats.has_true,
+                        stats.has_false,
                         stats.has_null,
-                        stats.has_nan,
                     ));
                 }
                 types::Stats::Unsupported => {}
             }
         }
+    }
 
-        // Batch insert all numeric stats in one query
-        repo::column_chunk_numeric_create_batch(&mut self.tx, &numeric_batch).await?;
+    // Batch insert all numeric stats in one query, wide topics are loaded with a
+    // (faster) COPY
+    if numeric_batch.len() >= params::configurables().stats_copy_threshold {
+        repo::column_chunk_numeric_copy(tx, &numeric_batch).await?;
+    } else {
+        for batch in numeric_batch.chunks(MAX_BATCH_ROWS) {
+            repo::column_chunk_numeric_create_batch(tx, batch).await?;
+        }
+    }
 
-        // Batch insert all literal stats in one query
-        repo::column_chunk_literal_create_batch(&mut self.tx, &literal_batch).await?;
+    // Batch insert all literal stats in one query (per batch)
+    for batch in literal_batch.chunks(MAX_BATCH_ROWS) {
+        repo::column_chunk_literal_create_batch(tx, batch).await?;
+    }
 
-        Ok(())
+    // Batch insert all boolean stats in one query (per batch)
+    for batch in boolean_batch.chunks(MAX_BATCH_ROWS) {
+        repo::column_chunk_boolean_create_batch(tx, batch).await?;
     }
 
-    pub async fn finalize(self) -> Result<(), FacadeError> {
-        self.tx.commit().await?;
-        Ok(())
+    Ok(())
+}
+
+/// Reads the data file of `chunk` and compares its checksum with the one computed when the
+/// chunk was written, returning [`FacadeError::CorruptChunk`] on mismatch.
+///
+/// Chunks created before checksums were tracked are not verified.
+pub(super) async fn verify_checksum(
+    store: &store::StoreRef,
+    chunk: &repo::Chunk,
+) -> Result<(), FacadeError> {
+    let Some(expected) = chunk.checksum() else {
+        return Ok(());
+    };
+
+    let bytes = store.read_bytes(chunk.data_file()).await?;
+    let found = crc32fast::hash(&bytes);
+
+    if found != expected {
+        return Err(FacadeError::CorruptChunk {
+            chunk_uuid: chunk.chunk_uuid,
+            data_file: chunk.data_file().to_string_lossy().into_owned(),
+        });
+    }
+    Ok(())
+}
+
+/// Splits the chunks of a topic into the groups of small chunks merged by the compaction
+/// (see [`super::FacadeTopic::compact`]).
+///
+/// Chunks are ordered by timestamp and those with a data file smaller than `target_bytes`
+/// are small: consecutive small chunks are grouped as long as their overall size stays
+/// within `target_bytes`. Groups of a single chunk are discarded, there is nothing to merge.
+pub(super) fn compaction_groups(
+    mut chunks: Vec<repo::Chunk>,
+    target_bytes: usize,
+) -> Vec<Vec<repo::Chunk>> {
+    chunks.sort_by_key(|chunk| (chunk.timestamp_bounds().map(|(min, _)| min), chunk.chunk_id));
+
+    let target_bytes = target_bytes as i64;
+    let mut groups = Vec::new();
+    let mut group: Vec<repo::Chunk> = Vec::new();
+    let mut group_bytes = 0;
+
+    for chunk in chunks {
+        if chunk.size_bytes >= target_bytes {
+            groups.push(std::mem::take(&mut group));
+            group_bytes = 0;
+            continue;
+        }
+
+        if group_bytes + chunk.size_bytes > target_bytes {
+            groups.push(std::mem::take(&mut group));
+            group_bytes = 0;
+        }
+        group_bytes += chunk.size_bytes;
+        group.push(chunk);
+    }
+    groups.push(group);
+
+    groups.retain(|group| group.len() > 1);
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_chunks_are_grouped() {
+        let chunk = |idx: i64, size_bytes: i64| {
+            repo::Chunk::new(1, format!("topic/data-{idx:05}.parquet"), size_bytes, 10)
+                .with_timestamp_bounds(idx.into(), (idx + 1).into())
+        };
+
+        // sizes are given out of order, chunks are sorted by timestamp
+        let chunks = vec![
+            chunk(5, 10),
+            chunk(0, 10),
+            chunk(1, 10),
+            chunk(2, 100),
+            chunk(3, 10),
+            chunk(4, 10),
+        ];
+
+        let groups = compaction_groups(chunks, 25);
+        let files: Vec<Vec<String>> = groups
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .map(|chunk| chunk.data_file().to_string_lossy().into_owned())
+                    .collect()
+            })
+            .collect();
+
+        // the large chunk splits the groups, the last small chunk exceeds the target size
+        assert_eq!(
+            files,
+            vec![
+                vec!["topic/data-00000.parquet", "topic/data-00001.parquet"],
+                vec!["topic/data-00003.parquet", "topic/data-00004.parquet"],
+            ]
+        );
+
+        assert!(compaction_groups(vec![chunk(0, 10), chunk(1, 100)], 25).is_empty());
     }
 }
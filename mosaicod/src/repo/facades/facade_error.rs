@@ -18,8 +18,16 @@ pub enum FacadeError {
     MetadataError(#[from] crate::types::MetadataError),
     #[error("repository error :: {0}")]
     RepositoryError(#[from] crate::repo::Error),
+    #[error("resource `{0}` already exists")]
+    AlreadyExists(String),
+    #[error("invalid name `{name}` :: {reason}")]
+    InvalidName { name: String, reason: String },
     #[error("sequence locked, unable to perform modifications")]
     SequenceLocked,
+    #[error(
+        "sequence unlocked, unable to perform the requested operation over an unlocked sequence"
+    )]
+    SequenceUnlocked,
     #[error("concurrecy error :: {0}")]
     ConcurrencyError(String),
     #[error("query error :: {0}")]
@@ -32,4 +40,54 @@ pub enum FacadeError {
     Unimplemented,
     #[error("unauthorized")]
     Unauthorized,
+    #[error(
+        "fields {fields:?} are not registered for ontology tag `{ontology_tag}`, new fields must be nullable"
+    )]
+    UnknownOntologyFields {
+        ontology_tag: String,
+        fields: Vec<String>,
+    },
+    #[error(
+        "contract of ontology tag `{ontology_tag}` violated :: {}",
+        .violations.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+    )]
+    ContractViolation {
+        ontology_tag: String,
+        violations: Vec<crate::types::ContractViolation>,
+    },
+    #[error(
+        "field `{field}` is incompatible with the data already stored in the topic :: {reason}"
+    )]
+    IncompatibleField { field: String, reason: String },
+    #[error("data file `{data_file}` of chunk `{chunk_uuid}` is corrupted (checksum mismatch)")]
+    CorruptChunk {
+        chunk_uuid: uuid::Uuid,
+        data_file: String,
+    },
+    #[error("topics {0:?} have no data")]
+    EmptyTopics(Vec<String>),
+    #[error("topic `{topic}` :: {source}")]
+    TopicError {
+        topic: String,
+        source: Box<FacadeError>,
+    },
+}
+
+impl FacadeError {
+    /// Returns `true` if the error is caused by a missing resource
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            Self::NotFound(_) => true,
+            Self::RepositoryError(e) => e.is_not_found(),
+            _ => false,
+        }
+    }
+
+    /// Attaches the name of the topic that caused the error
+    pub fn for_topic(self, topic: &str) -> Self {
+        Self::TopicError {
+            topic: topic.to_owned(),
+            source: Box::new(self),
+        }
+    }
 }
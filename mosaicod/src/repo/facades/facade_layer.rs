@@ -29,6 +29,47 @@ impl FacadeLayer {
         Ok(layers.into_iter().map(Into::into).collect())
     }
 
+    /// Read the repository record for this layer. If no record is found an error is returned.
+    pub async fn layer_id(&self) -> Result<i32, FacadeError> {
+        let mut cx = self.repo.connection();
+
+        let layer = repo::layer_find_by_locator(&mut cx, &self.locator).await?;
+
+        Ok(layer.layer_id)
+    }
+
+    /// Adds the sequence `sequence` to this layer, adding a sequence already in the layer has
+    /// no effect. Returns an error if the layer or the sequence do not exist.
+    pub async fn add_sequence(
+        &self,
+        sequence: &types::SequenceResourceLocator,
+    ) -> Result<(), FacadeError> {
+        let mut tx = self.repo.transaction().await?;
+
+        let layer = repo::layer_find_by_locator(&mut tx, &self.locator).await?;
+        let srecord = repo::sequence_find_by_locator(&mut tx, sequence).await?;
+        repo::layer_add_sequence(&mut tx, layer.layer_id, srecord.sequence_id).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Removes the sequence `sequence` from this layer, the sequence itself is not affected.
+    /// Returns an error if the sequence does not belong to the layer.
+    pub async fn remove_sequence(
+        &self,
+        sequence: &types::SequenceResourceLocator,
+    ) -> Result<(), FacadeError> {
+        let mut tx = self.repo.transaction().await?;
+
+        let layer = repo::layer_find_by_locator(&mut tx, &self.locator).await?;
+        let srecord = repo::sequence_find_by_locator(&mut tx, sequence).await?;
+        repo::layer_remove_sequence(&mut tx, layer.layer_id, srecord.sequence_id).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
     pub async fn create(&self, description: String) -> Result<i32, FacadeError> {
         let mut tx = self.repo.transaction().await?;
 
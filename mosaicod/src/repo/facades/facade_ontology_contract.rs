@@ -0,0 +1,72 @@
+use crate::{marshal, repo, types};
+
+use super::FacadeError;
+
+/// Handle to the data contract of an ontology tag (see [`types::OntologyContract`])
+pub struct FacadeOntologyContract {
+    pub ontology_tag: String,
+    repo: repo::Repository,
+}
+
+impl FacadeOntologyContract {
+    pub fn new(ontology_tag: String, repo: repo::Repository) -> Self {
+        Self { ontology_tag, repo }
+    }
+
+    /// Returns the contract of the ontology tag, [`None`] if no contract is registered
+    pub async fn get(&self) -> Result<Option<types::OntologyContract>, FacadeError> {
+        let mut cx = self.repo.connection();
+
+        let Some(doc) = repo::ontology_contract_find_by_tag(&mut cx, &self.ontology_tag).await?
+        else {
+            return Ok(None);
+        };
+
+        let contract =
+            marshal::JsonOntologyContract::try_from(doc)?.into_types(self.ontology_tag.clone())?;
+
+        Ok(Some(contract))
+    }
+
+    /// Registers the contract of the ontology tag, replacing the existing one.
+    ///
+    /// The contract applies to the topics created and to the data uploaded from now on,
+    /// existing topics and data are not validated.
+    pub async fn set(&self, contract: types::OntologyContract) -> Result<(), FacadeError> {
+        let mut cx = self.repo.connection();
+
+        let doc = serde_json::Value::try_from(marshal::JsonOntologyContract::from(contract))?;
+        repo::ontology_contract_upsert(&mut cx, &self.ontology_tag, doc).await?;
+
+        Ok(())
+    }
+
+    /// Removes the contract of the ontology tag, returns an error if no contract is registered
+    pub async fn delete(&self) -> Result<(), FacadeError> {
+        let mut cx = self.repo.connection();
+
+        repo::ontology_contract_delete(&mut cx, &self.ontology_tag).await?;
+
+        Ok(())
+    }
+
+    /// Checks the user metadata of a topic against the contract, if any
+    pub async fn check_metadata(
+        &self,
+        user_metadata: &marshal::JsonMetadataBlob,
+    ) -> Result<(), FacadeError> {
+        let Some(contract) = self.get().await? else {
+            return Ok(());
+        };
+
+        let violations = user_metadata.contract_violations(&contract.metadata);
+        if !violations.is_empty() {
+            return Err(FacadeError::ContractViolation {
+                ontology_tag: contract.ontology_tag,
+                violations,
+            });
+        }
+
+        Ok(())
+    }
+}
@@ -1,9 +1,9 @@
-use super::FacadeError;
-use crate::{params, query, repo, types};
+use super::{FacadeError, FacadeTopic};
+use crate::{params, query, repo, store, types};
 use futures::stream::{FuturesUnordered, StreamExt};
 use log::{debug, trace};
 use std::collections::{HashMap, HashSet};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use tokio::sync::Semaphore;
 
@@ -11,25 +11,46 @@ use tokio::sync::Semaphore;
 /// between different components (mainly `query` and `repo` modules).
 ///
 /// All complex query logics needs to be implemented inside this facade.
+///
+/// Queries are read-only and are performed on the read replica, if configured (see
+/// [`repo::Repository::replica_connection`]), so recently written data may be missing
+/// from the results.
 pub struct FacadeQuery {}
 
 impl FacadeQuery {
+    /// Retrieves the sequences (and their topics) matching the filter.
+    ///
+    /// With [`query::Scope::Topic`] only the matching topics are reported for each sequence,
+    /// while with [`query::Scope::Sequence`] each matching sequence reports all of its topics.
+    ///
+    /// If the filter carries a data time range, the ontology filter is evaluated only on the
+    /// data within the range (see [`match_topics`]).
+    ///
+    /// At most [`params::ConfigurablesParams::max_query_results`] topics are reported, the
+    /// returned flag is `true` if the results have been truncated. Topics are collected up to
+    /// the cap (plus one, to detect the truncation) whenever the final result does not depend
+    /// on the discarded ones.
     pub async fn query(
         filter: query::Filter,
+        scope: query::Scope,
         ts_gw: query::TimeseriesGwRef,
+        store: store::StoreRef,
         repo: repo::Repository,
-    ) -> Result<types::SequenceTopicGroups, FacadeError> {
+    ) -> Result<(types::SequenceTopicGroups, bool), FacadeError> {
         let mut result: Option<types::SequenceTopicGroups> = None;
+        let max_results = params::configurables().max_query_results;
 
-        let (seq_filt, top_filt, on_filt) = filter.into_parts();
+        let (seq_filt, top_filt, layer_filt, on_filt, time_range) = filter.into_parts();
+        ensure_time_range_has_ontology(&time_range, &on_filt)?;
 
         let no_topic_filter = (seq_filt.is_none() || seq_filt.as_ref().unwrap().is_empty())
-            && (top_filt.is_none() || top_filt.as_ref().unwrap().is_empty());
+            && (top_filt.is_none() || top_filt.as_ref().unwrap().is_empty())
+            && (layer_filt.is_none() || layer_filt.as_ref().unwrap().is_empty());
 
         // This holds the set of topic that the user requested with topic and sequence filters
         let on_topics = {
-            let mut cx = repo.connection();
-            repo::topic_from_query_filter(&mut cx, seq_filt, top_filt).await?
+            let mut cx = repo.replica_connection();
+            repo::topic_from_query_filter(&mut cx, seq_filt, top_filt, layer_filt).await?
         };
         let on_topics = Arc::new(on_topics);
 
@@ -47,136 +68,719 @@ impl FacadeQuery {
         // `SequenceTopicGroups`.
         // At the end sequence topic groups are merged (sequences are interseted and topic are
         // joined) before return.
+        //
+        // The data files of the candidate chunks are scanned concurrently, across all the
+        // groups, with at most `max_concurrent_chunk_queries` scans running at once (see
+        // `match_topics`). Jobs are not spawned, they are polled by this future: if the query
+        // is cancelled (or times out) all the jobs are dropped along with their permits
+        // (see `with_permit`).
         if let Some(ontology_filter) = on_filt {
             let start = Instant::now();
 
             let ontology_tag_expr_groups =
-                ontology_filter.into_expr_group().split_by_ontology_tag();
+                ontology_filter.into_expr_group().split_by_ontology_tag()?;
             let expression_groups_count = ontology_tag_expr_groups.len();
 
+            let max_concurrent = params::configurables().max_concurrent_chunk_queries;
+            let semaphore = Arc::new(Semaphore::new(max_concurrent));
+            let mut search_jobs = FuturesUnordered::new();
+
+            // Groups are intersected by sequence when merged, so the topics of a group can be
+            // capped only if it is the only one
+            let collect_limit = (expression_groups_count == 1).then_some(max_results + 1);
+
             for ontology_tag_exprs in ontology_tag_expr_groups {
-                if ontology_tag_exprs.group.is_empty() {
+                if ontology_tag_exprs.is_empty() {
                     continue;
                 }
                 trace!(
                     "starting search for ontology tag `{}` (expression groups: {})",
                     // grp is ensured to contain at least one element (previous check)
-                    ontology_tag_exprs.group[0].ontology_field().ontology_tag(),
+                    ontology_tag_exprs.ontology_tag().unwrap_or_default(),
                     &expression_groups_count,
                 );
 
                 let ts_engine = ts_gw.clone();
-                let max_concurrent = params::configurables().max_concurrent_chunk_queries;
-                let semaphore = Arc::new(Semaphore::new(max_concurrent));
-                let mut search_jobs = FuturesUnordered::new();
-
-                let permit = semaphore.clone().acquire_owned().await.map_err(|e| {
-                    FacadeError::ConcurrencyError(format!("semaphore acquire failed: {e}"))
-                })?;
-
                 let repo_clone = repo.clone();
+                let store = store.clone();
                 let on_topics = on_topics.clone();
+                let time_range = time_range.clone();
 
-                search_jobs.push(async move {
-                    let _permit = permit; // sentinel lock
+                let semaphore = semaphore.clone();
 
-                    let mut cx = repo_clone.connection();
-                    let chunks = repo::chunks_from_filters(
-                        &mut cx,
-                        ontology_tag_exprs.clone(),
-                        Some(&on_topics),
+                search_jobs.push(async move {
+                    let (matches, topics_map) = match_topics(
+                        &ts_engine,
+                        &store,
+                        &repo_clone,
+                        ontology_tag_exprs,
+                        on_topics,
+                        no_topic_filter,
+                        MatchOptions {
+                            mode: MatchMode::Exists,
+                            time_range,
+                            semaphore,
+                        },
                     )
                     .await?;
-                    trace!("found {} chunks for provided filter", chunks.len());
-
-                    // Extract a lookup structure holding all the topics for the current chunk set
-                    let on_topics = if no_topic_filter {
-                        None
-                    } else {
-                        Some(&on_topics)
-                    };
-                    let topics_map = pre_fetch_topics(&mut cx, &chunks, on_topics).await?;
-
-                    // Store which topic had a positive data file search
-                    let mut topics_with_data: HashSet<i32> = HashSet::new();
-
-                    for chunk in chunks {
-                        let topic = topics_map.get(&chunk.topic_id);
-                        if topic.is_none() {
-                            debug!(
-                                "can't find a topic associated with chunk `{}`, skipping",
-                                chunk.chunk_uuid
-                            );
-                            return Ok::<_, FacadeError>(types::SequenceTopicGroups::empty());
-                        }
-                        let topic = topic.unwrap();
-
-                        trace!(
-                            "searching data file `{}`",
-                            chunk.data_file().to_string_lossy()
-                        );
-
-                        let serialization_format =
-                            topic.serialization_format().ok_or_else(|| {
-                                FacadeError::MissingSerializationFormat(
-                                    topic.locator_name.to_owned(),
-                                )
-                            })?;
-
-                        let qr = ts_engine
-                            .read(chunk.data_file(), serialization_format, None)
-                            .await?;
-
-                        let qr = qr.filter(ontology_tag_exprs.to_owned())?;
-
-                        if qr.has_rows().await? {
-                            trace!("found matching records in chunk");
-                            topics_with_data.insert(topic.topic_id);
-                        } else {
-                            trace!("discarding chunk `{}` for no query match", chunk.chunk_uuid);
-                        }
-                    }
 
-                    trace!("topics with positive match: {:?}", topics_with_data);
                     let topics = topics_map
                         .values()
-                        .filter(|e| topics_with_data.contains(&e.topic_id));
-                    let group = repo::sequences_group_from_topics(&mut cx, topics).await?;
+                        .filter(|e| matches.contains_key(&e.topic_id));
+                    let topics = first_topics_by_name(topics, collect_limit);
+                    let mut cx = repo_clone.replica_connection();
+                    let group =
+                        repo::sequences_group_from_topics(&mut cx, topics.into_iter()).await?;
 
-                    Ok::<_, FacadeError>(group.into())
+                    Ok::<types::SequenceTopicGroups, FacadeError>(group.into())
                 });
+            }
 
-                while let Some(groups) = search_jobs.next().await {
-                    if let Some(r) = result {
-                        result = Some(r.merge(groups?));
-                    } else {
-                        result = Some(groups?);
-                    }
+            while let Some(groups) = search_jobs.next().await {
+                if let Some(r) = result {
+                    result = Some(r.merge(groups?));
+                } else {
+                    result = Some(groups?);
                 }
+            }
 
-                let elapsed = start.elapsed();
+            let elapsed = start.elapsed();
 
-                debug!(
-                    "expression groups search required {}us ({:.2}us/group, {} concurrent)",
-                    elapsed.as_micros(),
-                    elapsed.as_micros() as f64 / expression_groups_count.max(1) as f64,
-                    max_concurrent
-                );
-            }
+            debug!(
+                "expression groups search required {}us ({:.2}us/group, {} concurrent)",
+                elapsed.as_micros(),
+                elapsed.as_micros() as f64 / expression_groups_count.max(1) as f64,
+                max_concurrent
+            );
         } else {
             // No ontology filter branch, simply retrieve
-            let mut cx = repo.connection();
-            let group = repo::sequences_group_from_topics(&mut cx, on_topics.iter()).await?;
+            let topics = first_topics_by_name(on_topics.iter(), Some(max_results + 1));
+            let mut cx = repo.replica_connection();
+            let group = repo::sequences_group_from_topics(&mut cx, topics.into_iter()).await?;
             result = Some(group.into());
         }
 
-        Ok(result.unwrap_or_default())
+        // The cap is applied before expanding the groups to full sequences too, so that
+        // the expansion is bounded as well
+        let mut result = result.unwrap_or_default();
+        let mut truncated = result.truncate(max_results);
+
+        if scope == query::Scope::Sequence {
+            result = expand_to_sequences(&repo, result, max_results).await?;
+            truncated |= result.truncate(max_results);
+        }
+
+        if truncated {
+            debug!("query results truncated to {} topics", max_results);
+        }
+
+        Ok((result, truncated))
+    }
+
+    /// Counts, for each topic, the number of rows matching the filter.
+    ///
+    /// Topics are selected with the same rules used by [`FacadeQuery::query`]. The returned
+    /// map associates each selected topic locator with the number of rows matching the
+    /// ontology filter, or with the total number of rows of the topic if no ontology filter
+    /// is provided.
+    pub async fn query_count(
+        filter: query::Filter,
+        ts_gw: query::TimeseriesGwRef,
+        store: store::StoreRef,
+        repo: repo::Repository,
+    ) -> Result<HashMap<String, usize>, FacadeError> {
+        let (seq_filt, top_filt, layer_filt, on_filt, time_range) = filter.into_parts();
+        ensure_time_range_has_ontology(&time_range, &on_filt)?;
+
+        let no_topic_filter = (seq_filt.is_none() || seq_filt.as_ref().unwrap().is_empty())
+            && (top_filt.is_none() || top_filt.as_ref().unwrap().is_empty())
+            && (layer_filt.is_none() || layer_filt.as_ref().unwrap().is_empty());
+
+        let on_topics = {
+            let mut cx = repo.replica_connection();
+            repo::topic_from_query_filter(&mut cx, seq_filt, top_filt, layer_filt).await?
+        };
+        let on_topics = Arc::new(on_topics);
+
+        let Some(ontology_filter) = on_filt else {
+            // No ontology filter, all the rows of the selected topics are matching
+            let ids: Vec<i32> = on_topics.iter().map(|t| t.topic_id).collect();
+            let mut cx = repo.replica_connection();
+            let stats = repo::topics_get_stats(&mut cx, &ids).await?;
+            return Ok(on_topics
+                .iter()
+                .map(|topic| {
+                    let rows = stats.get(&topic.topic_id).map_or(0, |s| s.total_row_count);
+                    (topic.locator_name.clone(), rows as usize)
+                })
+                .collect());
+        };
+
+        // Each ontology tag group is evaluated separately and, as done by `query`, a topic
+        // is reported only if its sequence has a match for every group.
+        let mut counts: HashMap<String, (i32, usize)> = HashMap::new();
+        let mut sequences: Option<HashSet<i32>> = None;
+        let semaphore = Arc::new(Semaphore::new(
+            params::configurables().max_concurrent_chunk_queries,
+        ));
+
+        for ontology_tag_exprs in ontology_filter.into_expr_group().split_by_ontology_tag()? {
+            if ontology_tag_exprs.is_empty() {
+                continue;
+            }
+
+            let (matches, topics_map) = match_topics(
+                &ts_gw,
+                &store,
+                &repo,
+                ontology_tag_exprs,
+                on_topics.clone(),
+                no_topic_filter,
+                MatchOptions {
+                    mode: MatchMode::Count,
+                    time_range: time_range.clone(),
+                    semaphore: semaphore.clone(),
+                },
+            )
+            .await?;
+
+            let mut group_sequences = HashSet::new();
+            for (topic_id, count) in matches {
+                if let Some(topic) = topics_map.get(&topic_id) {
+                    group_sequences.insert(topic.sequence_id);
+                    counts
+                        .entry(topic.locator_name.clone())
+                        .or_insert((topic.sequence_id, 0))
+                        .1 += count;
+                }
+            }
+
+            sequences = Some(match sequences {
+                Some(sequences) => sequences.intersection(&group_sequences).copied().collect(),
+                None => group_sequences,
+            });
+        }
+
+        let sequences = sequences.unwrap_or_default();
+
+        Ok(counts
+            .into_iter()
+            .filter(|(_, (sequence_id, _))| sequences.contains(sequence_id))
+            .map(|(topic, (_, count))| (topic, count))
+            .collect())
+    }
+
+    /// Lists, for each topic matching the filter, the distinct values of `field`.
+    ///
+    /// Topics are selected as done by [`FacadeQuery::query`] with [`query::Scope::Topic`],
+    /// topics not holding `field` are not reported. At most
+    /// [`params::ConfigurablesParams::max_distinct_values`] values are returned per topic.
+    ///
+    /// The returned flag is `true` if the selected topics have been truncated (see
+    /// [`FacadeQuery::query`]).
+    pub async fn query_distinct(
+        filter: query::Filter,
+        field: &str,
+        ts_gw: query::TimeseriesGwRef,
+        store: store::StoreRef,
+        repo: repo::Repository,
+    ) -> Result<(HashMap<String, query::DistinctValues>, bool), FacadeError> {
+        let (groups, truncated) = Self::query(
+            filter,
+            query::Scope::Topic,
+            ts_gw.clone(),
+            store.clone(),
+            repo.clone(),
+        )
+        .await?;
+
+        let limit = params::configurables().max_distinct_values;
+        let mut values = HashMap::new();
+
+        for group in Vec::<types::SequenceTopicGroup>::from(groups) {
+            let (_, topics) = group.into_parts();
+            for topic in topics {
+                let name: String = topic.into();
+                let handle = FacadeTopic::new(name.clone(), store.clone(), repo.clone());
+
+                match handle.distinct_values(field, limit, &ts_gw).await {
+                    Ok(distinct) => {
+                        values.insert(name, distinct);
+                    }
+                    Err(FacadeError::QueryError(query::Error::UnknownColumn { .. })) => {
+                        trace!("field `{}` not found in topic `{}`, skipping", field, name);
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+
+        Ok((values, truncated))
+    }
+
+    /// Describes how the filter is executed by [`FacadeQuery::query`], without evaluating
+    /// it on the data files.
+    ///
+    /// Reports the SQL query selecting the topics and, for each ontology tag, the SQL query
+    /// selecting the candidate chunks along with the logical plan used to scan a data file.
+    /// Only the plan of the first candidate is reported (see
+    /// [`query::OntologyTagExplain::explained_chunk`]), the plans of the other candidates
+    /// differ only by the scanned data file. Catalog queries are run to select topics and
+    /// chunks.
+    pub async fn explain(
+        filter: query::Filter,
+        ts_gw: query::TimeseriesGwRef,
+        store: store::StoreRef,
+        repo: repo::Repository,
+    ) -> Result<query::Explain, FacadeError> {
+        let (seq_filt, top_filt, layer_filt, on_filt, time_range) = filter.into_parts();
+        ensure_time_range_has_ontology(&time_range, &on_filt)?;
+
+        let topics_sql =
+            repo::topic_query_from_filter(seq_filt.clone(), top_filt.clone(), layer_filt.clone())?
+                .map(query::SqlStatement::from);
+
+        let mut cx = repo.replica_connection();
+        let on_topics =
+            repo::topic_from_query_filter(&mut cx, seq_filt, top_filt, layer_filt).await?;
+
+        let mut explain = query::Explain {
+            topics_sql,
+            selected_topics: on_topics.len(),
+            ontology_tags: Vec::new(),
+        };
+
+        let Some(ontology_filter) = on_filt else {
+            return Ok(explain);
+        };
+
+        let quantile_pruning = params::configurables().quantile_pruning;
+
+        for exprs in ontology_filter.into_expr_group().split_by_ontology_tag()? {
+            if exprs.is_empty() {
+                continue;
+            }
+
+            ensure_no_mixed_chunk_level_ops(&exprs)?;
+
+            let chunks_sql = repo::chunks_query_from_filters(
+                exprs.clone(),
+                Some(&on_topics),
+                quantile_pruning,
+                time_range.as_ref(),
+            )?;
+            let chunks = repo::chunks_from_filters(
+                &mut cx,
+                exprs.clone(),
+                Some(&on_topics),
+                quantile_pruning,
+                time_range.as_ref(),
+            )
+            .await?;
+
+            // Groups made only of chunk level operations never scan data files
+            let logical_plan = match chunks.first() {
+                Some(chunk) if exprs.chunk_level_fields().is_empty() => {
+                    let topic = repo::topic_find_by_ids(&mut cx, &[chunk.topic_id])
+                        .await?
+                        .pop()
+                        .ok_or_else(|| FacadeError::NotFound(chunk.topic_id.to_string()))?;
+                    let serialization_format = topic.serialization_format().ok_or_else(|| {
+                        FacadeError::MissingSerializationFormat(topic.locator_name.to_owned())
+                    })?;
+
+                    let range = match &time_range {
+                        Some(time_range) => {
+                            let unit = FacadeTopic::new(
+                                topic.locator_name.clone(),
+                                store.clone(),
+                                repo.clone(),
+                            )
+                            .metadata()
+                            .await?
+                            .properties
+                            .timestamp_unit;
+                            Some(query::Range {
+                                min: unit.ceil_from(time_range.min).into(),
+                                max: unit.floor_from(time_range.max).into(),
+                            })
+                        }
+                        None => None,
+                    };
+
+                    let qr = ts_gw
+                        .read(
+                            chunk.data_file(),
+                            serialization_format,
+                            None,
+                            range,
+                            None,
+                            query::SortOrder::Asc,
+                        )
+                        .await?
+                        .filter(exprs.clone())?;
+
+                    Some(qr.logical_plan()?)
+                }
+                _ => None,
+            };
+            let explained_chunk = logical_plan
+                .as_ref()
+                .and(chunks.first())
+                .map(|chunk| chunk.data_file.clone());
+
+            explain.ontology_tags.push(query::OntologyTagExplain {
+                ontology_tag: exprs.ontology_tag().unwrap_or_default().to_owned(),
+                chunks_sql: chunks_sql.into(),
+                candidate_chunks: chunks.len(),
+                logical_plan,
+                explained_chunk,
+            });
+        }
+
+        Ok(explain)
+    }
+}
+
+/// Ensures that the chunk level operations of `exprs` (see [`query::Op::is_chunk_level`]),
+/// resolved using only the chunk statistics, are not combined with row level operations.
+fn ensure_no_mixed_chunk_level_ops(
+    exprs: &query::ExprGroup<query::Value>,
+) -> Result<(), FacadeError> {
+    let chunk_level_fields = exprs.chunk_level_fields();
+    if !chunk_level_fields.is_empty() && chunk_level_fields.len() != exprs.ontology_fields().len() {
+        return Err(query::Error::MixedChunkLevelOps.into());
     }
+    Ok(())
+}
+
+/// Runs `job` once a permit of `semaphore` is available, holding the permit until the
+/// job completes.
+///
+/// The permit is owned by the returned future, so dropping the future (e.g. when a query is
+/// cancelled or times out) releases the permit, also while the future is still waiting for
+/// it (waiting for a permit is cancel safe). Cancelled queries can't hold permits, so the
+/// semaphore can't be exhausted by them.
+async fn with_permit<T>(
+    semaphore: Arc<Semaphore>,
+    job: impl Future<Output = Result<T, FacadeError>>,
+) -> Result<T, FacadeError> {
+    // Acquisition fails only if the semaphore has been closed
+    let _permit = semaphore
+        .acquire_owned()
+        .await
+        .map_err(|e| FacadeError::ConcurrencyError(format!("semaphore acquire failed: {e}")))?;
+
+    job.await
 }
 
 /// A map holding pairs of (topic_id, topic_record) for easy lookup
 type TopicMap = HashMap<i32, repo::TopicRecord>;
 
+/// Defines how the data files of the candidate chunks are evaluated
+#[derive(Clone, Copy)]
+enum MatchMode {
+    /// Stops at the first matching row of each topic
+    Exists,
+    /// Counts all the matching rows
+    Count,
+}
+
+struct MatchOptions {
+    mode: MatchMode,
+    /// Data time range (in milliseconds) the search is restricted to
+    time_range: Option<query::Range<query::Timestamp>>,
+    /// Limits the number of data files scanned at once, may be shared by several searches
+    semaphore: Arc<Semaphore>,
+}
+
+/// The data time range restricts the evaluation of the ontology filter, so it can't be
+/// used on its own.
+fn ensure_time_range_has_ontology(
+    time_range: &Option<query::Range<query::Timestamp>>,
+    ontology: &Option<query::OntologyFilter>,
+) -> Result<(), FacadeError> {
+    if time_range.is_some() && ontology.is_none() {
+        return Err(query::Error::OpError {
+            field: "time_range".to_owned(),
+            err: query::OpError::UnsupportedOperation,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Evaluates the expressions (referring to a single ontology tag) against the data files of
+/// the chunks selected by the data catalog.
+///
+/// If a time range is provided, the candidate chunks are first pruned using their timestamp
+/// bounds, or the statistics of their timestamp column for chunks without bounds (cheap, no
+/// data is read), then the surviving data files are scanned restricting the rows to the time
+/// range. The time range is converted to the timestamp unit of each topic.
+///
+/// Groups made only of chunk level operations (see [`query::Op::is_chunk_level`]) are
+/// resolved entirely on the data catalog statistics: every chunk selected by the catalog is a
+/// match and no data file is scanned. With a time range, such groups are evaluated at chunk
+/// granularity. Chunk level operations can't be mixed with row level ones on the same
+/// ontology tag, nor used to count rows.
+///
+/// The data file of each chunk is scanned by its own job, running once a permit of the
+/// options semaphore is available. In [`MatchMode::Exists`] mode the chunks of a topic that
+/// already matched are not scanned.
+///
+/// Returns the number of matching rows for each topic with at least a match, indexed by
+/// topic id, along with the records of the topics involved in the search.
+/// In [`MatchMode::Exists`] mode the reported number of rows is not meaningful.
+async fn match_topics(
+    ts_engine: &query::TimeseriesGwRef,
+    store: &store::StoreRef,
+    repo: &repo::Repository,
+    exprs: query::ExprGroup<query::Value>,
+    on_topics: Arc<Vec<repo::TopicRecord>>,
+    no_topic_filter: bool,
+    options: MatchOptions,
+) -> Result<(HashMap<i32, usize>, Arc<TopicMap>), FacadeError> {
+    let mode = options.mode;
+
+    ensure_no_mixed_chunk_level_ops(&exprs)?;
+
+    let chunk_level_fields = exprs.chunk_level_fields();
+    let chunk_level = !chunk_level_fields.is_empty();
+    if chunk_level && matches!(mode, MatchMode::Count) {
+        return Err(query::Error::unsupported_op(chunk_level_fields[0].value().to_owned()).into());
+    }
+
+    let mut cx = repo.replica_connection();
+    // Quantile pruning may discard chunks with a few matching rows, so it is never used
+    // when counting rows
+    let quantile_pruning =
+        matches!(mode, MatchMode::Exists) && params::configurables().quantile_pruning;
+    // Chunks with timestamp bounds outside the time range are discarded by the data catalog
+    let chunks = repo::chunks_from_filters(
+        &mut cx,
+        exprs.clone(),
+        Some(&on_topics),
+        quantile_pruning,
+        options.time_range.as_ref(),
+    )
+    .await?;
+    trace!("found {} chunks for provided filter", chunks.len());
+
+    // Extract a lookup structure holding all the topics for the current chunk set
+    let on_topics = if no_topic_filter {
+        None
+    } else {
+        Some(&on_topics)
+    };
+    let topics_map = pre_fetch_topics(&mut cx, &chunks, on_topics).await?;
+
+    // Time range of each topic, converted to the topic timestamp unit
+    let mut topic_ranges: HashMap<i32, query::Range<query::Timestamp>> = HashMap::new();
+
+    let chunks = if let Some(time_range) = &options.time_range {
+        let topic_ids: HashSet<i32> = chunks.iter().map(|c| c.topic_id).collect();
+        for topic in topic_ids.iter().filter_map(|id| topics_map.get(id)) {
+            let unit = FacadeTopic::new(topic.locator_name.clone(), store.clone(), repo.clone())
+                .metadata()
+                .await?
+                .properties
+                .timestamp_unit;
+            topic_ranges.insert(
+                topic.topic_id,
+                query::Range {
+                    min: unit.ceil_from(time_range.min).into(),
+                    max: unit.floor_from(time_range.max).into(),
+                },
+            );
+        }
+
+        // Chunks created before timestamp bounds were tracked are pruned using the
+        // statistics of their timestamp column
+        let ids: Vec<i32> = chunks
+            .iter()
+            .filter(|c| c.timestamp_bounds().is_none())
+            .map(|c| c.chunk_id)
+            .collect();
+        let bounds = repo::chunks_timestamp_bounds(&mut cx, &ids).await?;
+
+        let before = chunks.len();
+        let chunks: Vec<repo::Chunk> = chunks
+            .into_iter()
+            .filter(|chunk| {
+                let range = topic_ranges.get(&chunk.topic_id);
+                match (range, bounds.get(&chunk.chunk_id)) {
+                    // Stats are stored as floating point values, the rounding is monotonic
+                    // so comparing the rounded values never discards an overlapping chunk
+                    (Some(range), Some((min, max))) => {
+                        *max >= i64::from(range.min) as f64 && *min <= i64::from(range.max) as f64
+                    }
+                    _ => true,
+                }
+            })
+            .collect();
+        trace!(
+            "time range pruning kept {} of {} chunks",
+            chunks.len(),
+            before
+        );
+        chunks
+    } else {
+        chunks
+    };
+
+    // A chunk without a topic aborts the search of the whole group
+    if let Some(chunk) = chunks
+        .iter()
+        .find(|chunk| !topics_map.contains_key(&chunk.topic_id))
+    {
+        debug!(
+            "can't find a topic associated with chunk `{}`, skipping",
+            chunk.chunk_uuid
+        );
+        return Ok((HashMap::new(), topics_map));
+    }
+
+    // Store which topic had a positive data file search
+    let mut matches: HashMap<i32, usize> = HashMap::new();
+
+    if chunk_level {
+        for chunk in &chunks {
+            trace!("chunk `{}` matched by its statistics", chunk.chunk_uuid);
+            matches.insert(chunk.topic_id, 1);
+        }
+        trace!("topics with positive match: {:?}", matches.keys());
+        return Ok((matches, topics_map));
+    }
+
+    // Topics with a positive data file search, shared by the scan jobs to skip the
+    // remaining chunks of these topics
+    let matched: Mutex<HashSet<i32>> = Mutex::new(HashSet::new());
+    let verify_checksums = params::configurables().verify_chunk_checksums;
+
+    let mut scans = FuturesUnordered::new();
+    for chunk in &chunks {
+        // Every chunk has a topic (checked above)
+        let topic = &topics_map[&chunk.topic_id];
+        let serialization_format = topic.serialization_format().ok_or_else(|| {
+            FacadeError::MissingSerializationFormat(topic.locator_name.to_owned())
+        })?;
+        let range = topic_ranges.get(&topic.topic_id).cloned();
+        let matched = &matched;
+        let exprs = &exprs;
+
+        scans.push(with_permit(options.semaphore.clone(), async move {
+            let already_matched = matches!(mode, MatchMode::Exists)
+                && matched.lock().unwrap().contains(&chunk.topic_id);
+            if already_matched {
+                trace!(
+                    "skipping chunk `{}`, topic already matched",
+                    chunk.chunk_uuid
+                );
+                return Ok((chunk, None));
+            }
+
+            trace!(
+                "searching data file `{}`",
+                chunk.data_file().to_string_lossy()
+            );
+
+            if verify_checksums {
+                super::facade_chunk::verify_checksum(store, chunk).await?;
+            }
+
+            // Reads on cloud object stores can fail intermittently, the whole scan of the
+            // chunk is retried on transient errors
+            let count = store::retry(store.retry_policy(), "chunk scan", || async {
+                let qr = ts_engine
+                    .read(
+                        chunk.data_file(),
+                        serialization_format,
+                        None,
+                        range.clone(),
+                        None,
+                        query::SortOrder::Asc,
+                    )
+                    .await?;
+
+                let qr = qr.filter(exprs.to_owned())?;
+
+                match mode {
+                    MatchMode::Exists => Ok(usize::from(qr.has_rows().await?)),
+                    MatchMode::Count => qr.count().await,
+                }
+            })
+            .await?;
+
+            if count > 0 {
+                matched.lock().unwrap().insert(chunk.topic_id);
+            }
+
+            Ok((chunk, Some(count)))
+        }));
+    }
+
+    while let Some(scan) = scans.next().await {
+        let (chunk, Some(count)) = scan? else {
+            continue;
+        };
+        if count > 0 {
+            trace!("found matching records in chunk `{}`", chunk.chunk_uuid);
+            *matches.entry(chunk.topic_id).or_default() += count;
+        } else {
+            trace!("discarding chunk `{}` for no query match", chunk.chunk_uuid);
+        }
+    }
+
+    trace!("topics with positive match: {:?}", matches.keys());
+
+    Ok((matches, topics_map))
+}
+
+/// Replaces the topics of each group with the full set of topics of its sequence
+async fn expand_to_sequences(
+    repo: &repo::Repository,
+    groups: types::SequenceTopicGroups,
+    max_topics: usize,
+) -> Result<types::SequenceTopicGroups, FacadeError> {
+    let mut cx = repo.replica_connection();
+    let mut expanded = Vec::new();
+
+    let mut groups = Vec::<types::SequenceTopicGroup>::from(groups);
+    groups.sort_by(|a, b| a.sequence.name().cmp(b.sequence.name()));
+
+    let mut topics_count = 0;
+    for group in groups {
+        // Sequences past the cap would be discarded by the truncation anyway
+        if topics_count > max_topics {
+            break;
+        }
+
+        let (sequence, _) = group.into_parts();
+        let topics = repo::sequence_find_all_topic_names(&mut cx, &sequence).await?;
+        topics_count += topics.len();
+        expanded.push(types::SequenceTopicGroup::new(sequence, topics));
+    }
+
+    trace!("query expanded to #{} full sequences", expanded.len());
+
+    Ok(expanded.into())
+}
+
+/// Returns the first `limit` topics, ordered by name, or all of them if no limit is given.
+fn first_topics_by_name<'a>(
+    topics: impl Iterator<Item = &'a repo::TopicRecord>,
+    limit: Option<usize>,
+) -> Vec<&'a repo::TopicRecord> {
+    let mut topics: Vec<_> = topics.collect();
+    if let Some(limit) = limit
+        && topics.len() > limit
+    {
+        topics.sort_by(|a, b| a.locator_name.cmp(&b.locator_name));
+        topics.truncate(limit);
+    }
+    topics
+}
+
 /// Pre-fetch all topics needed for chunks to avoid `N+1` queries
 async fn pre_fetch_topics(
     cx: &mut repo::Cx<'_>,
@@ -196,3 +800,41 @@ async fn pre_fetch_topics(
 
     Ok(Arc::new(topic_map))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn cancelled_jobs_release_permits() {
+        let semaphore = Arc::new(Semaphore::new(2));
+
+        // Jobs never completing, like scans stuck on slow reads
+        let mut jobs = FuturesUnordered::new();
+        for _ in 0..4 {
+            jobs.push(with_permit(
+                semaphore.clone(),
+                futures::future::pending::<Result<(), FacadeError>>(),
+            ));
+        }
+
+        // Query timeout, all permits are held by the running jobs
+        let res = tokio::time::timeout(Duration::from_millis(10), jobs.next()).await;
+        assert!(res.is_err());
+        assert_eq!(semaphore.available_permits(), 0);
+
+        // Cancelling the query drops both running and waiting jobs
+        drop(jobs);
+        assert_eq!(semaphore.available_permits(), 2);
+
+        let res = tokio::time::timeout(
+            Duration::from_secs(1),
+            with_permit(semaphore.clone(), async { Ok(1) }),
+        )
+        .await
+        .expect("semaphore not recovered")
+        .unwrap();
+        assert_eq!(res, 1);
+    }
+}
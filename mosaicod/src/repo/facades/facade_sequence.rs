@@ -5,10 +5,11 @@
 //! sequence and provides transactional methods for interacting with both the
 //! database respository and the object store.
 
-use log::trace;
+use log::{trace, warn};
+use std::collections::HashSet;
 
 use crate::{
-    marshal, repo, store,
+    marshal, params, query, repo, store,
     types::{self, Resource},
 };
 
@@ -39,7 +40,7 @@ impl FacadeSequence {
     pub async fn all(
         repo: repo::Repository,
     ) -> Result<Vec<types::SequenceResourceLocator>, FacadeError> {
-        let mut cx = repo.connection();
+        let mut cx = repo.replica_connection();
         let records = repo::sequence_find_all(&mut cx).await?;
 
         Ok(records
@@ -48,6 +49,29 @@ impl FacadeSequence {
             .collect())
     }
 
+    /// Retrieves a page of the sequences matching the provided filter, sorted by name.
+    ///
+    /// The returned page also reports the total number of sequences matching the filter,
+    /// so that callers know when to stop paging.
+    pub async fn list(
+        repo: repo::Repository,
+        filter: Option<query::SequenceFilter>,
+        limit: Option<usize>,
+        offset: usize,
+    ) -> Result<types::Page<types::SequenceResourceLocator>, FacadeError> {
+        let mut cx = repo.replica_connection();
+        let (records, total_count) =
+            repo::sequence_from_query_filter_paginated(&mut cx, filter, limit, offset).await?;
+
+        Ok(types::Page {
+            items: records
+                .into_iter()
+                .map(|record| types::SequenceResourceLocator::from(record.locator_name))
+                .collect(),
+            total_count,
+        })
+    }
+
     /// Creates a new repository entry for this sequence.
     ///
     /// The newly created sequence starts in an **unlocked** state, allowing
@@ -99,8 +123,7 @@ impl FacadeSequence {
     /// Permanently locks the sequence, preventing any new topics from being added.
     ///
     /// Once a sequence is locked, it becomes immutable — no further topics can be
-    /// appended or modified under it. This action is **irreversible**, as there is
-    /// currently no API or mechanism to unlock a sequence.
+    /// appended or modified under it, until it is re-opened with [`FacadeSequence::unlock`].
     ///
     /// A sequence can be locked only if all the associated topics are locked, calling this
     /// function on a sequence with an unlocked topic returns an error.
@@ -131,12 +154,62 @@ impl FacadeSequence {
         Ok(())
     }
 
-    /// Add a notification to the sequence
+    /// Re-opens a locked sequence, unlocking it together with all its topics so that
+    /// corrections can be uploaded.
+    ///
+    /// Since this breaks the immutability of a finalized sequence, the operation is recorded
+    /// in the sequence audit log along with the `author` and the `reason` of the request.
+    ///
+    /// Calling unlock on an unlocked sequence returns a [`FacadeError::SequenceUnlocked`] error.
+    pub async fn unlock(&self, author: String, reason: Option<String>) -> Result<(), FacadeError> {
+        let mut tx = self.repo.transaction().await?;
+
+        let record = repo::sequence_find_by_locator(&mut tx, &self.locator).await?;
+        if !record.is_locked() {
+            return Err(FacadeError::SequenceUnlocked);
+        }
+
+        repo::sequence_unlock(&mut tx, &self.locator).await?;
+
+        let audit = repo::SequenceAudit::new(record.sequence_id, "reopen", author, reason);
+        repo::sequence_audit_create(&mut tx, &audit).await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Returns an error listing the topics of the sequence without any chunk of data, see
+    /// [`FacadeError::EmptyTopics`].
+    pub async fn ensure_topics_have_data(&self) -> Result<(), FacadeError> {
+        let mut cx = self.repo.connection();
+
+        let empty = repo::sequence_find_empty_topic_names(&mut cx, &self.locator).await?;
+        if !empty.is_empty() {
+            return Err(FacadeError::EmptyTopics(
+                empty.into_iter().map(Into::into).collect(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Add a notification to the sequence.
+    ///
+    /// Notifications with a severity lower than `min_severity` (usually
+    /// [`params::ConfigurablesParams::min_notify_severity`]) are discarded, in this case `None`
+    /// is returned.
     pub async fn notify(
         &self,
         ntype: types::NotifyType,
         msg: String,
-    ) -> Result<types::Notify, FacadeError> {
+        min_severity: types::NotifyType,
+    ) -> Result<Option<types::Notify>, FacadeError> {
+        if ntype < min_severity {
+            trace!("discarding `{}` notify for {}", ntype, self.locator);
+            return Ok(None);
+        }
+
         let mut tx = self.repo.transaction().await?;
 
         let record = repo::sequence_find_by_locator(&mut tx, &self.locator).await?;
@@ -145,20 +218,48 @@ impl FacadeSequence {
 
         tx.commit().await?;
 
-        Ok(notify.into_types(self.locator.clone()))
+        Ok(Some(notify.into_types(self.locator.clone())))
     }
 
-    /// Returns a list of all notifications for the this sequence
-    pub async fn notify_list(&self) -> Result<Vec<types::Notify>, FacadeError> {
-        let mut trans = self.repo.transaction().await?;
-        let notifies = repo::sequence_notifies_find_by_name(&mut trans, &self.locator).await?;
-        trans.commit().await?;
+    /// Returns the notifications of this sequence, most recent first.
+    ///
+    /// Only the notifications with a severity of at least `min_severity` are returned (all of
+    /// them if [`None`]), if `limit` is provided at most `limit` notifications are returned.
+    pub async fn notify_list(
+        &self,
+        min_severity: Option<types::NotifyType>,
+        limit: Option<usize>,
+    ) -> Result<Vec<types::Notify>, FacadeError> {
+        let mut cx = self.repo.connection();
+
+        let notify_types: Vec<String> = min_severity
+            .unwrap_or(types::NotifyType::Info)
+            .at_least()
+            .map(|t| t.to_string())
+            .collect();
+        let limit = limit.map(|l| l as i64);
+
+        let notifies =
+            repo::sequence_notifies_find_by_types(&mut cx, &self.locator, &notify_types, limit)
+                .await?;
+
         Ok(notifies
             .into_iter()
             .map(|n| n.into_types(self.locator.clone()))
             .collect())
     }
 
+    /// Deletes the notification `id` of this sequence, the other notifications are kept.
+    ///
+    /// Returns an error if the sequence has no notification with the given id.
+    pub async fn notify_delete(&self, id: i32) -> Result<(), FacadeError> {
+        let mut cx = self.repo.connection();
+
+        repo::sequence_notify_delete_by_locator(&mut cx, &self.locator, id).await?;
+
+        Ok(())
+    }
+
     /// Deletes all the notifications associated with the sequence
     pub async fn notify_purge(&self) -> Result<(), FacadeError> {
         let mut trans = self.repo.transaction().await?;
@@ -199,6 +300,16 @@ impl FacadeSequence {
         Ok(())
     }
 
+    /// Returns the layers this sequence belongs to, sorted by name
+    pub async fn layers(&self) -> Result<Vec<types::Layer>, FacadeError> {
+        let mut cx = self.repo.connection();
+
+        let record = repo::sequence_find_by_locator(&mut cx, &self.locator).await?;
+        let layers = repo::layers_find_by_sequence(&mut cx, record.sequence_id).await?;
+
+        Ok(layers.into_iter().map(Into::into).collect())
+    }
+
     /// Returns the topic list associated with this sequence and returns the list of topic names
     pub async fn topic_list(&self) -> Result<Vec<types::TopicResourceLocator>, FacadeError> {
         let mut cx = self.repo.connection();
@@ -208,6 +319,47 @@ impl FacadeSequence {
         Ok(topics)
     }
 
+    /// Returns a summary (properties and chunk statistics) of the topics of this sequence,
+    /// optionally restricted to the topics matching `filter`.
+    pub async fn topics(
+        &self,
+        filter: Option<query::TopicFilter>,
+    ) -> Result<Vec<types::TopicSummary>, FacadeError> {
+        let mut cx = self.repo.connection();
+
+        // Fails if the sequence does not exist
+        repo::sequence_find_by_locator(&mut cx, &self.locator).await?;
+
+        let sequence_filter = query::SequenceFilter {
+            name: Some(query::Op::Eq(self.locator.name().clone())),
+            creation: None,
+            user_metadata: None,
+        };
+        let mut records = repo::topic_from_query_filter(
+            &mut cx,
+            Some(sequence_filter),
+            Some(filter.unwrap_or_default()),
+            None,
+        )
+        .await?;
+        records.sort_by(|a, b| a.locator_name.cmp(&b.locator_name));
+
+        let ids: Vec<i32> = records.iter().map(|r| r.topic_id).collect();
+        let mut stats = repo::topics_get_stats(&mut cx, &ids).await?;
+
+        // Topics without chunks are not reported by the stats query
+        Ok(records
+            .into_iter()
+            .map(|record| types::TopicSummary {
+                locator: types::TopicResourceLocator::from(record.locator_name.as_str()),
+                serialization_format: record.serialization_format(),
+                ontology_tag: record.ontology_tag.clone(),
+                created_datetime: record.creation_timestamp().into(),
+                stats: stats.remove(&record.topic_id).unwrap_or_default(),
+            })
+            .collect())
+    }
+
     /// Deletes a sequence and all its associated topics from the system.
     ///
     /// Both the sequence and its topics will be removed from the store and the repository.
@@ -243,6 +395,248 @@ impl FacadeSequence {
         Ok(())
     }
 
+    /// Reports the topics and the chunks that [`FacadeSequence::delete`] would remove,
+    /// without modifying anything.
+    pub async fn delete_summary(&self) -> Result<types::SequenceDeleteSummary, FacadeError> {
+        let mut cx = self.repo.connection();
+
+        // Fails if the sequence does not exist
+        repo::sequence_find_by_locator(&mut cx, &self.locator).await?;
+
+        let mut summary = types::SequenceDeleteSummary::default();
+        for topic in repo::sequence_find_all_topic_names(&mut cx, &self.locator).await? {
+            let stats = repo::topic_get_stats(&mut cx, &topic).await?;
+            summary.total_chunks += stats.total_chunks;
+            summary.total_size_bytes += stats.total_size_bytes;
+            summary.topics.push(topic);
+        }
+
+        Ok(summary)
+    }
+
+    /// Compares the chunks of the sequence with the data files in the store, reporting the
+    /// chunks whose data file is missing and the data files not referenced by any chunk.
+    ///
+    /// Inconsistencies may be left by interrupted uploads or deletions. With `repair` the
+    /// chunks with a missing data file are deleted from the repository and the orphan data
+    /// files are deleted from the store.
+    ///
+    /// Only locked sequences can be repaired: the data files written by uploads (or
+    /// compactions) in progress in an unlocked sequence have no chunk until they are
+    /// committed, and would be deleted as orphans.
+    pub async fn reconcile(
+        &self,
+        repair: bool,
+    ) -> Result<types::SequenceReconcileReport, FacadeError> {
+        let mut cx = self.repo.connection();
+
+        let record = repo::sequence_find_by_locator(&mut cx, &self.locator).await?;
+        if repair && !record.is_locked() {
+            return Err(FacadeError::SequenceUnlocked);
+        }
+
+        let chunks = repo::sequence_find_all_chunks(&mut cx, &self.locator).await?;
+        let objects: HashSet<String> = self
+            .store
+            .list(self.locator.name(), None)
+            .await?
+            .into_iter()
+            .collect();
+
+        let referenced: HashSet<String> = chunks
+            .iter()
+            .map(|chunk| store::Store::location(chunk.data_file()))
+            .collect();
+
+        let missing: Vec<&repo::Chunk> = chunks
+            .iter()
+            .filter(|chunk| !objects.contains(&store::Store::location(chunk.data_file())))
+            .collect();
+
+        let mut orphans: Vec<String> = objects
+            .into_iter()
+            .filter(|object| {
+                let ext = std::path::Path::new(object)
+                    .extension()
+                    .and_then(|ext| ext.to_str());
+                matches!(ext, Some(params::ext::PARQUET | params::ext::ARROW))
+                    && !referenced.contains(object)
+            })
+            .collect();
+        orphans.sort();
+
+        trace!(
+            "sequence `{}` has {} chunks with missing data files and {} orphan data files",
+            self.locator,
+            missing.len(),
+            orphans.len()
+        );
+
+        if repair {
+            let mut tx = self.repo.transaction().await?;
+            for chunk in &missing {
+                repo::chunk_delete(&mut tx, chunk.chunk_id).await?;
+            }
+            tx.commit().await?;
+
+            for object in &orphans {
+                self.store.delete(object).await?;
+            }
+        }
+
+        Ok(types::SequenceReconcileReport {
+            missing_data_files: missing
+                .iter()
+                .map(|chunk| chunk.data_file().to_string_lossy().into_owned())
+                .collect(),
+            orphan_data_files: orphans,
+            repaired: repair,
+        })
+    }
+
+    /// Renames an unlocked sequence, moving all its topics under the new name.
+    ///
+    /// The new name can't be empty, contain `/` or be equal to the current name, see
+    /// [`FacadeError::InvalidName`].
+    ///
+    /// Store objects are first copied under the new name, then the repository is updated
+    /// in a single transaction. Only after the transaction is committed the objects under
+    /// the old name are deleted, so that the repository never points to missing objects:
+    /// if something fails before the commit the copies are discarded and the
+    /// transaction is rolled back.
+    pub async fn rename(&self, new: types::SequenceResourceLocator) -> Result<(), FacadeError> {
+        let invalid = |reason: &str| FacadeError::InvalidName {
+            name: new.name().clone(),
+            reason: reason.to_owned(),
+        };
+        if new.name().is_empty() {
+            return Err(invalid("the name is empty"));
+        }
+        if new.name().contains('/') {
+            return Err(invalid("sequence names can't contain `/`"));
+        }
+        if new.name() == self.locator.name() {
+            return Err(invalid("the sequence already has this name"));
+        }
+
+        let mut tx = self.repo.transaction().await?;
+
+        let record = repo::sequence_find_by_locator(&mut tx, &self.locator).await?;
+        if record.is_locked() {
+            return Err(FacadeError::SequenceLocked);
+        }
+
+        if repo::sequence_find_by_locator(&mut tx, &new).await.is_ok() {
+            return Err(FacadeError::AlreadyExists(new.name().clone()));
+        }
+
+        if let Err(e) = self.copy_objects_to(&new).await {
+            self.discard_objects(&new).await;
+            return Err(e);
+        }
+
+        if let Err(e) = repo::sequence_rename(&mut tx, &self.locator, &new).await {
+            self.discard_objects(&new).await;
+            return Err(e.into());
+        }
+
+        if let Err(e) = tx.commit().await {
+            self.discard_objects(&new).await;
+            return Err(e.into());
+        }
+
+        // The repository is now pointing to the new objects, a failure here only leaves
+        // some unreferenced objects in the store.
+        if let Err(e) = self.store.delete_recursive(self.locator.name()).await {
+            warn!(
+                "unable to delete objects of renamed sequence `{}`: {}",
+                self.locator, e
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Clones a locked sequence as `new`, along with its topics and their data.
+    ///
+    /// Store objects are copied under the new name (copies are performed by the store
+    /// backend, data is not transferred through the server), then the repository records,
+    /// chunks and chunk statistics are cloned in a single transaction pointing to the copied
+    /// objects. The cloned sequence starts **unlocked**, so that new topics can be added.
+    ///
+    /// As for [`FacadeSequence::rename`], copied objects are discarded if the clone fails.
+    ///
+    /// Only deep copies are supported, copy-on-write clones are not: reads list the data
+    /// files under the topic path rather than following the chunk records, so a cloned topic
+    /// without its own data files would read as empty. Shared data files would also need to
+    /// be reference counted, since deleting or renaming the source sequence removes them.
+    pub async fn clone_as(
+        &self,
+        new: types::SequenceResourceLocator,
+    ) -> Result<types::ResourceId, FacadeError> {
+        let mut tx = self.repo.transaction().await?;
+
+        let record = repo::sequence_find_by_locator(&mut tx, &self.locator).await?;
+        if !record.is_locked() {
+            return Err(FacadeError::SequenceUnlocked);
+        }
+
+        if repo::sequence_find_by_locator(&mut tx, &new).await.is_ok() {
+            return Err(FacadeError::AlreadyExists(new.name().clone()));
+        }
+
+        if let Err(e) = self.copy_objects_to(&new).await {
+            self.discard_objects(&new).await;
+            return Err(e);
+        }
+
+        let cloned = async {
+            repo::sequence_clone(&mut tx, &self.locator, &new).await?;
+            repo::sequence_find_by_locator(&mut tx, &new).await
+        }
+        .await;
+
+        let cloned = match cloned {
+            Ok(cloned) => cloned,
+            Err(e) => {
+                self.discard_objects(&new).await;
+                return Err(e.into());
+            }
+        };
+
+        if let Err(e) = tx.commit().await {
+            self.discard_objects(&new).await;
+            return Err(e.into());
+        }
+
+        Ok(cloned.into())
+    }
+
+    /// Copies all the store objects of this sequence under the `new` sequence name
+    async fn copy_objects_to(
+        &self,
+        new: &types::SequenceResourceLocator,
+    ) -> Result<(), FacadeError> {
+        let prefix = self.locator.name();
+        let objects = self.store.list(prefix, None).await?;
+
+        for object in objects {
+            // Objects are listed under the sequence prefix
+            let suffix = object.strip_prefix(prefix.as_str()).unwrap_or(&object);
+            let target = format!("{}{}", new.name(), suffix);
+            self.store.copy(&object, &target).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Best effort removal of the objects copied under `new` during a failed rename or clone
+    async fn discard_objects(&self, new: &types::SequenceResourceLocator) {
+        if let Err(e) = self.store.delete_recursive(new.name()).await {
+            warn!("unable to discard objects copied to `{}`: {}", new, e);
+        }
+    }
+
     /// Computes system info for the sequence
     pub async fn system_info(&self) -> Result<types::SequenceSystemInfo, FacadeError> {
         let mut cx = self.repo.connection();
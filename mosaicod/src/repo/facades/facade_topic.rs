@@ -1,12 +1,16 @@
-use super::FacadeError;
+use super::{FacadeError, FacadeOntologyContract};
 use crate::rw;
-use crate::traits::AsExtension;
+use crate::traits::{AsExtension, SquashedIterator};
 use crate::{
-    marshal, repo, store,
+    marshal, params, query, repo, store,
     types::{self, Resource},
 };
-use arrow::datatypes::SchemaRef;
-use log::trace;
+use arrow::array::{RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use futures::{StreamExt, TryStreamExt};
+use log::{trace, warn};
+use std::collections::{BTreeSet, HashSet};
+use std::sync::{Arc, Mutex};
 
 /// Define topic metadata type contaning JSON user metadata
 type TopicMetadata = types::TopicMetadata<marshal::JsonMetadataBlob>;
@@ -35,6 +39,7 @@ impl FacadeTopic {
     ///
     /// If a record with the same name already exists, the operation fails and
     /// the repository transaction is rolled back, restoring the previous state.
+    /// The user metadata needs to satisfy the contract of the ontology tag, if any.
     pub async fn create(
         &self,
         sequence: &uuid::Uuid,
@@ -56,13 +61,23 @@ impl FacadeTopic {
             return Err(FacadeError::Unauthorized);
         }
 
+        if let Some(metadata) = &metadata
+            && let Some(ontology_tag) = &metadata.properties.ontology_tag
+        {
+            FacadeOntologyContract::new(ontology_tag.clone(), self.repo.clone())
+                .check_metadata(&metadata.user_metadata)
+                .await?;
+        }
+
         let mut record = repo::TopicRecord::new(self.locator.name(), srecord.sequence_id);
 
         if let Some(metadata) = &metadata {
             record = record
                 .with_user_metadata(metadata.user_metadata.clone())
-                .with_ontology_tag(&metadata.properties.ontology_tag)
                 .with_serialization_format(&metadata.properties.serialization_format.to_string());
+            if let Some(ontology_tag) = &metadata.properties.ontology_tag {
+                record = record.with_ontology_tag(ontology_tag);
+            }
         }
 
         let record = repo::topic_create(&mut tx, &record).await?;
@@ -78,6 +93,78 @@ impl FacadeTopic {
         Ok(record.into())
     }
 
+    /// Creates many topics under the same sequence in a single repository transaction.
+    ///
+    /// Each topic needs to be a child of the sequence. If the creation of any topic fails
+    /// none of the topics is created, and the returned [`FacadeError::TopicError`] reports
+    /// the offending topic.
+    pub async fn create_bulk(
+        sequence: &uuid::Uuid,
+        topics: Vec<(String, TopicMetadata)>,
+        store: store::StoreRef,
+        repo: repo::Repository,
+    ) -> Result<Vec<(FacadeTopic, types::ResourceId)>, FacadeError> {
+        let mut tx = repo.transaction().await?;
+
+        let srecord = repo::sequence_find_by_uuid(&mut tx, sequence).await?;
+        if srecord.is_locked() {
+            return Err(FacadeError::SequenceLocked);
+        }
+
+        let sloc = types::SequenceResourceLocator::from(&srecord.locator_name);
+
+        let mut created = Vec::with_capacity(topics.len());
+        let mut metadatas = Vec::with_capacity(topics.len());
+        for (name, metadata) in topics {
+            let handle = FacadeTopic::new(name, store.clone(), repo.clone());
+
+            if !handle.locator.is_sub_resource(&sloc) {
+                return Err(FacadeError::Unauthorized.for_topic(handle.path()));
+            }
+
+            let mut record = repo::TopicRecord::new(handle.locator.name(), srecord.sequence_id)
+                .with_user_metadata(metadata.user_metadata.clone())
+                .with_serialization_format(&metadata.properties.serialization_format.to_string());
+
+            if let Some(ontology_tag) = &metadata.properties.ontology_tag {
+                FacadeOntologyContract::new(ontology_tag.clone(), repo.clone())
+                    .check_metadata(&metadata.user_metadata)
+                    .await
+                    .map_err(|e| e.for_topic(handle.path()))?;
+
+                record = record.with_ontology_tag(ontology_tag);
+            }
+
+            let record = repo::topic_create(&mut tx, &record)
+                .await
+                .map_err(|e| FacadeError::from(e).for_topic(handle.path()))?;
+
+            created.push((handle, record.into()));
+            metadatas.push(metadata);
+        }
+
+        // Metadata files are written once all the records have been created, the ones
+        // already written are removed if a write fails and the transaction is rolled back
+        for (idx, metadata) in metadatas.into_iter().enumerate() {
+            let handle = &created[idx].0;
+            if let Err(e) = handle.metadata_write_to_store(metadata).await {
+                for (written, _) in &created[..idx] {
+                    if let Err(e) = written.store.delete_recursive(&written.path()).await {
+                        warn!(
+                            "unable to remove metadata of topic {}: {}",
+                            written.locator, e
+                        );
+                    }
+                }
+                return Err(e.for_topic(handle.path()));
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(created)
+    }
+
     pub async fn is_locked(&self) -> Result<bool, FacadeError> {
         let mut cx = self.repo.connection();
 
@@ -112,12 +199,14 @@ impl FacadeTopic {
             metadata.user_metadata.clone(),
         )
         .await?;
-        repo::topic_update_ontology_tag(
-            &mut tx, //
-            &self.locator,
-            &metadata.properties.ontology_tag,
-        )
-        .await?;
+        if let Some(ontology_tag) = &metadata.properties.ontology_tag {
+            repo::topic_update_ontology_tag(
+                &mut tx, //
+                &self.locator,
+                ontology_tag,
+            )
+            .await?;
+        }
         // Save the last record for returning it
         let _ = repo::topic_update_serialization_format(
             &mut tx,
@@ -133,6 +222,207 @@ impl FacadeTopic {
         Ok(())
     }
 
+    /// Sets the ontology tag of a topic created without one, the user metadata of the topic
+    /// needs to satisfy the contract of the tag.
+    ///
+    /// Returns the tag of the topic, which is the one set by a concurrent write if the
+    /// topic has been tagged in the meantime.
+    pub async fn set_ontology_tag(&self, ontology_tag: &str) -> Result<String, FacadeError> {
+        let mut tx = self.repo.transaction().await?;
+
+        let record = repo::topic_find_by_locator_for_update(&mut tx, &self.locator).await?;
+        if record.is_locked() {
+            return Err(FacadeError::TopicLocked);
+        }
+        if let Some(existing) = record.ontology_tag {
+            return Ok(existing);
+        }
+
+        let mut metadata = self.metadata().await?;
+
+        FacadeOntologyContract::new(ontology_tag.to_owned(), self.repo.clone())
+            .check_metadata(&metadata.user_metadata)
+            .await?;
+
+        repo::topic_update_ontology_tag(&mut tx, &self.locator, ontology_tag).await?;
+
+        tx.commit().await?;
+
+        metadata.properties.ontology_tag = Some(ontology_tag.to_owned());
+        self.metadata_write_to_store(metadata).await?;
+
+        Ok(ontology_tag.to_owned())
+    }
+
+    /// Checks that data can be written to this topic, i.e. that neither the topic
+    /// nor its parent sequence are locked.
+    pub async fn ensure_writable(&self) -> Result<(), FacadeError> {
+        let mut cx = self.repo.connection();
+
+        let record = repo::topic_find_by_locator(&mut cx, &self.locator).await?;
+        if record.is_locked() {
+            return Err(FacadeError::TopicLocked);
+        }
+
+        let sequence = repo::sequence_find_by_id(&mut cx, record.sequence_id).await?;
+        if sequence.is_locked() {
+            return Err(FacadeError::SequenceLocked);
+        }
+
+        Ok(())
+    }
+
+    /// Checks that `schema` is compatible with the data already stored for `ontology_tag`.
+    ///
+    /// The (flattened) fields registered by the uploads of an ontology tag define the fields
+    /// of that tag: a schema can omit some of them, and can add new fields only if they are
+    /// nullable (as done by [`crate::arrow::schema_evolution_violation`]), since the data
+    /// already stored for the tag has no values for them. Any schema is accepted for a new
+    /// ontology tag.
+    pub async fn check_ontology_schema(
+        &self,
+        ontology_tag: &str,
+        schema: &SchemaRef,
+    ) -> Result<(), FacadeError> {
+        let mut cx = self.repo.connection();
+
+        let known: HashSet<String> = repo::column_find_names_by_ontology_tag(&mut cx, ontology_tag)
+            .await?
+            .into_iter()
+            .collect();
+        if known.is_empty() {
+            return Ok(());
+        }
+
+        let unknown: Vec<String> = schema
+            .squashed_iter()
+            .filter(|(name, field)| !known.contains(name) && !field.is_nullable())
+            .map(|(name, _)| name)
+            .collect();
+
+        if !unknown.is_empty() {
+            return Err(FacadeError::UnknownOntologyFields {
+                ontology_tag: ontology_tag.to_owned(),
+                fields: unknown,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks that data with `schema` can be appended to the chunks already stored in the
+    /// topic, so that all the chunks can be read together.
+    ///
+    /// The schema of the first chunk of the topic is the established schema, see
+    /// [`crate::arrow::schema_evolution_violation`] for the compatibility rules. Any schema is
+    /// accepted for a topic without chunks.
+    pub async fn check_schema_evolution(
+        &self,
+        format: rw::Format,
+        schema: &SchemaRef,
+    ) -> Result<(), FacadeError> {
+        let mut cx = self.repo.connection();
+        let first = repo::topic_find_all_chunks(&mut cx, &self.locator)
+            .await?
+            .into_iter()
+            .min_by_key(|chunk| chunk.chunk_id);
+
+        let Some(first) = first else {
+            return Ok(());
+        };
+
+        let established = self.data_file_schema(first.data_file(), format).await?;
+
+        if let Some((field, reason)) =
+            crate::arrow::schema_evolution_violation(&established, schema)
+        {
+            return Err(FacadeError::IncompatibleField { field, reason });
+        }
+
+        Ok(())
+    }
+
+    /// Returns the data contract of `ontology_tag`, checking that `schema` satisfies it.
+    ///
+    /// The returned contract is used to check the uploaded batches (see
+    /// [`crate::arrow::contract_null_violations`] and
+    /// [`crate::arrow::contract_range_violations`]), [`None`] is returned if the tag has no
+    /// contract.
+    pub async fn check_ontology_contract(
+        &self,
+        ontology_tag: &str,
+        schema: &SchemaRef,
+    ) -> Result<Option<types::OntologyContract>, FacadeError> {
+        let contract = FacadeOntologyContract::new(ontology_tag.to_owned(), self.repo.clone())
+            .get()
+            .await?;
+
+        if let Some(contract) = &contract {
+            let violations = crate::arrow::contract_violations(schema, &contract.data);
+            if !violations.is_empty() {
+                return Err(FacadeError::ContractViolation {
+                    ontology_tag: ontology_tag.to_owned(),
+                    violations,
+                });
+            }
+        }
+
+        Ok(contract)
+    }
+
+    /// Updates the user metadata of this topic, both in the repository and in the store.
+    ///
+    /// If `merge` is `true` the provided metadata is shallow-merged with the existing one,
+    /// otherwise the existing metadata is replaced. The operation fails if the topic
+    /// (or its parent sequence) is locked.
+    ///
+    /// The topic record is locked while updating it, so concurrent updates are merged with
+    /// each other. The store is written only once the repository update is committed.
+    pub async fn update_metadata(
+        &self,
+        user_metadata: marshal::JsonMetadataBlob,
+        merge: bool,
+    ) -> Result<(), FacadeError> {
+        let mut tx = self.repo.transaction().await?;
+
+        let record = repo::topic_find_by_locator_for_update(&mut tx, &self.locator).await?;
+        if record.is_locked() {
+            return Err(FacadeError::TopicLocked);
+        }
+
+        let sequence = repo::sequence_find_by_id(&mut tx, record.sequence_id).await?;
+        if sequence.is_locked() {
+            return Err(FacadeError::SequenceLocked);
+        }
+
+        let mut metadata = self.metadata().await?;
+        metadata.user_metadata = if merge {
+            // The metadata of the store may not include a concurrent update yet
+            record
+                .user_metadata()
+                .unwrap_or(metadata.user_metadata)
+                .merge(user_metadata)
+        } else {
+            user_metadata
+        };
+
+        // Updated metadata is bound to the same contract checked at creation
+        if let Some(ontology_tag) = &metadata.properties.ontology_tag {
+            FacadeOntologyContract::new(ontology_tag.clone(), self.repo.clone())
+                .check_metadata(&metadata.user_metadata)
+                .await?;
+        }
+
+        repo::topic_update_user_metadata(&mut tx, &self.locator, metadata.user_metadata.clone())
+            .await?;
+
+        tx.commit().await?;
+
+        self.metadata_write_to_store(metadata).await?;
+
+        Ok(())
+    }
+
     /// Read the repository record for this sequence. If no record is found an error is returned.
     pub async fn resource_id(&self) -> Result<types::ResourceId, FacadeError> {
         let mut cx = self.repo.connection();
@@ -171,14 +461,41 @@ impl FacadeTopic {
     /// Returns the topic arrow schema.
     /// The serialization format is required to extract the schema, can be retrieved using [`TopicHandle::metadata`] function.
     pub async fn arrow_schema(&self, format: rw::Format) -> Result<SchemaRef, FacadeError> {
-        // Get chunk 0 since this chunk needs to exist always
-        let path = self.locator.datafile(0, &format);
+        // Get the first chunk, once compacted the data files of a topic are no longer
+        // numbered from 0
+        let mut cx = self.repo.connection();
+        let path = repo::topic_find_all_chunks(&mut cx, &self.locator)
+            .await?
+            .first()
+            .map(|chunk| chunk.data_file().to_path_buf())
+            .unwrap_or_else(|| self.locator.datafile(0, &format));
 
-        // Build a chunk reader reading in memory a file
-        // (cabba) TODO: avoid reading the whole file, get from store only the header
-        let buffer = self.store.read_bytes(path).await?;
-        let reader = rw::ChunkReader::new(format, bytes::Bytes::from_owner(buffer))?;
-        Ok(reader.schema())
+        self.data_file_schema(path, format).await
+    }
+
+    /// Returns the schema of the data file `path`, reading only the footer of the file.
+    async fn data_file_schema(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        format: rw::Format,
+    ) -> Result<SchemaRef, FacadeError> {
+        let path = path.as_ref();
+        let size = self.store.size(path).await?;
+
+        let tail_len = rw::chunk_reader::footer_tail_len(format);
+        let tail = self
+            .store
+            .read_range(path, size.saturating_sub(tail_len)..size)
+            .await?;
+        let footer_len = rw::chunk_reader::footer_len(format, &tail)?;
+
+        let footer_end = size - tail_len;
+        let footer = self
+            .store
+            .read_range(path, footer_end.saturating_sub(footer_len)..footer_end)
+            .await?;
+
+        Ok(rw::chunk_reader::schema_from_footer(format, &footer)?)
     }
 
     /// Serializes and writes [`TopicMetadata`] to the object store.
@@ -207,6 +524,20 @@ impl FacadeTopic {
         )
     }
 
+    /// Returns a writer encoding data exactly like [`FacadeTopic::writer`], but the produced
+    /// chunks are discarded instead of being written to the store.
+    pub fn dry_run_writer(
+        &self,
+        format: rw::Format,
+    ) -> rw::ChunkedWriter<'static, rw::DiscardTarget> {
+        rw::ChunkedWriter::new(
+            &rw::DiscardTarget,
+            self.path(),
+            format,
+            |path, format, idx| types::TopicResourceLocator::from(path).datafile(idx, format),
+        )
+    }
+
     pub async fn delete(self) -> Result<(), FacadeError> {
         let mut tx = self.repo.transaction().await?;
 
@@ -240,12 +571,22 @@ impl FacadeTopic {
         Ok(())
     }
 
-    /// Add a notification to the sequence
+    /// Add a notification to the topic.
+    ///
+    /// Notifications with a severity lower than `min_severity` (usually
+    /// [`params::ConfigurablesParams::min_notify_severity`]) are discarded, in this case `None`
+    /// is returned.
     pub async fn notify(
         &self,
         ntype: types::NotifyType,
         msg: String,
-    ) -> Result<types::Notify, FacadeError> {
+        min_severity: types::NotifyType,
+    ) -> Result<Option<types::Notify>, FacadeError> {
+        if ntype < min_severity {
+            trace!("discarding `{}` notify for {}", ntype, self.locator);
+            return Ok(None);
+        }
+
         let mut tx = self.repo.transaction().await?;
 
         let record = repo::topic_find_by_locator(&mut tx, &self.locator).await?;
@@ -254,19 +595,48 @@ impl FacadeTopic {
 
         tx.commit().await?;
 
-        Ok(notify.into_types(self.locator.clone()))
+        Ok(Some(notify.into_types(self.locator.clone())))
     }
 
-    /// Returns a list of all notifications for the this topic
-    pub async fn notify_list(&self) -> Result<Vec<types::Notify>, FacadeError> {
+    /// Returns the notifications of this topic, most recent first.
+    ///
+    /// Only the notifications with a severity of at least `min_severity` are returned (all of
+    /// them if [`None`]), if `limit` is provided at most `limit` notifications are returned.
+    pub async fn notify_list(
+        &self,
+        min_severity: Option<types::NotifyType>,
+        limit: Option<usize>,
+    ) -> Result<Vec<types::Notify>, FacadeError> {
         let mut cx = self.repo.connection();
-        let notifies = repo::topic_notifies_find_by_locator(&mut cx, &self.locator).await?;
+
+        let notify_types: Vec<String> = min_severity
+            .unwrap_or(types::NotifyType::Info)
+            .at_least()
+            .map(|t| t.to_string())
+            .collect();
+        let limit = limit.map(|l| l as i64);
+
+        let notifies =
+            repo::topic_notifies_find_by_types(&mut cx, &self.locator, &notify_types, limit)
+                .await?;
+
         Ok(notifies
             .into_iter()
-            .map(|e| e.into_types(self.locator.clone()))
+            .map(|n| n.into_types(self.locator.clone()))
             .collect())
     }
 
+    /// Deletes the notification `id` of this topic, the other notifications are kept.
+    ///
+    /// Returns an error if the topic has no notification with the given id.
+    pub async fn notify_delete(&self, id: i32) -> Result<(), FacadeError> {
+        let mut cx = self.repo.connection();
+
+        repo::topic_notify_delete_by_locator(&mut cx, &self.locator, id).await?;
+
+        Ok(())
+    }
+
     /// Deletes all the notifications associated with the sequence
     pub async fn notify_purge(&self) -> Result<(), FacadeError> {
         let mut tx = self.repo.transaction().await?;
@@ -288,6 +658,356 @@ impl FacadeTopic {
         Ok(stats)
     }
 
+    /// Returns the chunks having NaN or null values for `field`, ordered by timestamp.
+    ///
+    /// Only the chunk statistics are read, no data file is scanned.
+    pub async fn field_quality_chunks(
+        &self,
+        field: &str,
+    ) -> Result<Vec<types::ChunkQuality>, FacadeError> {
+        let mut cx = self.repo.connection();
+        // Fails if the topic does not exist
+        repo::topic_find_by_locator(&mut cx, &self.locator).await?;
+        let chunks = repo::topic_field_quality_chunks(&mut cx, &self.locator, field).await?;
+        Ok(chunks)
+    }
+
+    /// Returns the value ranges of the numeric and literal fields of the topic, aggregated
+    /// across all of its chunks.
+    ///
+    /// Only the chunk statistics are read, no data file is scanned.
+    pub async fn fields_stats(&self) -> Result<types::TopicFieldsStats, FacadeError> {
+        let mut cx = self.repo.connection();
+        // Fails if the topic does not exist
+        repo::topic_find_by_locator(&mut cx, &self.locator).await?;
+        let stats = repo::topic_fields_stats(&mut cx, &self.locator).await?;
+        Ok(stats)
+    }
+
+    /// Recomputes the column statistics of all the chunks of the topic from their data files,
+    /// returning the number of chunks processed.
+    ///
+    /// Each chunk is processed in its own transaction: if a chunk fails the previous ones
+    /// keep the recomputed statistics, while the failed one keeps the old statistics.
+    pub async fn restat(&self) -> Result<usize, FacadeError> {
+        let timestamp_unit = self.metadata().await?.properties.timestamp_unit;
+
+        let mut cx = self.repo.connection();
+        let record = repo::topic_find_by_locator(&mut cx, &self.locator).await?;
+
+        let format = record
+            .serialization_format()
+            .ok_or_else(|| FacadeError::MissingSerializationFormat(self.locator.to_string()))?;
+        let ontology_tag = record
+            .ontology_tag
+            .ok_or_else(|| FacadeError::MissingMetadataField("ontology_tag".to_owned()))?;
+
+        let chunks = repo::topic_find_all_chunks(&mut cx, &self.locator).await?;
+        let count = chunks.len();
+        for chunk in chunks {
+            trace!("recomputing statistics of chunk `{}`", chunk.chunk_uuid);
+            super::FacadeChunk::restat(
+                chunk,
+                format,
+                timestamp_unit,
+                &ontology_tag,
+                &self.store,
+                &self.repo,
+            )
+            .await?;
+        }
+
+        Ok(count)
+    }
+
+    /// Returns the index of the next data file of the topic, following the ones of the
+    /// existing chunks (see [`rw::ChunkedWriter::with_first_chunk_index`]).
+    ///
+    /// Writes to a topic already holding some chunks (e.g. after a sequence is re-opened)
+    /// must not overwrite the existing data files.
+    pub async fn next_chunk_index(&self) -> Result<usize, FacadeError> {
+        let mut cx = self.repo.connection();
+        let chunks = repo::topic_find_all_chunks(&mut cx, &self.locator).await?;
+
+        Ok(next_chunk_index(&chunks))
+    }
+
+    /// Deletes the data files written by a failed upload, which have no chunk record.
+    ///
+    /// Reads list the data files of the topic, so the data of a failed upload must not be
+    /// left in the topic directory. Files that can't be deleted are reported as orphans by
+    /// the sequence reconciliation.
+    pub async fn discard_data_files(&self, data_files: &[std::path::PathBuf]) {
+        for data_file in data_files {
+            if let Err(e) = self.store.delete(data_file).await {
+                warn!(
+                    "unable to delete the data file `{}` of a failed upload :: {}",
+                    data_file.to_string_lossy(),
+                    e
+                );
+            }
+        }
+    }
+
+    /// Returns `true` if the topic holds at least
+    /// [`params::ConfigurablesParams::compaction_min_chunks`] chunks smaller than the
+    /// compaction target size, i.e. if it is worth to [`FacadeTopic::compact`] it.
+    pub async fn needs_compaction(&self) -> Result<bool, FacadeError> {
+        let params = params::configurables();
+        if params.compaction_min_chunks == 0 {
+            return Ok(false);
+        }
+
+        let mut cx = self.repo.connection();
+        let small_chunks = repo::topic_find_all_chunks(&mut cx, &self.locator)
+            .await?
+            .iter()
+            .filter(|chunk| chunk.size_bytes < params.compaction_target_chunk_bytes as i64)
+            .count();
+
+        Ok(small_chunks >= params.compaction_min_chunks)
+    }
+
+    /// Merges the small chunks of the topic into larger ones, of about
+    /// [`params::ConfigurablesParams::compaction_target_chunk_bytes`] bytes, reducing the
+    /// number of data files scanned by reads.
+    ///
+    /// Only unlocked topics can be compacted: reads of locked topics may run concurrently and
+    /// list the data files of the topic, so the data of locked topics is never rewritten.
+    /// Each group of merged chunks is replaced in a single transaction (see
+    /// [`super::FacadeChunk::replace`]) and the statistics of the new chunks are computed while
+    /// writing them, the data files of the merged chunks are deleted afterwards.
+    ///
+    /// New chunks are written to data files with a name unique to this compaction
+    /// (`compact-{uuid}-{index}`), so they never collide with the `data-{index}` files of
+    /// uploads running concurrently.
+    pub async fn compact(&self) -> Result<types::TopicCompaction, FacadeError> {
+        let mut cx = self.repo.connection();
+        let record = repo::topic_find_by_locator(&mut cx, &self.locator).await?;
+        if record.is_locked() {
+            return Err(FacadeError::TopicLocked);
+        }
+
+        let mdata = self.metadata().await?;
+        let properties = mdata.properties;
+
+        let chunks = repo::topic_find_all_chunks(&mut cx, &self.locator).await?;
+
+        // Topics are tagged by their first write, untagged topics have no chunks
+        let Some(ontology_tag) = properties.ontology_tag.clone() else {
+            return Ok(types::TopicCompaction::default());
+        };
+
+        let run = uuid::Uuid::new_v4();
+        let mut next_index = 0;
+        let groups = super::facade_chunk::compaction_groups(
+            chunks,
+            params::configurables().compaction_target_chunk_bytes,
+        );
+
+        let mut report = types::TopicCompaction::default();
+        for group in groups {
+            trace!("merging #{} chunks of `{}`", group.len(), self.locator);
+
+            let new_chunks = self
+                .merge_chunks(&group, &properties, run, next_index)
+                .await?;
+            next_index += new_chunks.len();
+            let created = new_chunks.len();
+
+            super::FacadeChunk::replace(
+                record.sequence_id,
+                record.topic_id,
+                &ontology_tag,
+                &group,
+                new_chunks,
+                &self.repo,
+            )
+            .await?;
+
+            // Data files left by a failure are reported as orphans by the sequence
+            // reconciliation
+            for chunk in &group {
+                if let Err(e) = self.store.delete(chunk.data_file()).await {
+                    warn!(
+                        "unable to delete the data file `{}` of a merged chunk :: {}",
+                        chunk.data_file().to_string_lossy(),
+                        e
+                    );
+                }
+            }
+
+            report.merged_chunks += group.len();
+            report.created_chunks += created;
+        }
+
+        Ok(report)
+    }
+
+    /// Writes the data of `chunks`, in order, to new data files of the compaction `run`
+    /// numbered from `first_index`, and returns the records of the new chunks.
+    ///
+    /// Chunks with a different schema (allowed by the schema evolution) are never merged
+    /// together, a new chunk is started whenever the schema changes.
+    ///
+    /// Data files are decoded with [`rw::ChunkReader`], so that the new chunks keep the column
+    /// types of the merged ones.
+    async fn merge_chunks(
+        &self,
+        chunks: &[repo::Chunk],
+        properties: &types::TopicProperties,
+        run: uuid::Uuid,
+        first_index: usize,
+    ) -> Result<Vec<super::NewChunk>, FacadeError> {
+        let new_chunks = Arc::new(Mutex::new(Vec::new()));
+        let timestamp_unit = properties.timestamp_unit;
+
+        let new_writer = |first_index: usize| {
+            let new_chunks = new_chunks.clone();
+            rw::ChunkedWriter::new(
+                self.store.as_ref(),
+                self.path(),
+                properties.serialization_format,
+                move |path, format, idx| {
+                    let mut path = path.join(format!("compact-{}-{:05}", run.simple(), idx));
+                    path.set_extension(format.as_extension());
+                    path
+                },
+            )
+            .with_first_chunk_index(first_index)
+            .with_max_chunk_bytes(params::configurables().max_chunk_bytes)
+            .with_max_row_group_size(properties.row_group_size_limit())
+            .on_chunk_created(move |path, stats, metadata| {
+                new_chunks.lock().unwrap().push(super::NewChunk::new(
+                    timestamp_unit,
+                    path,
+                    stats,
+                    metadata,
+                ));
+                async { Ok(()) }
+            })
+        };
+
+        let mut writer = new_writer(first_index);
+        let mut schema: Option<SchemaRef> = None;
+
+        for chunk in chunks {
+            let buffer = self.store.read_bytes(chunk.data_file()).await?;
+            let reader = rw::ChunkReader::new(
+                properties.serialization_format,
+                bytes::Bytes::from_owner(buffer),
+            )?;
+
+            if schema.as_ref().is_some_and(|s| *s != reader.schema()) {
+                writer.finalize().await?;
+                writer = new_writer(first_index + new_chunks.lock().unwrap().len());
+            }
+            schema = Some(reader.schema());
+
+            for batch in reader {
+                writer.write(&batch?).await?;
+            }
+        }
+        writer.finalize().await?;
+
+        Ok(std::mem::take(&mut *new_chunks.lock().unwrap()))
+    }
+
+    /// Returns at most `limit` distinct values of `field` (see
+    /// [`query::TimeseriesGwResult::distinct`]).
+    ///
+    /// If every chunk holds a single value of a literal field, the values are taken from the
+    /// chunk statistics without scanning the data files.
+    pub async fn distinct_values(
+        &self,
+        field: &str,
+        limit: usize,
+        ts_gw: &query::TimeseriesGwRef,
+    ) -> Result<query::DistinctValues, FacadeError> {
+        let mut cx = self.repo.connection();
+        let record = repo::topic_find_by_locator(&mut cx, &self.locator).await?;
+        let format = record
+            .serialization_format()
+            .ok_or_else(|| FacadeError::MissingSerializationFormat(self.locator.to_string()))?;
+
+        // Empty topics have no data files to read
+        let total_chunks = repo::topic_get_stats(&mut cx, &self.locator)
+            .await?
+            .total_chunks;
+        if total_chunks == 0 {
+            return Ok(query::DistinctValues::default());
+        }
+
+        let stats = repo::topic_literal_field_chunk_stats(&mut cx, &self.locator, field).await?;
+        // Empty strings are also used as bounds of chunks without values, so they are ambiguous
+        let single_valued = stats.len() as i64 == total_chunks
+            && stats
+                .iter()
+                .all(|s| s.min == s.max && s.min.as_deref().is_some_and(|v| !v.is_empty()));
+
+        if single_valued {
+            trace!("distinct values of `{}` resolved from statistics", field);
+            let has_null = stats.iter().any(|s| s.has_null);
+            let values: BTreeSet<String> = stats.into_iter().filter_map(|s| s.min).collect();
+
+            let mut values: Vec<Option<String>> = values.into_iter().map(Some).collect();
+            if has_null {
+                values.push(None);
+            }
+            let truncated = values.len() > limit;
+            values.truncate(limit);
+
+            let schema = Arc::new(Schema::new(vec![Field::new(
+                query::DISTINCT_VALUE_COLUMN,
+                DataType::Utf8,
+                true,
+            )]));
+            let batch = RecordBatch::try_new(schema, vec![Arc::new(StringArray::from(values))])
+                .map_err(rw::Error::from)?;
+
+            return Ok(query::DistinctValues {
+                batches: vec![batch],
+                truncated,
+            });
+        }
+
+        Ok(ts_gw
+            .read(self.path(), format, None, None, None, query::SortOrder::Asc)
+            .await?
+            .distinct(field, limit)
+            .await?)
+    }
+
+    /// Verifies the data files of the chunks of the topic overlapping `time_range` (all the
+    /// chunks if [`None`]) against the checksums computed when they were written, see
+    /// [`FacadeError::CorruptChunk`]. Chunks without timestamp bounds are always verified.
+    ///
+    /// Data files are downloaded for the check, at most
+    /// [`params::ConfigurablesParams::max_concurrent_chunk_queries`] at once, and downloaded
+    /// again by the read following it: reads fetch byte ranges of the data files, so the
+    /// checksum of the whole file can't be verified by the read itself.
+    pub async fn verify_checksums(
+        &self,
+        time_range: Option<&query::Range<query::Timestamp>>,
+    ) -> Result<(), FacadeError> {
+        let mut cx = self.repo.connection();
+        let chunks = repo::topic_find_all_chunks(&mut cx, &self.locator).await?;
+
+        let selected = chunks
+            .iter()
+            .filter(|chunk| match (time_range, chunk.timestamp_bounds()) {
+                (Some(range), Some((min, max))) => min <= range.max && max >= range.min,
+                _ => true,
+            });
+
+        futures::stream::iter(
+            selected.map(|chunk| super::facade_chunk::verify_checksum(&self.store, chunk)),
+        )
+        .buffer_unordered(params::configurables().max_concurrent_chunk_queries.max(1))
+        .try_for_each(|()| async { Ok(()) })
+        .await
+    }
+
     /// Computes system info for the topic
     pub async fn system_info(&self) -> Result<types::TopicSystemInfo, FacadeError> {
         let mut cx = self.repo.connection();
@@ -307,13 +1027,36 @@ impl FacadeTopic {
             total_size += self.store.size(file).await?;
         }
 
+        let stats = repo::topic_get_stats(&mut cx, &self.locator).await?;
+
         Ok(types::TopicSystemInfo {
             chunks_number: datafiles.len(),
             is_locked: record.is_locked(),
             total_size_bytes: total_size,
             created_datetime: record.creation_timestamp().into(),
+            last_chunk_datetime: stats.last_chunk_timestamp.map(Into::into),
         })
     }
 }
 
+/// Returns the index following the ones of the data files of `chunks` (`data-{index}`),
+/// 0 if there are no chunks. The data files written by compactions (`compact-{uuid}-{index}`)
+/// are ignored.
+fn next_chunk_index(chunks: &[repo::Chunk]) -> usize {
+    chunks
+        .iter()
+        .filter_map(|chunk| {
+            chunk
+                .data_file()
+                .file_stem()?
+                .to_str()?
+                .strip_prefix("data-")?
+                .parse::<usize>()
+                .ok()
+        })
+        .map(|index| index + 1)
+        .max()
+        .unwrap_or(0)
+}
+
 // Batch Reader needs to implement Stream trait
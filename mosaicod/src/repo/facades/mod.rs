@@ -24,6 +24,9 @@ pub use facade_topic::*;
 mod facade_layer;
 pub use facade_layer::*;
 
+mod facade_ontology_contract;
+pub use facade_ontology_contract::*;
+
 mod facade_error;
 pub use facade_error::*;
 
@@ -1,5 +1,5 @@
 pub mod core;
-pub use core::{AsExec, Config, Cx, Database, Repository, Tx, UNREGISTERED};
+pub use core::{AsExec, Config, Cx, Database, PoolStats, Repository, Tx, UNREGISTERED};
 
 mod facades;
 pub use facades::*;
@@ -1,4 +1,4 @@
-use crate::repo;
+use crate::{repo, types};
 
 #[derive(Debug)]
 pub struct Column {
@@ -29,6 +29,12 @@ pub struct Chunk {
     pub(super) data_file: String,
     pub size_bytes: i64,
     pub row_count: i64,
+    pub(super) creation_unix_tstamp: i64,
+    /// Bounds (in milliseconds) of the chunk timestamps, rounded outwards
+    pub(super) min_unix_tstamp: Option<i64>,
+    pub(super) max_unix_tstamp: Option<i64>,
+    /// CRC32 checksum of the data file
+    pub(super) checksum: Option<i64>,
 }
 
 impl Chunk {
@@ -45,12 +51,43 @@ impl Chunk {
             data_file: data_file.as_ref().to_string_lossy().to_string(),
             size_bytes,
             row_count,
+            creation_unix_tstamp: types::Timestamp::now().into(),
+            min_unix_tstamp: None,
+            max_unix_tstamp: None,
+            checksum: None,
         }
     }
 
+    pub fn with_timestamp_bounds(mut self, min: types::Timestamp, max: types::Timestamp) -> Self {
+        self.min_unix_tstamp = Some(min.into());
+        self.max_unix_tstamp = Some(max.into());
+        self
+    }
+
+    pub fn with_checksum(mut self, checksum: u32) -> Self {
+        self.checksum = Some(checksum.into());
+        self
+    }
+
     pub fn data_file(&self) -> &std::path::Path {
         std::path::Path::new(&self.data_file)
     }
+
+    pub fn creation_timestamp(&self) -> types::Timestamp {
+        types::Timestamp::from(self.creation_unix_tstamp)
+    }
+
+    /// Returns the bounds of the chunk timestamps, [`None`] for chunks created before
+    /// bounds were tracked
+    pub fn timestamp_bounds(&self) -> Option<(types::Timestamp, types::Timestamp)> {
+        Some((self.min_unix_tstamp?.into(), self.max_unix_tstamp?.into()))
+    }
+
+    /// Returns the CRC32 checksum of the data file, [`None`] for chunks created before
+    /// checksums were tracked
+    pub fn checksum(&self) -> Option<u32> {
+        self.checksum.and_then(|c| u32::try_from(c).ok())
+    }
 }
 
 /// Chunk of literal data associated with a column.
@@ -65,6 +102,8 @@ pub struct ColumnChunkLiteral {
     pub max_value: String,
 
     pub has_null: bool,
+    /// Number of null values in the chunk (missing for chunks created before null counts were tracked)
+    pub null_count: Option<i64>,
 }
 
 impl ColumnChunkLiteral {
@@ -81,8 +120,14 @@ impl ColumnChunkLiteral {
             min_value,
             max_value,
             has_null,
+            null_count: None,
         })
     }
+
+    pub fn with_null_count(mut self, null_count: u64) -> Self {
+        self.null_count = i64::try_from(null_count).ok();
+        self
+    }
 }
 
 /// Chunk of literal data associated with a column.
@@ -98,24 +143,58 @@ pub struct ColumnChunkNumeric {
 
     pub has_null: bool,
     pub has_nan: bool,
+    pub has_inf: bool,
+    /// Number of null values in the chunk (missing for chunks created before null counts were tracked)
+    pub null_count: Option<i64>,
+
+    /// Approximate median of the finite values (missing for chunks created before quantiles were tracked)
+    pub p50_value: Option<f64>,
+    /// Approximate 95th percentile of the finite values (missing for chunks created before quantiles were tracked)
+    pub p95_value: Option<f64>,
 }
 
 impl ColumnChunkNumeric {
+    pub fn new(column_id: i32, chunk_id: i32, stats: &types::NumericStats) -> Self {
+        Self {
+            column_id,
+            chunk_id,
+            min_value: stats.min,
+            max_value: stats.max,
+            has_null: stats.has_null,
+            has_nan: stats.has_nan,
+            has_inf: stats.has_inf,
+            null_count: i64::try_from(stats.null_count).ok(),
+            p50_value: stats.p50(),
+            p95_value: stats.p95(),
+        }
+    }
+}
+
+/// Chunk of boolean data associated with a column.
+#[derive(Debug)]
+pub struct ColumnChunkBoolean {
+    pub column_id: i32,
+    pub chunk_id: i32,
+
+    pub has_true: bool,
+    pub has_false: bool,
+    pub has_null: bool,
+}
+
+impl ColumnChunkBoolean {
     pub fn new(
         column_id: i32,
         chunk_id: i32,
-        min: f64,
-        max: f64,
+        has_true: bool,
+        has_false: bool,
         has_null: bool,
-        has_nan: bool,
     ) -> Self {
         Self {
             column_id,
             chunk_id,
-            min_value: min,
-            max_value: max,
+            has_true,
+            has_false,
             has_null,
-            has_nan,
         }
     }
 }
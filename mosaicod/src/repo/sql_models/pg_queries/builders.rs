@@ -4,12 +4,16 @@ use crate::query;
 
 pub struct ChunkQueryBuilder {
     placeholder_counter: usize,
+    /// If enabled, `>` comparisons also discard chunks whose approximate `p95` is below the
+    /// threshold. This is lossy, a chunk with a few values above its `p95` could be discarded.
+    quantile_pruning: bool,
 }
 
 impl ChunkQueryBuilder {
     pub fn build(
         filter: query::ExprGroup<query::Value>,
         on_topic_ids: Vec<i64>,
+        quantile_pruning: bool,
     ) -> Result<(String, Vec<query::Value>), query::Error> {
         let mut qb = query::ClausesCompiler::new();
 
@@ -29,6 +33,7 @@ impl ChunkQueryBuilder {
 
         let mut qb_chunk = Self {
             placeholder_counter: pidx,
+            quantile_pruning,
         };
 
         qb = qb.filter(filter, &mut qb_chunk);
@@ -56,7 +61,7 @@ pub fn build_query(joined_clauses: String) -> String {
 
 fn build_clause(where_clauses: String, v: &query::Value) -> String {
     match v {
-        query::Value::Integer(_) | query::Value::Float(_) | query::Value::Boolean(_) => {
+        query::Value::Integer(_) | query::Value::Float(_) => {
             let select = r#"
             SELECT chunk_id FROM chunk_t 
             JOIN column_chunk_numeric_t __stats__ USING(chunk_id)
@@ -74,9 +79,140 @@ fn build_clause(where_clauses: String, v: &query::Value) -> String {
 
             format!("{select} WHERE {where_clauses}")
         }
+        query::Value::Boolean(_) => {
+            let select = r#"
+            SELECT chunk_id FROM chunk_t 
+            JOIN column_chunk_boolean_t __stats__ USING(chunk_id) 
+            JOIN column_t __column__ USING(column_id)
+            "#;
+
+            format!("{select} WHERE {where_clauses}")
+        }
+    }
+}
+
+/// Selects the chunks where the boolean `field` holds the value `p` or, with `other`, the
+/// value opposite to `p`.
+///
+/// Chunks written before boolean columns had their own statistics table hold numeric
+/// statistics for them (`false` as 0 and `true` as 1) until their statistics are recomputed,
+/// these are searched as well.
+fn boolean_clause(field: &str, p: &str, other: bool) -> String {
+    let column_name = column_table_name_by_value(&query::Value::Boolean(true));
+    let (has_p, has_not_p) = if other {
+        ("__stats__.has_false", "__stats__.has_true")
+    } else {
+        ("__stats__.has_true", "__stats__.has_false")
+    };
+    let (num_p, num_not_p) = if other {
+        ("__stats__.min_value <= 0", "__stats__.max_value >= 1")
+    } else {
+        ("__stats__.max_value >= 1", "__stats__.min_value <= 0")
+    };
+
+    let boolean = build_clause(
+        format!("{column_name} = {field} AND (CASE WHEN {p} THEN {has_p} ELSE {has_not_p} END)"),
+        &query::Value::Boolean(true),
+    );
+    let numeric = build_clause(
+        format!("{column_name} = {field} AND (CASE WHEN {p} THEN {num_p} ELSE {num_not_p} END)"),
+        &query::Value::Float(0.0),
+    );
+
+    // Clauses are intersected, the union is wrapped since INTERSECT binds tighter
+    format!("SELECT chunk_id FROM ({boolean} UNION {numeric}) __boolean__")
+}
+
+/// Boolean stats only track which values appear in a chunk, so only equality
+/// based operations can be evaluated on them.
+fn ensure_not_boolean(field: &str, v: &query::Value) -> Result<(), query::Error> {
+    if matches!(v, query::Value::Boolean(_)) {
+        return Err(query::Error::unsupported_op(field.into()));
+    }
+    Ok(())
+}
+
+/// Infinite values are excluded from numeric min/max stats, so chunks flagged with
+/// `has_inf` can't be pruned by range comparisons.
+fn non_finite_clause(v: &query::Value) -> &'static str {
+    match v {
+        query::Value::Integer(_) | query::Value::Float(_) => " OR __stats__.has_inf",
+        query::Value::Text(_) | query::Value::Boolean(_) => "",
+    }
+}
+
+/// Like [`non_finite_clause`] for equality: only chunks flagged with `has_inf` can hold an
+/// infinite value, which is never within the min/max range.
+fn infinite_value_clause(v: &query::Value) -> &'static str {
+    match v {
+        query::Value::Float(f) if f.is_infinite() => " OR __stats__.has_inf",
+        _ => "",
     }
 }
 
+/// Null and NaN values are excluded from min/max stats, so chunks flagged with `has_null` or
+/// `has_nan` can't be discarded because of the values found in their min/max range.
+fn excluded_values_clause(v: &query::Value) -> &'static str {
+    match v {
+        query::Value::Integer(_) | query::Value::Float(_) => {
+            " OR __stats__.has_null OR __stats__.has_nan"
+        }
+        query::Value::Text(_) => " OR __stats__.has_null",
+        query::Value::Boolean(_) => "",
+    }
+}
+
+/// Selects the chunks having statistics for `field` that satisfy `condition`.
+///
+/// Nulls are tracked for every column type, so all the stats tables are searched.
+fn any_stats_clause(field: &str, condition: &str) -> String {
+    let column_name = "(__column__.ontology_tag || '.' || __column__.column_name)";
+    format!(
+        r#"
+        SELECT chunk_id FROM chunk_t
+        JOIN (
+            SELECT chunk_id, column_id, has_null FROM column_chunk_numeric_t
+            UNION ALL
+            SELECT chunk_id, column_id, has_null FROM column_chunk_literal_t
+            UNION ALL
+            SELECT chunk_id, column_id, has_null FROM column_chunk_boolean_t
+        ) __stats__ USING(chunk_id)
+        JOIN column_t __column__ USING(column_id)
+        WHERE {column_name} = {field} AND {condition}
+        "#
+    )
+}
+
+/// Like [`any_stats_clause`], but also selects the chunks of topics with the ontology tag of
+/// `field` that have no statistics for it, e.g. chunks written without the (nullable) field
+/// or fields without statistics (like binary fields). Nothing is known about such chunks.
+fn any_stats_or_unknown_clause(field: &str, condition: &str) -> String {
+    let column_name = "(__column__.ontology_tag || '.' || __column__.column_name)";
+    let with_stats = any_stats_clause(field, condition);
+    format!(
+        r#"
+        SELECT chunk_id FROM (
+            {with_stats}
+            UNION
+            SELECT chunk_id FROM chunk_t
+            JOIN topic_t USING(topic_id)
+            JOIN column_t __column__ ON __column__.ontology_tag = topic_t.ontology_tag
+            WHERE {column_name} = {field} AND NOT EXISTS (
+                SELECT 1 FROM (
+                    SELECT chunk_id, column_id FROM column_chunk_numeric_t
+                    UNION ALL
+                    SELECT chunk_id, column_id FROM column_chunk_literal_t
+                    UNION ALL
+                    SELECT chunk_id, column_id FROM column_chunk_boolean_t
+                ) __stats__
+                WHERE __stats__.chunk_id = chunk_t.chunk_id
+                    AND __stats__.column_id = __column__.column_id
+            )
+        ) __any__
+        "#
+    )
+}
+
 fn column_table_name_by_value(_v: &query::Value) -> String {
     "(__column__.ontology_tag || '.' || __column__.column_name)".into()
 }
@@ -96,68 +232,261 @@ impl query::CompileClause for ChunkQueryBuilder {
                 let p = self.consume_placeholder();
                 let column_name = column_table_name_by_value(&v);
 
-                let clause = format!(
-                    "{column_name} = {field} AND __stats__.min_value >= {p} AND __stats__.max_value <= {p}"
-                );
-                query::CompiledClause::new(build_clause(clause, &v), vec![v])
+                if let query::Value::Boolean(_) = v {
+                    query::CompiledClause::new(boolean_clause(field, &p, false), vec![v])
+                } else {
+                    let inf = infinite_value_clause(&v);
+                    let clause = format!(
+                        "{column_name} = {field} AND ((__stats__.min_value >= {p} AND __stats__.max_value <= {p}){inf})"
+                    );
+                    query::CompiledClause::new(build_clause(clause, &v), vec![v])
+                }
+            }
+            query::Op::Neq(v) => {
+                let v = v.into();
+                // Only boolean stats allow to discard chunks not containing a different value
+                if !matches!(v, query::Value::Boolean(_)) {
+                    return Err(query::Error::unsupported_op(field.into()));
+                }
+                let p = self.consume_placeholder();
+
+                query::CompiledClause::new(boolean_clause(field, &p, true), vec![v])
             }
-            query::Op::Neq(_) => return Err(query::Error::unsupported_op(field.into())),
             query::Op::Leq(v) => {
                 let v = v.into();
+                ensure_not_boolean(field, &v)?;
                 let p = self.consume_placeholder();
                 let column_name = column_table_name_by_value(&v);
 
-                let clause = format!("{column_name} = {field} AND __stats__.min_value <= {p}");
+                let inf = non_finite_clause(&v);
+
+                let clause =
+                    format!("{column_name} = {field} AND (__stats__.min_value <= {p}{inf})");
                 query::CompiledClause::new(build_clause(clause, &v), vec![v])
             }
             query::Op::Geq(v) => {
                 let v = v.into();
+                ensure_not_boolean(field, &v)?;
                 let p = self.consume_placeholder();
                 let column_name = column_table_name_by_value(&v);
 
-                let clause = format!("{column_name} = {field} AND __stats__.max_value >= {p}");
+                let inf = non_finite_clause(&v);
+
+                let clause =
+                    format!("{column_name} = {field} AND (__stats__.max_value >= {p}{inf})");
                 query::CompiledClause::new(build_clause(clause, &v), vec![v])
             }
             query::Op::Lt(v) => {
                 let v = v.into();
+                ensure_not_boolean(field, &v)?;
                 let p = self.consume_placeholder();
                 let column_name = column_table_name_by_value(&v);
 
-                let clause = format!("{column_name} = {field} AND __stats__.min_value < {p}");
+                let inf = non_finite_clause(&v);
+
+                let clause =
+                    format!("{column_name} = {field} AND (__stats__.min_value < {p}{inf})");
                 query::CompiledClause::new(build_clause(clause, &v), vec![v])
             }
             query::Op::Gt(v) => {
                 let v = v.into();
+                ensure_not_boolean(field, &v)?;
                 let p = self.consume_placeholder();
                 let column_name = column_table_name_by_value(&v);
 
-                let clause = format!("{column_name} = {field} AND __stats__.max_value > {p}");
+                let inf = non_finite_clause(&v);
+
+                // Chunks created before quantiles were tracked have no `p95`, and are
+                // evaluated on min/max only
+                let quantile = if self.quantile_pruning {
+                    format!(" AND (__stats__.p95_value IS NULL OR __stats__.p95_value > {p})")
+                } else {
+                    String::new()
+                };
+
+                let clause = format!(
+                    "{column_name} = {field} AND ((__stats__.max_value > {p}{quantile}){inf})"
+                );
                 query::CompiledClause::new(build_clause(clause, &v), vec![v])
             }
 
-            query::Op::Ex => return Err(query::Error::unsupported_op(field.into())),
-            query::Op::Nex => return Err(query::Error::unsupported_op(field.into())),
+            query::Op::Ex => {
+                // Stats can't tell if a chunk holds only null values, so every chunk
+                // with the column is selected
+                query::CompiledClause::new(any_stats_or_unknown_clause(field, "TRUE"), Vec::new())
+            }
+
+            query::Op::Nex => {
+                // Chunks without nulls in the column can't hold any matching row
+                query::CompiledClause::new(
+                    any_stats_or_unknown_clause(field, "__stats__.has_null"),
+                    Vec::new(),
+                )
+            }
+
+            query::Op::IsNan => {
+                // NaN values are only tracked for numeric columns
+                let v = query::Value::Float(f64::NAN);
+                let column_name = column_table_name_by_value(&v);
+                let clause = format!("{column_name} = {field} AND __stats__.has_nan");
+                query::CompiledClause::new(build_clause(clause, &v), Vec::new())
+            }
+
+            query::Op::IsNotNan => {
+                // Stats can't tell if a chunk holds only NaN values, so every chunk
+                // with the column is selected
+                let v = query::Value::Float(f64::NAN);
+                let column_name = column_table_name_by_value(&v);
+                let clause = format!("{column_name} = {field}");
+                query::CompiledClause::new(build_clause(clause, &v), Vec::new())
+            }
+
+            query::Op::HasNan => {
+                // Same selection of `IsNan`, the chunk statistics are exact
+                let v = query::Value::Float(f64::NAN);
+                let column_name = column_table_name_by_value(&v);
+                let clause = format!("{column_name} = {field} AND __stats__.has_nan");
+                query::CompiledClause::new(build_clause(clause, &v), Vec::new())
+            }
+
+            query::Op::HasNull => query::CompiledClause::new(
+                any_stats_clause(field, "__stats__.has_null"),
+                Vec::new(),
+            ),
 
             query::Op::Between(range) => {
                 let vmin = range.min.into();
                 let vmax = range.max.into();
+                ensure_not_boolean(field, &vmin)?;
                 let pmin = self.consume_placeholder();
                 let pmax = self.consume_placeholder();
                 let column_name = column_table_name_by_value(&vmin);
+                let inf = non_finite_clause(&vmin);
 
                 let clause = format!(
-                    "{column_name} = {field} AND __stats__.min_value <= {pmax} AND __stats__.max_value >= {pmin}"
+                    "{column_name} = {field} AND ((__stats__.min_value <= {pmax} AND __stats__.max_value >= {pmin}){inf})"
+                );
+
+                query::CompiledClause::new(build_clause(clause, &vmin), vec![vmin, vmax])
+            }
+
+            query::Op::BetweenHalfOpen(range) => {
+                let vmin = range.min.into();
+                let vmax = range.max.into();
+                ensure_not_boolean(field, &vmin)?;
+                let pmin = self.consume_placeholder();
+                let pmax = self.consume_placeholder();
+                let column_name = column_table_name_by_value(&vmin);
+                let inf = non_finite_clause(&vmin);
+
+                // The upper bound is excluded, a chunk starting exactly at `max` is discarded
+                let clause = format!(
+                    "{column_name} = {field} AND ((__stats__.min_value < {pmax} AND __stats__.max_value >= {pmin}){inf})"
                 );
 
                 query::CompiledClause::new(build_clause(clause, &vmin), vec![vmin, vmax])
             }
 
             query::Op::In(_) => return Err(query::Error::unsupported_op(field.into())),
+
+            query::Op::NotIn(items) => {
+                let values: Vec<query::Value> = items.into_iter().map(Into::into).collect();
+                let Some(first) = values.first() else {
+                    return Err(query::Error::OpError {
+                        field: field.into(),
+                        err: query::OpError::EmptySet,
+                    });
+                };
+
+                ensure_not_boolean(field, first)?;
+
+                let placeholders: Vec<String> =
+                    values.iter().map(|_| self.consume_placeholder()).collect();
+                let column_name = column_table_name_by_value(first);
+                let inf = non_finite_clause(first);
+                let excluded = excluded_values_clause(first);
+
+                // A chunk can be discarded only if all its values are the same and are
+                // included in the set
+                let clause = format!(
+                    "{column_name} = {field} AND (__stats__.min_value != __stats__.max_value OR __stats__.min_value NOT IN ({}){inf}{excluded})",
+                    placeholders.join(", ")
+                );
+
+                query::CompiledClause::new(build_clause(clause, first), values)
+            }
+
             query::Op::Match(_) => return Err(query::Error::unsupported_op(field.into())),
+
+            query::Op::IMatch(v) => {
+                let v = query::imatch_pattern(v.into());
+                if !matches!(v, query::Value::Text(_)) {
+                    return Err(query::Error::unsupported_op(field.into()));
+                }
+                let p = self.consume_placeholder();
+                let column_name = column_table_name_by_value(&v);
+
+                // A pattern can't be evaluated against min/max stats, so a chunk can be
+                // discarded only if all its values are the same and do not match the pattern
+                let clause = format!(
+                    "{column_name} = {field} AND (__stats__.min_value != __stats__.max_value OR __stats__.min_value ILIKE {p})"
+                );
+                query::CompiledClause::new(build_clause(clause, &v), vec![v])
+            }
+
+            query::Op::Regex(v) => {
+                let v = v.into();
+                if !matches!(v, query::Value::Text(_)) {
+                    return Err(query::Error::unsupported_op(field.into()));
+                }
+                let p = self.consume_placeholder();
+                let column_name = column_table_name_by_value(&v);
+
+                let clause = format!(
+                    "{column_name} = {field} AND (__stats__.min_value != __stats__.max_value OR __stats__.min_value ~ {p})"
+                );
+                query::CompiledClause::new(build_clause(clause, &v), vec![v])
+            }
+
+            query::Op::StartsWith(v) => {
+                let query::Value::Text(text) = v.into() else {
+                    return Err(query::Error::unsupported_op(field.into()));
+                };
+                let v = query::Value::Text(format!("{}%", query::escape_like(&text)));
+                let p = self.consume_placeholder();
+                let column_name = column_table_name_by_value(&v);
+
+                let clause = format!(
+                    "{column_name} = {field} AND (__stats__.min_value != __stats__.max_value OR __stats__.min_value LIKE {p})"
+                );
+                query::CompiledClause::new(build_clause(clause, &v), vec![v])
+            }
+
+            query::Op::EndsWith(v) => {
+                let query::Value::Text(text) = v.into() else {
+                    return Err(query::Error::unsupported_op(field.into()));
+                };
+                let v = query::Value::Text(format!("%{}", query::escape_like(&text)));
+                let p = self.consume_placeholder();
+                let column_name = column_table_name_by_value(&v);
+
+                let clause = format!(
+                    "{column_name} = {field} AND (__stats__.min_value != __stats__.max_value OR __stats__.min_value LIKE {p})"
+                );
+                query::CompiledClause::new(build_clause(clause, &v), vec![v])
+            }
         };
 
         Ok(clause)
     }
+
+    /// Each clause selects a set of chunks, so boolean connectors are mapped to set operations
+    fn connector_fmt(&self, connector: query::Connector) -> &'static str {
+        match connector {
+            query::Connector::And => " INTERSECT ",
+            query::Connector::Or => " UNION ",
+        }
+    }
 }
 
 impl query::OntologyFieldFmt for ChunkQueryBuilder {
@@ -227,3 +556,198 @@ impl query::CompileClause for FilterChunksByTopicMapper {
         Ok(clause)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn boolean_filter(op: query::Op<query::Value>) -> query::ExprGroup<query::Value> {
+        let field = query::OntologyField::try_new("imu.is_moving".to_owned()).unwrap();
+        query::ExprGroup::new(vec![(field, op).into()])
+    }
+
+    #[test]
+    fn boolean_equality_uses_boolean_stats() {
+        let (query, values) = ChunkQueryBuilder::build(
+            boolean_filter(query::Op::Eq(true.into())),
+            Vec::new(),
+            false,
+        )
+        .unwrap();
+
+        assert!(query.contains("column_chunk_boolean_t"));
+        assert!(
+            query.contains("CASE WHEN $1 THEN __stats__.has_true ELSE __stats__.has_false END")
+        );
+        // Chunks written before the boolean stats table store booleans as numeric stats
+        assert!(query.contains("column_chunk_numeric_t"));
+        assert!(query.contains(
+            "CASE WHEN $1 THEN __stats__.max_value >= 1 ELSE __stats__.min_value <= 0 END"
+        ));
+        assert_eq!(values, vec![query::Value::Boolean(true)]);
+
+        let (query, _) = ChunkQueryBuilder::build(
+            boolean_filter(query::Op::Neq(false.into())),
+            Vec::new(),
+            false,
+        )
+        .unwrap();
+        assert!(
+            query.contains("CASE WHEN $1 THEN __stats__.has_false ELSE __stats__.has_true END")
+        );
+
+        let res = ChunkQueryBuilder::build(
+            boolean_filter(query::Op::Gt(true.into())),
+            Vec::new(),
+            false,
+        );
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn greater_than_quantile_pruning() {
+        let filter = || {
+            let field = query::OntologyField::try_new("imu.acc.x".to_owned()).unwrap();
+            query::ExprGroup::new(vec![(field, query::Op::Gt(10.0.into())).into()])
+        };
+
+        let (query, _) = ChunkQueryBuilder::build(filter(), Vec::new(), false).unwrap();
+        assert!(!query.contains("p95_value"));
+
+        let (query, values) = ChunkQueryBuilder::build(filter(), Vec::new(), true).unwrap();
+        assert!(query.contains(
+            "((__stats__.max_value > $1 AND (__stats__.p95_value IS NULL OR __stats__.p95_value > $1)) OR __stats__.has_inf)"
+        ));
+        assert_eq!(values, vec![query::Value::Float(10.0)]);
+    }
+
+    #[test]
+    fn imatch_pruning() {
+        let field = query::OntologyField::try_new("sensor.id".to_owned()).unwrap();
+        let filter = query::ExprGroup::<query::Value>::new(vec![
+            (field, query::Op::IMatch("imu".into())).into(),
+        ]);
+
+        // the pattern is searched anywhere in the value, as done by the catalog and the
+        // query engine
+        let (query, values) = ChunkQueryBuilder::build(filter, Vec::new(), false).unwrap();
+        assert!(query.contains("__stats__.min_value ILIKE $1"));
+        assert_eq!(values, vec![query::Value::Text("%imu%".to_owned())]);
+    }
+
+    #[test]
+    fn between_half_open_pruning() {
+        let field = query::OntologyField::try_new("imu.acc.x".to_owned()).unwrap();
+        let range = query::Range::try_new(1.0.into(), 2.0.into()).unwrap();
+        let filter = query::ExprGroup::<query::Value>::new(vec![
+            (field, query::Op::BetweenHalfOpen(range)).into(),
+        ]);
+
+        let (query, values) = ChunkQueryBuilder::build(filter, Vec::new(), false).unwrap();
+        assert!(query.contains("__stats__.min_value < $2 AND __stats__.max_value >= $1"));
+        assert_eq!(
+            values,
+            vec![query::Value::Float(1.0), query::Value::Float(2.0)]
+        );
+    }
+
+    #[test]
+    fn equality_on_infinite_values() {
+        let filter = |v: f64| {
+            let field = query::OntologyField::try_new("imu.acc.x".to_owned()).unwrap();
+            query::ExprGroup::<query::Value>::new(vec![(field, query::Op::Eq(v.into())).into()])
+        };
+
+        let (query, _) = ChunkQueryBuilder::build(filter(1.0), Vec::new(), false).unwrap();
+        assert!(!query.contains("has_inf"));
+
+        for v in [f64::INFINITY, f64::NEG_INFINITY] {
+            let (query, values) = ChunkQueryBuilder::build(filter(v), Vec::new(), false).unwrap();
+            assert!(query.contains(
+                "((__stats__.min_value >= $1 AND __stats__.max_value <= $1) OR __stats__.has_inf)"
+            ));
+            assert_eq!(values, vec![query::Value::Float(v)]);
+        }
+    }
+
+    #[test]
+    fn not_in_pruning() {
+        let filter = |field: &str, values: Vec<query::Value>| {
+            let field = query::OntologyField::try_new(field.to_owned()).unwrap();
+            query::ExprGroup::<query::Value>::new(vec![(field, query::Op::NotIn(values)).into()])
+        };
+
+        let (query, values) = ChunkQueryBuilder::build(
+            filter("imu.acc.x", vec![1.0.into(), 2.0.into()]),
+            Vec::new(),
+            false,
+        )
+        .unwrap();
+        // Null and NaN values are not in the set, chunks holding them are kept
+        assert!(query.contains(
+            "__stats__.min_value NOT IN ($1, $2) OR __stats__.has_inf OR __stats__.has_null OR __stats__.has_nan)"
+        ));
+        assert_eq!(values.len(), 2);
+
+        let (query, _) = ChunkQueryBuilder::build(
+            filter("imu.frame_id", vec!["base".into()]),
+            Vec::new(),
+            false,
+        )
+        .unwrap();
+        assert!(query.contains("__stats__.min_value NOT IN ($1) OR __stats__.has_null)"));
+
+        assert!(
+            ChunkQueryBuilder::build(filter("imu.acc.x", Vec::new()), Vec::new(), false).is_err()
+        );
+    }
+
+    #[test]
+    fn nan_pruning() {
+        let field = query::OntologyField::try_new("imu.acc.x".to_owned()).unwrap();
+        let filter = query::ExprGroup::<query::Value>::new(vec![(field, query::Op::IsNan).into()]);
+
+        let (query, values) = ChunkQueryBuilder::build(filter, Vec::new(), false).unwrap();
+        assert!(query.contains("column_chunk_numeric_t"));
+        assert!(query.contains("AND __stats__.has_nan"));
+        assert!(values.is_empty());
+    }
+
+    #[test]
+    fn null_pruning_searches_all_stats() {
+        let field = query::OntologyField::try_new("imu.frame_id".to_owned()).unwrap();
+        let filter =
+            query::ExprGroup::<query::Value>::new(vec![(field, query::Op::HasNull).into()]);
+
+        let (query, values) = ChunkQueryBuilder::build(filter, vec![1], false).unwrap();
+        for table in [
+            "column_chunk_numeric_t",
+            "column_chunk_literal_t",
+            "column_chunk_boolean_t",
+        ] {
+            assert!(query.contains(table));
+        }
+        assert!(query.contains("AND __stats__.has_null"));
+        assert_eq!(values, vec![query::Value::Integer(1)]);
+    }
+
+    #[test]
+    fn existence_pruning() {
+        let field = query::OntologyField::try_new("image.info.height".to_owned()).unwrap();
+
+        let filter =
+            query::ExprGroup::<query::Value>::new(vec![(field.clone(), query::Op::Nex).into()]);
+        let (query, values) = ChunkQueryBuilder::build(filter, Vec::new(), false).unwrap();
+        assert!(query.contains("AND __stats__.has_null"));
+        assert!(values.is_empty());
+        // Chunks without stats for the column (e.g. written without it) are kept
+        assert!(query.contains("NOT EXISTS"));
+
+        // Chunks holding the column are kept
+        let filter = query::ExprGroup::<query::Value>::new(vec![(field, query::Op::Ex).into()]);
+        let (query, _) = ChunkQueryBuilder::build(filter, Vec::new(), false).unwrap();
+        assert!(!query.contains("has_null"));
+        assert!(query.contains("column_chunk_numeric_t"));
+        assert!(query.contains("NOT EXISTS"));
+    }
+}
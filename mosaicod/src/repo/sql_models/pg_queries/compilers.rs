@@ -20,6 +20,11 @@ impl JsonQueryCompiler {
         self.internal.placeholder(placeholder);
         &mut self.internal
     }
+
+    /// Returns the next placeholder that will be consumed by the compiler.
+    pub fn current_placeholder(&self) -> usize {
+        self.internal.current_placeholder()
+    }
 }
 
 pub struct SqlQueryCompiler {
@@ -109,6 +114,9 @@ impl query::CompileClause for SqlQueryCompiler {
                 query::CompiledClause::new(format!("({field}) IS NOT NULL"), Vec::new())
             }
             query::Op::Nex => query::CompiledClause::new(format!("({field}) IS NULL"), Vec::new()),
+            query::Op::IsNan | query::Op::IsNotNan | query::Op::HasNan | query::Op::HasNull => {
+                return Err(query::Error::unsupported_op(field.to_owned()));
+            }
             query::Op::Between(range) => {
                 let min: query::Value = range.min.into();
                 let max: query::Value = range.max.into();
@@ -120,6 +128,17 @@ impl query::CompileClause for SqlQueryCompiler {
 
                 query::CompiledClause::new(clause, vec![min, max])
             }
+            query::Op::BetweenHalfOpen(range) => {
+                let min: query::Value = range.min.into();
+                let max: query::Value = range.max.into();
+
+                let pmin = self.consume_placeholder();
+                let pmax = self.consume_placeholder();
+
+                let clause = format!("({field} >= {pmin}) AND ({field} < {pmax})");
+
+                query::CompiledClause::new(clause, vec![min, max])
+            }
             query::Op::In(items) => {
                 if items.is_empty() {
                     return Ok(query::CompiledClause::empty());
@@ -134,6 +153,20 @@ impl query::CompileClause for SqlQueryCompiler {
 
                 query::CompiledClause::new(clause, values)
             }
+            query::Op::NotIn(items) => {
+                if items.is_empty() {
+                    return Ok(query::CompiledClause::empty());
+                }
+
+                // Generate placeholders and collect values
+                let values: Vec<query::Value> = items.into_iter().map(Into::into).collect();
+                let placeholders: Vec<String> =
+                    values.iter().map(|_| self.consume_placeholder()).collect();
+
+                let clause = format!("{} NOT IN ({})", field, placeholders.join(", "));
+
+                query::CompiledClause::new(clause, values)
+            }
             query::Op::Match(v) => {
                 let value: query::Value = v.into();
                 if let query::Value::Text(text) = value {
@@ -144,6 +177,45 @@ impl query::CompileClause for SqlQueryCompiler {
                     return Err(query::Error::unsupported_op(field.to_owned()));
                 }
             }
+            query::Op::IMatch(v) => {
+                let value: query::Value = v.into();
+                if let query::Value::Text(_) = value {
+                    let value = query::imatch_pattern(value);
+                    let clause = format!("{} ILIKE {}", field, self.consume_placeholder());
+                    query::CompiledClause::new(clause, vec![value])
+                } else {
+                    return Err(query::Error::unsupported_op(field.to_owned()));
+                }
+            }
+            query::Op::Regex(v) => {
+                let value: query::Value = v.into();
+                if let query::Value::Text(_) = value {
+                    let clause = format!("{} ~ {}", field, self.consume_placeholder());
+                    query::CompiledClause::new(clause, vec![value])
+                } else {
+                    return Err(query::Error::unsupported_op(field.to_owned()));
+                }
+            }
+            query::Op::StartsWith(v) => {
+                let value: query::Value = v.into();
+                if let query::Value::Text(text) = value {
+                    let value = query::Value::Text(format!("{}%", query::escape_like(&text)));
+                    let clause = format!("{} LIKE {}", field, self.consume_placeholder());
+                    query::CompiledClause::new(clause, vec![value])
+                } else {
+                    return Err(query::Error::unsupported_op(field.to_owned()));
+                }
+            }
+            query::Op::EndsWith(v) => {
+                let value: query::Value = v.into();
+                if let query::Value::Text(text) = value {
+                    let value = query::Value::Text(format!("%{}", query::escape_like(&text)));
+                    let clause = format!("{} LIKE {}", field, self.consume_placeholder());
+                    query::CompiledClause::new(clause, vec![value])
+                } else {
+                    return Err(query::Error::unsupported_op(field.to_owned()));
+                }
+            }
         };
 
         Ok(r)
@@ -174,6 +246,10 @@ mod internal {
             self.placeholder_counter = placeholder;
         }
 
+        pub fn current_placeholder(&self) -> usize {
+            self.placeholder_counter
+        }
+
         fn consume_placeholder(&mut self) -> String {
             let p = format!("${}", self.placeholder_counter);
             self.placeholder_counter += 1;
@@ -275,6 +351,9 @@ mod internal {
                 query::Op::Nex => {
                     query::CompiledClause::new(format!("({field}) IS NULL"), Vec::new())
                 }
+                query::Op::IsNan | query::Op::IsNotNan | query::Op::HasNan | query::Op::HasNull => {
+                    return Err(query::Error::unsupported_op(field.to_owned()));
+                }
                 query::Op::Between(range) => {
                     let min: query::Value = range.min.into();
                     let max: query::Value = range.max.into();
@@ -290,8 +369,30 @@ mod internal {
 
                     query::CompiledClause::new(clause, vec![min, max])
                 }
+                query::Op::BetweenHalfOpen(range) => {
+                    let min: query::Value = range.min.into();
+                    let max: query::Value = range.max.into();
+
+                    let pmin = self.consume_placeholder();
+                    let pmax = self.consume_placeholder();
+
+                    let field = self.fmt_value(field, &min);
+
+                    let clause = format!("({field} >= {pmin}) AND ({field} < {pmax})");
+
+                    query::CompiledClause::new(clause, vec![min, max])
+                }
                 query::Op::In(_) => return Err(query::Error::unsupported_op(field.to_owned())),
-                query::Op::Match(_) => return Err(query::Error::unsupported_op(field.to_owned())),
+                query::Op::NotIn(_) => {
+                    return Err(query::Error::unsupported_op(field.to_owned()));
+                }
+                query::Op::Match(_)
+                | query::Op::IMatch(_)
+                | query::Op::Regex(_)
+                | query::Op::StartsWith(_)
+                | query::Op::EndsWith(_) => {
+                    return Err(query::Error::unsupported_op(field.to_owned()));
+                }
             };
 
             Ok(r)
@@ -299,14 +400,43 @@ mod internal {
     }
 
     impl query::OntologyFieldFmt for JsonQueryCompiler {
+        /// Translates a dotted field (e.g. `robot.config.version`) into a Postgres JSONB
+        /// path expression (e.g. `user_metadata #>> '{robot,config,version}'`).
         fn ontology_column_fmt(&self, subfield: &query::OntologyField) -> String {
-            let subfield = format!(
-                "{{{}}}",
-                subfield.value().split(".").collect::<Vec<&str>>().join(",")
-            );
-            format!("{} #>> '{subfield}'", self.field)
+            format!(
+                "{} #>> '{}'",
+                self.field,
+                json_path_literal(subfield.value())
+            )
         }
     }
+
+    /// Builds the text array literal used by the `#>>` operator from a dotted path.
+    ///
+    /// Path elements containing characters with a special meaning inside an array literal
+    /// are double-quoted and escaped, single quotes are doubled since the literal is
+    /// embedded in the SQL string.
+    fn json_path_literal(path: &str) -> String {
+        let elements: Vec<String> = path
+            .split('.')
+            .map(|elem| {
+                let needs_quotes = elem.is_empty()
+                    || elem.eq_ignore_ascii_case("null")
+                    || elem
+                        .chars()
+                        .any(|c| matches!(c, ',' | '{' | '}' | '"' | '\\') || c.is_whitespace());
+
+                let elem = elem.replace('\'', "''");
+                if needs_quotes {
+                    format!("\"{}\"", elem.replace('\\', "\\\\").replace('"', "\\\""))
+                } else {
+                    elem
+                }
+            })
+            .collect();
+
+        format!("{{{}}}", elements.join(","))
+    }
 }
 
 #[cfg(test)]
@@ -328,6 +458,47 @@ mod tests {
         assert!(matches!(qr.err().unwrap(), query::Error::OpError { .. }));
     }
 
+    #[test]
+    fn prefix_and_suffix_are_escaped() {
+        let mut fmt = SqlQueryCompiler::new();
+
+        let qr = ClausesCompiler::new()
+            .expr(
+                "topic.locator_name",
+                Op::StartsWith("seq_1/%".to_owned()),
+                &mut fmt,
+            )
+            .expr(
+                "topic.ontology_tag",
+                Op::EndsWith("imu".to_owned()),
+                &mut fmt,
+            )
+            .compile()
+            .expect("problem building query");
+
+        assert_eq!(qr.clauses[0], "topic.locator_name LIKE $1");
+        assert_eq!(qr.values[0], query::Value::Text(r"seq\_1/\%%".to_owned()));
+        assert_eq!(qr.clauses[1], "topic.ontology_tag LIKE $2");
+        assert_eq!(qr.values[1], query::Value::Text("%imu".to_owned()));
+    }
+
+    #[test]
+    fn regex() {
+        let mut fmt = SqlQueryCompiler::new();
+
+        let qr = ClausesCompiler::new()
+            .expr(
+                "topic.locator_name",
+                Op::Regex("^seq_[0-9]+/".to_owned()),
+                &mut fmt,
+            )
+            .compile()
+            .expect("problem building query");
+
+        assert_eq!(qr.clauses[0], "topic.locator_name ~ $1");
+        assert_eq!(qr.values[0], query::Value::Text("^seq_[0-9]+/".to_owned()));
+    }
+
     #[test]
     fn topic_fields() {
         let mut fmt = SqlQueryCompiler::new();
@@ -424,4 +595,191 @@ mod tests {
             panic!("match not found");
         }
     }
+
+    #[test]
+    fn nested_user_metadata() {
+        let mdata: HashMap<query::OntologyField, query::Op<query::Value>> = HashMap::from([
+            (
+                query::OntologyField::try_new("robot.config.version".into()).unwrap(),
+                query::Op::Eq(query::Value::Text("v1.2".to_owned())),
+            ),
+            (
+                query::OntologyField::try_new("robot.config.max_speed".into()).unwrap(),
+                query::Op::Geq(query::Value::Integer(10)),
+            ),
+        ]);
+        let kv = query::OntologyFilter::new(mdata);
+
+        let mut sql_fmt = SqlQueryCompiler::new();
+        let mut json_fmt = JsonQueryCompiler::new();
+
+        let qr = ClausesCompiler::new()
+            .expr(
+                "sequence.locator_name",
+                Op::Eq("seq".to_owned()),
+                &mut sql_fmt,
+            )
+            .filter(
+                kv.into_expr_group(),
+                json_fmt.with_field_and_placeholder(
+                    "sequence.user_metadata".into(),
+                    sql_fmt.current_placeholder(),
+                ),
+            )
+            .compile()
+            .expect("problem building query");
+
+        // Placeholders continue after the ones consumed by the sql compiler
+        assert_eq!(json_fmt.current_placeholder(), 4);
+
+        if let Some(idx) = qr.clauses.iter().position(|c| {
+            c.starts_with(r#"sequence.user_metadata #>> '{robot,config,version}' = $"#)
+        }) {
+            assert_eq!(
+                qr.clauses[idx],
+                format!(
+                    "sequence.user_metadata #>> '{{robot,config,version}}' = ${}",
+                    idx + 1
+                )
+            );
+            assert_eq!(qr.values[idx], query::Value::Text("v1.2".to_owned()));
+        } else {
+            panic!("match not found");
+        }
+
+        if let Some(idx) = qr.clauses.iter().position(|c| {
+            c.starts_with(
+                r#"(sequence.user_metadata #>> '{robot,config,max_speed}')::numeric >= $"#,
+            )
+        }) {
+            assert!(qr.clauses[idx].ends_with(&format!("${}", idx + 1)));
+            assert_eq!(qr.values[idx], query::Value::Integer(10));
+        } else {
+            panic!("match not found");
+        }
+    }
+
+    #[test]
+    fn user_metadata_path_escaping() {
+        let mdata: HashMap<query::OntologyField, query::Op<query::Value>> = HashMap::from([(
+            query::OntologyField::try_new("robot.it's a {key}".into()).unwrap(),
+            query::Op::Ex,
+        )]);
+        let kv = query::OntologyFilter::new(mdata);
+
+        let mut fmt = JsonQueryCompiler::new();
+
+        let qr = ClausesCompiler::new()
+            .filter(
+                kv.into_expr_group(),
+                fmt.with_field_and_placeholder("topic.user_metadata".into(), 1),
+            )
+            .compile()
+            .expect("problem building query");
+
+        assert_eq!(
+            qr.clauses[0],
+            r#"(topic.user_metadata #>> '{robot,"it''s a {key}"}') IS NOT NULL"#
+        );
+        assert!(qr.values.is_empty());
+    }
+
+    #[test]
+    fn user_metadata_or_group() {
+        let field = |f: &str| query::OntologyField::try_new(f.into()).unwrap();
+
+        let any_of = ExprGroup::new(vec![
+            (field("robot.speed"), Op::Gt(query::Value::Integer(10))).into(),
+            (field("robot.speed"), Op::Lt(query::Value::Integer(-10))).into(),
+        ])
+        .with_connector(Connector::Or);
+
+        let grp = ExprGroup::new(vec![
+            (
+                field("robot.name"),
+                Op::Eq(query::Value::Text("r2".to_owned())),
+            )
+                .into(),
+        ])
+        .with_subgroups(vec![any_of]);
+
+        let mut fmt = JsonQueryCompiler::new();
+
+        let qr = ClausesCompiler::new()
+            .filter(
+                grp,
+                fmt.with_field_and_placeholder("topic.user_metadata".into(), 1),
+            )
+            .compile()
+            .expect("problem building query");
+
+        assert_eq!(qr.clauses.len(), 2);
+        assert_eq!(qr.clauses[0], "topic.user_metadata #>> '{robot,name}' = $1");
+        assert_eq!(
+            qr.clauses[1],
+            "((topic.user_metadata #>> '{robot,speed}')::numeric > $2 OR (topic.user_metadata #>> '{robot,speed}')::numeric < $3)"
+        );
+        assert_eq!(
+            qr.values,
+            vec![
+                query::Value::Text("r2".to_owned()),
+                query::Value::Integer(10),
+                query::Value::Integer(-10)
+            ]
+        );
+    }
+
+    #[test]
+    fn not_in() {
+        let mut fmt = SqlQueryCompiler::new();
+
+        let qr = ClausesCompiler::new()
+            .expr(
+                "topic.ontology_tag",
+                Op::NotIn(vec!["imu".to_owned(), "gps".to_owned()]),
+                &mut fmt,
+            )
+            .compile()
+            .expect("problem building query");
+
+        assert_eq!(qr.clauses[0], "topic.ontology_tag NOT IN ($1, $2)");
+        assert_eq!(
+            qr.values,
+            vec![
+                query::Value::Text("imu".to_owned()),
+                query::Value::Text("gps".to_owned())
+            ]
+        );
+
+        // Empty sets are rejected instead of panicking
+        let qr = ClausesCompiler::new()
+            .expr(
+                "topic.ontology_tag",
+                Op::<String>::NotIn(Vec::new()),
+                &mut fmt,
+            )
+            .compile();
+
+        assert!(matches!(qr, Err(query::Error::OpError { .. })));
+    }
+
+    #[test]
+    fn imatch() {
+        let mut fmt = SqlQueryCompiler::new();
+
+        let qr = ClausesCompiler::new()
+            .expr(
+                "topic.locator_name",
+                Op::IMatch("imu_front".to_owned()),
+                &mut fmt,
+            )
+            .compile()
+            .expect("problem building query");
+
+        assert_eq!(qr.clauses[0], "topic.locator_name ILIKE $1");
+        assert_eq!(
+            qr.values,
+            vec![query::Value::Text("%imu_front%".to_owned())]
+        );
+    }
 }
@@ -1,10 +1,11 @@
 use crate::{
-    query,
+    params, query,
     repo::{self, sql_models},
     types::{self, Resource},
 };
 use log::trace;
 use sqlx::{Row, postgres::PgRow};
+use std::collections::HashMap;
 
 pub async fn column_get_or_create(
     exec: &mut impl repo::AsExec,
@@ -29,26 +30,117 @@ pub async fn column_get_or_create(
     Ok(res)
 }
 
+/// Returns the names of all the columns registered for `ontology_tag`
+pub async fn column_find_names_by_ontology_tag(
+    exec: &mut impl repo::AsExec,
+    ontology_tag: &str,
+) -> Result<Vec<String>, repo::Error> {
+    trace!("searching columns of ontology tag `{}`", ontology_tag);
+    let res = sqlx::query_scalar!(
+        r#"SELECT column_name FROM column_t WHERE ontology_tag = $1"#,
+        ontology_tag,
+    )
+    .fetch_all(exec.as_exec())
+    .await?;
+    Ok(res)
+}
+
+/// Returns the bounds (min, max) of the timestamps of the provided chunks, indexed by chunk id.
+///
+/// Bounds are retrieved from the statistics of the timestamp column, chunks without
+/// statistics are not reported.
+pub async fn chunks_timestamp_bounds(
+    exec: &mut impl repo::AsExec,
+    chunk_ids: &[i32],
+) -> Result<HashMap<i32, (f64, f64)>, repo::Error> {
+    trace!("searching timestamp bounds of #{} chunks", chunk_ids.len());
+    let res = sqlx::query!(
+        r#"SELECT __stats__.chunk_id, __stats__.min_value, __stats__.max_value
+        FROM column_chunk_numeric_t __stats__
+        JOIN column_t __column__ USING(column_id)
+        WHERE __stats__.chunk_id = ANY($1) AND __column__.column_name = $2"#,
+        chunk_ids,
+        params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+    )
+    .fetch_all(exec.as_exec())
+    .await?;
+
+    Ok(res
+        .into_iter()
+        .map(|r| (r.chunk_id, (r.min_value, r.max_value)))
+        .collect())
+}
+
 pub async fn chunk_create(
     exec: &mut impl repo::AsExec,
     chunk: &sql_models::Chunk,
 ) -> Result<sql_models::Chunk, repo::Error> {
     let res = sqlx::query_as!(
         sql_models::Chunk,
-        r#"INSERT INTO chunk_t(chunk_uuid, topic_id, data_file, size_bytes, row_count)
-        VALUES ($1, $2, $3, $4, $5)
+        r#"INSERT INTO chunk_t(chunk_uuid, topic_id, data_file, size_bytes, row_count, creation_unix_tstamp, min_unix_tstamp, max_unix_tstamp, checksum)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
         RETURNING *"#,
         chunk.chunk_uuid,
         chunk.topic_id,
         chunk.data_file,
         chunk.size_bytes,
         chunk.row_count,
+        chunk.creation_unix_tstamp,
+        chunk.min_unix_tstamp,
+        chunk.max_unix_tstamp,
+        chunk.checksum,
     )
     .fetch_one(exec.as_exec())
     .await?;
     Ok(res)
 }
 
+/// Batch insert multiple chunks in a single query, returning the chunks with the ids
+/// assigned by the database (in the same order of `chunks`).
+///
+/// The rows returned by a multi-row `INSERT` are not guaranteed to follow the order of the
+/// values, so the assigned ids are matched to the chunks using their uuid.
+pub async fn chunk_create_batch(
+    exec: &mut impl repo::AsExec,
+    chunks: Vec<sql_models::Chunk>,
+) -> Result<Vec<sql_models::Chunk>, repo::Error> {
+    if chunks.is_empty() {
+        return Ok(chunks);
+    }
+
+    let mut query_builder: sqlx::QueryBuilder<sqlx::Postgres> = sqlx::QueryBuilder::new(
+        "INSERT INTO chunk_t(chunk_uuid, topic_id, data_file, size_bytes, row_count, creation_unix_tstamp, min_unix_tstamp, max_unix_tstamp, checksum) ",
+    );
+
+    query_builder.push_values(&chunks, |mut b, chunk| {
+        b.push_bind(chunk.chunk_uuid)
+            .push_bind(chunk.topic_id)
+            .push_bind(&chunk.data_file)
+            .push_bind(chunk.size_bytes)
+            .push_bind(chunk.row_count)
+            .push_bind(chunk.creation_unix_tstamp)
+            .push_bind(chunk.min_unix_tstamp)
+            .push_bind(chunk.max_unix_tstamp)
+            .push_bind(chunk.checksum);
+    });
+    query_builder.push(" RETURNING chunk_id, chunk_uuid");
+
+    let rows = query_builder.build().fetch_all(exec.as_exec()).await?;
+
+    let mut ids: HashMap<uuid::Uuid, i32> = HashMap::with_capacity(rows.len());
+    for row in rows {
+        ids.insert(row.try_get("chunk_uuid")?, row.try_get("chunk_id")?);
+    }
+
+    chunks
+        .into_iter()
+        .map(|mut chunk| {
+            chunk.chunk_id = *ids.get(&chunk.chunk_uuid).ok_or(repo::Error::NotFound)?;
+            Ok(chunk)
+        })
+        .collect()
+}
+
 pub async fn column_chunk_literal_create(
     exec: &mut impl repo::AsExec,
     val: &sql_models::ColumnChunkLiteral,
@@ -58,15 +150,16 @@ pub async fn column_chunk_literal_create(
         r#"INSERT INTO column_chunk_literal_t(
             column_id, chunk_id,
             min_value, max_value,
-            has_null 
+            has_null, null_count
         )
-        VALUES ($1, $2, $3, $4, $5)
+        VALUES ($1, $2, $3, $4, $5, $6)
         RETURNING *"#,
         val.column_id,
         val.chunk_id,
         val.min_value,
         val.max_value,
         val.has_null,
+        val.null_count,
     )
     .fetch_one(exec.as_exec())
     .await?;
@@ -82,9 +175,10 @@ pub async fn column_chunk_numeric_create(
         r#"INSERT INTO column_chunk_numeric_t(
             column_id, chunk_id,
             min_value, max_value,
-            has_null, has_nan
+            has_null, has_nan, has_inf,
+            p50_value, p95_value, null_count
         )
-        VALUES ($1, $2, $3, $4, $5, $6)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
         RETURNING *"#,
         val.column_id,
         val.chunk_id,
@@ -92,6 +186,10 @@ pub async fn column_chunk_numeric_create(
         val.max_value,
         val.has_null,
         val.has_nan,
+        val.has_inf,
+        val.p50_value,
+        val.p95_value,
+        val.null_count,
     )
     .fetch_one(exec.as_exec())
     .await?;
@@ -109,7 +207,7 @@ pub async fn column_chunk_numeric_create_batch(
     }
 
     let mut query_builder: sqlx::QueryBuilder<sqlx::Postgres> = sqlx::QueryBuilder::new(
-        "INSERT INTO column_chunk_numeric_t(column_id, chunk_id, min_value, max_value, has_null, has_nan) ",
+        "INSERT INTO column_chunk_numeric_t(column_id, chunk_id, min_value, max_value, has_null, has_nan, has_inf, p50_value, p95_value, null_count) ",
     );
 
     query_builder.push_values(values, |mut b, val| {
@@ -118,13 +216,108 @@ pub async fn column_chunk_numeric_create_batch(
             .push_bind(val.min_value)
             .push_bind(val.max_value)
             .push_bind(val.has_null)
-            .push_bind(val.has_nan);
+            .push_bind(val.has_nan)
+            .push_bind(val.has_inf)
+            .push_bind(val.p50_value)
+            .push_bind(val.p95_value)
+            .push_bind(val.null_count);
     });
 
     query_builder.build().execute(exec.as_exec()).await?;
     Ok(())
 }
 
+/// Loads multiple numeric column chunk stats with a binary `COPY`, producing the same rows
+/// of [`column_chunk_numeric_create_batch`].
+///
+/// Faster than a multi-row `INSERT` when loading many stats (e.g. for topics with hundreds
+/// of columns) and not subject to the limit on the number of bind parameters. The `COPY`
+/// is performed on the connection of the provided transaction.
+pub async fn column_chunk_numeric_copy(
+    tx: &mut repo::Tx<'_>,
+    values: &[sql_models::ColumnChunkNumeric],
+) -> Result<(), repo::Error> {
+    if values.is_empty() {
+        return Ok(());
+    }
+
+    trace!("copying #{} numeric column chunk stats", values.len());
+
+    let mut copy = tx
+        .connection_mut()
+        .copy_in_raw(
+            "COPY column_chunk_numeric_t(column_id, chunk_id, min_value, max_value, has_null, has_nan, has_inf, p50_value, p95_value, null_count) FROM STDIN WITH (FORMAT BINARY)",
+        )
+        .await?;
+
+    if let Err(e) = copy.send(encode_numeric_copy(values)).await {
+        copy.abort(e.to_string()).await?;
+        return Err(e.into());
+    }
+
+    copy.finish().await?;
+    Ok(())
+}
+
+/// Encodes numeric column chunk stats using the binary `COPY` format of Postgres
+fn encode_numeric_copy(values: &[sql_models::ColumnChunkNumeric]) -> Vec<u8> {
+    const FIELDS: i16 = 10;
+
+    fn push_i32(buf: &mut Vec<u8>, v: i32) {
+        buf.extend_from_slice(&4_i32.to_be_bytes());
+        buf.extend_from_slice(&v.to_be_bytes());
+    }
+
+    fn push_f64(buf: &mut Vec<u8>, v: Option<f64>) {
+        match v {
+            Some(v) => {
+                buf.extend_from_slice(&8_i32.to_be_bytes());
+                buf.extend_from_slice(&v.to_be_bytes());
+            }
+            // Null values are encoded with a length of -1 and no data
+            None => buf.extend_from_slice(&(-1_i32).to_be_bytes()),
+        }
+    }
+
+    fn push_i64(buf: &mut Vec<u8>, v: Option<i64>) {
+        match v {
+            Some(v) => {
+                buf.extend_from_slice(&8_i32.to_be_bytes());
+                buf.extend_from_slice(&v.to_be_bytes());
+            }
+            None => buf.extend_from_slice(&(-1_i32).to_be_bytes()),
+        }
+    }
+
+    fn push_bool(buf: &mut Vec<u8>, v: bool) {
+        buf.extend_from_slice(&1_i32.to_be_bytes());
+        buf.push(u8::from(v));
+    }
+
+    // Signature, flags and header extension length
+    let mut buf = b"PGCOPY\n\xff\r\n\0".to_vec();
+    buf.extend_from_slice(&0_i32.to_be_bytes());
+    buf.extend_from_slice(&0_i32.to_be_bytes());
+
+    for val in values {
+        buf.extend_from_slice(&FIELDS.to_be_bytes());
+        push_i32(&mut buf, val.column_id);
+        push_i32(&mut buf, val.chunk_id);
+        push_f64(&mut buf, Some(val.min_value));
+        push_f64(&mut buf, Some(val.max_value));
+        push_bool(&mut buf, val.has_null);
+        push_bool(&mut buf, val.has_nan);
+        push_bool(&mut buf, val.has_inf);
+        push_f64(&mut buf, val.p50_value);
+        push_f64(&mut buf, val.p95_value);
+        push_i64(&mut buf, val.null_count);
+    }
+
+    // File trailer
+    buf.extend_from_slice(&(-1_i16).to_be_bytes());
+    buf
+}
+
 /// Batch insert multiple literal column chunk stats in a single query.
 /// More efficient than individual inserts when inserting many stats.
 pub async fn column_chunk_literal_create_batch(
@@ -136,7 +329,7 @@ pub async fn column_chunk_literal_create_batch(
     }
 
     let mut query_builder: sqlx::QueryBuilder<sqlx::Postgres> = sqlx::QueryBuilder::new(
-        "INSERT INTO column_chunk_literal_t(column_id, chunk_id, min_value, max_value, has_null) ",
+        "INSERT INTO column_chunk_literal_t(column_id, chunk_id, min_value, max_value, has_null, null_count) ",
     );
 
     query_builder.push_values(values, |mut b, val| {
@@ -144,6 +337,33 @@ pub async fn column_chunk_literal_create_batch(
             .push_bind(val.chunk_id)
             .push_bind(&val.min_value)
             .push_bind(&val.max_value)
+            .push_bind(val.has_null)
+            .push_bind(val.null_count);
+    });
+
+    query_builder.build().execute(exec.as_exec()).await?;
+    Ok(())
+}
+
+/// Batch insert multiple boolean column chunk stats in a single query.
+/// More efficient than individual inserts when inserting many stats.
+pub async fn column_chunk_boolean_create_batch(
+    exec: &mut impl repo::AsExec,
+    values: &[sql_models::ColumnChunkBoolean],
+) -> Result<(), repo::Error> {
+    if values.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder: sqlx::QueryBuilder<sqlx::Postgres> = sqlx::QueryBuilder::new(
+        "INSERT INTO column_chunk_boolean_t(column_id, chunk_id, has_true, has_false, has_null) ",
+    );
+
+    query_builder.push_values(values, |mut b, val| {
+        b.push_bind(val.column_id)
+            .push_bind(val.chunk_id)
+            .push_bind(val.has_true)
+            .push_bind(val.has_false)
             .push_bind(val.has_null);
     });
 
@@ -151,21 +371,130 @@ pub async fn column_chunk_literal_create_batch(
     Ok(())
 }
 
+/// Returns all the chunks of the topics of the sequence `loc`
+pub async fn sequence_find_all_chunks(
+    exec: &mut impl repo::AsExec,
+    loc: &types::SequenceResourceLocator,
+) -> Result<Vec<sql_models::Chunk>, repo::Error> {
+    let res = sqlx::query_as!(
+        sql_models::Chunk,
+        r#"
+            SELECT chunk.*
+            FROM chunk_t AS chunk
+            JOIN topic_t AS topic ON chunk.topic_id = topic.topic_id
+            JOIN sequence_t AS sequence ON topic.sequence_id = sequence.sequence_id
+            WHERE sequence.locator_name = $1
+            ORDER BY chunk.chunk_id
+    "#,
+        loc.name(),
+    )
+    .fetch_all(exec.as_exec())
+    .await?;
+
+    Ok(res)
+}
+
+/// Returns all the chunks of the topic `loc`
+pub async fn topic_find_all_chunks(
+    exec: &mut impl repo::AsExec,
+    loc: &types::TopicResourceLocator,
+) -> Result<Vec<sql_models::Chunk>, repo::Error> {
+    let res = sqlx::query_as!(
+        sql_models::Chunk,
+        r#"
+            SELECT chunk.*
+            FROM chunk_t AS chunk
+            JOIN topic_t AS topic ON chunk.topic_id = topic.topic_id
+            WHERE topic.locator_name = $1
+            ORDER BY chunk.chunk_id
+    "#,
+        loc.name(),
+    )
+    .fetch_all(exec.as_exec())
+    .await?;
+
+    Ok(res)
+}
+
+/// Deletes the chunk `chunk_id` along with its column statistics
+pub async fn chunk_delete(exec: &mut impl repo::AsExec, chunk_id: i32) -> Result<(), repo::Error> {
+    let res = sqlx::query!("DELETE FROM chunk_t WHERE chunk_id = $1", chunk_id)
+        .execute(exec.as_exec())
+        .await?;
+
+    if res.rows_affected() == 0 {
+        return Err(repo::Error::NotFound);
+    }
+    Ok(())
+}
+
+/// Deletes all the column statistics (numeric, literal and boolean) of the chunk `chunk_id`
+pub async fn chunk_stats_delete(
+    exec: &mut impl repo::AsExec,
+    chunk_id: i32,
+) -> Result<(), repo::Error> {
+    sqlx::query!(
+        "DELETE FROM column_chunk_numeric_t WHERE chunk_id = $1",
+        chunk_id
+    )
+    .execute(exec.as_exec())
+    .await?;
+    sqlx::query!(
+        "DELETE FROM column_chunk_literal_t WHERE chunk_id = $1",
+        chunk_id
+    )
+    .execute(exec.as_exec())
+    .await?;
+    sqlx::query!(
+        "DELETE FROM column_chunk_boolean_t WHERE chunk_id = $1",
+        chunk_id
+    )
+    .execute(exec.as_exec())
+    .await?;
+
+    Ok(())
+}
+
+/// Sets the timestamp bounds (in milliseconds) of the chunk `chunk_id`, [`None`] if the
+/// chunk has no timestamps
+pub async fn chunk_update_timestamp_bounds(
+    exec: &mut impl repo::AsExec,
+    chunk_id: i32,
+    bounds: Option<(types::Timestamp, types::Timestamp)>,
+) -> Result<(), repo::Error> {
+    let (min, max): (Option<i64>, Option<i64>) = match bounds {
+        Some((min, max)) => (Some(min.into()), Some(max.into())),
+        None => (None, None),
+    };
+    sqlx::query!(
+        "UPDATE chunk_t SET min_unix_tstamp = $2, max_unix_tstamp = $3 WHERE chunk_id = $1",
+        chunk_id,
+        min,
+        max
+    )
+    .execute(exec.as_exec())
+    .await?;
+
+    Ok(())
+}
+
 /// Returns the list of chunks matching the provided `filter` criteria.
 /// Optionally the query can be fitlered across a list of topics (`on_topics`).
+/// With `quantile_pruning` chunks are also discarded using approximate quantiles (see
+/// [`super::ChunkQueryBuilder`]).
+///
+/// If a (inclusive) `time_range` is provided, only the chunks whose timestamp bounds overlap
+/// the range are returned. Chunks without bounds are always returned.
 pub async fn chunks_from_filters(
     exec: &mut impl repo::AsExec,
     filter: query::ExprGroup<query::Value>,
     on_topics: Option<&Vec<sql_models::TopicRecord>>, // (cabba) TODO: pass only topic names or ids?
+    quantile_pruning: bool,
+    time_range: Option<&query::Range<types::Timestamp>>,
 ) -> Result<Vec<sql_models::Chunk>, repo::Error> {
-    // Collect topic ids, if any
-    let ids: Vec<i64> = if let Some(topics) = on_topics {
-        topics.iter().map(|t| t.topic_id as i64).collect()
-    } else {
-        Vec::new()
-    };
-
-    let (query, values) = super::ChunkQueryBuilder::build(filter, ids)?;
+    let (query, values) =
+        chunks_query_from_filters(filter, on_topics, quantile_pruning, time_range)?;
+    check_bound_values(&query, &values)?;
 
     trace!("chunk SQL query values: {:?}", values);
     trace!("chunk SQL query: {}", &query);
@@ -177,8 +506,7 @@ pub async fn chunks_from_filters(
             query::Value::Integer(v) => r = r.bind(v),
             query::Value::Float(v) => r = r.bind(v),
             query::Value::Text(v) => r = r.bind(v),
-            // Cast boolean value to numeric, since for now there is no custom column for boolean values
-            query::Value::Boolean(v) => r = r.bind(if v { 1.0 } else { 0.0 }),
+            query::Value::Boolean(v) => r = r.bind(v),
         }
     }
 
@@ -186,6 +514,68 @@ pub async fn chunks_from_filters(
     r.into_iter().collect()
 }
 
+/// Compiles the SQL query used by [`chunks_from_filters`], along with the values bound to
+/// its placeholders, without running it.
+pub fn chunks_query_from_filters(
+    filter: query::ExprGroup<query::Value>,
+    on_topics: Option<&Vec<sql_models::TopicRecord>>,
+    quantile_pruning: bool,
+    time_range: Option<&query::Range<types::Timestamp>>,
+) -> Result<(String, Vec<query::Value>), repo::Error> {
+    // Collect topic ids, if any
+    let ids: Vec<i64> = if let Some(topics) = on_topics {
+        topics.iter().map(|t| t.topic_id as i64).collect()
+    } else {
+        Vec::new()
+    };
+
+    let (mut query, mut values) = super::ChunkQueryBuilder::build(filter, ids, quantile_pruning)?;
+
+    if let Some(range) = time_range {
+        let (pmin, pmax) = (values.len() + 1, values.len() + 2);
+        query.push_str(&format!(
+            " WHERE chunk_t.min_unix_tstamp IS NULL OR chunk_t.max_unix_tstamp IS NULL \
+            OR (chunk_t.max_unix_tstamp >= ${pmin} AND chunk_t.min_unix_tstamp <= ${pmax})"
+        ));
+        values.push(query::Value::Integer(range.min.into()));
+        values.push(query::Value::Integer(range.max.into()));
+    }
+
+    Ok((query, values))
+}
+
+/// Ensures that the highest placeholder (`$N`) used in `query` matches the number of
+/// `values` to bind, placeholders can be used more than once. Text within single quotes
+/// (e.g. metadata paths) is skipped.
+fn check_bound_values(query: &str, values: &[query::Value]) -> Result<(), repo::Error> {
+    let mut placeholders = 0;
+    let mut quoted = false;
+    let mut chars = query.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => quoted = !quoted,
+            '$' if !quoted => {
+                let mut n = 0;
+                while let Some(d) = chars.peek().and_then(|d| d.to_digit(10)) {
+                    n = n * 10 + d as usize;
+                    chars.next();
+                }
+                placeholders = placeholders.max(n);
+            }
+            _ => {}
+        }
+    }
+
+    if placeholders != values.len() {
+        return Err(repo::Error::BoundValuesMismatch {
+            placeholders,
+            values: values.len(),
+        });
+    }
+    Ok(())
+}
+
 fn cast_chunk_data(row: PgRow) -> Result<sql_models::Chunk, repo::Error> {
     Ok(sql_models::Chunk {
         chunk_id: row.try_get("chunk_id")?,
@@ -194,10 +584,15 @@ fn cast_chunk_data(row: PgRow) -> Result<sql_models::Chunk, repo::Error> {
         data_file: row.try_get("data_file")?,
         size_bytes: row.try_get("size_bytes")?,
         row_count: row.try_get("row_count")?,
+        creation_unix_tstamp: row.try_get("creation_unix_tstamp")?,
+        min_unix_tstamp: row.try_get("min_unix_tstamp")?,
+        max_unix_tstamp: row.try_get("max_unix_tstamp")?,
+        checksum: row.try_get("checksum")?,
     })
 }
 
-/// Returns aggregated size and row count statistics for all chunks belonging to a topic.
+/// Returns aggregated size, row count and last creation time statistics for all chunks
+/// belonging to a topic.
 pub async fn topic_get_stats(
     exec: &mut impl repo::AsExec,
     loc: &types::TopicResourceLocator,
@@ -205,7 +600,9 @@ pub async fn topic_get_stats(
     let res = sqlx::query!(
         r#"SELECT
             COALESCE(SUM(size_bytes), 0)::BIGINT as "total_size_bytes!",
-            COALESCE(SUM(row_count), 0)::BIGINT as "total_row_count!"
+            COALESCE(SUM(row_count), 0)::BIGINT as "total_row_count!",
+            COUNT(*) as "total_chunks!",
+            MAX(creation_unix_tstamp) as "last_chunk_unix_tstamp?"
         FROM chunk_t
         WHERE topic_id = (SELECT topic_id FROM topic_t WHERE locator_name = $1)"#,
         loc.name(),
@@ -216,5 +613,840 @@ pub async fn topic_get_stats(
     Ok(types::TopicChunksStats {
         total_size_bytes: res.total_size_bytes,
         total_row_count: res.total_row_count,
+        total_chunks: res.total_chunks,
+        last_chunk_timestamp: res.last_chunk_unix_tstamp.map(types::Timestamp::from),
+    })
+}
+
+/// Returns the same statistics of [`topic_get_stats`] for each of the provided topics in a
+/// single query, indexed by topic id.
+///
+/// Topics without chunks are not reported.
+pub async fn topics_get_stats(
+    exec: &mut impl repo::AsExec,
+    topic_ids: &[i32],
+) -> Result<HashMap<i32, types::TopicChunksStats>, repo::Error> {
+    trace!("collecting chunks stats of #{} topics", topic_ids.len());
+    let res = sqlx::query!(
+        r#"SELECT
+            topic_id,
+            COALESCE(SUM(size_bytes), 0)::BIGINT as "total_size_bytes!",
+            COALESCE(SUM(row_count), 0)::BIGINT as "total_row_count!",
+            COUNT(*) as "total_chunks!",
+            MAX(creation_unix_tstamp) as "last_chunk_unix_tstamp?"
+        FROM chunk_t
+        WHERE topic_id = ANY($1)
+        GROUP BY topic_id"#,
+        topic_ids,
+    )
+    .fetch_all(exec.as_exec())
+    .await?;
+
+    Ok(res
+        .into_iter()
+        .map(|r| {
+            (
+                r.topic_id,
+                types::TopicChunksStats {
+                    total_size_bytes: r.total_size_bytes,
+                    total_row_count: r.total_row_count,
+                    total_chunks: r.total_chunks,
+                    last_chunk_timestamp: r.last_chunk_unix_tstamp.map(types::Timestamp::from),
+                },
+            )
+        })
+        .collect())
+}
+
+/// Returns the chunks of a topic having NaN or null values for the topic `field`, ordered by
+/// timestamp bounds (chunks without bounds come last).
+///
+/// Only the chunk statistics are read, the field is searched among the columns of the topic
+/// ontology tag.
+pub async fn topic_field_quality_chunks(
+    exec: &mut impl repo::AsExec,
+    loc: &types::TopicResourceLocator,
+    field: &str,
+) -> Result<Vec<types::ChunkQuality>, repo::Error> {
+    let rows = sqlx::query!(
+        r#"
+            SELECT
+                chunk.chunk_uuid, chunk.min_unix_tstamp, chunk.max_unix_tstamp,
+                stats.has_nan AS "has_nan!", stats.has_null AS "has_null!"
+            FROM chunk_t AS chunk
+            JOIN topic_t AS topic ON chunk.topic_id = topic.topic_id
+            JOIN (
+                SELECT chunk_id, column_id, has_nan, has_null FROM column_chunk_numeric_t
+                UNION ALL
+                SELECT chunk_id, column_id, FALSE, has_null FROM column_chunk_literal_t
+                UNION ALL
+                SELECT chunk_id, column_id, FALSE, has_null FROM column_chunk_boolean_t
+            ) AS stats ON stats.chunk_id = chunk.chunk_id
+            JOIN column_t AS col ON stats.column_id = col.column_id
+            WHERE topic.locator_name = $1
+                AND col.column_name = $2
+                AND col.ontology_tag = topic.ontology_tag
+                AND (stats.has_nan OR stats.has_null)
+            ORDER BY chunk.min_unix_tstamp NULLS LAST, chunk.max_unix_tstamp NULLS LAST, chunk.chunk_id
+    "#,
+        loc.name(),
+        field,
+    )
+    .fetch_all(exec.as_exec())
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| types::ChunkQuality {
+            chunk_uuid: row.chunk_uuid,
+            timestamp_range: row
+                .min_unix_tstamp
+                .zip(row.max_unix_tstamp)
+                .map(|(min, max)| (min.into(), max.into())),
+            has_nan: row.has_nan,
+            has_null: row.has_null,
+        })
+        .collect())
+}
+
+/// Aggregates the numeric and literal column statistics of all the chunks of a topic.
+///
+/// Placeholders stored for chunks without finite (or non null) values are not considered
+/// when computing the bounds.
+///
+/// Row counts are the rows of the whole topic: the rows of the chunks not holding a field
+/// (e.g. written before the field was added) are counted as nulls of the field.
+pub async fn topic_fields_stats(
+    exec: &mut impl repo::AsExec,
+    loc: &types::TopicResourceLocator,
+) -> Result<types::TopicFieldsStats, repo::Error> {
+    let numeric = sqlx::query!(
+        r#"
+            SELECT
+                col.column_name,
+                MIN(stats.min_value) FILTER (WHERE stats.min_value <= stats.max_value) AS "min_value?",
+                MAX(stats.max_value) FILTER (WHERE stats.min_value <= stats.max_value) AS "max_value?",
+                BOOL_OR(stats.has_null) AS "has_null!",
+                BOOL_OR(stats.has_nan) AS "has_nan!",
+                BOOL_OR(stats.has_inf) AS "has_inf!",
+                CASE WHEN COUNT(stats.null_count) = COUNT(*)
+                    THEN SUM(stats.null_count)::BIGINT
+                END AS "null_count?",
+                SUM(chunk.row_count)::BIGINT AS "row_count!"
+            FROM column_chunk_numeric_t AS stats
+            JOIN chunk_t AS chunk ON stats.chunk_id = chunk.chunk_id
+            JOIN topic_t AS topic ON chunk.topic_id = topic.topic_id
+            JOIN column_t AS col ON stats.column_id = col.column_id
+            WHERE topic.locator_name = $1
+            GROUP BY col.column_name
+            ORDER BY col.column_name
+    "#,
+        loc.name(),
+    )
+    .fetch_all(exec.as_exec())
+    .await?;
+
+    let literal = sqlx::query!(
+        r#"
+            SELECT
+                col.column_name,
+                MIN(NULLIF(stats.min_value, '')) AS "min_value?",
+                MAX(NULLIF(stats.max_value, '')) AS "max_value?",
+                BOOL_OR(stats.has_null) AS "has_null!",
+                CASE WHEN COUNT(stats.null_count) = COUNT(*)
+                    THEN SUM(stats.null_count)::BIGINT
+                END AS "null_count?",
+                SUM(chunk.row_count)::BIGINT AS "row_count!"
+            FROM column_chunk_literal_t AS stats
+            JOIN chunk_t AS chunk ON stats.chunk_id = chunk.chunk_id
+            JOIN topic_t AS topic ON chunk.topic_id = topic.topic_id
+            JOIN column_t AS col ON stats.column_id = col.column_id
+            WHERE topic.locator_name = $1
+            GROUP BY col.column_name
+            ORDER BY col.column_name
+    "#,
+        loc.name(),
+    )
+    .fetch_all(exec.as_exec())
+    .await?;
+
+    let total_row_count = topic_get_stats(exec, loc).await?.total_row_count;
+    // Rows of the topic in the chunks not holding the field
+    let missing = |row_count: i64| total_row_count - row_count;
+
+    Ok(types::TopicFieldsStats {
+        numeric: numeric
+            .into_iter()
+            .map(|row| types::NumericFieldStats {
+                field: row.column_name,
+                min: row.min_value,
+                max: row.max_value,
+                has_null: row.has_null || missing(row.row_count) > 0,
+                has_nan: row.has_nan,
+                has_inf: row.has_inf,
+                null_count: row.null_count.map(|n| n + missing(row.row_count)),
+                row_count: total_row_count,
+            })
+            .collect(),
+        literal: literal
+            .into_iter()
+            .map(|row| types::LiteralFieldStats {
+                field: row.column_name,
+                min: row.min_value,
+                max: row.max_value,
+                has_null: row.has_null || missing(row.row_count) > 0,
+                null_count: row.null_count.map(|n| n + missing(row.row_count)),
+                row_count: total_row_count,
+            })
+            .collect(),
     })
 }
+
+/// Returns the statistics of the literal field `field` for each chunk of the topic `loc`
+/// holding it. No statistics are returned if the field is not a literal field.
+pub async fn topic_literal_field_chunk_stats(
+    exec: &mut impl repo::AsExec,
+    loc: &types::TopicResourceLocator,
+    field: &str,
+) -> Result<Vec<types::LiteralFieldStats>, repo::Error> {
+    let rows = sqlx::query!(
+        r#"
+            SELECT stats.min_value, stats.max_value, stats.has_null, stats.null_count, chunk.row_count
+            FROM column_chunk_literal_t AS stats
+            JOIN chunk_t AS chunk ON stats.chunk_id = chunk.chunk_id
+            JOIN topic_t AS topic ON chunk.topic_id = topic.topic_id
+            JOIN column_t AS col ON stats.column_id = col.column_id
+            WHERE topic.locator_name = $1 AND col.column_name = $2
+    "#,
+        loc.name(),
+        field,
+    )
+    .fetch_all(exec.as_exec())
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| types::LiteralFieldStats {
+            field: field.to_owned(),
+            min: Some(row.min_value),
+            max: Some(row.max_value),
+            has_null: row.has_null,
+            null_count: row.null_count,
+            row_count: row.row_count,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::Pool;
+
+    use super::*;
+    use crate::repo::AsExec;
+
+    #[test]
+    fn bound_values() {
+        let values = vec![query::Value::Integer(1), query::Value::Integer(2)];
+
+        check_bound_values("SELECT * FROM t WHERE a > $1 AND a < $2 OR b = $1", &values).unwrap();
+        // Placeholders within quoted text are not counted
+        check_bound_values("SELECT '{$3}' FROM t WHERE a > $1 AND a < $2", &values).unwrap();
+
+        let Err(repo::Error::BoundValuesMismatch {
+            placeholders,
+            values,
+        }) = check_bound_values("SELECT * FROM t WHERE a > $1", &values)
+        else {
+            panic!("wrong error returned")
+        };
+        assert_eq!((placeholders, values), (1, 2));
+    }
+
+    #[sqlx::test]
+    async fn topic_stats_last_chunk(pool: Pool<repo::Database>) -> sqlx::Result<()> {
+        let repo = repo::testing::Repository::new(pool);
+        let mut cx = repo.connection();
+
+        let sequence =
+            super::super::sequence_create(&mut cx, &sql_models::SequenceRecord::new("seq"))
+                .await
+                .unwrap();
+        let topic = super::super::topic_create(
+            &mut cx,
+            &sql_models::TopicRecord::new("seq/topic", sequence.sequence_id),
+        )
+        .await
+        .unwrap();
+        let loc = types::TopicResourceLocator::from("seq/topic");
+
+        let stats = topic_get_stats(&mut cx, &loc).await.unwrap();
+        assert_eq!(stats.total_row_count, 0);
+        assert!(stats.last_chunk_timestamp.is_none());
+
+        let first = chunk_create(
+            &mut cx,
+            &sql_models::Chunk::new(topic.topic_id, "seq/topic/0.parquet", 10, 1),
+        )
+        .await
+        .unwrap();
+        let last = chunk_create(
+            &mut cx,
+            &sql_models::Chunk::new(topic.topic_id, "seq/topic/1.parquet", 10, 1),
+        )
+        .await
+        .unwrap();
+
+        let stats = topic_get_stats(&mut cx, &loc).await.unwrap();
+        assert_eq!(stats.total_row_count, 2);
+        assert_eq!(stats.last_chunk_timestamp, Some(last.creation_timestamp()));
+        assert!(first.creation_timestamp() <= last.creation_timestamp());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn chunk_batch_ids(pool: Pool<repo::Database>) -> sqlx::Result<()> {
+        let repo = repo::testing::Repository::new(pool);
+        let mut cx = repo.connection();
+
+        let sequence =
+            super::super::sequence_create(&mut cx, &sql_models::SequenceRecord::new("/seq"))
+                .await
+                .unwrap();
+        let topic = super::super::topic_create(
+            &mut cx,
+            &sql_models::TopicRecord::new("/seq/topic", sequence.sequence_id),
+        )
+        .await
+        .unwrap();
+
+        let chunks = (0..16)
+            .map(|idx| {
+                sql_models::Chunk::new(topic.topic_id, format!("/seq/topic/{idx}.parquet"), 10, idx)
+            })
+            .collect();
+        let chunks = chunk_create_batch(&mut cx, chunks).await.unwrap();
+
+        assert_eq!(chunks.len(), 16);
+        for (idx, chunk) in chunks.iter().enumerate() {
+            assert_ne!(chunk.chunk_id, repo::UNREGISTERED);
+            let (data_file, row_count): (String, i64) =
+                sqlx::query_as("SELECT data_file, row_count FROM chunk_t WHERE chunk_id = $1")
+                    .bind(chunk.chunk_id)
+                    .fetch_one(cx.as_exec())
+                    .await
+                    .unwrap();
+            assert_eq!(data_file, format!("/seq/topic/{idx}.parquet"));
+            assert_eq!(row_count, idx as i64);
+        }
+
+        assert!(
+            chunk_create_batch(&mut cx, Vec::new())
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn topic_fields_stats_aggregate(pool: Pool<repo::Database>) -> sqlx::Result<()> {
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let mut cx = repo.connection();
+
+        let sequence =
+            super::super::sequence_create(&mut cx, &sql_models::SequenceRecord::new("seq"))
+                .await
+                .unwrap();
+        let topic = super::super::topic_create(
+            &mut cx,
+            &sql_models::TopicRecord::new("seq/topic", sequence.sequence_id),
+        )
+        .await
+        .unwrap();
+        let loc = types::TopicResourceLocator::from("seq/topic");
+        let acc = column_get_or_create(&mut cx, "acc", "imu").await.unwrap();
+        let label = column_get_or_create(&mut cx, "label", "imu").await.unwrap();
+
+        let mut first = types::NumericStats::new();
+        first.eval(&Some(1.0));
+        first.eval(&Some(5.0));
+        let mut second = types::NumericStats::new();
+        second.eval(&Some(-2.0));
+        second.eval(&None);
+        // Chunk holding only NaN values, its placeholders must not affect the bounds
+        let mut third = types::NumericStats::new();
+        third.eval(&Some(f64::NAN));
+
+        for (i, stats) in [first, second, third].iter().enumerate() {
+            let chunk = chunk_create(
+                &mut cx,
+                &sql_models::Chunk::new(topic.topic_id, &format!("seq/topic/{i}.parquet"), 10, 1),
+            )
+            .await
+            .unwrap();
+            column_chunk_numeric_create(
+                &mut cx,
+                &sql_models::ColumnChunkNumeric::new(acc.column_id, chunk.chunk_id, stats),
+            )
+            .await
+            .unwrap();
+            column_chunk_literal_create(
+                &mut cx,
+                &sql_models::ColumnChunkLiteral::try_new(
+                    label.column_id,
+                    chunk.chunk_id,
+                    format!("b{i}"),
+                    format!("z{i}"),
+                    false,
+                )
+                .unwrap()
+                .with_null_count(0),
+            )
+            .await
+            .unwrap();
+        }
+
+        let stats = topic_fields_stats(&mut cx, &loc).await.unwrap();
+
+        assert_eq!(
+            stats.numeric,
+            vec![types::NumericFieldStats {
+                field: "acc".to_owned(),
+                min: Some(-2.0),
+                max: Some(5.0),
+                has_null: true,
+                has_nan: true,
+                has_inf: false,
+                null_count: Some(1),
+                row_count: 3,
+            }]
+        );
+        let completeness = stats.numeric[0].completeness().unwrap();
+        assert!((completeness - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(
+            stats.literal,
+            vec![types::LiteralFieldStats {
+                field: "label".to_owned(),
+                min: Some("b0".to_owned()),
+                max: Some("z2".to_owned()),
+                has_null: false,
+                null_count: Some(0),
+                row_count: 3,
+            }]
+        );
+        assert_eq!(stats.literal[0].completeness(), Some(1.0));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    /// Test checking that the rows of the chunks not holding a field are counted as nulls of
+    /// the field, so that the completeness is relative to all the rows of the topic.
+    async fn topic_fields_stats_missing_field(pool: Pool<repo::Database>) -> sqlx::Result<()> {
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let mut cx = repo.connection();
+
+        let sequence =
+            super::super::sequence_create(&mut cx, &sql_models::SequenceRecord::new("seq"))
+                .await
+                .unwrap();
+        let topic = super::super::topic_create(
+            &mut cx,
+            &sql_models::TopicRecord::new("seq/topic", sequence.sequence_id),
+        )
+        .await
+        .unwrap();
+        let loc = types::TopicResourceLocator::from("seq/topic");
+        let acc = column_get_or_create(&mut cx, "acc", "imu").await.unwrap();
+
+        let mut stats = types::NumericStats::new();
+        stats.eval(&Some(1.0));
+        stats.eval(&None);
+        stats.eval(&Some(3.0));
+
+        // Chunk holding the field
+        let chunk = chunk_create(
+            &mut cx,
+            &sql_models::Chunk::new(topic.topic_id, "seq/topic/0.parquet", 10, 3),
+        )
+        .await
+        .unwrap();
+        column_chunk_numeric_create(
+            &mut cx,
+            &sql_models::ColumnChunkNumeric::new(acc.column_id, chunk.chunk_id, &stats),
+        )
+        .await
+        .unwrap();
+
+        // Chunk written before the field was added
+        chunk_create(
+            &mut cx,
+            &sql_models::Chunk::new(topic.topic_id, "seq/topic/1.parquet", 10, 5),
+        )
+        .await
+        .unwrap();
+
+        let stats = topic_fields_stats(&mut cx, &loc).await.unwrap();
+
+        assert_eq!(
+            stats.numeric,
+            vec![types::NumericFieldStats {
+                field: "acc".to_owned(),
+                min: Some(1.0),
+                max: Some(3.0),
+                has_null: true,
+                has_nan: false,
+                has_inf: false,
+                null_count: Some(6),
+                row_count: 8,
+            }]
+        );
+        let completeness = stats.numeric[0].completeness().unwrap();
+        assert!((completeness - 2.0 / 8.0).abs() < 1e-9);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn chunks_time_range_pruning(pool: Pool<repo::Database>) -> sqlx::Result<()> {
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let mut cx = repo.connection();
+
+        let sequence =
+            super::super::sequence_create(&mut cx, &sql_models::SequenceRecord::new("/seq"))
+                .await
+                .unwrap();
+        let topic = super::super::topic_create(
+            &mut cx,
+            &sql_models::TopicRecord::new("/seq/topic", sequence.sequence_id),
+        )
+        .await
+        .unwrap();
+        let column = column_get_or_create(&mut cx, "acc", "imu").await.unwrap();
+
+        let mut stats = types::NumericStats::new();
+        stats.eval(&Some(1.0));
+
+        let chunks = [
+            sql_models::Chunk::new(topic.topic_id, "/seq/topic/0.parquet", 10, 1)
+                .with_timestamp_bounds(0.into(), 10.into()),
+            sql_models::Chunk::new(topic.topic_id, "/seq/topic/1.parquet", 10, 1)
+                .with_timestamp_bounds(20.into(), 30.into()),
+            // chunk without bounds
+            sql_models::Chunk::new(topic.topic_id, "/seq/topic/2.parquet", 10, 1),
+        ];
+        let mut ids = Vec::new();
+        for chunk in &chunks {
+            let chunk = chunk_create(&mut cx, chunk).await.unwrap();
+            column_chunk_numeric_create(
+                &mut cx,
+                &sql_models::ColumnChunkNumeric::new(column.column_id, chunk.chunk_id, &stats),
+            )
+            .await
+            .unwrap();
+            ids.push(chunk.chunk_id);
+        }
+
+        let filter = || {
+            let field = query::OntologyField::try_new("imu.acc".to_owned()).unwrap();
+            query::ExprGroup::new(vec![(field, query::Op::Gt(0.0.into())).into()])
+        };
+
+        let found = chunks_from_filters(&mut cx, filter(), None, false, None)
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 3);
+
+        let range = query::Range {
+            min: 15.into(),
+            max: 20.into(),
+        };
+        let mut found: Vec<i32> = chunks_from_filters(&mut cx, filter(), None, false, Some(&range))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|c| c.chunk_id)
+            .collect();
+        found.sort();
+        assert_eq!(found, vec![ids[1], ids[2]]);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn chunks_has_null_across_stats(pool: Pool<repo::Database>) -> sqlx::Result<()> {
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let mut cx = repo.connection();
+
+        let sequence =
+            super::super::sequence_create(&mut cx, &sql_models::SequenceRecord::new("/seq"))
+                .await
+                .unwrap();
+        let topic = super::super::topic_create(
+            &mut cx,
+            &sql_models::TopicRecord::new("/seq/topic", sequence.sequence_id),
+        )
+        .await
+        .unwrap();
+        let numeric = column_get_or_create(&mut cx, "acc", "imu").await.unwrap();
+        let literal = column_get_or_create(&mut cx, "frame_id", "imu")
+            .await
+            .unwrap();
+
+        let mut ids = Vec::new();
+        // nulls of the numeric column are in the first chunk, the ones of the literal
+        // column in the second one, both chunks hold NaN values
+        for (idx, numeric_null) in [true, false].into_iter().enumerate() {
+            let chunk = chunk_create(
+                &mut cx,
+                &sql_models::Chunk::new(topic.topic_id, format!("/seq/topic/{idx}.parquet"), 10, 1),
+            )
+            .await
+            .unwrap();
+
+            let mut stats = types::NumericStats::new();
+            stats.eval(&Some(f64::NAN));
+            if numeric_null {
+                stats.eval(&None);
+            }
+            column_chunk_numeric_create(
+                &mut cx,
+                &sql_models::ColumnChunkNumeric::new(numeric.column_id, chunk.chunk_id, &stats),
+            )
+            .await
+            .unwrap();
+
+            column_chunk_literal_create(
+                &mut cx,
+                &sql_models::ColumnChunkLiteral::try_new(
+                    literal.column_id,
+                    chunk.chunk_id,
+                    "a".to_owned(),
+                    "b".to_owned(),
+                    !numeric_null,
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+            ids.push(chunk.chunk_id);
+        }
+
+        async fn found(
+            cx: &mut repo::Cx<'_>,
+            field: &str,
+            op: query::Op<query::Value>,
+        ) -> Vec<i32> {
+            let field = query::OntologyField::try_new(field.to_owned()).unwrap();
+            let filter = query::ExprGroup::new(vec![(field, op).into()]);
+            let mut found: Vec<i32> = chunks_from_filters(cx, filter, None, false, None)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|c| c.chunk_id)
+                .collect();
+            found.sort();
+            found
+        }
+
+        assert_eq!(found(&mut cx, "imu.acc", query::Op::HasNan).await, ids);
+        assert_eq!(
+            found(&mut cx, "imu.acc", query::Op::HasNull).await,
+            vec![ids[0]]
+        );
+        assert_eq!(
+            found(&mut cx, "imu.frame_id", query::Op::HasNull).await,
+            vec![ids[1]]
+        );
+        assert!(
+            found(&mut cx, "imu.frame_id", query::Op::HasNan)
+                .await
+                .is_empty()
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn field_quality_chunks_ordering(pool: Pool<repo::Database>) -> sqlx::Result<()> {
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let mut cx = repo.connection();
+
+        let sequence =
+            super::super::sequence_create(&mut cx, &sql_models::SequenceRecord::new("seq"))
+                .await
+                .unwrap();
+        let topic = super::super::topic_create(
+            &mut cx,
+            &sql_models::TopicRecord::new("seq/topic", sequence.sequence_id)
+                .with_ontology_tag("imu"),
+        )
+        .await
+        .unwrap();
+        let column = column_get_or_create(&mut cx, "acc", "imu").await.unwrap();
+        // same field name, different ontology tag
+        let other = column_get_or_create(&mut cx, "acc", "gps").await.unwrap();
+
+        // (bounds, value) of each chunk, created out of timestamp order
+        let data: [(Option<(i64, i64)>, Option<f64>); 4] = [
+            (Some((20, 30)), Some(f64::NAN)),
+            (Some((0, 10)), None),
+            (None, Some(f64::NAN)),
+            (Some((10, 20)), Some(1.0)),
+        ];
+        let mut uuids = Vec::new();
+        for (idx, (bounds, value)) in data.into_iter().enumerate() {
+            let mut chunk =
+                sql_models::Chunk::new(topic.topic_id, format!("seq/topic/{idx}.parquet"), 10, 1);
+            if let Some((min, max)) = bounds {
+                chunk = chunk.with_timestamp_bounds(min.into(), max.into());
+            }
+            let chunk = chunk_create(&mut cx, &chunk).await.unwrap();
+
+            let mut stats = types::NumericStats::new();
+            stats.eval(&value);
+            column_chunk_numeric_create(
+                &mut cx,
+                &sql_models::ColumnChunkNumeric::new(column.column_id, chunk.chunk_id, &stats),
+            )
+            .await
+            .unwrap();
+
+            let mut stats = types::NumericStats::new();
+            stats.eval(&None);
+            column_chunk_numeric_create(
+                &mut cx,
+                &sql_models::ColumnChunkNumeric::new(other.column_id, chunk.chunk_id, &stats),
+            )
+            .await
+            .unwrap();
+
+            uuids.push(chunk.chunk_uuid);
+        }
+
+        let loc = types::TopicResourceLocator::from("seq/topic");
+        let found = topic_field_quality_chunks(&mut cx, &loc, "acc")
+            .await
+            .unwrap();
+
+        let found: Vec<_> = found
+            .into_iter()
+            .map(|c| (c.chunk_uuid, c.has_nan, c.has_null))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (uuids[1], false, true),
+                (uuids[0], true, false),
+                (uuids[2], true, false),
+            ]
+        );
+
+        Ok(())
+    }
+
+    /// Numeric stats loaded with `COPY` and with a multi-row `INSERT` produce the same rows
+    #[sqlx::test]
+    async fn numeric_stats_copy_insert_equivalence(pool: Pool<repo::Database>) -> sqlx::Result<()> {
+        let repo = repo::testing::Repository::new(pool);
+        let mut tx = repo.transaction().await.unwrap();
+
+        let sequence =
+            super::super::sequence_create(&mut tx, &sql_models::SequenceRecord::new("/seq"))
+                .await
+                .unwrap();
+        let topic = super::super::topic_create(
+            &mut tx,
+            &sql_models::TopicRecord::new("/seq/topic", sequence.sequence_id),
+        )
+        .await
+        .unwrap();
+
+        let mut columns = Vec::new();
+        for name in ["acc.x", "acc.y", "acc.z"] {
+            columns.push(column_get_or_create(&mut tx, name, "imu").await.unwrap());
+        }
+
+        let stats = |chunk_id: i32| -> Vec<sql_models::ColumnChunkNumeric> {
+            columns
+                .iter()
+                .enumerate()
+                .map(|(idx, column)| sql_models::ColumnChunkNumeric {
+                    column_id: column.column_id,
+                    chunk_id,
+                    min_value: -1.5 * idx as f64,
+                    max_value: f64::MAX,
+                    has_null: idx == 0,
+                    has_nan: idx == 1,
+                    has_inf: idx == 2,
+                    p50_value: (idx != 0).then_some(0.25),
+                    p95_value: (idx != 1).then_some(1e-300),
+                    null_count: (idx != 2).then_some(idx as i64 * 1_000_000_007),
+                })
+                .collect()
+        };
+
+        let inserted = chunk_create(
+            &mut tx,
+            &sql_models::Chunk::new(topic.topic_id, "/seq/topic/0.parquet", 10, 1),
+        )
+        .await
+        .unwrap();
+        let copied = chunk_create(
+            &mut tx,
+            &sql_models::Chunk::new(topic.topic_id, "/seq/topic/1.parquet", 10, 1),
+        )
+        .await
+        .unwrap();
+
+        column_chunk_numeric_create_batch(&mut tx, &stats(inserted.chunk_id))
+            .await
+            .unwrap();
+        column_chunk_numeric_copy(&mut tx, &stats(copied.chunk_id))
+            .await
+            .unwrap();
+
+        type NumericRow = (
+            i32,
+            f64,
+            f64,
+            bool,
+            bool,
+            bool,
+            Option<f64>,
+            Option<f64>,
+            Option<i64>,
+        );
+
+        let mut rows: Vec<Vec<NumericRow>> = Vec::new();
+        for chunk_id in [inserted.chunk_id, copied.chunk_id] {
+            rows.push(
+                sqlx::query_as(
+                    "SELECT column_id, min_value, max_value, has_null, has_nan, has_inf, p50_value, p95_value, null_count
+                    FROM column_chunk_numeric_t WHERE chunk_id = $1 ORDER BY column_id",
+                )
+                .bind(chunk_id)
+                .fetch_all(tx.as_exec())
+                .await
+                .unwrap(),
+            );
+        }
+
+        assert_eq!(rows[0].len(), columns.len());
+        assert_eq!(rows[0], rows[1]);
+
+        Ok(())
+    }
+}
@@ -115,3 +115,71 @@ pub async fn layer_find_all(
         .fetch_all(exe.as_exec())
         .await?)
 }
+
+/// Adds the sequence `sequence_id` to the layer `layer_id`, adding a sequence already in the
+/// layer has no effect
+pub async fn layer_add_sequence(
+    exe: &mut impl repo::AsExec,
+    layer_id: i32,
+    sequence_id: i32,
+) -> Result<(), repo::Error> {
+    sqlx::query!(
+        r#"
+          INSERT INTO layer_sequence_t
+            (layer_id, sequence_id)
+          VALUES
+            ($1, $2)
+          ON CONFLICT DO NOTHING
+    "#,
+        layer_id,
+        sequence_id,
+    )
+    .execute(exe.as_exec())
+    .await?;
+    Ok(())
+}
+
+/// Removes the sequence `sequence_id` from the layer `layer_id`.
+///
+/// Returns [`repo::Error::NotFound`] if the sequence does not belong to the layer.
+pub async fn layer_remove_sequence(
+    exe: &mut impl repo::AsExec,
+    layer_id: i32,
+    sequence_id: i32,
+) -> Result<(), repo::Error> {
+    let res = sqlx::query!(
+        r#"
+          DELETE FROM layer_sequence_t
+          WHERE layer_id=$1 AND sequence_id=$2
+    "#,
+        layer_id,
+        sequence_id,
+    )
+    .execute(exe.as_exec())
+    .await?;
+
+    if res.rows_affected() == 0 {
+        return Err(repo::Error::NotFound);
+    }
+    Ok(())
+}
+
+/// Return all the layers containing the sequence `sequence_id`, sorted by name
+pub async fn layers_find_by_sequence(
+    exe: &mut impl repo::AsExec,
+    sequence_id: i32,
+) -> Result<Vec<sql_models::Layer>, repo::Error> {
+    let res = sqlx::query_as!(
+        sql_models::Layer,
+        r#"
+          SELECT layer.* FROM layer_t AS layer
+          JOIN layer_sequence_t AS member ON member.layer_id = layer.layer_id
+          WHERE member.sequence_id=$1
+          ORDER BY layer.layer_name
+    "#,
+        sequence_id,
+    )
+    .fetch_all(exe.as_exec())
+    .await?;
+    Ok(res)
+}
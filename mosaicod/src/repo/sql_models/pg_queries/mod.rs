@@ -16,6 +16,9 @@ pub use data_catalog::*;
 mod layers;
 pub use layers::*;
 
+mod ontology_contracts;
+pub use ontology_contracts::*;
+
 mod group;
 pub use group::*;
 
@@ -51,6 +51,34 @@ pub async fn topic_notifies_find_by_locator(
     Ok(res)
 }
 
+/// Find the notifies of a topic with one of the given `notify_types`, most recent first.
+///
+/// If `limit` is provided at most `limit` notifies are returned.
+pub async fn topic_notifies_find_by_types(
+    exe: &mut impl repo::AsExec,
+    loc: &types::TopicResourceLocator,
+    notify_types: &[String],
+    limit: Option<i64>,
+) -> Result<Vec<sql_models::TopicNotify>, repo::Error> {
+    trace!("searching notifies for {} (types: {:?})", loc, notify_types);
+    let res = sqlx::query_as!(
+        sql_models::TopicNotify,
+        r#"
+          SELECT notify.* FROM topic_notify_t AS notify
+          JOIN topic_t AS topic ON notify.topic_id = topic.topic_id
+          WHERE topic.locator_name=$1 AND notify.notify_type = ANY($2)
+          ORDER BY notify.creation_unix_tstamp DESC, notify.topic_notify_id DESC
+          LIMIT $3
+    "#,
+        loc.name(),
+        notify_types,
+        limit,
+    )
+    .fetch_all(exe.as_exec())
+    .await?;
+    Ok(res)
+}
+
 /// Deletes a sequence notify from the repository
 ///
 /// If the notify does not exist, the operation has no effect.
@@ -62,6 +90,33 @@ pub async fn topic_notify_delete(exe: &mut impl repo::AsExec, id: i32) -> Result
     Ok(())
 }
 
+/// Deletes the notify `id` of a topic.
+///
+/// Returns [`repo::Error::NotFound`] if the topic has no notify with the given id.
+pub async fn topic_notify_delete_by_locator(
+    exe: &mut impl repo::AsExec,
+    loc: &types::TopicResourceLocator,
+    id: i32,
+) -> Result<(), repo::Error> {
+    trace!("deleting topic notify `{}` of {}", id, loc);
+    let res = sqlx::query!(
+        r#"
+          DELETE FROM topic_notify_t
+          WHERE topic_notify_id=$1
+            AND topic_id=(SELECT topic_id FROM topic_t WHERE locator_name=$2)
+    "#,
+        id,
+        loc.name(),
+    )
+    .execute(exe.as_exec())
+    .await?;
+
+    if res.rows_affected() == 0 {
+        return Err(repo::Error::NotFound);
+    }
+    Ok(())
+}
+
 pub async fn sequence_notify_create(
     exe: &mut impl repo::AsExec,
     notify: &sql_models::SequenceNotify,
@@ -107,6 +162,37 @@ pub async fn sequence_notifies_find_by_name(
     Ok(res)
 }
 
+/// Find the notifies of a sequence with one of the given `notify_types`, most recent first.
+///
+/// If `limit` is provided at most `limit` notifies are returned.
+pub async fn sequence_notifies_find_by_types(
+    exe: &mut impl repo::AsExec,
+    loc: &types::SequenceResourceLocator,
+    notify_types: &[String],
+    limit: Option<i64>,
+) -> Result<Vec<sql_models::SequenceNotify>, repo::Error> {
+    trace!(
+        "searching notifies for `{}` (types: {:?})",
+        loc, notify_types
+    );
+    let res = sqlx::query_as!(
+        sql_models::SequenceNotify,
+        r#"
+          SELECT notify.* FROM sequence_notify_t AS notify
+          JOIN sequence_t AS seq ON notify.sequence_id = seq.sequence_id
+          WHERE seq.locator_name=$1 AND notify.notify_type = ANY($2)
+          ORDER BY notify.creation_unix_tstamp DESC, notify.sequence_notify_id DESC
+          LIMIT $3
+    "#,
+        loc.name(),
+        notify_types,
+        limit,
+    )
+    .fetch_all(exe.as_exec())
+    .await?;
+    Ok(res)
+}
+
 /// Deletes a sequence report from the repository
 ///
 /// If the report does not exist, the operation has no effect.
@@ -123,3 +209,30 @@ pub async fn sequence_notify_delete(
     .await?;
     Ok(())
 }
+
+/// Deletes the notify `id` of a sequence.
+///
+/// Returns [`repo::Error::NotFound`] if the sequence has no notify with the given id.
+pub async fn sequence_notify_delete_by_locator(
+    exe: &mut impl repo::AsExec,
+    loc: &types::SequenceResourceLocator,
+    id: i32,
+) -> Result<(), repo::Error> {
+    trace!("deleting sequence notify `{}` of `{}`", id, loc);
+    let res = sqlx::query!(
+        r#"
+          DELETE FROM sequence_notify_t
+          WHERE sequence_notify_id=$1
+            AND sequence_id=(SELECT sequence_id FROM sequence_t WHERE locator_name=$2)
+    "#,
+        id,
+        loc.name(),
+    )
+    .execute(exe.as_exec())
+    .await?;
+
+    if res.rows_affected() == 0 {
+        return Err(repo::Error::NotFound);
+    }
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use log::trace;
+
+use crate::repo;
+
+/// Stores the contract of `ontology_tag`, replacing the existing one (if any)
+pub async fn ontology_contract_upsert(
+    exe: &mut impl repo::AsExec,
+    ontology_tag: &str,
+    contract: serde_json::Value,
+) -> Result<(), repo::Error> {
+    trace!("storing contract for ontology tag `{}`", ontology_tag);
+    sqlx::query!(
+        r#"
+          INSERT INTO ontology_contract_t
+            (ontology_tag, contract)
+          VALUES
+            ($1, $2)
+          ON CONFLICT (ontology_tag) DO UPDATE SET contract = EXCLUDED.contract
+    "#,
+        ontology_tag,
+        contract,
+    )
+    .execute(exe.as_exec())
+    .await?;
+    Ok(())
+}
+
+/// Returns the contract document of `ontology_tag`, [`None`] if the tag has no contract
+pub async fn ontology_contract_find_by_tag(
+    exe: &mut impl repo::AsExec,
+    ontology_tag: &str,
+) -> Result<Option<serde_json::Value>, repo::Error> {
+    let res = sqlx::query_scalar!(
+        r#"
+          SELECT contract
+          FROM ontology_contract_t
+          WHERE ontology_tag=$1
+    "#,
+        ontology_tag,
+    )
+    .fetch_optional(exe.as_exec())
+    .await?;
+    Ok(res)
+}
+
+/// Deletes the contract of `ontology_tag`.
+///
+/// Returns [`repo::Error::NotFound`] if the tag has no contract.
+pub async fn ontology_contract_delete(
+    exe: &mut impl repo::AsExec,
+    ontology_tag: &str,
+) -> Result<(), repo::Error> {
+    trace!("deleting contract for ontology tag `{}`", ontology_tag);
+    let res = sqlx::query!(
+        "DELETE FROM ontology_contract_t WHERE ontology_tag=$1",
+        ontology_tag,
+    )
+    .execute(exe.as_exec())
+    .await?;
+
+    if res.rows_affected() == 0 {
+        return Err(repo::Error::NotFound);
+    }
+    Ok(())
+}
@@ -1,10 +1,38 @@
 use log::trace;
+use sqlx::{Row, postgres::PgArguments, postgres::PgRow};
 
 use crate::{
+    query,
     repo::{self, Error, sql_models},
     types::{self, Resource},
 };
 
+fn cast_sequence_data(row: PgRow) -> Result<sql_models::SequenceRecord, Error> {
+    Ok(sql_models::SequenceRecord {
+        sequence_id: row.try_get("sequence_id")?,
+        sequence_uuid: row.try_get("sequence_uuid")?,
+        locator_name: row.try_get("locator_name")?,
+        locked: row.try_get("locked")?,
+        user_metadata: row.try_get("user_metadata")?,
+        creation_unix_tstamp: row.try_get("creation_unix_tstamp")?,
+    })
+}
+
+fn bind_values<'q>(
+    mut q: sqlx::query::Query<'q, repo::Database, PgArguments>,
+    values: &[query::Value],
+) -> sqlx::query::Query<'q, repo::Database, PgArguments> {
+    for v in values {
+        match v {
+            query::Value::Integer(v) => q = q.bind(*v),
+            query::Value::Float(v) => q = q.bind(*v),
+            query::Value::Text(v) => q = q.bind(v.clone()),
+            query::Value::Boolean(v) => q = q.bind(*v),
+        }
+    }
+    q
+}
+
 /// Find a sequence given its id.
 pub async fn sequence_find_by_id(
     exe: &mut impl repo::AsExec,
@@ -21,6 +49,23 @@ pub async fn sequence_find_by_id(
     Ok(res)
 }
 
+/// Find a sequence given its id, holding a shared lock on its row until the end of the
+/// transaction, so that the sequence can't be locked (or deleted) concurrently.
+pub async fn sequence_find_by_id_for_share(
+    exe: &mut impl repo::AsExec,
+    id: i32,
+) -> Result<sql_models::SequenceRecord, Error> {
+    trace!("searching sequence by id `{}` (for share)", id);
+    let res = sqlx::query_as!(
+        sql_models::SequenceRecord,
+        "SELECT * FROM sequence_t WHERE sequence_id=$1 FOR SHARE",
+        id
+    )
+    .fetch_one(exe.as_exec())
+    .await?;
+    Ok(res)
+}
+
 /// Find a sequence given its uuid.
 pub async fn sequence_find_by_uuid(
     exe: &mut impl repo::AsExec,
@@ -75,6 +120,31 @@ pub async fn sequence_find_all_topic_names(
         .collect())
 }
 
+/// Returns the locators of the topics of a sequence without any chunk
+pub async fn sequence_find_empty_topic_names(
+    exe: &mut impl repo::AsExec,
+    loc: &types::SequenceResourceLocator,
+) -> Result<Vec<types::TopicResourceLocator>, Error> {
+    trace!("searching empty topic locators by `{}`", loc);
+    let res = sqlx::query_scalar!(
+        r#"
+        SELECT topic.locator_name
+        FROM topic_t AS topic
+        JOIN sequence_t AS sequence ON topic.sequence_id = sequence.sequence_id
+        WHERE sequence.locator_name = $1
+            AND NOT EXISTS (SELECT 1 FROM chunk_t AS chunk WHERE chunk.topic_id = topic.topic_id)
+        ORDER BY topic.locator_name
+        "#,
+        loc.name()
+    )
+    .fetch_all(exe.as_exec())
+    .await?;
+    Ok(res
+        .into_iter()
+        .map(types::TopicResourceLocator::from)
+        .collect())
+}
+
 /// Return all sequences
 pub async fn sequence_find_all(
     exe: &mut impl repo::AsExec,
@@ -87,6 +157,76 @@ pub async fn sequence_find_all(
     )
 }
 
+/// Returns a page of the sequences matching the provided filter (sorted by name),
+/// along with the total number of sequences matching the filter.
+///
+/// If no filter is provided all the sequences are considered.
+pub async fn sequence_from_query_filter_paginated(
+    exe: &mut impl repo::AsExec,
+    filter: Option<query::SequenceFilter>,
+    limit: Option<usize>,
+    offset: usize,
+) -> Result<(Vec<sql_models::SequenceRecord>, usize), Error> {
+    let mut qb = query::ClausesCompiler::new();
+    let mut sql_fmt = super::SqlQueryCompiler::new();
+    let mut json_fmt = super::JsonQueryCompiler::new();
+
+    if let Some(seq) = filter {
+        if let Some(op) = seq.name {
+            qb = qb.expr("sequence.locator_name", op, &mut sql_fmt);
+        }
+
+        if let Some(op) = seq.creation {
+            qb = qb.expr("sequence.creation_unix_tstamp", op, &mut sql_fmt);
+        }
+
+        if let Some(mdata) = seq.user_metadata {
+            qb = qb.filter(
+                mdata.into_expr_group(),
+                json_fmt.with_field_and_placeholder(
+                    "sequence.user_metadata".into(),
+                    sql_fmt.current_placeholder(),
+                ),
+            );
+        }
+    }
+
+    let qr = qb.compile()?;
+
+    let where_clause = if qr.is_unfiltered() {
+        String::new()
+    } else {
+        format!(" WHERE {}", qr.clauses.join(" AND "))
+    };
+
+    let count_query = format!("SELECT COUNT(*) FROM sequence_t sequence{where_clause}");
+
+    let mut page_query = format!(
+        "SELECT sequence.* FROM sequence_t sequence{where_clause} ORDER BY sequence.locator_name"
+    );
+    if let Some(limit) = limit {
+        page_query.push_str(&format!(" LIMIT {limit}"));
+    }
+    if offset > 0 {
+        page_query.push_str(&format!(" OFFSET {offset}"));
+    }
+
+    trace!("query values: {:?}", qr.values);
+    trace!("generated SQL query: {}", page_query);
+
+    let total_count: i64 = bind_values(sqlx::query(&count_query), &qr.values)
+        .fetch_one(exe.as_exec())
+        .await?
+        .try_get(0)?;
+
+    let records = bind_values(sqlx::query(&page_query), &qr.values)
+        .map(cast_sequence_data)
+        .fetch_all(exe.as_exec())
+        .await?;
+
+    Ok((records, total_count as usize))
+}
+
 /// Deletes a sequence record from the repository **only if it is unlocked**.
 ///
 /// If the sequence is locked or does not exist, the operation has no effect.
@@ -164,6 +304,296 @@ pub async fn sequence_lock(
     Ok(())
 }
 
+/// Unlocks a sequence and all its topics, allowing new topics and data to be added.
+pub async fn sequence_unlock(
+    exe: &mut impl repo::AsExec,
+    loc: &types::SequenceResourceLocator,
+) -> Result<(), Error> {
+    trace!("unlocking `{}`", loc);
+    sqlx::query!(
+        r#"
+            UPDATE sequence_t
+            SET locked = FALSE
+            WHERE locator_name = $1
+    "#,
+        loc.name()
+    )
+    .execute(exe.as_exec())
+    .await?;
+
+    sqlx::query!(
+        r#"
+            UPDATE topic_t
+            SET locked = FALSE
+            FROM sequence_t AS sequence
+            WHERE topic_t.sequence_id = sequence.sequence_id AND sequence.locator_name = $1
+    "#,
+        loc.name()
+    )
+    .execute(exe.as_exec())
+    .await?;
+    Ok(())
+}
+
+/// Records a new audit entry for a sequence
+pub async fn sequence_audit_create(
+    exe: &mut impl repo::AsExec,
+    audit: &sql_models::SequenceAudit,
+) -> Result<sql_models::SequenceAudit, Error> {
+    trace!("creating a new sequence audit entry {:?}", audit);
+    let res = sqlx::query_as!(
+        sql_models::SequenceAudit,
+        r#"
+            INSERT INTO sequence_audit_t
+                (sequence_id, action, author, reason, creation_unix_tstamp)
+            VALUES
+                ($1, $2, $3, $4, $5)
+            RETURNING
+                *
+    "#,
+        audit.sequence_id,
+        audit.action,
+        audit.author,
+        audit.reason,
+        audit.creation_unix_tstamp,
+    )
+    .fetch_one(exe.as_exec())
+    .await?;
+    Ok(res)
+}
+
+/// Returns the audit entries of a sequence, most recent first
+pub async fn sequence_audits_find_by_locator(
+    exe: &mut impl repo::AsExec,
+    loc: &types::SequenceResourceLocator,
+) -> Result<Vec<sql_models::SequenceAudit>, Error> {
+    trace!("searching audit entries for {}", loc);
+    let res = sqlx::query_as!(
+        sql_models::SequenceAudit,
+        r#"
+          SELECT audit.* FROM sequence_audit_t AS audit
+          JOIN sequence_t AS sequence ON audit.sequence_id = sequence.sequence_id
+          WHERE sequence.locator_name = $1
+          ORDER BY audit.creation_unix_tstamp DESC, audit.sequence_audit_id DESC
+    "#,
+        loc.name()
+    )
+    .fetch_all(exe.as_exec())
+    .await?;
+    Ok(res)
+}
+
+/// Renames a sequence, cascading the new locator prefix to all its topics and to the
+/// data files of their chunks.
+///
+/// **Note**: this function does not move any object in the store.
+pub async fn sequence_rename(
+    exe: &mut impl repo::AsExec,
+    loc: &types::SequenceResourceLocator,
+    new_loc: &types::SequenceResourceLocator,
+) -> Result<(), Error> {
+    trace!("renaming `{}` to `{}`", loc, new_loc);
+    sqlx::query!(
+        r#"
+            UPDATE chunk_t
+            SET data_file = $1 || SUBSTRING(data_file, LENGTH($2) + 1)
+            WHERE STARTS_WITH(data_file, $2) AND topic_id IN (
+                SELECT topic.topic_id
+                FROM topic_t AS topic
+                JOIN sequence_t AS sequence ON topic.sequence_id = sequence.sequence_id
+                WHERE sequence.locator_name = $2
+            )
+    "#,
+        new_loc.name(),
+        loc.name()
+    )
+    .execute(exe.as_exec())
+    .await?;
+
+    sqlx::query!(
+        r#"
+            UPDATE topic_t
+            SET locator_name = $1 || SUBSTRING(locator_name, LENGTH($2) + 1)
+            WHERE sequence_id = (
+                SELECT sequence_id FROM sequence_t WHERE locator_name = $2
+            )
+    "#,
+        new_loc.name(),
+        loc.name()
+    )
+    .execute(exe.as_exec())
+    .await?;
+
+    sqlx::query!(
+        r#"
+            UPDATE sequence_t
+            SET locator_name = $1
+            WHERE locator_name = $2
+    "#,
+        new_loc.name(),
+        loc.name()
+    )
+    .execute(exe.as_exec())
+    .await?;
+
+    Ok(())
+}
+
+/// Clones the sequence `loc` (along with its topics, chunks and chunk statistics) as
+/// `new_loc`.
+///
+/// The cloned sequence is unlocked, while topics keep their lock state. Chunks are assumed
+/// to have been copied under the new sequence name, so their data files are moved under it.
+pub async fn sequence_clone(
+    exe: &mut impl repo::AsExec,
+    loc: &types::SequenceResourceLocator,
+    new_loc: &types::SequenceResourceLocator,
+) -> Result<(), Error> {
+    trace!("cloning `{}` as `{}`", loc, new_loc);
+    let now: i64 = types::Timestamp::now().into();
+
+    sqlx::query!(
+        r#"
+            INSERT INTO sequence_t(sequence_uuid, locator_name, locked, user_metadata, creation_unix_tstamp)
+            SELECT gen_random_uuid(), $1, FALSE, user_metadata, $3
+            FROM sequence_t
+            WHERE locator_name = $2
+    "#,
+        new_loc.name(),
+        loc.name(),
+        now,
+    )
+    .execute(exe.as_exec())
+    .await?;
+
+    sqlx::query!(
+        r#"
+            INSERT INTO topic_t(
+                topic_uuid, sequence_id, locator_name, locked, user_metadata,
+                serialization_format, ontology_tag, creation_unix_tstamp
+            )
+            SELECT
+                gen_random_uuid(), new_sequence.sequence_id,
+                $1 || SUBSTRING(topic.locator_name, LENGTH($2) + 1), topic.locked,
+                topic.user_metadata, topic.serialization_format, topic.ontology_tag, $3
+            FROM topic_t AS topic
+            JOIN sequence_t AS sequence ON topic.sequence_id = sequence.sequence_id
+            CROSS JOIN (SELECT sequence_id FROM sequence_t WHERE locator_name = $1) AS new_sequence
+            WHERE sequence.locator_name = $2
+    "#,
+        new_loc.name(),
+        loc.name(),
+        now,
+    )
+    .execute(exe.as_exec())
+    .await?;
+
+    sqlx::query!(
+        r#"
+            INSERT INTO chunk_t(
+                chunk_uuid, topic_id, data_file, size_bytes, row_count,
+                creation_unix_tstamp, min_unix_tstamp, max_unix_tstamp, checksum
+            )
+            SELECT
+                gen_random_uuid(), new_topic.topic_id,
+                $1 || SUBSTRING(chunk.data_file, LENGTH($2) + 1), chunk.size_bytes, chunk.row_count,
+                chunk.creation_unix_tstamp, chunk.min_unix_tstamp, chunk.max_unix_tstamp,
+                chunk.checksum
+            FROM chunk_t AS chunk
+            JOIN topic_t AS topic ON chunk.topic_id = topic.topic_id
+            JOIN sequence_t AS sequence ON topic.sequence_id = sequence.sequence_id
+            JOIN topic_t AS new_topic
+                ON new_topic.locator_name = $1 || SUBSTRING(topic.locator_name, LENGTH($2) + 1)
+            WHERE sequence.locator_name = $2 AND STARTS_WITH(chunk.data_file, $2)
+    "#,
+        new_loc.name(),
+        loc.name(),
+    )
+    .execute(exe.as_exec())
+    .await?;
+
+    // Statistics are associated to the cloned chunks by matching the data files
+    sqlx::query!(
+        r#"
+            WITH __chunk_map__ AS (
+                SELECT chunk.chunk_id AS old_id, new_chunk.chunk_id AS new_id
+                FROM chunk_t AS chunk
+                JOIN topic_t AS topic ON chunk.topic_id = topic.topic_id
+                JOIN sequence_t AS sequence ON topic.sequence_id = sequence.sequence_id
+                JOIN chunk_t AS new_chunk
+                    ON new_chunk.data_file = $1 || SUBSTRING(chunk.data_file, LENGTH($2) + 1)
+                WHERE sequence.locator_name = $2 AND STARTS_WITH(chunk.data_file, $2)
+            )
+            INSERT INTO column_chunk_numeric_t(
+                column_id, chunk_id, min_value, max_value,
+                has_null, has_nan, has_inf, p50_value, p95_value, null_count
+            )
+            SELECT
+                stats.column_id, __chunk_map__.new_id, stats.min_value, stats.max_value,
+                stats.has_null, stats.has_nan, stats.has_inf, stats.p50_value, stats.p95_value,
+                stats.null_count
+            FROM column_chunk_numeric_t AS stats
+            JOIN __chunk_map__ ON stats.chunk_id = __chunk_map__.old_id
+    "#,
+        new_loc.name(),
+        loc.name(),
+    )
+    .execute(exe.as_exec())
+    .await?;
+
+    sqlx::query!(
+        r#"
+            WITH __chunk_map__ AS (
+                SELECT chunk.chunk_id AS old_id, new_chunk.chunk_id AS new_id
+                FROM chunk_t AS chunk
+                JOIN topic_t AS topic ON chunk.topic_id = topic.topic_id
+                JOIN sequence_t AS sequence ON topic.sequence_id = sequence.sequence_id
+                JOIN chunk_t AS new_chunk
+                    ON new_chunk.data_file = $1 || SUBSTRING(chunk.data_file, LENGTH($2) + 1)
+                WHERE sequence.locator_name = $2 AND STARTS_WITH(chunk.data_file, $2)
+            )
+            INSERT INTO column_chunk_literal_t(
+                column_id, chunk_id, min_value, max_value, has_null, null_count
+            )
+            SELECT
+                stats.column_id, __chunk_map__.new_id, stats.min_value, stats.max_value,
+                stats.has_null, stats.null_count
+            FROM column_chunk_literal_t AS stats
+            JOIN __chunk_map__ ON stats.chunk_id = __chunk_map__.old_id
+    "#,
+        new_loc.name(),
+        loc.name(),
+    )
+    .execute(exe.as_exec())
+    .await?;
+
+    sqlx::query!(
+        r#"
+            WITH __chunk_map__ AS (
+                SELECT chunk.chunk_id AS old_id, new_chunk.chunk_id AS new_id
+                FROM chunk_t AS chunk
+                JOIN topic_t AS topic ON chunk.topic_id = topic.topic_id
+                JOIN sequence_t AS sequence ON topic.sequence_id = sequence.sequence_id
+                JOIN chunk_t AS new_chunk
+                    ON new_chunk.data_file = $1 || SUBSTRING(chunk.data_file, LENGTH($2) + 1)
+                WHERE sequence.locator_name = $2 AND STARTS_WITH(chunk.data_file, $2)
+            )
+            INSERT INTO column_chunk_boolean_t(column_id, chunk_id, has_true, has_false, has_null)
+            SELECT
+                stats.column_id, __chunk_map__.new_id, stats.has_true, stats.has_false,
+                stats.has_null
+            FROM column_chunk_boolean_t AS stats
+            JOIN __chunk_map__ ON stats.chunk_id = __chunk_map__.old_id
+    "#,
+        new_loc.name(),
+        loc.name(),
+    )
+    .execute(exe.as_exec())
+    .await?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use sqlx::Pool;
@@ -186,5 +616,91 @@ mod tests {
         Ok(())
     }
 
+    #[sqlx::test]
+    async fn test_unlock_with_audit(pool: Pool<repo::Database>) -> sqlx::Result<()> {
+        let repo = repo::testing::Repository::new(pool);
+        let loc = types::SequenceResourceLocator::from("my/path".to_owned());
+
+        let record = sequence_create(
+            &mut repo.connection(),
+            &sql_models::SequenceRecord::new("my/path"),
+        )
+        .await
+        .unwrap();
+        sequence_lock(&mut repo.connection(), &loc).await.unwrap();
+
+        sequence_unlock(&mut repo.connection(), &loc).await.unwrap();
+        let audit =
+            sql_models::SequenceAudit::new(record.sequence_id, "reopen", "tester".to_owned(), None);
+        sequence_audit_create(&mut repo.connection(), &audit)
+            .await
+            .unwrap();
+
+        let record = sequence_find_by_locator(&mut repo.connection(), &loc)
+            .await
+            .unwrap();
+        assert!(!record.is_locked());
+
+        let audits = sequence_audits_find_by_locator(&mut repo.connection(), &loc)
+            .await
+            .unwrap();
+        assert_eq!(audits.len(), 1);
+        assert_eq!(audits[0].action, "reopen");
+        assert_eq!(audits[0].author, "tester");
+        assert_eq!(audits[0].creation_unix_tstamp, audit.creation_unix_tstamp);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_paginated_listing(pool: Pool<repo::Database>) -> sqlx::Result<()> {
+        let repo = repo::testing::Repository::new(pool);
+
+        for name in ["seq_c", "seq_a", "other", "seq_b"] {
+            let record = sql_models::SequenceRecord::new(name);
+            sequence_create(&mut repo.connection(), &record)
+                .await
+                .unwrap();
+        }
+
+        let filter = query::SequenceFilter {
+            name: Some(query::Op::Match("seq".to_owned())),
+            creation: None,
+            user_metadata: None,
+        };
+
+        let (page, total) = sequence_from_query_filter_paginated(
+            &mut repo.connection(),
+            Some(filter.clone()),
+            Some(2),
+            0,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(total, 3);
+        let names: Vec<_> = page.iter().map(|r| r.locator_name.as_str()).collect();
+        assert_eq!(names, vec!["seq_a", "seq_b"]);
+
+        let (page, total) =
+            sequence_from_query_filter_paginated(&mut repo.connection(), Some(filter), Some(2), 2)
+                .await
+                .unwrap();
+
+        assert_eq!(total, 3);
+        let names: Vec<_> = page.iter().map(|r| r.locator_name.as_str()).collect();
+        assert_eq!(names, vec!["seq_c"]);
+
+        let (page, total) =
+            sequence_from_query_filter_paginated(&mut repo.connection(), None, None, 0)
+                .await
+                .unwrap();
+
+        assert_eq!(total, 4);
+        assert_eq!(page.len(), 4);
+
+        Ok(())
+    }
+
     // (cabba) TODO: extend tests
 }
@@ -53,6 +53,23 @@ pub async fn topic_find_by_locator(
     Ok(res)
 }
 
+/// Find a topic given its locator, locking its row until the end of the transaction so that
+/// concurrent updates of the topic are serialized.
+pub async fn topic_find_by_locator_for_update(
+    exe: &mut impl repo::AsExec,
+    topic: &types::TopicResourceLocator,
+) -> Result<sql_models::TopicRecord, repo::Error> {
+    trace!("searching by resource name `{}` (for update)", topic);
+    let res = sqlx::query_as!(
+        sql_models::TopicRecord,
+        "SELECT * FROM topic_t WHERE locator_name=$1 FOR UPDATE",
+        topic.name()
+    )
+    .fetch_one(exe.as_exec())
+    .await?;
+    Ok(res)
+}
+
 /// Return all sequences
 pub async fn topic_find_all(
     exe: &mut impl repo::AsExec,
@@ -225,18 +242,66 @@ pub async fn topic_from_query_filter(
     exe: &mut impl repo::AsExec,
     filter_seq: Option<query::SequenceFilter>,
     filter_top: Option<query::TopicFilter>,
+    filter_layer: Option<query::LayerFilter>,
 ) -> Result<Vec<sql_models::TopicRecord>, repo::Error> {
     // Return empty vector if there is nothing to filter
-    if filter_seq.is_none() && filter_top.is_none() {
+    let Some((query, values)) = topic_query_from_filter(filter_seq, filter_top, filter_layer)?
+    else {
         return Ok(Vec::new());
+    };
+
+    trace!("query values: {:?}", values);
+    trace!("generated SQL query: {}", query);
+
+    let mut r = sqlx::query(&query);
+
+    for v in values.into_iter() {
+        match v {
+            query::Value::Integer(v) => r = r.bind(v),
+            query::Value::Float(v) => r = r.bind(v),
+            query::Value::Text(v) => r = r.bind(v),
+            query::Value::Boolean(v) => r = r.bind(v),
+        }
     }
 
-    let select = r#"
+    let r = r.map(cast_topic_data).fetch_all(exe.as_exec()).await?;
+    trace!("query returned {} results", r.len());
+    r.into_iter().collect()
+}
+
+/// Compiles the sequence, topic and layer filters into the SQL query used by
+/// [`topic_from_query_filter`], along with the values bound to its placeholders.
+///
+/// Returns `None` if there is nothing to filter, since no topic is selected in that case.
+pub fn topic_query_from_filter(
+    filter_seq: Option<query::SequenceFilter>,
+    filter_top: Option<query::TopicFilter>,
+    filter_layer: Option<query::LayerFilter>,
+) -> Result<Option<(String, Vec<query::Value>)>, repo::Error> {
+    if filter_seq.is_none() && filter_top.is_none() && filter_layer.is_none() {
+        return Ok(None);
+    }
+
+    let select = if filter_layer.is_some() {
+        // A sequence can belong to more than one matching layer, so topics are deduplicated
+        r#"
+        SELECT DISTINCT ON (topic.topic_id) topic.*
+        FROM topic_t topic
+        INNER JOIN sequence_t sequence 
+        ON topic.sequence_id = sequence.sequence_id
+        INNER JOIN layer_sequence_t member
+        ON member.sequence_id = sequence.sequence_id
+        INNER JOIN layer_t layer
+        ON layer.layer_id = member.layer_id
+    "#
+    } else {
+        r#"
         SELECT topic.*
         FROM topic_t topic
         INNER JOIN sequence_t sequence 
         ON topic.sequence_id = sequence.sequence_id
-    "#;
+    "#
+    };
 
     let mut qb = query::ClausesCompiler::new();
     let mut sql_fmt = super::SqlQueryCompiler::new();
@@ -259,6 +324,8 @@ pub async fn topic_from_query_filter(
                     sql_fmt.current_placeholder(),
                 ),
             );
+            // Keep placeholders numbering consistent across compilers
+            sql_fmt = sql_fmt.with_starting_placeholder(json_fmt.current_placeholder());
         }
     }
 
@@ -295,34 +362,181 @@ pub async fn topic_from_query_filter(
                     sql_fmt.current_placeholder(),
                 ),
             );
+            // Keep placeholders numbering consistent across compilers
+            sql_fmt = sql_fmt.with_starting_placeholder(json_fmt.current_placeholder());
         }
     }
 
+    if let Some(layer) = filter_layer
+        && let Some(op) = layer.name
+    {
+        qb = qb.expr("layer.layer_name", op, &mut sql_fmt);
+    }
+
     let qr = qb.compile()?;
 
     // If the query has no filters skip, to avoid retuning too mutch elements
     if qr.is_unfiltered() {
-        return Ok(Vec::new());
+        return Ok(None);
     }
 
     // Since we have do an early-return is the query is unfiltered there is always a WHERE clause
     let query = format!("{select} WHERE {}", qr.clauses.join(" AND "));
 
-    trace!("query values: {:?}", qr.values);
-    trace!("generated SQL query: {}", query);
+    Ok(Some((query, qr.values)))
+}
 
-    let mut r = sqlx::query(&query);
+#[cfg(test)]
+mod tests {
+    use sqlx::Pool;
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::types::MetadataBlob;
+
+    async fn create_topic(
+        repo: &repo::testing::Repository,
+        sequence_id: i32,
+        name: &str,
+        format: &str,
+        user_metadata: &str,
+    ) {
+        let record = sql_models::TopicRecord::new(name, sequence_id)
+            .with_serialization_format(format)
+            .with_user_metadata(marshal::JsonMetadataBlob::try_from_str(user_metadata).unwrap());
+        topic_create(&mut repo.connection(), &record).await.unwrap();
+    }
 
-    for v in qr.values.into_iter() {
-        match v {
-            query::Value::Integer(v) => r = r.bind(v),
-            query::Value::Float(v) => r = r.bind(v),
-            query::Value::Text(v) => r = r.bind(v),
-            query::Value::Boolean(v) => r = r.bind(v),
-        }
+    #[sqlx::test]
+    async fn filter_by_serialization_format(pool: Pool<repo::Database>) -> sqlx::Result<()> {
+        let repo = repo::testing::Repository::new(pool);
+
+        let sequence = super::super::sequence_create(
+            &mut repo.connection(),
+            &sql_models::SequenceRecord::new("/seq"),
+        )
+        .await
+        .unwrap();
+
+        let id = sequence.sequence_id;
+        create_topic(&repo, id, "/seq/cam_front", "image", r#"{"side": "front"}"#).await;
+        create_topic(&repo, id, "/seq/cam_rear", "image", r#"{"side": "rear"}"#).await;
+        create_topic(
+            &repo,
+            id,
+            "/seq/imu_front",
+            "default",
+            r#"{"side": "front"}"#,
+        )
+        .await;
+
+        let filter = query::TopicFilter {
+            serialization_format: Some(query::Op::Eq("image".to_owned())),
+            ..Default::default()
+        };
+        let topics = topic_from_query_filter(&mut repo.connection(), None, Some(filter), None)
+            .await
+            .unwrap();
+        let mut names: Vec<&str> = topics.iter().map(|t| t.locator_name.as_str()).collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["/seq/cam_front", "/seq/cam_rear"]);
+
+        // The format filter composes with name and user metadata filters
+        let user_metadata = query::OntologyFilter::new(HashMap::from([(
+            query::OntologyField::try_new("side".to_owned()).unwrap(),
+            query::Op::Eq(query::Value::Text("front".to_owned())),
+        )]));
+        let filter = query::TopicFilter {
+            name: Some(query::Op::Match("front".to_owned())),
+            serialization_format: Some(query::Op::Eq("image".to_owned())),
+            user_metadata: Some(user_metadata),
+            ..Default::default()
+        };
+        let topics = topic_from_query_filter(&mut repo.connection(), None, Some(filter), None)
+            .await
+            .unwrap();
+        assert_eq!(topics.len(), 1);
+        assert_eq!(topics[0].locator_name, "/seq/cam_front");
+
+        Ok(())
     }
 
-    let r = r.map(cast_topic_data).fetch_all(exe.as_exec()).await?;
-    trace!("query returned {} results", r.len());
-    r.into_iter().collect()
+    #[sqlx::test]
+    async fn filter_by_layer(pool: Pool<repo::Database>) -> sqlx::Result<()> {
+        let repo = repo::testing::Repository::new(pool);
+        let mut cx = repo.connection();
+
+        let mut sequence_ids = Vec::new();
+        for name in ["/seq_a", "/seq_b"] {
+            let sequence =
+                super::super::sequence_create(&mut cx, &sql_models::SequenceRecord::new(name))
+                    .await
+                    .unwrap();
+            create_topic(
+                &repo,
+                sequence.sequence_id,
+                &format!("{name}/imu"),
+                "default",
+                "{}",
+            )
+            .await;
+            create_topic(
+                &repo,
+                sequence.sequence_id,
+                &format!("{name}/cam"),
+                "image",
+                "{}",
+            )
+            .await;
+            sequence_ids.push(sequence.sequence_id);
+        }
+
+        for name in ["curated_a", "curated_b"] {
+            let layer =
+                super::super::layer_create(&mut cx, types::Layer::new(name.into(), String::new()))
+                    .await
+                    .unwrap();
+            // Only the first sequence belongs to the layers
+            super::super::layer_add_sequence(&mut cx, layer.layer_id, sequence_ids[0])
+                .await
+                .unwrap();
+        }
+
+        let names = |topics: Vec<sql_models::TopicRecord>| {
+            let mut names: Vec<String> = topics.into_iter().map(|t| t.locator_name).collect();
+            names.sort_unstable();
+            names
+        };
+
+        let layer = query::LayerFilter {
+            name: Some(query::Op::Eq("curated_a".to_owned())),
+        };
+        let topics = topic_from_query_filter(&mut cx, None, None, Some(layer))
+            .await
+            .unwrap();
+        assert_eq!(names(topics), vec!["/seq_a/cam", "/seq_a/imu"]);
+
+        // Topics are reported once even if the sequence belongs to several matching layers
+        let layer = query::LayerFilter {
+            name: Some(query::Op::Match("curated".to_owned())),
+        };
+        let filter = query::TopicFilter {
+            serialization_format: Some(query::Op::Eq("image".to_owned())),
+            ..Default::default()
+        };
+        let topics = topic_from_query_filter(&mut cx, None, Some(filter), Some(layer))
+            .await
+            .unwrap();
+        assert_eq!(names(topics), vec!["/seq_a/cam"]);
+
+        let layer = query::LayerFilter {
+            name: Some(query::Op::Eq("missing".to_owned())),
+        };
+        let topics = topic_from_query_filter(&mut cx, None, None, Some(layer))
+            .await
+            .unwrap();
+        assert!(topics.is_empty());
+
+        Ok(())
+    }
 }
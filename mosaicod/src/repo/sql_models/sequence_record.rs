@@ -67,3 +67,39 @@ impl SequenceRecord {
         types::Timestamp::from(self.creation_unix_tstamp)
     }
 }
+
+/// Audit entry of an operation performed on a sequence outside of its normal
+/// lifecycle (e.g. re-opening a finalized sequence).
+#[derive(Debug)]
+pub struct SequenceAudit {
+    pub(super) sequence_audit_id: i32,
+    pub sequence_id: i32,
+    /// Name of the audited operation (e.g. `reopen`)
+    pub action: String,
+    /// Who requested the operation
+    pub author: String,
+    pub reason: Option<String>,
+    /// UNIX timestamp in milliseconds from the creation
+    pub(super) creation_unix_tstamp: i64,
+}
+
+impl SequenceAudit {
+    /// Creates a new audit entry.
+    ///
+    /// **Note**: This function only creates a local instance. The record will not be present
+    /// in the repository until [`sequence_audit_create`] is called.
+    pub fn new(sequence_id: i32, action: &str, author: String, reason: Option<String>) -> Self {
+        Self {
+            sequence_audit_id: repo::UNREGISTERED,
+            sequence_id,
+            action: action.to_owned(),
+            author,
+            reason,
+            creation_unix_tstamp: types::Timestamp::now().into(),
+        }
+    }
+
+    pub fn creation_timestamp(&self) -> types::Timestamp {
+        types::Timestamp::from(self.creation_unix_tstamp)
+    }
+}
@@ -13,8 +13,8 @@ pub struct TopicRecord {
     pub(super) locked: bool,
     pub(super) serialization_format: Option<String>,
 
-    /// This metadata field is only for database query access and
-    /// should not be exposed
+    /// This metadata field is only for database query access, clients are served the
+    /// metadata of the store
     pub(super) user_metadata: Option<serde_json::Value>,
 
     /// UNIX timestamp in milliseconds from the creation
@@ -64,6 +64,12 @@ impl TopicRecord {
         self.locked
     }
 
+    /// Returns the user metadata stored in the repository, used to update it consistently
+    /// within a transaction (see [`repo::topic_find_by_locator_for_update`])
+    pub fn user_metadata(&self) -> Option<marshal::JsonMetadataBlob> {
+        self.user_metadata.clone().map(Into::into)
+    }
+
     pub fn serialization_format(&self) -> Option<rw::Format> {
         self.serialization_format.as_ref().map(|value| {
             rw::Format::from_str(value).expect("BUG: invalid serialization format in database")
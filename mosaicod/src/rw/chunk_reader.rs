@@ -1,22 +1,45 @@
+use arrow::array::RecordBatch;
 use arrow::datatypes::SchemaRef;
+use arrow::error::ArrowError;
+use arrow::ipc::reader::FileReader;
 use parquet::arrow::arrow_reader::{ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder};
+use parquet::errors::ParquetError;
+use parquet::file::metadata::ParquetMetaDataReader;
+use std::io::Cursor;
+use std::sync::Arc;
 
-use super::{Error, Format};
+use super::{Container, Error, Format};
 pub enum Reader {
     /// Parquet file format https://parquet.apache.org/docs/file-format/
     Parquet {
         reader: ParquetRecordBatchReader,
         schema: SchemaRef,
     },
+    /// Arrow IPC file format https://arrow.apache.org/docs/format/Columnar.html#ipc-file-format
+    Ipc {
+        reader: FileReader<Cursor<bytes::Bytes>>,
+        schema: SchemaRef,
+    },
 }
 
 impl Reader {
-    pub fn try_new(_format: Format, buffer: bytes::Bytes) -> Result<Self, Error> {
-        let builder = ParquetRecordBatchReaderBuilder::try_new(buffer)?;
-        Ok(Self::Parquet {
-            schema: builder.schema().clone(),
-            reader: builder.build()?,
-        })
+    pub fn try_new(format: Format, buffer: bytes::Bytes) -> Result<Self, Error> {
+        match format.container() {
+            Container::Parquet => {
+                let builder = ParquetRecordBatchReaderBuilder::try_new(buffer)?;
+                Ok(Self::Parquet {
+                    schema: builder.schema().clone(),
+                    reader: builder.build()?,
+                })
+            }
+            Container::Ipc => {
+                let reader = FileReader::try_new(Cursor::new(buffer), None)?;
+                Ok(Self::Ipc {
+                    schema: reader.schema(),
+                    reader,
+                })
+            }
+        }
     }
 }
 pub struct ChunkReader {
@@ -32,7 +55,72 @@ impl ChunkReader {
 
     pub fn schema(&self) -> SchemaRef {
         match &self.reader {
-            Reader::Parquet { schema, .. } => schema.clone(),
+            Reader::Parquet { schema, .. } | Reader::Ipc { schema, .. } => schema.clone(),
+        }
+    }
+}
+
+/// Iterates over the batches of the chunk, with the types stored in the file (e.g. no
+/// view types are introduced for parquet string columns).
+impl Iterator for ChunkReader {
+    type Item = Result<RecordBatch, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.reader {
+            Reader::Parquet { reader, .. } => reader.next().map(|b| b.map_err(Error::from)),
+            Reader::Ipc { reader, .. } => reader.next().map(|b| b.map_err(Error::from)),
+        }
+    }
+}
+
+/// Returns the number of bytes at the end of a data file of `format` holding the length of
+/// its footer (see [`footer_len`]).
+pub fn footer_tail_len(format: Format) -> usize {
+    match format.container() {
+        Container::Parquet => parquet::file::FOOTER_SIZE,
+        Container::Ipc => 10,
+    }
+}
+
+/// Returns the length of the footer of a data file of `format`, given the last
+/// [`footer_tail_len`] bytes of the file.
+pub fn footer_len(format: Format, tail: &[u8]) -> Result<usize, Error> {
+    match format.container() {
+        Container::Parquet => {
+            let tail = tail
+                .try_into()
+                .map_err(|_| ParquetError::General("invalid parquet footer length".to_owned()))?;
+            Ok(ParquetMetaDataReader::decode_footer_tail(tail)?.metadata_length())
+        }
+        Container::Ipc => {
+            let tail = tail
+                .try_into()
+                .map_err(|_| ArrowError::ParseError("invalid ipc footer length".to_owned()))?;
+            Ok(arrow::ipc::reader::read_footer_length(tail)?)
+        }
+    }
+}
+
+/// Decodes the schema of a data file of `format` from its `footer`, so that the schema is
+/// known without reading (and decoding) the whole file.
+pub fn schema_from_footer(format: Format, footer: &[u8]) -> Result<SchemaRef, Error> {
+    match format.container() {
+        Container::Parquet => {
+            let metadata = ParquetMetaDataReader::decode_metadata(footer)?;
+            let file_metadata = metadata.file_metadata();
+            let schema = parquet::arrow::parquet_to_arrow_schema(
+                file_metadata.schema_descr(),
+                file_metadata.key_value_metadata(),
+            )?;
+            Ok(Arc::new(schema))
+        }
+        Container::Ipc => {
+            let footer = arrow::ipc::root_as_footer(footer)
+                .map_err(|e| ArrowError::ParseError(format!("invalid ipc footer :: {e}")))?;
+            let schema = footer
+                .schema()
+                .ok_or_else(|| ArrowError::ParseError("ipc footer without schema".to_owned()))?;
+            Ok(Arc::new(arrow::ipc::convert::fb_to_schema(schema)))
         }
     }
 }
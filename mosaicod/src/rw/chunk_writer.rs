@@ -1,13 +1,20 @@
 use super::{Error, Format, writer::Writer};
-use crate::types;
-use arrow::{array::RecordBatch, datatypes::Schema, datatypes::SchemaRef};
+use crate::{params, types};
+use arrow::array::{AsArray, RecordBatch};
+use arrow::compute;
+use arrow::datatypes::{Int64Type, Schema, SchemaRef};
 use std::sync::Arc;
 
-/// Metadata about a finalized chunk, including size and row count.
+/// Metadata about a finalized chunk, including size, row count and timestamp bounds.
 #[derive(Debug, Clone)]
 pub struct ChunkMetadata {
     pub size_bytes: usize,
     pub row_count: usize,
+    /// Min and max timestamp (in the unit of the topic) of the chunk, [`None`] if the chunk
+    /// has no timestamps
+    pub timestamp_bounds: Option<(i64, i64)>,
+    /// CRC32 checksum of the serialized chunk
+    pub checksum: u32,
 }
 
 /// The [`ChunkWriter`] is used to serialize [`RecordBatch`] instances into a single memory chunk,
@@ -22,6 +29,11 @@ pub struct ChunkWriter {
     stats: types::ColumnsStats,
     schema: SchemaRef,
     row_count: usize,
+    /// Min and max timestamp of the rows written so far
+    timestamp_bounds: Option<(i64, i64)>,
+    /// Timestamp of the last row written, tracked only if timestamps are required
+    /// to be non-decreasing
+    monotonic: Option<Option<i64>>,
 }
 
 impl ChunkWriter {
@@ -30,25 +42,76 @@ impl ChunkWriter {
     /// This fallible constructor initializes an appropriate underlying writer
     /// based on the provided `format`.
     pub fn try_new(schema: Arc<Schema>, format: Format) -> Result<Self, Error> {
+        Self::try_new_with_max_row_group_size(schema, format, None)
+    }
+
+    /// Creates a new [`ChunkWriter`] as [`ChunkWriter::try_new`], writing row groups of at most
+    /// `max_row_group_size` rows (the format default if [`None`]).
+    pub fn try_new_with_max_row_group_size(
+        schema: Arc<Schema>,
+        format: Format,
+        max_row_group_size: Option<usize>,
+    ) -> Result<Self, Error> {
         Ok(ChunkWriter {
-            writer: Writer::new(&schema, format)?,
+            writer: Writer::new(&schema, format, max_row_group_size)?,
             format,
             stats: crate::arrow::column_stats_from_schema(&schema),
             schema,
             row_count: 0,
+            timestamp_bounds: None,
+            monotonic: None,
         })
     }
 
+    /// Requires the timestamps to be non-decreasing, both within each batch and
+    /// across the batches written in the chunk.
+    pub fn with_monotonic_timestamps(mut self) -> Self {
+        self.monotonic = Some(None);
+        self
+    }
+
+    /// Checks in a single pass that timestamps do not decrease, reporting the row
+    /// (of `batch`) of the first violation.
+    fn check_monotonic(&mut self, batch: &RecordBatch) -> Result<(), Error> {
+        let Some(last) = &mut self.monotonic else {
+            return Ok(());
+        };
+
+        // The timestamp column is validated before writing, so a missing or
+        // different column leaves nothing to check
+        let Some(timestamps) = batch
+            .column_by_name(params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP)
+            .and_then(|c| c.as_primitive_opt::<Int64Type>())
+        else {
+            return Ok(());
+        };
+
+        for (row, &ts) in timestamps.values().iter().enumerate() {
+            if last.is_some_and(|last| ts < last) {
+                return Err(Error::NonMonotonicTimestamp { at_row: row });
+            }
+            *last = Some(ts);
+        }
+
+        Ok(())
+    }
+
+    /// Extends the timestamp bounds of the chunk with the timestamps of `batch`
+    fn update_timestamp_bounds(&mut self, batch: &RecordBatch) {
+        self.timestamp_bounds = merge_timestamp_bounds(self.timestamp_bounds, batch);
+    }
+
     /// Wrties the provided [`RecordBatch`].
     ///
     /// The `RecordBatch` is serialized according to the writer's format, and the internal statistics
     /// are updated based on the data in the batch. The method returns an error if the serialization fails
     pub fn write(&mut self, batch: &RecordBatch) -> Result<(), Error> {
+        self.check_monotonic(batch)?;
+        crate::arrow::column_stats_inspect_record_batch(&mut self.stats, batch)?;
+        self.update_timestamp_bounds(batch);
         match &mut self.writer {
-            Writer::Parquet(writer) => {
-                crate::arrow::column_stats_inspect_record_batch(&mut self.stats, batch)?;
-                writer.write(batch)?;
-            }
+            Writer::Parquet(writer) => writer.write(batch)?,
+            Writer::Ipc(writer) => writer.write(batch)?,
         }
         self.row_count += batch.num_rows();
         Ok(())
@@ -70,6 +133,7 @@ impl ChunkWriter {
     pub fn buffer_mut(&mut self) -> &mut Vec<u8> {
         match &mut self.writer {
             Writer::Parquet(writer) => writer.inner_mut(),
+            Writer::Ipc(writer) => writer.get_mut(),
         }
     }
 
@@ -77,12 +141,24 @@ impl ChunkWriter {
     pub fn buffer(&self) -> &Vec<u8> {
         match &self.writer {
             Writer::Parquet(writer) => writer.inner(),
+            Writer::Ipc(writer) => writer.get_ref(),
+        }
+    }
+
+    /// Returns the memory used by the chunk, including both the serialized data and the
+    /// data buffered but not serialized yet.
+    pub fn memory_usage(&self) -> usize {
+        match &self.writer {
+            Writer::Parquet(writer) => writer.inner().len() + writer.memory_size(),
+            Writer::Ipc(writer) => writer.get_ref().len(),
         }
     }
 
     pub fn memory_size(&self) -> usize {
         match &self.writer {
             Writer::Parquet(writer) => writer.memory_size(),
+            // Batches are serialized as soon as they are written
+            Writer::Ipc(writer) => writer.get_ref().len(),
         }
     }
 
@@ -91,22 +167,48 @@ impl ChunkWriter {
     /// This method must be called to complete the writing process. It consumes the writer object,
     /// preventing any further writes.
     ///
-    /// Returns the serialized buffer, column statistics, and chunk metadata (size and row count).
+    /// Returns the serialized buffer, column statistics, and chunk metadata (size, row count,
+    /// timestamp bounds and checksum).
     pub fn finalize(self) -> Result<(Vec<u8>, types::ColumnsStats, ChunkMetadata), Error> {
         // We are calling `finish`` since the implementation is the same as
         // close but takes no ownership of the writer. And we return the internal data buffer.
         let row_count = self.row_count;
         let buffer = match self.writer {
             Writer::Parquet(w) => w.into_inner()?,
+            Writer::Ipc(w) => w.into_inner()?,
         };
         let metadata = ChunkMetadata {
             size_bytes: buffer.len(),
             row_count,
+            timestamp_bounds: self.timestamp_bounds,
+            checksum: crc32fast::hash(&buffer),
         };
         Ok((buffer, self.stats, metadata))
     }
 }
 
+/// Returns the timestamp bounds `bounds` extended with the timestamps of `batch`, the
+/// bounds are unchanged if `batch` has no timestamps.
+pub fn merge_timestamp_bounds(
+    bounds: Option<(i64, i64)>,
+    batch: &RecordBatch,
+) -> Option<(i64, i64)> {
+    let Some(timestamps) = batch
+        .column_by_name(params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP)
+        .and_then(|c| c.as_primitive_opt::<Int64Type>())
+    else {
+        return bounds;
+    };
+
+    let (Some(min), Some(max)) = (compute::min(timestamps), compute::max(timestamps)) else {
+        return bounds;
+    };
+    Some(match bounds {
+        Some((cur_min, cur_max)) => (cur_min.min(min), cur_max.max(max)),
+        None => (min, max),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{params, types};
@@ -172,6 +274,8 @@ mod tests {
 
     #[test]
     fn chunk_writer_statistics() {
+        params::load_configurables_from_env();
+
         let batch = create_test_batch();
         let schema = batch.schema();
 
@@ -196,11 +300,10 @@ mod tests {
         }
 
         // Check  stats for "is_braking"
-        if let Some(types::Stats::Numeric(v)) = cstats.stats.get("is_braking") {
-            assert_eq!(v.min, 0.0);
-            assert_eq!(v.max, 1.0);
+        if let Some(types::Stats::Boolean(v)) = cstats.stats.get("is_braking") {
+            assert!(v.has_true);
+            assert!(v.has_false);
             assert!(!v.has_null);
-            assert!(!v.has_nan);
         } else {
             panic!("Missing or incorrect type for `is_braking` stats");
         }
@@ -250,4 +353,96 @@ mod tests {
         assert_eq!(metadata.row_count, 3);
         assert_eq!(metadata.size_bytes, buffer.len());
     }
+
+    #[test]
+    fn chunk_writer_ipc() {
+        params::load_configurables_from_env();
+
+        let batch = create_test_batch();
+        let schema = batch.schema();
+
+        let mut writer = ChunkWriter::try_new(schema.clone(), Format::Ipc)
+            .expect("Failed to create ChunkWriter");
+        writer.write(&batch).expect("Failed to write batch");
+        assert_eq!(writer.statistics().stats.len(), 6);
+
+        let (buffer, _, metadata) = writer.finalize().expect("Failed to finalize writer");
+        assert_eq!(metadata.row_count, 3);
+
+        let reader = crate::rw::ChunkReader::new(Format::Ipc, bytes::Bytes::from_owner(buffer))
+            .expect("Failed to read IPC chunk");
+        assert_eq!(reader.schema(), schema);
+    }
+
+    #[test]
+    fn chunk_writer_monotonic_timestamps() {
+        params::load_configurables_from_env();
+
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+            DataType::Int64,
+            false,
+        )]));
+        let batch = |values: Vec<i64>| {
+            RecordBatch::try_new(schema.clone(), vec![Arc::new(Int64Array::from(values))]).unwrap()
+        };
+
+        // without the check any order is accepted
+        let mut writer = ChunkWriter::try_new(schema.clone(), Format::Default).unwrap();
+        writer.write(&batch(vec![3, 1])).unwrap();
+
+        let mut writer = ChunkWriter::try_new(schema.clone(), Format::Default)
+            .unwrap()
+            .with_monotonic_timestamps();
+        writer.write(&batch(vec![1, 2, 2, 3])).unwrap();
+
+        let err = writer.write(&batch(vec![4, 5, 4])).unwrap_err();
+        assert!(matches!(err, Error::NonMonotonicTimestamp { at_row: 2 }));
+
+        // violations across batches are reported on the first row
+        let mut writer = ChunkWriter::try_new(schema.clone(), Format::Default)
+            .unwrap()
+            .with_monotonic_timestamps();
+        writer.write(&batch(vec![10, 20])).unwrap();
+        let err = writer.write(&batch(vec![15])).unwrap_err();
+        assert!(matches!(err, Error::NonMonotonicTimestamp { at_row: 0 }));
+    }
+
+    #[test]
+    fn chunk_writer_timestamp_bounds() {
+        params::load_configurables_from_env();
+
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+            DataType::Int64,
+            false,
+        )]));
+        let batch = |values: Vec<i64>| {
+            RecordBatch::try_new(schema.clone(), vec![Arc::new(Int64Array::from(values))]).unwrap()
+        };
+
+        let writer = ChunkWriter::try_new(schema.clone(), Format::Default).unwrap();
+        let (_, _, metadata) = writer.finalize().unwrap();
+        assert_eq!(metadata.timestamp_bounds, None);
+
+        let mut writer = ChunkWriter::try_new(schema.clone(), Format::Default).unwrap();
+        writer.write(&batch(vec![30, 10, 20])).unwrap();
+        writer.write(&batch(vec![-5, 25])).unwrap();
+        let (_, _, metadata) = writer.finalize().unwrap();
+        assert_eq!(metadata.timestamp_bounds, Some((-5, 30)));
+    }
+
+    #[test]
+    fn chunk_writer_checksum() {
+        params::load_configurables_from_env();
+
+        let batch = create_test_batch();
+
+        let mut writer = ChunkWriter::try_new(batch.schema(), Format::Default).unwrap();
+        writer.write(&batch).unwrap();
+        let (buffer, _, metadata) = writer.finalize().unwrap();
+
+        assert_eq!(metadata.checksum, crc32fast::hash(&buffer));
+        assert_ne!(metadata.checksum, crc32fast::hash(&buffer[1..]));
+    }
 }
@@ -2,6 +2,7 @@ use std::path::PathBuf;
 use std::pin::Pin;
 
 use arrow::array::RecordBatch;
+use arrow::datatypes::SchemaRef;
 use log::{debug, trace};
 
 use crate::{traits, types};
@@ -24,6 +25,22 @@ type OnChunkCallback = Box<
 /// Callback used to define a format function for files
 type OnFileFormat = Box<dyn Fn(&std::path::Path, &Format, usize) -> std::path::PathBuf + Send>;
 
+/// Write target dropping the chunks it receives.
+///
+/// Used to compute the size and the statistics of the chunks produced by a write
+/// without persisting them (dry run).
+pub struct DiscardTarget;
+
+impl traits::AsyncWriteToPath for DiscardTarget {
+    async fn write_to_path(
+        &self,
+        _path: impl AsRef<std::path::Path>,
+        _buf: impl Into<bytes::Bytes>,
+    ) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 /// Writes [`RecordBatch`] into multiple chunks to a location. A location is a path like structure.
 /// Internally the [`ChunkedWriter`] can subdivide the batches in multiple files
 pub struct ChunkedWriter<'a, W>
@@ -36,6 +53,15 @@ where
     /// When the chunk-size constraint is reached, a new writer will be created.
     writer: Option<ChunkWriter>,
     format: Format,
+    /// Schema of the first batch written, shared by all the chunks
+    schema: Option<SchemaRef>,
+    /// If `true` each chunk requires non-decreasing timestamps
+    monotonic_timestamps: bool,
+    /// Memory usage (in bytes) above which the current chunk is finalized and a new
+    /// one is started
+    max_chunk_bytes: Option<usize>,
+    /// Maximum number of rows of a row group, the format default if [`None`]
+    max_row_group_size: Option<usize>,
     write_target: &'a W,
     /// Target path where the data will be serialized (e.g., `my/target/path`).
     ///
@@ -69,6 +95,10 @@ where
             writer: None,
             write_target: target,
             format,
+            schema: None,
+            monotonic_timestamps: false,
+            max_chunk_bytes: None,
+            max_row_group_size: None,
             path: path.as_ref().to_path_buf(),
             chunk_serialized_number: 0,
             on_chunk_created_clbk: None,
@@ -76,6 +106,34 @@ where
         }
     }
 
+    /// Rejects batches with timestamps lower than the previous ones in the same chunk
+    /// (see [`ChunkWriter::with_monotonic_timestamps`]).
+    pub fn with_monotonic_timestamps(mut self, enabled: bool) -> Self {
+        self.monotonic_timestamps = enabled;
+        self
+    }
+
+    /// Numbers the data files starting from `index` instead of 0, so that the data files of
+    /// a location already holding some chunks are not overwritten.
+    pub fn with_first_chunk_index(mut self, index: usize) -> Self {
+        self.chunk_serialized_number = index;
+        self
+    }
+
+    /// Bounds the memory used by each chunk, once a chunk uses more than `max_chunk_bytes`
+    /// bytes it is finalized and the following batches are written in a new chunk.
+    pub fn with_max_chunk_bytes(mut self, max_chunk_bytes: usize) -> Self {
+        self.max_chunk_bytes = Some(max_chunk_bytes);
+        self
+    }
+
+    /// Limits the number of rows of each row group of the chunks, [`None`] uses the default of
+    /// the format (see [`Format::default_max_row_group_size`]).
+    pub fn with_max_row_group_size(mut self, max_row_group_size: Option<usize>) -> Self {
+        self.max_row_group_size = max_row_group_size;
+        self
+    }
+
     /// Sets a callback function that will be called every time a chunk is produced just before
     /// serialization.
     pub fn on_chunk_created<F1, Fut>(mut self, clbk: F1) -> Self
@@ -111,7 +169,19 @@ where
         // chunk produced callback will be triggered
         let mut writer = match self.writer.take() {
             Some(w) => w,
-            None => ChunkWriter::try_new(batch.schema(), self.format)?,
+            None => {
+                let schema = self.schema.get_or_insert_with(|| batch.schema()).clone();
+                let writer = ChunkWriter::try_new_with_max_row_group_size(
+                    schema,
+                    self.format,
+                    self.max_row_group_size,
+                )?;
+                if self.monotonic_timestamps {
+                    writer.with_monotonic_timestamps()
+                } else {
+                    writer
+                }
+            }
         };
 
         // Clone batch for spawn_blocking (requires 'static)
@@ -125,8 +195,20 @@ where
         .await
         .map_err(|e| Error::SpawnBlockingError(e.to_string()))??;
 
+        let rollover = self
+            .max_chunk_bytes
+            .is_some_and(|max| writer.memory_usage() >= max);
+
         self.writer = Some(writer);
 
+        if rollover {
+            debug!(
+                "chunk #{} reached the maximum size, starting a new chunk",
+                self.chunk_serialized_number
+            );
+            self.finalize().await?;
+        }
+
         Ok(())
     }
 
@@ -169,3 +251,72 @@ where
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params;
+    use arrow::array::Int64Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+    use std::sync::{Arc, Mutex};
+
+    /// Keeps in memory the paths written
+    #[derive(Default)]
+    struct MemoryTarget {
+        paths: Mutex<Vec<PathBuf>>,
+    }
+
+    impl traits::AsyncWriteToPath for MemoryTarget {
+        async fn write_to_path(
+            &self,
+            path: impl AsRef<std::path::Path>,
+            _buf: impl Into<bytes::Bytes>,
+        ) -> std::io::Result<()> {
+            self.paths.lock().unwrap().push(path.as_ref().to_path_buf());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn chunk_rollover() {
+        params::load_configurables_from_env();
+
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+            DataType::Int64,
+            false,
+        )]));
+        let batch = |start: i64| {
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![Arc::new(Int64Array::from_iter_values(start..start + 1024))],
+            )
+            .unwrap()
+        };
+
+        let chunks = Arc::new(Mutex::new(Vec::new()));
+        let target = MemoryTarget::default();
+
+        let chunks_clbk = chunks.clone();
+        let mut writer = ChunkedWriter::new(&target, "topic", Format::Ipc, |path, _, idx| {
+            path.join(format!("{idx}"))
+        })
+        .with_max_chunk_bytes(1)
+        .on_chunk_created(move |_, _, metadata| {
+            chunks_clbk.lock().unwrap().push(metadata);
+            async { Ok(()) }
+        });
+
+        for idx in 0..3 {
+            writer.write(&batch(idx * 1024)).await.unwrap();
+        }
+        writer.finalize().await.unwrap();
+
+        // each batch exceeds the limit, so each batch is written in its own chunk
+        let chunks = chunks.lock().unwrap();
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks.iter().all(|c| c.row_count == 1024));
+        assert_eq!(chunks[2].timestamp_bounds, Some((2048, 3071)));
+        assert_eq!(target.paths.lock().unwrap().len(), 3);
+    }
+}
@@ -13,6 +13,16 @@ pub enum Error {
     IOError(#[from] std::io::Error),
     #[error("chunk creation callback error with message `{0}`")]
     ChunkCreationCallbackError(String),
+    #[error("timestamp at row {at_row} is lower than the previous one")]
+    NonMonotonicTimestamp { at_row: usize },
+    #[error(
+        "encoding `{encoding}` is not supported by column `{column}` of type `{physical_type}`"
+    )]
+    UnsupportedColumnEncoding {
+        column: String,
+        encoding: String,
+        physical_type: String,
+    },
     #[error("unsupported write format")]
     Unsupported,
     #[error("spawn_blocking task failed: {0}")]
@@ -4,7 +4,7 @@ use crate::{params, rw::Error, traits};
 
 /// This enum allows choosing the appropriate storage strategy based on the
 /// structure of the data being written.
-#[derive(Debug, Serialize, Deserialize, PartialEq, Copy, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Copy, Clone)]
 #[serde(rename_all = "snake_case")]
 pub enum Format {
     /// Serialization format used to store data in a columnar format.
@@ -18,14 +18,51 @@ pub enum Format {
     /// Serialization format for images and dense multi-dimensional arrays.
     /// This format is optimized for storing high-dimensional data efficiently.
     Image,
+
+    /// Serialization format for high-frequency, low-latency data (e.g. control loops).
+    /// Data is compressed with Snappy, which trades compression ratio for decoding speed:
+    /// files are larger than the ones produced by [`Format::Image`] (ZSTD level 22),
+    /// but they are much cheaper to decode.
+    Fast,
+
+    /// Serialization format compressing data with GZIP, for downstream tools that can't
+    /// read ZSTD compressed parquet files. Apart from the codec it matches [`Format::Ragged`].
+    Gzip,
+
+    /// Serialization format compressing data with LZ4 (raw), for downstream tools that can't
+    /// read ZSTD compressed parquet files. Apart from the codec it matches [`Format::Ragged`].
+    Lz4,
+
+    /// Serialization format storing data as uncompressed Arrow IPC (Feather v2) files.
+    /// Files can be loaded as they are by Arrow based tools (e.g. pandas, polars).
+    Ipc,
+}
+
+/// File container in which the data of a [`Format`] is stored
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Container {
+    /// Parquet files <https://parquet.apache.org/docs/file-format/>
+    Parquet,
+    /// Arrow IPC files <https://arrow.apache.org/docs/format/Columnar.html#ipc-file-format>
+    Ipc,
+}
+
+impl Format {
+    pub fn container(&self) -> Container {
+        match self {
+            Self::Default | Self::Ragged | Self::Image | Self::Fast | Self::Gzip | Self::Lz4 => {
+                Container::Parquet
+            }
+            Self::Ipc => Container::Ipc,
+        }
+    }
 }
 
 impl traits::AsExtension for Format {
     fn as_extension(&self) -> String {
-        match self {
-            Self::Default => params::ext::PARQUET.to_owned(),
-            Self::Ragged => params::ext::PARQUET.to_owned(),
-            Self::Image => params::ext::PARQUET.to_owned(),
+        match self.container() {
+            Container::Parquet => params::ext::PARQUET.to_owned(),
+            Container::Ipc => params::ext::ARROW.to_owned(),
         }
     }
 }
@@ -36,6 +73,10 @@ impl std::fmt::Display for Format {
             Self::Default => write!(f, "default"),
             Self::Ragged => write!(f, "ragged"),
             Self::Image => write!(f, "image"),
+            Self::Fast => write!(f, "fast"),
+            Self::Gzip => write!(f, "gzip"),
+            Self::Lz4 => write!(f, "lz4"),
+            Self::Ipc => write!(f, "ipc"),
         }
     }
 }
@@ -48,6 +89,10 @@ impl std::str::FromStr for Format {
             "default" => Ok(Self::Default),
             "ragged" => Ok(Self::Ragged),
             "image" => Ok(Self::Image),
+            "fast" => Ok(Self::Fast),
+            "gzip" => Ok(Self::Gzip),
+            "lz4" => Ok(Self::Lz4),
+            "ipc" => Ok(Self::Ipc),
             _ => Err(Error::UnkownFormat(value.to_owned())),
         }
     }
@@ -76,6 +121,27 @@ mod tests {
         assert!(image.is_ok());
         assert_eq!(image.as_ref().unwrap(), &Format::Image);
         assert_eq!(image.unwrap().as_extension(), params::ext::PARQUET);
+
+        let fast = Format::from_str("fast");
+        assert!(fast.is_ok());
+        assert_eq!(fast.as_ref().unwrap(), &Format::Fast);
+        assert_eq!(fast.unwrap().as_extension(), params::ext::PARQUET);
+
+        let gzip = Format::from_str("gzip");
+        assert!(gzip.is_ok());
+        assert_eq!(gzip.as_ref().unwrap(), &Format::Gzip);
+        assert_eq!(gzip.unwrap().as_extension(), params::ext::PARQUET);
+
+        let lz4 = Format::from_str("lz4");
+        assert!(lz4.is_ok());
+        assert_eq!(lz4.as_ref().unwrap(), &Format::Lz4);
+        assert_eq!(lz4.unwrap().as_extension(), params::ext::PARQUET);
+
+        let ipc = Format::from_str("ipc");
+        assert!(ipc.is_ok());
+        assert_eq!(ipc.as_ref().unwrap(), &Format::Ipc);
+        assert_eq!(ipc.as_ref().unwrap().container(), Container::Ipc);
+        assert_eq!(ipc.unwrap().as_extension(), params::ext::ARROW);
     }
 
     #[test]
@@ -83,5 +149,9 @@ mod tests {
         assert_eq!("ragged", Format::Ragged.to_string());
         assert_eq!("default", Format::Default.to_string());
         assert_eq!("image", Format::Image.to_string());
+        assert_eq!("fast", Format::Fast.to_string());
+        assert_eq!("gzip", Format::Gzip.to_string());
+        assert_eq!("lz4", Format::Lz4.to_string());
+        assert_eq!("ipc", Format::Ipc.to_string());
     }
 }
@@ -1,28 +1,43 @@
 use std::sync::Arc;
 
 use arrow::datatypes::Schema;
+use arrow::ipc::writer::{FileWriter, IpcWriteOptions};
 use parquet::{
-    arrow::ArrowWriter,
-    basic::{Compression, ZstdLevel},
-    file::properties::{WriterProperties, WriterVersion},
-    schema::types::ColumnPath,
+    arrow::{ArrowSchemaConverter, ArrowWriter},
+    basic::{Compression, Encoding, GzipLevel, Type as PhysicalType, ZstdLevel},
+    file::properties::{
+        DEFAULT_MAX_ROW_GROUP_SIZE, EnabledStatistics, WriterProperties, WriterPropertiesBuilder,
+        WriterVersion,
+    },
+    schema::types::{ColumnDescriptor, ColumnPath},
 };
 
 use super::{Error, Format};
+use crate::params;
 
 pub enum Writer {
     /// Parquet file format <https://parquet.apache.org/docs/file-format/>
-    /// (cabba) TODO: evaluate `AsyncArrowWriter`
+    ///
+    /// The writer is synchronous, encoding and compression are CPU bound and are offloaded
+    /// to the blocking thread pool by [`super::ChunkedWriter`]. An `AsyncArrowWriter` would
+    /// only make the I/O on the sink asynchronous (here an in-memory buffer), while still
+    /// encoding on the async executor.
     Parquet(ArrowWriter<Vec<u8>>),
+    /// Arrow IPC file format <https://arrow.apache.org/docs/format/Columnar.html#ipc-file-format>
+    Ipc(FileWriter<Vec<u8>>),
 }
 
 impl Writer {
-    pub fn new(schema: &Arc<Schema>, format: Format) -> Result<Self, Error> {
-        match format {
-            Format::Default => {
-                let props = WriterProperties::builder()
-                    .set_writer_version(WriterVersion::PARQUET_2_0)
-                    .build();
+    /// Creates a writer for `format`, row groups of parquet files hold at most
+    /// `max_row_group_size` rows (the format default if [`None`]).
+    pub fn new(
+        schema: &Arc<Schema>,
+        format: Format,
+        max_row_group_size: Option<usize>,
+    ) -> Result<Self, Error> {
+        match format.container() {
+            super::Container::Parquet => {
+                let props = format.writer_properties(schema, max_row_group_size)?;
 
                 Ok(Self::Parquet(ArrowWriter::try_new(
                     Vec::new(),
@@ -30,54 +45,359 @@ impl Writer {
                     Some(props),
                 )?))
             }
-            Format::Ragged => {
-                let ts_path = ColumnPath::from("timestamp");
-
-                let props = WriterProperties::builder()
-                    .set_writer_version(WriterVersion::PARQUET_2_0)
-                    // Data will be compressed with ZSTD at a lower compression rate
-                    .set_compression(Compression::ZSTD(ZstdLevel::try_new(5).unwrap()))
-                    .set_dictionary_enabled(false)
-                    .set_statistics_enabled(parquet::file::properties::EnabledStatistics::None)
-                    // set timestamp specific parameters
-                    .set_column_compression(ts_path.clone(), Compression::UNCOMPRESSED)
-                    .set_column_statistics_enabled(
-                        ts_path.clone(),
-                        parquet::file::properties::EnabledStatistics::Page,
-                    )
-                    .set_column_bloom_filter_enabled(ts_path, true)
-                    .build();
+            super::Container::Ipc => {
+                // Buffers are left uncompressed, so that files can be memory mapped by readers
+                let options = IpcWriteOptions::default();
 
-                Ok(Self::Parquet(ArrowWriter::try_new(
+                Ok(Self::Ipc(FileWriter::try_new_with_options(
                     Vec::new(),
-                    schema.clone(),
-                    Some(props),
+                    schema,
+                    options,
                 )?))
             }
-            Format::Image => {
-                let ts_path = ColumnPath::from("timestamp");
-
-                let props = WriterProperties::builder()
-                    .set_writer_version(WriterVersion::PARQUET_2_0)
-                    // Data will be compressed with ZSTD at maximum compression rate
-                    .set_compression(Compression::ZSTD(ZstdLevel::try_new(22).unwrap()))
-                    .set_dictionary_enabled(false)
-                    .set_statistics_enabled(parquet::file::properties::EnabledStatistics::None)
-                    // set timestamp specific parameters
-                    .set_column_compression(ts_path.clone(), Compression::UNCOMPRESSED)
-                    .set_column_statistics_enabled(
-                        ts_path.clone(),
-                        parquet::file::properties::EnabledStatistics::Page,
-                    )
-                    .set_column_bloom_filter_enabled(ts_path, true)
-                    .build();
+        }
+    }
+}
 
-                Ok(Self::Parquet(ArrowWriter::try_new(
-                    Vec::new(),
-                    schema.clone(),
-                    Some(props),
-                )?))
+/// Columns of a schema a parquet [`Encoding`] is applied to
+#[derive(Debug, Clone, Copy)]
+enum EncodedColumns {
+    /// The timestamp column ([`params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP`])
+    Timestamp,
+    /// Every floating point leaf column, including the ones nested in lists and structs
+    Floats,
+}
+
+impl EncodedColumns {
+    fn matches(&self, column: &ColumnDescriptor) -> bool {
+        match self {
+            Self::Timestamp => {
+                column.path().parts() == [params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP]
+            }
+            Self::Floats => matches!(
+                column.physical_type(),
+                PhysicalType::FLOAT | PhysicalType::DOUBLE
+            ),
+        }
+    }
+}
+
+/// Returns `true` if the parquet specification allows `encoding` for columns of type `physical_type`.
+///
+/// Dictionary encodings are excluded since they are driven by the dictionary settings of the writer.
+fn is_encoding_supported(encoding: Encoding, physical_type: PhysicalType) -> bool {
+    use PhysicalType as T;
+
+    match encoding {
+        Encoding::PLAIN => true,
+        Encoding::RLE => physical_type == T::BOOLEAN,
+        Encoding::DELTA_BINARY_PACKED => matches!(physical_type, T::INT32 | T::INT64),
+        Encoding::DELTA_LENGTH_BYTE_ARRAY => physical_type == T::BYTE_ARRAY,
+        Encoding::DELTA_BYTE_ARRAY => {
+            matches!(physical_type, T::BYTE_ARRAY | T::FIXED_LEN_BYTE_ARRAY)
+        }
+        Encoding::BYTE_STREAM_SPLIT => matches!(physical_type, T::FLOAT | T::DOUBLE),
+        _ => false,
+    }
+}
+
+/// Sets `encoding` on every column of `schema` selected by `columns`.
+///
+/// Dictionary encoding is disabled on those columns, otherwise the encoding would only be used
+/// as a fallback when the dictionary grows too large.
+fn set_column_encodings(
+    mut builder: WriterPropertiesBuilder,
+    schema: &Schema,
+    encodings: &[(EncodedColumns, Encoding)],
+) -> Result<WriterPropertiesBuilder, Error> {
+    let descriptor = ArrowSchemaConverter::new().convert(schema)?;
+
+    for column in descriptor.columns() {
+        for (columns, encoding) in encodings {
+            if !columns.matches(column) {
+                continue;
+            }
+
+            if !is_encoding_supported(*encoding, column.physical_type()) {
+                return Err(Error::UnsupportedColumnEncoding {
+                    column: column.path().string(),
+                    encoding: encoding.to_string(),
+                    physical_type: column.physical_type().to_string(),
+                });
+            }
+
+            builder = builder
+                .set_column_dictionary_enabled(column.path().clone(), false)
+                .set_column_encoding(column.path().clone(), *encoding);
+        }
+    }
+
+    Ok(builder)
+}
+
+impl Format {
+    /// Per column encodings used by the format.
+    ///
+    /// Timestamps are usually increasing with a (nearly) constant step, which delta encoding
+    /// reduces to a few bits per value. Splitting the bytes of floating point values groups
+    /// together the slowly changing ones (sign and exponent), making them easier to compress.
+    fn column_encodings(&self) -> &'static [(EncodedColumns, Encoding)] {
+        match self {
+            Self::Default | Self::Fast => {
+                &[(EncodedColumns::Timestamp, Encoding::DELTA_BINARY_PACKED)]
             }
+            Self::Ragged | Self::Image | Self::Gzip | Self::Lz4 => &[
+                (EncodedColumns::Timestamp, Encoding::DELTA_BINARY_PACKED),
+                (EncodedColumns::Floats, Encoding::BYTE_STREAM_SPLIT),
+            ],
+            Self::Ipc => &[],
+        }
+    }
+
+    /// Default maximum number of rows of a parquet row group.
+    ///
+    /// Time series favor small row groups, so that statistics based pruning can skip most of
+    /// the data of a seek. Images are large rows usually read in full, fewer row groups reduce
+    /// the metadata and the per group overhead.
+    pub fn default_max_row_group_size(&self) -> usize {
+        match self {
+            Self::Default | Self::Fast | Self::Ragged | Self::Gzip | Self::Lz4 => 64 * 1024,
+            Self::Image => 4 * DEFAULT_MAX_ROW_GROUP_SIZE,
+            Self::Ipc => DEFAULT_MAX_ROW_GROUP_SIZE,
         }
     }
+
+    /// Codec used to compress the columns of the format, except the timestamp column which is
+    /// left uncompressed. [`None`] if the format keeps the parquet defaults (or is not stored
+    /// in parquet files).
+    ///
+    /// The codec is recorded in the parquet metadata, so readers do not need to know it.
+    fn compression(&self) -> Option<Compression> {
+        match self {
+            Self::Default | Self::Ipc => None,
+            // ZSTD at a lower compression rate
+            Self::Ragged => Some(Compression::ZSTD(ZstdLevel::try_new(5).unwrap())),
+            // ZSTD at maximum compression rate
+            Self::Image => Some(Compression::ZSTD(ZstdLevel::try_new(22).unwrap())),
+            // Snappy favors decoding speed over compression rate
+            Self::Fast => Some(Compression::SNAPPY),
+            Self::Gzip => Some(Compression::GZIP(GzipLevel::default())),
+            Self::Lz4 => Some(Compression::LZ4_RAW),
+        }
+    }
+
+    /// Returns the parquet writer properties used to write data with `schema` in this format.
+    ///
+    /// Row groups hold at most `max_row_group_size` rows, if [`None`] (or zero) the format
+    /// default is used (see [`Format::default_max_row_group_size`]).
+    ///
+    /// Returns an error if the format is not stored in parquet files or if an encoding of the
+    /// format is not supported by the type of the column it applies to.
+    pub fn writer_properties(
+        &self,
+        schema: &Schema,
+        max_row_group_size: Option<usize>,
+    ) -> Result<WriterProperties, Error> {
+        let ts_path = ColumnPath::from(params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP);
+
+        let builder = WriterProperties::builder()
+            .set_writer_version(WriterVersion::PARQUET_2_0)
+            .set_max_row_group_size(
+                max_row_group_size
+                    .filter(|size| *size > 0)
+                    .unwrap_or_else(|| self.default_max_row_group_size()),
+            );
+
+        let builder = match (self, self.compression()) {
+            (Self::Ipc, _) => return Err(Error::Unsupported),
+            (_, None) => builder,
+            (_, Some(compression)) => builder
+                .set_compression(compression)
+                .set_dictionary_enabled(false)
+                .set_statistics_enabled(EnabledStatistics::None)
+                // set timestamp specific parameters
+                .set_column_compression(ts_path.clone(), Compression::UNCOMPRESSED)
+                .set_column_statistics_enabled(ts_path.clone(), EnabledStatistics::Page)
+                .set_column_bloom_filter_enabled(ts_path, true),
+        };
+
+        Ok(set_column_encodings(builder, schema, self.column_encodings())?.build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{ArrayRef, Float64Array, Int64Array, RecordBatch};
+    use arrow::datatypes::{DataType, Field};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    fn write(schema: Arc<Schema>, batch: &RecordBatch, props: WriterProperties) -> Vec<u8> {
+        let mut writer = ArrowWriter::try_new(Vec::new(), schema, Some(props)).unwrap();
+        writer.write(batch).unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    fn column_encodings_reduce_size() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+                DataType::Int64,
+                false,
+            ),
+            Field::new("value", DataType::Float64, false),
+        ]));
+
+        // A 1kHz signal sampled with a small jitter
+        let rows = 100_000;
+        let timestamps: Vec<i64> = (0..rows).map(|i| i * 1_000_000 + (i % 7)).collect();
+        let values: Vec<f64> = (0..rows).map(|i| (i as f64 / 1000.0).sin()).collect();
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(timestamps)) as ArrayRef,
+                Arc::new(Float64Array::from(values)) as ArrayRef,
+            ],
+        )
+        .unwrap();
+
+        for format in [
+            Format::Default,
+            Format::Ragged,
+            Format::Image,
+            Format::Fast,
+            Format::Gzip,
+            Format::Lz4,
+        ] {
+            let encoded = format.writer_properties(&schema, None).unwrap();
+            // Same properties, without the per column encodings
+            let plain = set_column_encodings(
+                WriterProperties::into_builder(encoded.clone()),
+                &schema,
+                &[
+                    (EncodedColumns::Timestamp, Encoding::PLAIN),
+                    (EncodedColumns::Floats, Encoding::PLAIN),
+                ],
+            )
+            .unwrap()
+            .build();
+
+            let encoded = write(schema.clone(), &batch, encoded);
+            let plain_size = write(schema.clone(), &batch, plain).len();
+            let encoded_size = encoded.len();
+
+            let read: Vec<RecordBatch> =
+                ParquetRecordBatchReaderBuilder::try_new(bytes::Bytes::from(encoded))
+                    .unwrap()
+                    .build()
+                    .unwrap()
+                    .collect::<Result<_, _>>()
+                    .unwrap();
+            assert_eq!(
+                arrow::compute::concat_batches(&schema, &read).unwrap(),
+                batch
+            );
+
+            assert!(
+                encoded_size < plain_size,
+                "format `{format}`: encoded {encoded_size} bytes, plain {plain_size} bytes"
+            );
+        }
+    }
+
+    #[test]
+    fn max_row_group_size() {
+        let schema = Schema::new(vec![Field::new(
+            params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+            DataType::Int64,
+            false,
+        )]);
+
+        let props = Format::Ragged.writer_properties(&schema, None).unwrap();
+        assert_eq!(props.max_row_group_size(), 64 * 1024);
+
+        let props = Format::Image.writer_properties(&schema, None).unwrap();
+        assert_eq!(props.max_row_group_size(), 4 * DEFAULT_MAX_ROW_GROUP_SIZE);
+
+        // An explicit size overrides the format default
+        let props = Format::Image.writer_properties(&schema, Some(512)).unwrap();
+        assert_eq!(props.max_row_group_size(), 512);
+
+        // Parquet requires at least one row per group
+        let props = Format::Default.writer_properties(&schema, Some(0)).unwrap();
+        assert_eq!(props.max_row_group_size(), 64 * 1024);
+    }
+
+    #[test]
+    fn compression_codecs() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+                DataType::Int64,
+                false,
+            ),
+            Field::new("value", DataType::Float64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(vec![0, 1, 2])) as ArrayRef,
+                Arc::new(Float64Array::from(vec![0.0, 0.5, 1.0])) as ArrayRef,
+            ],
+        )
+        .unwrap();
+
+        for (format, codec) in [
+            (Format::Gzip, "GZIP"),
+            (Format::Lz4, "LZ4_RAW"),
+            (Format::Fast, "SNAPPY"),
+        ] {
+            let props = format.writer_properties(&schema, None).unwrap();
+            let data = write(schema.clone(), &batch, props);
+
+            // The codec is read back from the file metadata
+            let reader =
+                ParquetRecordBatchReaderBuilder::try_new(bytes::Bytes::from(data)).unwrap();
+            let row_group = reader.metadata().row_group(0);
+            assert_eq!(row_group.column(0).compression(), Compression::UNCOMPRESSED);
+            assert!(
+                row_group
+                    .column(1)
+                    .compression()
+                    .to_string()
+                    .starts_with(codec),
+                "format `{format}`: {}",
+                row_group.column(1).compression()
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_column_encoding() {
+        let schema = Schema::new(vec![
+            Field::new(
+                params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+                DataType::Utf8,
+                false,
+            ),
+            Field::new("value", DataType::Float64, false),
+        ]);
+
+        let err = Format::Image.writer_properties(&schema, None).unwrap_err();
+        assert!(matches!(err, Error::UnsupportedColumnEncoding { .. }));
+
+        assert!(matches!(
+            Format::Ipc.writer_properties(&schema, None),
+            Err(Error::Unsupported)
+        ));
+
+        // Encodings are checked against the physical type of the column
+        let builder = set_column_encodings(
+            WriterProperties::builder(),
+            &schema,
+            &[(EncodedColumns::Floats, Encoding::DELTA_BINARY_PACKED)],
+        );
+        assert!(builder.is_err());
+    }
 }
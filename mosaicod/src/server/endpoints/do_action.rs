@@ -1,9 +1,14 @@
+use futures::StreamExt;
+use futures::stream::BoxStream;
 use log::{info, trace, warn};
 
 use crate::{
     marshal::{self, ActionRequest, ActionResponse},
-    query,
-    repo::{self, FacadeError, FacadeLayer, FacadeQuery, FacadeSequence, FacadeTopic},
+    params, query,
+    repo::{
+        self, FacadeError, FacadeLayer, FacadeOntologyContract, FacadeQuery, FacadeSequence,
+        FacadeTopic,
+    },
     server::errors::ServerError,
     store, types,
     types::{MetadataBlob, Resource},
@@ -45,7 +50,11 @@ pub async fn do_action(
         }
 
         ActionRequest::SequenceDelete(data) => {
-            warn!("requested deletion of resource {}", data.name);
+            if data.dry_run {
+                info!("requested deletion summary of resource {}", data.name);
+            } else {
+                warn!("requested deletion of resource {}", data.name);
+            }
 
             let handle = FacadeSequence::new(data.name, store, repo);
 
@@ -53,8 +62,15 @@ pub async fn do_action(
                 return Err(ServerError::SequenceLocked);
             }
 
+            if data.dry_run {
+                let summary = handle.delete_summary().await?;
+                return Ok(ActionResponse::SequenceDeleteSummary(summary.into()));
+            }
+
             let loc = handle.locator.clone();
             handle.delete().await?;
+            ts_engine.read_cache().invalidate(loc.name());
+            ts_engine.schema_cache().invalidate(loc.name());
             warn!("resource {} deleted", loc);
 
             ActionResponse::Empty
@@ -80,6 +96,8 @@ pub async fn do_action(
             // Save handle name (for logging) since the delete will consume the handle
             let loc = handle.locator.clone();
             handle.delete().await?;
+            ts_engine.read_cache().invalidate(loc.name());
+            ts_engine.schema_cache().invalidate(loc.name());
             warn!("resource {} deleted", loc.name());
 
             ActionResponse::Empty
@@ -98,18 +116,30 @@ pub async fn do_action(
                 return Err(ServerError::BadKey);
             }
 
+            // Topics can't receive data once locked, so they can be checked before locking
+            // the sequence
+            if data.strict {
+                handle.ensure_topics_have_data().await?;
+            }
+
             handle.lock().await?;
             trace!("resource {} locked", handle.locator);
 
             ActionResponse::Empty
         }
 
+        ActionRequest::SequenceReopen(data) => {
+            let allow_reopen = params::configurables().allow_sequence_reopen;
+            sequence_reopen(data, allow_reopen, store, repo, &ts_engine).await?
+        }
+
         ActionRequest::SequenceNotifyCreate(data) => {
             info!("new notify for {}", data.name);
 
             let handle = FacadeSequence::new(data.name, store, repo);
             let ntype: types::NotifyType = data.notify_type.parse()?;
-            handle.notify(ntype, data.msg).await?;
+            let min_severity = params::configurables().min_notify_severity;
+            handle.notify(ntype, data.msg, min_severity).await?;
 
             ActionResponse::Empty
         }
@@ -118,9 +148,10 @@ pub async fn do_action(
             info!("notify list for {}", data.name);
 
             let handle = FacadeSequence::new(data.name, store, repo);
+            let min_severity = data.min_severity.map(|s| s.parse()).transpose()?;
 
             // Convert notifies to response messages
-            let notifies = handle.notify_list().await?;
+            let notifies = handle.notify_list(min_severity, data.limit).await?;
 
             ActionResponse::SequenceNotifyList(notifies.into())
         }
@@ -158,8 +189,20 @@ pub async fn do_action(
                 marshal::JsonMetadataBlob::try_from_str(data.user_metadata()?.as_str())
                     .map_err(FacadeError::from)?;
 
+            // An explicit tag always wins over the one found in the metadata, without both
+            // the tag is set by the first write to the topic
+            let ontology_tag = data.ontology_tag.clone().or_else(|| {
+                data.ontology_tag_from_metadata(
+                    &params::configurables().ontology_tag_metadata_field,
+                )
+            });
+
             let mdata = types::TopicMetadata::new(
-                types::TopicProperties::new(data.serialization_format, data.ontology_tag),
+                types::TopicProperties::new(data.serialization_format, ontology_tag)
+                    .with_timestamp_unit(data.timestamp_unit)
+                    .with_monotonic_timestamps(data.monotonic_timestamps)
+                    .with_max_row_group_size(data.max_row_group_size)
+                    .with_declared_schema(data.declared_schema),
                 user_mdata,
             );
 
@@ -175,6 +218,73 @@ pub async fn do_action(
             ActionResponse::TopicCreate(r_id.into())
         }
 
+        ActionRequest::TopicCreateBulk(data) => {
+            info!(
+                "requested bulk creation of {} topics under sequence with key `{}`",
+                data.topics.len(),
+                data.sequence_key
+            );
+
+            let received_uuid: uuid::Uuid = data.sequence_key.parse()?;
+
+            let mut topics = Vec::with_capacity(data.topics.len());
+            for spec in data.topics {
+                let user_mdata =
+                    marshal::JsonMetadataBlob::try_from_str(spec.user_metadata()?.as_str())
+                        .map_err(|e| FacadeError::from(e).for_topic(&spec.name))?;
+
+                let mdata = types::TopicMetadata::new(
+                    types::TopicProperties::new(spec.serialization_format, Some(spec.ontology_tag))
+                        .with_timestamp_unit(spec.timestamp_unit)
+                        .with_monotonic_timestamps(spec.monotonic_timestamps)
+                        .with_max_row_group_size(spec.max_row_group_size)
+                        .with_declared_schema(spec.declared_schema),
+                    user_mdata,
+                );
+
+                topics.push((spec.name, mdata));
+            }
+
+            let created = FacadeTopic::create_bulk(&received_uuid, topics, store, repo).await?;
+
+            ActionResponse::TopicCreateBulk(marshal::TopicCreateBulk {
+                topics: created
+                    .into_iter()
+                    .map(|(handle, r_id)| {
+                        trace!(
+                            "resource {} created with uuid {}",
+                            handle.locator, r_id.uuid,
+                        );
+                        marshal::TopicKey {
+                            name: handle.locator.name().clone(),
+                            key: r_id.uuid.to_string(),
+                        }
+                    })
+                    .collect(),
+            })
+        }
+
+        ActionRequest::TopicUpdate(data) => {
+            info!(
+                "requested resource {} metadata update (merge: {})",
+                data.name, data.merge
+            );
+
+            let handle = FacadeTopic::new(data.name.clone(), store, repo);
+
+            if handle.is_locked().await? {
+                return Err(FacadeError::TopicLocked.into());
+            }
+
+            let user_mdata =
+                marshal::JsonMetadataBlob::try_from_str(data.user_metadata()?.as_str())
+                    .map_err(FacadeError::from)?;
+
+            handle.update_metadata(user_mdata, data.merge).await?;
+
+            ActionResponse::Empty
+        }
+
         ActionRequest::TopicDelete(data) => {
             warn!("requested deletion of resource {}", data.name);
 
@@ -185,6 +295,8 @@ pub async fn do_action(
             }
 
             handle.delete().await?;
+            ts_engine.read_cache().invalidate(handle.locator.name());
+            ts_engine.schema_cache().invalidate(handle.locator.name());
             warn!("resource {} deleted", data.name);
 
             ActionResponse::Empty
@@ -194,7 +306,10 @@ pub async fn do_action(
             info!("nofity for {}", data.name);
 
             let handle = FacadeTopic::new(data.name, store, repo);
-            handle.notify(data.notify_type.parse()?, data.msg).await?;
+            let min_severity = params::configurables().min_notify_severity;
+            handle
+                .notify(data.notify_type.parse()?, data.msg, min_severity)
+                .await?;
 
             ActionResponse::Empty
         }
@@ -203,7 +318,9 @@ pub async fn do_action(
             info!("notify list for {}", data.name);
 
             let handle = FacadeTopic::new(data.name, store, repo);
-            let notifies = handle.notify_list().await?;
+            let min_severity = data.min_severity.map(|s| s.parse()).transpose()?;
+
+            let notifies = handle.notify_list(min_severity, data.limit).await?;
             ActionResponse::TopicNotifyList(notifies.into())
         }
 
@@ -216,6 +333,25 @@ pub async fn do_action(
             ActionResponse::Empty
         }
 
+        ActionRequest::NotifyDelete(data) => {
+            warn!("deleting notify `{}` of {}", data.id, data.name);
+
+            let resource = repo::get_resource_locator_from_name(&repo, &data.name).await?;
+
+            match resource.resource_type() {
+                types::ResourceType::Sequence => {
+                    let handle = FacadeSequence::new(resource.name().into(), store, repo);
+                    handle.notify_delete(data.id).await?;
+                }
+                types::ResourceType::Topic => {
+                    let handle = FacadeTopic::new(resource.name().into(), store, repo);
+                    handle.notify_delete(data.id).await?;
+                }
+            }
+
+            ActionResponse::Empty
+        }
+
         ActionRequest::SequenceSystemInfo(data) => {
             info!("[{}] sequence system informations", data.name);
 
@@ -225,6 +361,100 @@ pub async fn do_action(
             ActionResponse::SequenceSystemInfo(sysinfo.into())
         }
 
+        ActionRequest::SequenceReconcile(data) => {
+            if data.repair {
+                warn!("requested reconciliation and repair of {}", data.name);
+            } else {
+                info!("requested reconciliation of {}", data.name);
+            }
+
+            let handle = FacadeSequence::new(data.name, store, repo);
+            let report = handle.reconcile(data.repair).await?;
+            if report.repaired {
+                ts_engine.read_cache().invalidate(handle.locator.name());
+                ts_engine.schema_cache().invalidate(handle.locator.name());
+            }
+
+            if !report.missing_data_files.is_empty() || !report.orphan_data_files.is_empty() {
+                warn!(
+                    "{} has {} chunks with missing data files and {} orphan data files",
+                    handle.locator,
+                    report.missing_data_files.len(),
+                    report.orphan_data_files.len()
+                );
+            }
+
+            ActionResponse::SequenceReconcile(report.into())
+        }
+
+        ActionRequest::SequenceRename(data) => {
+            warn!(
+                "requested rename of resource {} to {}",
+                data.name, data.new_name
+            );
+
+            let handle = FacadeSequence::new(data.name, store, repo);
+            let new = types::SequenceResourceLocator::from(data.new_name);
+            handle.rename(new.clone()).await?;
+
+            // Reads and schemas are cached by path, entries of a previous sequence with the
+            // new name would be stale as well
+            ts_engine.read_cache().invalidate(handle.locator.name());
+            ts_engine.read_cache().invalidate(new.name());
+            ts_engine.schema_cache().invalidate(handle.locator.name());
+            ts_engine.schema_cache().invalidate(new.name());
+
+            ActionResponse::Empty
+        }
+
+        ActionRequest::SequenceClone(data) => {
+            info!(
+                "requested clone of resource {} as {}",
+                data.name, data.new_name
+            );
+
+            let handle = FacadeSequence::new(data.name, store, repo);
+            let r_id = handle
+                .clone_as(types::SequenceResourceLocator::from(data.new_name))
+                .await?;
+
+            ActionResponse::SequenceClone(r_id.into())
+        }
+
+        ActionRequest::SequenceList(data) => {
+            info!(
+                "request sequence list (limit: {:?}, offset: {})",
+                data.limit, data.offset
+            );
+
+            let filter = data
+                .filter
+                .map(marshal::sequence_filter_from_serde_value)
+                .transpose()?;
+
+            trace!("sequence filter: {:?}", filter);
+
+            let page = FacadeSequence::list(repo, filter, data.limit, data.offset).await?;
+
+            ActionResponse::SequenceList(page.into())
+        }
+
+        ActionRequest::TopicList(data) => {
+            info!("[{}] request topic list", data.name);
+
+            let filter = data
+                .filter
+                .map(marshal::topic_filter_from_serde_value)
+                .transpose()?;
+
+            trace!("topic filter: {:?}", filter);
+
+            let handle = FacadeSequence::new(data.name, store, repo);
+            let topics = handle.topics(filter).await?;
+
+            ActionResponse::TopicList(topics.into())
+        }
+
         ActionRequest::TopicSystemInfo(data) => {
             info!("[{}] topic system informations", data.name);
 
@@ -234,6 +464,294 @@ pub async fn do_action(
             ActionResponse::TopicSystemInfo(sysinfo.into())
         }
 
+        ActionRequest::TopicRestat(data) => {
+            warn!("[{}] recomputing chunk statistics", data.name);
+
+            let handle = FacadeTopic::new(data.name, store, repo);
+            let chunks = handle.restat().await;
+
+            // Timestamp bounds may have changed, a failed restat may have updated some chunks
+            ts_engine.read_cache().invalidate(handle.locator.name());
+            let chunks = chunks?;
+
+            info!(
+                "[{}] statistics recomputed for {} chunks",
+                handle.locator, chunks
+            );
+
+            ActionResponse::TopicRestat(marshal::TopicRestat { chunks })
+        }
+
+        ActionRequest::TopicCompact(data) => {
+            info!("[{}] compacting chunks", data.name);
+
+            let handle = FacadeTopic::new(data.name, store, repo);
+            let compaction = handle.compact().await;
+
+            // A failed compaction may have replaced some of the chunks
+            ts_engine.read_cache().invalidate(handle.locator.name());
+            ts_engine.schema_cache().invalidate(handle.locator.name());
+            let compaction = compaction?;
+
+            info!(
+                "[{}] {} chunks merged into {} chunks",
+                handle.locator, compaction.merged_chunks, compaction.created_chunks
+            );
+
+            ActionResponse::TopicCompact(compaction.into())
+        }
+
+        ActionRequest::TopicSchema(data) => {
+            info!("[{}] topic schema", data.name);
+
+            let handle = FacadeTopic::new(data.name, store, repo);
+            let metadata = handle.metadata().await?;
+            let schema = handle
+                .arrow_schema(metadata.properties.serialization_format)
+                .await?;
+
+            ActionResponse::TopicSchema(schema.into())
+        }
+
+        ActionRequest::TopicSchemaCompare(data) => {
+            info!("[{}] comparing topic schemas", data.sequence);
+
+            let handle = FacadeSequence::new(data.sequence, store.clone(), repo.clone());
+            let mut names: Vec<String> = handle
+                .topic_list()
+                .await?
+                .into_iter()
+                .map(|topic| topic.name().clone())
+                .collect();
+
+            if !data.topics.is_empty() {
+                if let Some(unknown) = data.topics.iter().find(|t| !names.contains(t)) {
+                    return Err(FacadeError::NotFound(unknown.clone()).into());
+                }
+                names = data.topics;
+            }
+            names.sort();
+            names.dedup();
+
+            let mut schemas = Vec::with_capacity(names.len());
+            for name in names {
+                let topic = FacadeTopic::new(name.clone(), store.clone(), repo.clone());
+                let metadata = topic.metadata().await.map_err(|e| e.for_topic(&name))?;
+
+                if data
+                    .ontology_tag
+                    .as_ref()
+                    .is_some_and(|tag| Some(tag) != metadata.properties.ontology_tag.as_ref())
+                {
+                    continue;
+                }
+
+                let schema = topic
+                    .arrow_schema(metadata.properties.serialization_format)
+                    .await
+                    .map_err(|e| e.for_topic(&name))?;
+                schemas.push((name, schema));
+            }
+
+            let inconsistencies = crate::arrow::missing_fields(&schemas);
+
+            trace!(
+                "{} topics compared, {} inconsistent",
+                schemas.len(),
+                inconsistencies.len()
+            );
+
+            ActionResponse::TopicSchemaCompare(marshal::TopicSchemaCompare {
+                topics: schemas.into_iter().map(|(name, _)| name).collect(),
+                consistent: inconsistencies.is_empty(),
+                inconsistencies: inconsistencies
+                    .into_iter()
+                    .map(|(topic, missing_fields)| marshal::TopicMissingFields {
+                        topic,
+                        missing_fields,
+                    })
+                    .collect(),
+            })
+        }
+
+        ActionRequest::TopicLatest(data) => {
+            info!("[{}] latest topic row", data.name);
+
+            let handle = FacadeTopic::new(data.name, store, repo);
+            let metadata = handle.metadata().await?;
+
+            // Empty topics have no data files to read
+            if handle.chunks_stats().await?.total_row_count == 0 {
+                ActionResponse::TopicLatest(marshal::TopicLatest { row: None })
+            } else {
+                let batches = ts_engine
+                    .read(
+                        handle.path(),
+                        metadata.properties.serialization_format,
+                        None,
+                        None,
+                        None,
+                        query::SortOrder::Desc,
+                    )
+                    .await?
+                    .limit(1)?
+                    .collect()
+                    .await?;
+
+                ActionResponse::TopicLatest(batches.try_into()?)
+            }
+        }
+
+        ActionRequest::TopicExportCsv(data) => {
+            info!("[{}] exporting topic as csv", data.name);
+
+            let delimiter = u8::try_from(data.delimiter)
+                .ok()
+                .filter(u8::is_ascii)
+                .ok_or_else(|| marshal::ActionError::InvalidField {
+                    field: "delimiter".to_owned(),
+                    reason: format!("`{}` is not an ASCII character", data.delimiter),
+                })?;
+
+            let handle = FacadeTopic::new(data.name, store, repo);
+            let metadata = handle.metadata().await?;
+
+            let stream: BoxStream<'static, _> = if handle.chunks_stats().await?.total_row_count == 0
+            {
+                // Empty topics have no data files to read
+                futures::stream::empty().boxed()
+            } else {
+                ts_engine
+                    .read(
+                        handle.path(),
+                        metadata.properties.serialization_format,
+                        None,
+                        None,
+                        None,
+                        query::SortOrder::Asc,
+                    )
+                    .await?
+                    .flatten()?
+                    .stream()
+                    .await?
+                    .boxed()
+            };
+
+            // The header (if requested) is written only before the first batch
+            let mut header = data.header;
+            let csv = stream.map(move |batch| {
+                let batch = batch
+                    .map_err(|e| marshal::ActionError::ResponseSerializationError(e.to_string()))?;
+                let csv = crate::arrow::record_batch_to_csv(
+                    &batch,
+                    delimiter,
+                    std::mem::take(&mut header),
+                )
+                .map_err(|e| marshal::ActionError::ResponseSerializationError(e.to_string()))?;
+                Ok(csv)
+            });
+
+            ActionResponse::TopicExportCsv(marshal::CsvStream(csv.boxed()))
+        }
+
+        ActionRequest::MultiTopicRead(data) => {
+            info!("preparing merged read of topics {:?}", data.topics);
+
+            // Fail early if any of the topics does not exist
+            for topic in &data.topics {
+                FacadeTopic::new(topic.clone(), store.clone(), repo.clone())
+                    .resource_id()
+                    .await?;
+            }
+
+            let ticket = marshal::MultiTopicTicket::new(data.topics).to_bytes()?;
+
+            ActionResponse::MultiTopicRead(marshal::FlightTicket {
+                ticket: String::from_utf8_lossy(&ticket).into_owned(),
+            })
+        }
+
+        ActionRequest::QueryAggregate(data) => {
+            info!(
+                "[{}] preparing aggregation in windows of {}ms",
+                data.topic, data.window_ms
+            );
+
+            if data.window_ms == 0 {
+                return Err(query::Error::BadWindow.into());
+            }
+
+            // Fail early if the topic does not exist
+            FacadeTopic::new(data.topic.clone(), store, repo)
+                .resource_id()
+                .await?;
+
+            let mut ticket =
+                marshal::TopicTicket::new(data.topic).with_aggregate(marshal::TicketAggregate {
+                    window_ms: data.window_ms,
+                    fields: data.fields,
+                });
+            if let Some([start, end]) = data.time_range {
+                ticket = ticket.with_time_range(start.into(), end.into());
+            }
+
+            ActionResponse::QueryAggregate(marshal::FlightTicket {
+                ticket: String::from_utf8_lossy(&ticket.to_bytes()?).into_owned(),
+            })
+        }
+
+        ActionRequest::ApproxDistinct(data) => {
+            info!(
+                "[{}] approximate distinct count for field `{}`",
+                data.topic, data.field
+            );
+
+            let handle = FacadeTopic::new(data.topic, store, repo);
+            let metadata = handle.metadata().await?;
+
+            let count = ts_engine
+                .read(
+                    handle.path(),
+                    metadata.properties.serialization_format,
+                    None,
+                    None,
+                    None,
+                    query::SortOrder::Asc,
+                )
+                .await?
+                .approx_distinct(&data.field)
+                .await?;
+
+            ActionResponse::ApproxDistinct(marshal::ApproxDistinct {
+                field: data.field,
+                approx_distinct_count: count,
+            })
+        }
+
+        ActionRequest::FieldQualityChunks(data) => {
+            info!(
+                "[{}] chunks with NaN or null values for field `{}`",
+                data.topic, data.field
+            );
+
+            let handle = FacadeTopic::new(data.topic, store, repo);
+            let chunks = handle.field_quality_chunks(&data.field).await?;
+
+            ActionResponse::FieldQualityChunks(marshal::FieldQualityChunks {
+                field: data.field,
+                chunks: chunks.into_iter().map(Into::into).collect(),
+            })
+        }
+
+        ActionRequest::TopicColumnStats(data) => {
+            info!("[{}] fields statistics", data.topic);
+
+            let handle = FacadeTopic::new(data.topic, store, repo);
+            let stats = handle.fields_stats().await?;
+
+            ActionResponse::TopicColumnStats(stats.into())
+        }
+
         ActionRequest::LayerCreate(data) => {
             info!("creating layer `{}`", data.name);
 
@@ -289,90 +807,332 @@ pub async fn do_action(
             ActionResponse::LayerList(layers.into())
         }
 
-        ActionRequest::Query(data) => {
-            info!("performing a query");
+        ActionRequest::LayerAddSequence(data) => {
+            info!(
+                "adding sequence `{}` to layer `{}`",
+                data.sequence, data.name
+            );
 
-            let filter = marshal::query_filter_from_serde_value(data.query)?;
+            let handle = FacadeLayer::new(
+                types::LayerLocator::from(data.name.as_str()), //
+                store,
+                repo,
+            );
+            handle
+                .add_sequence(&types::SequenceResourceLocator::from(data.sequence))
+                .await?;
 
-            trace!("query filter: {:?}", filter);
+            ActionResponse::Empty
+        }
 
-            let groups = FacadeQuery::query(filter, ts_engine, repo).await?;
+        ActionRequest::LayerRemoveSequence(data) => {
+            info!(
+                "removing sequence `{}` from layer `{}`",
+                data.sequence, data.name
+            );
 
-            trace!("groups found: {:?}", groups);
+            let handle = FacadeLayer::new(
+                types::LayerLocator::from(data.name.as_str()), //
+                store,
+                repo,
+            );
+            handle
+                .remove_sequence(&types::SequenceResourceLocator::from(data.sequence))
+                .await?;
 
-            ActionResponse::Query(groups.into())
+            ActionResponse::Empty
         }
-    };
 
-    Ok(response)
-}
+        ActionRequest::OntologyContractSet(data) => {
+            info!("setting contract for ontology tag `{}`", data.ontology_tag);
 
-#[cfg(test)]
-mod tests {
-    use std::sync::Arc;
+            let contract = data
+                .contract
+                .into_types(data.ontology_tag.clone())
+                .map_err(FacadeError::from)?;
 
-    use super::*;
+            FacadeOntologyContract::new(data.ontology_tag, repo)
+                .set(contract)
+                .await?;
 
-    use crate::{repo, rw};
+            ActionResponse::Empty
+        }
 
-    /// Creates and empty sequence (no data) for testing purposes.
-    async fn create_empty_sequence(
-        repo: &repo::testing::Repository,
-        store: &store::testing::Store,
-        name: &str,
-    ) -> Result<types::ResourceId, repo::FacadeError> {
-        let handle = FacadeSequence::new(name.to_owned(), (*store).clone(), (*repo).clone());
+        ActionRequest::OntologyContractDelete(data) => {
+            warn!("deleting contract for ontology tag `{}`", data.ontology_tag);
 
-        let metadata = types::SequenceMetadata::new(
-            marshal::JsonMetadataBlob::try_from_str(
-                r#"{
-                    "test_field_1" : "value1",
-                    "test_field_2" : "value2"
-                }"#,
-            )
-            .expect("Error parsing user metadata"),
-        );
+            FacadeOntologyContract::new(data.ontology_tag, repo)
+                .delete()
+                .await?;
 
-        let record = handle.create(Some(metadata)).await?;
+            ActionResponse::Empty
+        }
 
-        Ok(record)
-    }
+        ActionRequest::ResourceExists(data) => {
+            info!("[{}] checking if {:?} exists", data.name, data.kind);
+
+            let res = match data.kind {
+                marshal::requests::ResourceKind::Sequence => {
+                    FacadeSequence::new(data.name, store, repo)
+                        .resource_id()
+                        .await
+                        .map(|_| ())
+                }
+                marshal::requests::ResourceKind::Topic => FacadeTopic::new(data.name, store, repo)
+                    .resource_id()
+                    .await
+                    .map(|_| ()),
+                marshal::requests::ResourceKind::Layer => {
+                    FacadeLayer::new(types::LayerLocator::from(data.name.as_str()), store, repo)
+                        .layer_id()
+                        .await
+                        .map(|_| ())
+                }
+            };
+
+            // Only a missing record means that the resource does not exist, other
+            // errors (e.g. an unreachable database) are reported
+            let exists = match res {
+                Ok(()) => true,
+                Err(e) if e.is_not_found() => false,
+                Err(e) => return Err(e.into()),
+            };
+
+            ActionResponse::ResourceExists(marshal::ResourceExists { exists })
+        }
 
-    /// Creates and empty sequence (no data) for testing purposes.
-    async fn create_empty_topic(
-        repo: &repo::testing::Repository,
-        store: &store::testing::Store,
-        sequence: &types::ResourceId,
-        name: &str,
-    ) -> Result<types::ResourceId, repo::FacadeError> {
-        let handle = FacadeTopic::new(name.to_owned(), (*store).clone(), (*repo).clone());
-        let props = types::TopicProperties::new(rw::Format::Default, "test_tag".to_owned());
+        ActionRequest::Metrics(_) => {
+            info!("request metrics");
 
-        let metadata = types::TopicMetadata::new(
-            props,
-            marshal::JsonMetadataBlob::try_from_str(
-                r#"{
-                    "test_field_1" : "test_value_1",
-                    "test_field_2" : "test_value_2"
-                }"#,
-            )
-            .expect("Error parsing user metadata json string"),
-        );
+            let read_cache = ts_engine.read_cache().stats();
+            let schema_cache = ts_engine.schema_cache().stats();
 
-        let record = handle.create(&sequence.uuid, Some(metadata)).await?;
+            ActionResponse::Metrics(marshal::Metrics {
+                read_cache: read_cache.into(),
+                schema_cache: schema_cache.into(),
+            })
+        }
 
-        Ok(record)
-    }
+        ActionRequest::SystemHealth(_) => {
+            info!("request system health");
 
-    #[sqlx::test]
-    /// This tests checks the creation against the repository and compares values to check if
-    /// the creation was successful.
-    async fn sequence_create(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
-        let name = "/test_sequence".to_owned();
+            let (store_health, repo_health) =
+                tokio::join!(store.health_check(), repo.health_check());
 
-        let repo = repo::testing::Repository::new(pool);
-        let store = store::testing::Store::new_random_on_tmp().unwrap();
-        let ts_engine = query::TimeseriesGw::try_new(store.clone()).unwrap();
+            if let Err(e) = &store_health {
+                warn!("store health check failed: {}", e);
+            }
+            if let Err(e) = &repo_health {
+                warn!("repository health check failed: {}", e);
+            }
+
+            let store_health: marshal::ComponentHealth = store_health.into();
+            let repo_health: marshal::ComponentHealth = repo_health.into();
+
+            ActionResponse::SystemHealth(marshal::SystemHealth {
+                healthy: store_health.healthy && repo_health.healthy,
+                store: store_health,
+                repository: repo_health,
+                database_pool: repo.pool_stats().into(),
+                replica_pool: repo.replica_pool_stats().map(Into::into),
+            })
+        }
+
+        ActionRequest::ServerConfig(_) => {
+            info!("request server configuration");
+
+            ActionResponse::ServerConfig(marshal::ServerConfig::new(
+                params::configurables(),
+                store.target(),
+            ))
+        }
+
+        ActionRequest::Query(data) => {
+            info!("performing a query (scope: {:?})", data.scope);
+
+            let filter = marshal::query_filter_from_serde_value(data.query)?;
+
+            trace!("query filter: {:?}", filter);
+
+            let (groups, truncated) =
+                FacadeQuery::query(filter, data.scope, ts_engine, store, repo).await?;
+
+            trace!("groups found: {:?} (truncated: {})", groups, truncated);
+
+            ActionResponse::Query(marshal::Query::from(groups).with_truncated(truncated))
+        }
+
+        ActionRequest::QueryCount(data) => {
+            info!("performing a count query");
+
+            // Rows are counted per topic, expanding the results to whole sequences would
+            // only add topics without matching rows
+            if data.scope != query::Scope::Topic {
+                return Err(marshal::ActionError::InvalidField {
+                    field: "scope".to_owned(),
+                    reason: "count queries only support the `topic` scope".to_owned(),
+                }
+                .into());
+            }
+
+            let filter = marshal::query_filter_from_serde_value(data.query)?;
+
+            trace!("query filter: {:?}", filter);
+
+            let counts = FacadeQuery::query_count(filter, ts_engine, store, repo).await?;
+
+            trace!("counts found: {:?}", counts);
+
+            ActionResponse::QueryCount(counts.into())
+        }
+
+        ActionRequest::QueryDistinct(data) => {
+            info!("listing distinct values of `{}`", data.field);
+
+            let filter = marshal::query_filter_from_serde_value(data.query)?;
+
+            trace!("query filter: {:?}", filter);
+
+            let (values, truncated) =
+                FacadeQuery::query_distinct(filter, &data.field, ts_engine, store, repo).await?;
+
+            let topics = values
+                .into_iter()
+                .map(|(topic, values)| Ok((topic, values.try_into()?)))
+                .collect::<Result<_, arrow::error::ArrowError>>()?;
+
+            ActionResponse::QueryDistinct(marshal::QueryDistinct {
+                field: data.field,
+                topics,
+                truncated,
+            })
+        }
+
+        ActionRequest::QueryExplain(data) => {
+            info!("explaining a query");
+
+            if data.scope != query::Scope::Topic {
+                warn!(
+                    "query scope `{:?}` is ignored by explain queries",
+                    data.scope
+                );
+            }
+
+            let filter = marshal::query_filter_from_serde_value(data.query)?;
+
+            trace!("query filter: {:?}", filter);
+
+            let explain = FacadeQuery::explain(filter, ts_engine, store, repo).await?;
+
+            ActionResponse::QueryExplain(explain.into())
+        }
+    };
+
+    Ok(response)
+}
+
+/// Re-opens a locked sequence, `allow_reopen` is the server setting enabling the action.
+async fn sequence_reopen(
+    data: marshal::requests::SequenceReopen,
+    allow_reopen: bool,
+    store: store::StoreRef,
+    repo: repo::Repository,
+    ts_engine: &query::TimeseriesGwRef,
+) -> Result<ActionResponse, ServerError> {
+    info!("requested re-open of {} by `{}`", data.name, data.author);
+
+    if !allow_reopen {
+        return Err(ServerError::SequenceReopenDisabled);
+    }
+
+    let handle = FacadeSequence::new(data.name, store, repo);
+
+    // Check that key matches the sequence id
+    let r_id = handle.resource_id().await?;
+    let received_uuid: uuid::Uuid = data.key.parse()?;
+
+    if r_id.uuid != received_uuid {
+        return Err(ServerError::BadKey);
+    }
+
+    handle.unlock(data.author, data.reason).await?;
+
+    // Cached reads are only valid for locked topics, new data may now be written
+    for topic in handle.topic_list().await? {
+        ts_engine.read_cache().invalidate(topic.name());
+        ts_engine.schema_cache().invalidate(topic.name());
+    }
+    warn!("resource {} re-opened", handle.locator);
+
+    Ok(ActionResponse::Empty)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    use crate::{repo, rw};
+
+    /// Creates and empty sequence (no data) for testing purposes.
+    async fn create_empty_sequence(
+        repo: &repo::testing::Repository,
+        store: &store::testing::Store,
+        name: &str,
+    ) -> Result<types::ResourceId, repo::FacadeError> {
+        let handle = FacadeSequence::new(name.to_owned(), (*store).clone(), (*repo).clone());
+
+        let metadata = types::SequenceMetadata::new(
+            marshal::JsonMetadataBlob::try_from_str(
+                r#"{
+                    "test_field_1" : "value1",
+                    "test_field_2" : "value2"
+                }"#,
+            )
+            .expect("Error parsing user metadata"),
+        );
+
+        let record = handle.create(Some(metadata)).await?;
+
+        Ok(record)
+    }
+
+    /// Creates and empty sequence (no data) for testing purposes.
+    async fn create_empty_topic(
+        repo: &repo::testing::Repository,
+        store: &store::testing::Store,
+        sequence: &types::ResourceId,
+        name: &str,
+    ) -> Result<types::ResourceId, repo::FacadeError> {
+        let handle = FacadeTopic::new(name.to_owned(), (*store).clone(), (*repo).clone());
+        let props = types::TopicProperties::new(rw::Format::Default, Some("test_tag".to_owned()));
+
+        let metadata = types::TopicMetadata::new(
+            props,
+            marshal::JsonMetadataBlob::try_from_str(
+                r#"{
+                    "test_field_1" : "test_value_1",
+                    "test_field_2" : "test_value_2"
+                }"#,
+            )
+            .expect("Error parsing user metadata json string"),
+        );
+
+        let record = handle.create(&sequence.uuid, Some(metadata)).await?;
+
+        Ok(record)
+    }
+
+    #[sqlx::test]
+    /// This tests checks the creation against the repository and compares values to check if
+    /// the creation was successful.
+    async fn sequence_create(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        let name = "/test_sequence".to_owned();
+
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = query::TimeseriesGw::try_new(store.clone()).unwrap();
 
         #[derive(serde::Serialize, Debug)]
         struct Request {
@@ -478,4 +1238,1830 @@ mod tests {
 
         Ok(())
     }
+
+    #[sqlx::test]
+    /// Test checking that the sequence scope reports all the topics of the matching sequences.
+    async fn query_sequence_scope(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let sequence = create_empty_sequence(&repo, &store, "test_sequence")
+            .await
+            .unwrap();
+        for topic in ["test_sequence/topic_a", "test_sequence/topic_b"] {
+            create_empty_topic(&repo, &store, &sequence, topic)
+                .await
+                .unwrap();
+        }
+
+        let run = async |body: &str| {
+            let action = ActionRequest::try_new("query", body.as_bytes()).unwrap();
+            let response = do_action((*store).clone(), repo.clone(), ts_engine.clone(), action)
+                .await
+                .unwrap();
+
+            let ActionResponse::Query(response) = response else {
+                panic!("wrong response return")
+            };
+            assert_eq!(response.items.len(), 1);
+            assert!(!response.truncated);
+            let mut topics = response.items[0].topics.clone();
+            topics.sort();
+            topics
+        };
+
+        // Topic names are matched relative to their sequence
+        let topics = run(r#"{ "topic": { "name": { "$eq": "/topic_a" } } }"#).await;
+        assert_eq!(topics, vec!["test_sequence/topic_a"]);
+
+        let topics =
+            run(r#"{ "scope": "sequence", "topic": { "name": { "$eq": "/topic_a" } } }"#).await;
+        assert_eq!(
+            topics,
+            vec!["test_sequence/topic_a", "test_sequence/topic_b"]
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    /// Test checking that distinct values are taken from the chunk statistics when every
+    /// chunk holds a single value of the field, and from the data files otherwise.
+    async fn query_distinct(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        use arrow::array::{Int64Array, RecordBatch, StringArray};
+        use arrow::datatypes::{DataType, Field, Schema};
+
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let sequence = create_empty_sequence(&repo, &store, "test_sequence")
+            .await
+            .unwrap();
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+                DataType::Int64,
+                false,
+            ),
+            Field::new("label", DataType::Utf8, true),
+        ]));
+
+        // Writes a chunk for each group of labels
+        let write_chunks = async |name: &str, labels: Vec<Vec<Option<&str>>>| {
+            let topic = create_empty_topic(&repo, &store, &sequence, name)
+                .await
+                .unwrap();
+
+            let mut chunks = Vec::new();
+            for (idx, labels) in labels.into_iter().enumerate() {
+                let batch = RecordBatch::try_new(
+                    schema.clone(),
+                    vec![
+                        Arc::new(Int64Array::from_iter_values(0..labels.len() as i64)),
+                        Arc::new(StringArray::from(labels)),
+                    ],
+                )
+                .unwrap();
+                let mut writer =
+                    rw::ChunkWriter::try_new(schema.clone(), rw::Format::Default).unwrap();
+                writer.write(&batch).unwrap();
+                let (buffer, stats, metadata) = writer.finalize().unwrap();
+
+                let data_file = format!("{name}/data-{idx:05}.parquet");
+                store.write_bytes(&data_file, buffer).await.unwrap();
+                chunks.push(repo::NewChunk::new(
+                    types::TimestampUnit::default(),
+                    data_file.into(),
+                    stats,
+                    metadata,
+                ));
+            }
+            repo::FacadeChunk::create_batch(topic.id, "test_tag", chunks, &repo)
+                .await
+                .unwrap();
+        };
+
+        write_chunks(
+            "test_sequence/single",
+            vec![vec![Some("y"), Some("y")], vec![Some("x")]],
+        )
+        .await;
+        write_chunks(
+            "test_sequence/mixed",
+            vec![vec![Some("b"), Some("a")], vec![None, Some("a")]],
+        )
+        .await;
+
+        // Values of the single valued chunks are resolved without reading the data files
+        for idx in 0..2 {
+            store
+                .delete(format!("test_sequence/single/data-{idx:05}.parquet"))
+                .await
+                .unwrap();
+        }
+
+        let handle = FacadeTopic::new(
+            "test_sequence/single".to_owned(),
+            (*store).clone(),
+            repo.clone(),
+        );
+        let distinct = handle
+            .distinct_values("label", 1, &ts_engine)
+            .await
+            .unwrap();
+        assert!(distinct.truncated);
+
+        let body = r#"{ "field": "label", "sequence": { "name": { "$match": "test_sequence" } } }"#;
+        let action = ActionRequest::try_new("query_distinct", body.as_bytes()).unwrap();
+        let response = do_action((*store).clone(), repo.clone(), ts_engine.clone(), action)
+            .await
+            .unwrap();
+        let ActionResponse::QueryDistinct(response) = response else {
+            panic!("wrong response return")
+        };
+
+        assert_eq!(response.field, "label");
+        assert!(!response.truncated);
+        assert_eq!(response.topics.len(), 2);
+
+        let single = &response.topics["test_sequence/single"];
+        assert_eq!(
+            single.values,
+            vec![serde_json::json!("x"), serde_json::json!("y")]
+        );
+        assert!(!single.truncated);
+
+        let mixed = &response.topics["test_sequence/mixed"];
+        assert_eq!(
+            mixed.values,
+            vec![
+                serde_json::json!("a"),
+                serde_json::json!("b"),
+                serde_json::Value::Null
+            ]
+        );
+        assert!(!mixed.truncated);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    /// Test checking that count queries without an ontology filter report the total rows
+    /// of every selected topic, and that a `sequence` scope is rejected.
+    async fn query_count(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let sequence = create_empty_sequence(&repo, &store, "test_sequence")
+            .await
+            .unwrap();
+        let topic = create_empty_topic(&repo, &store, &sequence, "test_sequence/full")
+            .await
+            .unwrap();
+        create_empty_topic(&repo, &store, &sequence, "test_sequence/empty")
+            .await
+            .unwrap();
+
+        for (idx, rows) in [3, 4].into_iter().enumerate() {
+            repo::FacadeChunk::create(
+                topic.id,
+                format!("test_sequence/full/data-{idx:05}.parquet"),
+                10,
+                rows,
+                None,
+                None,
+                &repo,
+            )
+            .await
+            .unwrap();
+        }
+
+        let body = r#"{ "sequence": { "name": { "$match": "test_sequence" } } }"#;
+        let action = ActionRequest::try_new("query_count", body.as_bytes()).unwrap();
+        let response = do_action((*store).clone(), repo.clone(), ts_engine.clone(), action)
+            .await
+            .unwrap();
+        let ActionResponse::QueryCount(response) = response else {
+            panic!("wrong response return")
+        };
+
+        assert_eq!(response.topics.len(), 2);
+        assert_eq!(response.topics["test_sequence/full"], 7);
+        assert_eq!(response.topics["test_sequence/empty"], 0);
+
+        let body =
+            r#"{ "scope": "sequence", "sequence": { "name": { "$match": "test_sequence" } } }"#;
+        let action = ActionRequest::try_new("query_count", body.as_bytes()).unwrap();
+        let err = do_action((*store).clone(), repo.clone(), ts_engine, action)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ServerError::ActionError(marshal::ActionError::InvalidField { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    /// Test checking that reconciliation finds chunks without data files and data files
+    /// without chunks, and repairs them only in locked sequences.
+    async fn sequence_reconcile(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let sequence = create_empty_sequence(&repo, &store, "test_sequence")
+            .await
+            .unwrap();
+        let topic = create_empty_topic(&repo, &store, &sequence, "test_sequence/test_topic")
+            .await
+            .unwrap();
+
+        // Chunk whose data file has never been written
+        repo::FacadeChunk::create(
+            topic.id,
+            "test_sequence/test_topic/data-00000.parquet",
+            10,
+            1,
+            None,
+            None,
+            &repo,
+        )
+        .await
+        .unwrap()
+        .finalize()
+        .await
+        .unwrap();
+        // Data file without a chunk
+        store
+            .write_bytes("test_sequence/test_topic/data-00001.parquet", vec![0u8; 4])
+            .await
+            .unwrap();
+
+        let run = async |repair: bool| {
+            let body = format!(r#"{{ "name": "test_sequence", "repair": {repair} }}"#);
+            let action = ActionRequest::try_new("sequence_reconcile", body.as_bytes()).unwrap();
+            let response =
+                do_action((*store).clone(), repo.clone(), ts_engine.clone(), action).await?;
+
+            let ActionResponse::SequenceReconcile(report) = response else {
+                panic!("wrong response return")
+            };
+            Ok::<_, ServerError>(report)
+        };
+
+        let report = run(false).await.unwrap();
+        assert_eq!(
+            report.missing_data_files,
+            vec!["test_sequence/test_topic/data-00000.parquet"]
+        );
+        assert_eq!(
+            report.orphan_data_files,
+            vec!["test_sequence/test_topic/data-00001.parquet"]
+        );
+        assert!(!report.repaired);
+
+        // Files of uploads in progress may be missing their chunks in unlocked sequences
+        assert!(matches!(
+            run(true).await,
+            Err(ServerError::FacadeError(FacadeError::SequenceUnlocked))
+        ));
+        assert_eq!(run(false).await.unwrap().orphan_data_files.len(), 1);
+
+        FacadeTopic::new(
+            "test_sequence/test_topic".to_owned(),
+            (*store).clone(),
+            repo.clone(),
+        )
+        .lock()
+        .await
+        .unwrap();
+        FacadeSequence::new("test_sequence".to_owned(), (*store).clone(), repo.clone())
+            .lock()
+            .await
+            .unwrap();
+        assert!(run(true).await.unwrap().repaired);
+
+        let report = run(false).await.unwrap();
+        assert!(report.missing_data_files.is_empty());
+        assert!(report.orphan_data_files.is_empty());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    /// Test checking that a dry run deletion reports the topics of the sequence without
+    /// deleting anything.
+    async fn sequence_delete_dry_run(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let sequence = create_empty_sequence(&repo, &store, "test_sequence")
+            .await
+            .unwrap();
+        let mut topics = Vec::new();
+        for topic in ["test_sequence/topic_a", "test_sequence/topic_b"] {
+            let topic = create_empty_topic(&repo, &store, &sequence, topic)
+                .await
+                .unwrap();
+            topics.push(topic);
+        }
+
+        // Chunks in both topics, the totals are summed over the whole sequence
+        for (idx, (topic, size_bytes)) in [(&topics[0], 10), (&topics[0], 20), (&topics[1], 5)]
+            .into_iter()
+            .enumerate()
+        {
+            repo::FacadeChunk::create(
+                topic.id,
+                format!("test_sequence/data-{idx:05}.parquet"),
+                size_bytes,
+                1,
+                None,
+                None,
+                &repo,
+            )
+            .await
+            .unwrap();
+        }
+
+        let body = r#"{ "name": "test_sequence", "dry_run": true }"#;
+        let action = ActionRequest::try_new("sequence_delete", body.as_bytes()).unwrap();
+        let response = do_action((*store).clone(), repo.clone(), ts_engine.clone(), action)
+            .await
+            .unwrap();
+
+        let ActionResponse::SequenceDeleteSummary(summary) = response else {
+            panic!("wrong response return")
+        };
+        assert_eq!(summary.topics.len(), 2);
+        assert_eq!(summary.total_chunks, 3);
+        assert_eq!(summary.total_size_bytes, 35);
+        assert_eq!(summary.total_size, "35 B");
+
+        // Nothing has been deleted
+        let handle =
+            FacadeSequence::new("test_sequence".to_owned(), (*store).clone(), repo.clone());
+        assert_eq!(handle.topic_list().await.unwrap().len(), 2);
+
+        let topic = FacadeTopic::new(
+            "test_sequence/topic_a".to_owned(),
+            (*store).clone(),
+            repo.clone(),
+        );
+        assert_eq!(topic.chunks_stats().await.unwrap().total_chunks, 2);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    /// Test checking that explain reports the generated SQL queries without running the
+    /// ontology filter.
+    async fn query_explain(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let sequence = create_empty_sequence(&repo, &store, "test_sequence")
+            .await
+            .unwrap();
+        create_empty_topic(&repo, &store, &sequence, "test_sequence/test_topic")
+            .await
+            .unwrap();
+
+        let body = r#"{
+            "sequence": { "name": { "$match": "test_sequence" } },
+            "ontology": { "test_tag.acc.x": { "$gt": 1.0 } }
+        }"#;
+        let action = ActionRequest::try_new("query_explain", body.as_bytes()).unwrap();
+        let response = do_action((*store).clone(), repo.clone(), ts_engine.clone(), action)
+            .await
+            .unwrap();
+
+        let ActionResponse::QueryExplain(response) = response else {
+            panic!("wrong response return")
+        };
+
+        let topics_sql = response.topics_sql.unwrap();
+        assert!(topics_sql.sql.contains("WHERE"));
+        assert_eq!(topics_sql.values.len(), 1);
+        assert_eq!(response.selected_topics, 1);
+
+        assert_eq!(response.ontology_tags.len(), 1);
+        let explain = &response.ontology_tags[0];
+        assert_eq!(explain.ontology_tag, "test_tag");
+        assert!(explain.chunks_sql.sql.contains("chunk_t"));
+        // The topic has no data, so there is no data file to scan
+        assert_eq!(explain.candidate_chunks, 0);
+        assert!(explain.logical_plan.is_none());
+        assert!(explain.explained_chunk.is_none());
+
+        // Chunk level operations can't be combined with row level ones, as done by queries
+        let body = r#"{
+            "sequence": { "name": { "$match": "test_sequence" } },
+            "ontology": {
+                "test_tag.acc.x": { "$gt": 1.0 },
+                "test_tag.acc.y": "$has_nan"
+            }
+        }"#;
+        let action = ActionRequest::try_new("query_explain", body.as_bytes()).unwrap();
+        let res = do_action((*store).clone(), repo.clone(), ts_engine.clone(), action).await;
+        assert!(matches!(
+            res,
+            Err(ServerError::FacadeError(FacadeError::QueryError(
+                query::Error::MixedChunkLevelOps
+            )))
+        ));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    /// Test checking that the latest row of an empty topic is empty.
+    async fn topic_latest_empty(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let sequence = create_empty_sequence(&repo, &store, "test_sequence")
+            .await
+            .unwrap();
+        create_empty_topic(&repo, &store, &sequence, "test_sequence/test_topic")
+            .await
+            .unwrap();
+
+        let action = ActionRequest::try_new(
+            "topic_latest",
+            r#"{ "name": "test_sequence/test_topic" }"#.as_bytes(),
+        )
+        .unwrap();
+        let response = do_action((*store).clone(), repo.clone(), ts_engine, action)
+            .await
+            .unwrap();
+
+        let ActionResponse::TopicLatest(response) = response else {
+            panic!("wrong response return")
+        };
+        assert!(response.row.is_none());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    /// Test checking that an empty topic is exported as an empty csv, and that
+    /// non ASCII delimiters are rejected.
+    async fn topic_export_csv_empty(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let sequence = create_empty_sequence(&repo, &store, "test_sequence")
+            .await
+            .unwrap();
+        create_empty_topic(&repo, &store, &sequence, "test_sequence/test_topic")
+            .await
+            .unwrap();
+
+        let action = ActionRequest::try_new(
+            "topic_export_csv",
+            r#"{ "name": "test_sequence/test_topic", "delimiter": ";" }"#.as_bytes(),
+        )
+        .unwrap();
+        let response = do_action((*store).clone(), repo.clone(), ts_engine.clone(), action)
+            .await
+            .unwrap();
+
+        let ActionResponse::TopicExportCsv(csv) = response else {
+            panic!("wrong response return")
+        };
+        assert_eq!(csv.0.count().await, 0);
+
+        let action = ActionRequest::try_new(
+            "topic_export_csv",
+            r#"{ "name": "test_sequence/test_topic", "delimiter": "é" }"#.as_bytes(),
+        )
+        .unwrap();
+        let response = do_action((*store).clone(), repo.clone(), ts_engine, action).await;
+        assert!(response.is_err());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    /// Test checking that topic metadata can be replaced or merged while the topic is unlocked.
+    async fn topic_update(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let sequence = create_empty_sequence(&repo, &store, "test_sequence")
+            .await
+            .unwrap();
+        create_empty_topic(&repo, &store, &sequence, "test_sequence/test_topic")
+            .await
+            .unwrap();
+
+        let update = async |body: &str| {
+            let action = ActionRequest::try_new("topic_update", body.as_bytes()).unwrap();
+            do_action((*store).clone(), repo.clone(), ts_engine.clone(), action)
+                .await
+                .unwrap();
+
+            let handle = FacadeTopic::new(
+                "test_sequence/test_topic".to_owned(),
+                (*store).clone(),
+                repo.clone(),
+            );
+            let user_metadata: serde_json::Value =
+                handle.metadata().await.unwrap().user_metadata.into();
+            user_metadata
+        };
+
+        let mdata = update(
+            r#"{ "name": "test_sequence/test_topic", "merge": true, "user_metadata": { "test_field_2": "new", "test_field_3": 3 } }"#,
+        )
+        .await;
+        assert_eq!(
+            mdata,
+            serde_json::json!({
+                "test_field_1": "test_value_1",
+                "test_field_2": "new",
+                "test_field_3": 3
+            })
+        );
+
+        let mdata =
+            update(r#"{ "name": "test_sequence/test_topic", "user_metadata": { "only": true } }"#)
+                .await;
+        assert_eq!(mdata, serde_json::json!({ "only": true }));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    /// Test checking that topics are created in bulk, and that a failure on any topic
+    /// prevents the creation of all of them.
+    async fn topic_create_bulk(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let sequence = create_empty_sequence(&repo, &store, "test_sequence")
+            .await
+            .unwrap();
+
+        let create_bulk = async |names: &[&str]| {
+            let topics: Vec<serde_json::Value> = names
+                .iter()
+                .map(|name| {
+                    serde_json::json!({
+                        "name": name,
+                        "serialization_format": "default",
+                        "ontology_tag": "test_tag",
+                        "user_metadata": { "name": name },
+                    })
+                })
+                .collect();
+            let body = serde_json::json!({
+                "sequence_key": sequence.uuid.to_string(),
+                "topics": topics,
+            });
+
+            let action =
+                ActionRequest::try_new("topic_create_bulk", body.to_string().as_bytes()).unwrap();
+            do_action((*store).clone(), repo.clone(), ts_engine.clone(), action).await
+        };
+
+        let exists = async |name: &str| {
+            FacadeTopic::new(name.to_owned(), (*store).clone(), repo.clone())
+                .resource_id()
+                .await
+                .is_ok()
+        };
+
+        let response = create_bulk(&["test_sequence/camera", "test_sequence/imu"])
+            .await
+            .unwrap();
+        let ActionResponse::TopicCreateBulk(response) = response else {
+            panic!("wrong response return")
+        };
+        assert_eq!(response.topics.len(), 2);
+        assert!(exists("test_sequence/camera").await);
+        assert!(exists("test_sequence/imu").await);
+
+        // the second topic is not a child of the sequence
+        let err = create_bulk(&["test_sequence/lidar", "other_sequence/gps"])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("other_sequence/gps"));
+        assert!(!exists("test_sequence/lidar").await);
+
+        // the second topic already exists
+        let err = create_bulk(&["test_sequence/lidar", "test_sequence/imu"])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("test_sequence/imu"));
+        assert!(!exists("test_sequence/lidar").await);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    /// Test checking that a rename moves the sequence, its topics and the stored metadata.
+    async fn sequence_rename(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let sequence = create_empty_sequence(&repo, &store, "test_sequence")
+            .await
+            .unwrap();
+        create_empty_topic(&repo, &store, &sequence, "test_sequence/test_topic")
+            .await
+            .unwrap();
+
+        let action = ActionRequest::try_new(
+            "sequence_rename",
+            br#"{ "name": "test_sequence", "new_name": "renamed_sequence" }"#,
+        )
+        .unwrap();
+        do_action((*store).clone(), repo.clone(), ts_engine.clone(), action)
+            .await
+            .unwrap();
+
+        let old = FacadeSequence::new("test_sequence".to_owned(), (*store).clone(), repo.clone());
+        assert!(old.resource_id().await.is_err());
+        assert!(old.metadata().await.is_err());
+
+        let new = FacadeSequence::new(
+            "renamed_sequence".to_owned(),
+            (*store).clone(),
+            repo.clone(),
+        );
+        assert_eq!(new.resource_id().await.unwrap().uuid, sequence.uuid);
+        assert!(new.metadata().await.is_ok());
+
+        let topics: Vec<String> = new
+            .topic_list()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        assert_eq!(topics, vec!["renamed_sequence/test_topic"]);
+
+        let topic = FacadeTopic::new(
+            "renamed_sequence/test_topic".to_owned(),
+            (*store).clone(),
+            repo.clone(),
+        );
+        assert!(topic.metadata().await.is_ok());
+
+        // Empty names, names holding a path and the current name are rejected
+        for new_name in ["", " ", "renamed/sequence", "renamed_sequence"] {
+            let body = serde_json::json!({ "name": "renamed_sequence", "new_name": new_name });
+            let action =
+                ActionRequest::try_new("sequence_rename", body.to_string().as_bytes()).unwrap();
+            assert!(matches!(
+                do_action((*store).clone(), repo.clone(), ts_engine.clone(), action).await,
+                Err(ServerError::FacadeError(
+                    repo::FacadeError::InvalidName { .. }
+                ))
+            ));
+        }
+        assert_eq!(new.resource_id().await.unwrap().uuid, sequence.uuid);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn sequence_finalize_strict(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let sequence = create_empty_sequence(&repo, &store, "test_sequence")
+            .await
+            .unwrap();
+        create_empty_topic(&repo, &store, &sequence, "test_sequence/test_topic")
+            .await
+            .unwrap();
+        FacadeTopic::new(
+            "test_sequence/test_topic".to_owned(),
+            (*store).clone(),
+            repo.clone(),
+        )
+        .lock()
+        .await
+        .unwrap();
+
+        let finalize = |strict: bool| {
+            let body = serde_json::json!({
+                "name": "test_sequence",
+                "key": sequence.uuid.to_string(),
+                "strict": strict,
+            });
+            ActionRequest::try_new("sequence_finalize", body.to_string().as_bytes()).unwrap()
+        };
+
+        // the topic holds no data
+        let res = do_action(
+            (*store).clone(),
+            repo.clone(),
+            ts_engine.clone(),
+            finalize(true),
+        )
+        .await;
+        assert!(matches!(
+            res,
+            Err(ServerError::FacadeError(FacadeError::EmptyTopics(topics))) if topics == vec!["test_sequence/test_topic".to_owned()]
+        ));
+
+        let handle =
+            FacadeSequence::new("test_sequence".to_owned(), (*store).clone(), repo.clone());
+        assert!(!handle.is_locked().await.unwrap());
+
+        do_action(
+            (*store).clone(),
+            repo.clone(),
+            ts_engine.clone(),
+            finalize(false),
+        )
+        .await
+        .unwrap();
+        assert!(handle.is_locked().await.unwrap());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    /// Test checking that a strict finalization succeeds when every topic holds data.
+    async fn sequence_finalize_strict_with_data(
+        pool: sqlx::Pool<repo::Database>,
+    ) -> sqlx::Result<()> {
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let sequence = create_empty_sequence(&repo, &store, "test_sequence")
+            .await
+            .unwrap();
+        for name in ["test_sequence/topic_a", "test_sequence/topic_b"] {
+            let topic = create_empty_topic(&repo, &store, &sequence, name)
+                .await
+                .unwrap();
+            repo::FacadeChunk::create(
+                topic.id,
+                format!("{name}/data-00000.parquet"),
+                10,
+                1,
+                None,
+                None,
+                &repo,
+            )
+            .await
+            .unwrap();
+            FacadeTopic::new(name.to_owned(), (*store).clone(), repo.clone())
+                .lock()
+                .await
+                .unwrap();
+        }
+
+        let body = serde_json::json!({
+            "name": "test_sequence",
+            "key": sequence.uuid.to_string(),
+            "strict": true,
+        });
+        let action =
+            ActionRequest::try_new("sequence_finalize", body.to_string().as_bytes()).unwrap();
+        do_action((*store).clone(), repo.clone(), ts_engine, action)
+            .await
+            .unwrap();
+
+        let handle =
+            FacadeSequence::new("test_sequence".to_owned(), (*store).clone(), repo.clone());
+        assert!(handle.is_locked().await.unwrap());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn sequence_reopen(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let sequence = create_empty_sequence(&repo, &store, "test_sequence")
+            .await
+            .unwrap();
+        create_empty_topic(&repo, &store, &sequence, "test_sequence/test_topic")
+            .await
+            .unwrap();
+
+        let topic = FacadeTopic::new(
+            "test_sequence/test_topic".to_owned(),
+            (*store).clone(),
+            repo.clone(),
+        );
+        topic.lock().await.unwrap();
+        let handle =
+            FacadeSequence::new("test_sequence".to_owned(), (*store).clone(), repo.clone());
+        handle.lock().await.unwrap();
+
+        // re-opening sequences is disabled by default
+        let body = serde_json::json!({
+            "name": "test_sequence",
+            "key": sequence.uuid.to_string(),
+            "author": "tester",
+        });
+        let action =
+            ActionRequest::try_new("sequence_reopen", body.to_string().as_bytes()).unwrap();
+        let res = do_action((*store).clone(), repo.clone(), ts_engine.clone(), action).await;
+        assert!(matches!(res, Err(ServerError::SequenceReopenDisabled)));
+        assert!(handle.is_locked().await.unwrap());
+
+        handle
+            .unlock("tester".to_owned(), Some("wrong calibration".to_owned()))
+            .await
+            .unwrap();
+        assert!(!handle.is_locked().await.unwrap());
+        assert!(!topic.is_locked().await.unwrap());
+        topic.ensure_writable().await.unwrap();
+
+        assert!(matches!(
+            handle.unlock("tester".to_owned(), None).await,
+            Err(FacadeError::SequenceUnlocked)
+        ));
+
+        // Re-open through the action, with the setting enabled
+        topic.lock().await.unwrap();
+        handle.lock().await.unwrap();
+
+        let request = |key: String| marshal::requests::SequenceReopen {
+            name: "test_sequence".to_owned(),
+            key,
+            author: "tester".to_owned(),
+            reason: Some("late data".to_owned()),
+        };
+
+        let res = sequence_reopen(
+            request(uuid::Uuid::nil().to_string()),
+            true,
+            (*store).clone(),
+            repo.clone(),
+            &ts_engine,
+        )
+        .await;
+        assert!(matches!(res, Err(ServerError::BadKey)));
+        assert!(handle.is_locked().await.unwrap());
+
+        let res = sequence_reopen(
+            request(sequence.uuid.to_string()),
+            true,
+            (*store).clone(),
+            repo.clone(),
+            &ts_engine,
+        )
+        .await;
+        assert!(matches!(res, Ok(ActionResponse::Empty)));
+        assert!(!handle.is_locked().await.unwrap());
+        topic.ensure_writable().await.unwrap();
+
+        let audits = repo::sequence_audits_find_by_locator(&mut repo.connection(), &handle.locator)
+            .await
+            .unwrap();
+        assert_eq!(audits.len(), 2);
+        // Most recent entries come first
+        assert_eq!(audits[0].reason.as_deref(), Some("late data"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn sequence_clone(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let sequence = create_empty_sequence(&repo, &store, "test_sequence")
+            .await
+            .unwrap();
+        create_empty_topic(&repo, &store, &sequence, "test_sequence/test_topic")
+            .await
+            .unwrap();
+
+        let action = || {
+            ActionRequest::try_new(
+                "sequence_clone",
+                br#"{ "name": "test_sequence", "new_name": "cloned_sequence" }"#,
+            )
+            .unwrap()
+        };
+
+        // only locked sequences can be cloned
+        let res = do_action((*store).clone(), repo.clone(), ts_engine.clone(), action()).await;
+        assert!(res.is_err());
+
+        let topic = FacadeTopic::new(
+            "test_sequence/test_topic".to_owned(),
+            (*store).clone(),
+            repo.clone(),
+        );
+        topic.lock().await.unwrap();
+        let old = FacadeSequence::new("test_sequence".to_owned(), (*store).clone(), repo.clone());
+        old.lock().await.unwrap();
+
+        do_action((*store).clone(), repo.clone(), ts_engine.clone(), action())
+            .await
+            .unwrap();
+
+        assert!(old.is_locked().await.unwrap());
+        assert!(old.metadata().await.is_ok());
+
+        let new = FacadeSequence::new("cloned_sequence".to_owned(), (*store).clone(), repo.clone());
+        assert_ne!(new.resource_id().await.unwrap().uuid, sequence.uuid);
+        assert!(!new.is_locked().await.unwrap());
+        assert!(new.metadata().await.is_ok());
+
+        let topics: Vec<String> = new
+            .topic_list()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        assert_eq!(topics, vec!["cloned_sequence/test_topic"]);
+
+        let topic = FacadeTopic::new(
+            "cloned_sequence/test_topic".to_owned(),
+            (*store).clone(),
+            repo.clone(),
+        );
+        assert!(topic.is_locked().await.unwrap());
+        assert!(topic.metadata().await.is_ok());
+
+        // the clone name is now taken
+        let res = do_action((*store).clone(), repo.clone(), ts_engine, action()).await;
+        assert!(res.is_err());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    /// Test checking that a cloned topic owns a copy of the data files, chunks and chunk
+    /// statistics of the source topic.
+    async fn sequence_clone_with_data(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        use arrow::array::{Float64Array, Int64Array, RecordBatch};
+        use arrow::datatypes::{DataType, Field, Schema};
+
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let sequence = create_empty_sequence(&repo, &store, "test_sequence")
+            .await
+            .unwrap();
+        let topic = create_empty_topic(&repo, &store, &sequence, "test_sequence/test_topic")
+            .await
+            .unwrap();
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+                DataType::Int64,
+                false,
+            ),
+            Field::new("acc", DataType::Float64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(vec![0, 1, 2])),
+                Arc::new(Float64Array::from(vec![-1.0, 0.5, 2.0])),
+            ],
+        )
+        .unwrap();
+
+        let cmd = serde_json::json!({
+            "topic": { "name": "test_sequence/test_topic", "key": topic.uuid.to_string() }
+        });
+        let mut decoder = super::super::do_put::testing::decoder(cmd, vec![batch]);
+        super::super::do_put(
+            (*store).clone(),
+            repo.clone(),
+            ts_engine.clone(),
+            &mut decoder,
+        )
+        .await
+        .unwrap();
+        FacadeSequence::new("test_sequence".to_owned(), (*store).clone(), repo.clone())
+            .lock()
+            .await
+            .unwrap();
+
+        let action = ActionRequest::try_new(
+            "sequence_clone",
+            br#"{ "name": "test_sequence", "new_name": "cloned_sequence" }"#,
+        )
+        .unwrap();
+        do_action((*store).clone(), repo.clone(), ts_engine.clone(), action)
+            .await
+            .unwrap();
+
+        let source = FacadeTopic::new(
+            "test_sequence/test_topic".to_owned(),
+            (*store).clone(),
+            repo.clone(),
+        );
+        let cloned = FacadeTopic::new(
+            "cloned_sequence/test_topic".to_owned(),
+            (*store).clone(),
+            repo.clone(),
+        );
+        let source_chunks = source.system_info().await.unwrap().chunks_number;
+        assert!(source_chunks > 0);
+        assert_eq!(
+            cloned.system_info().await.unwrap().chunks_number,
+            source_chunks
+        );
+
+        // Chunks point to the copied data files
+        let files = store
+            .list("cloned_sequence/test_topic", Some(params::ext::PARQUET))
+            .await
+            .unwrap();
+        assert_eq!(files.len(), source_chunks);
+        let report =
+            FacadeSequence::new("cloned_sequence".to_owned(), (*store).clone(), repo.clone())
+                .reconcile(false)
+                .await
+                .unwrap();
+        assert!(report.missing_data_files.is_empty());
+        assert!(report.orphan_data_files.is_empty());
+
+        let body = br#"{ "topic": "cloned_sequence/test_topic" }"#;
+        let action = ActionRequest::try_new("topic_column_stats", body).unwrap();
+        let ActionResponse::TopicColumnStats(stats) =
+            do_action((*store).clone(), repo.clone(), ts_engine.clone(), action)
+                .await
+                .unwrap()
+        else {
+            panic!("wrong response return")
+        };
+        let acc = stats.numeric.iter().find(|s| s.field == "acc").unwrap();
+        assert_eq!((acc.min, acc.max), (Some(-1.0), Some(2.0)));
+        assert_eq!(acc.row_count, 3);
+
+        let rows: usize = ts_engine
+            .read(
+                cloned.path(),
+                rw::Format::Default,
+                None,
+                None,
+                None,
+                query::SortOrder::Asc,
+            )
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap()
+            .iter()
+            .map(|batch| batch.num_rows())
+            .sum();
+        assert_eq!(rows, 3);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    /// Test checking that restat rebuilds the statistics and the timestamp bounds of a chunk
+    /// stored without them.
+    async fn topic_restat(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        params::load_configurables_from_env();
+
+        use arrow::array::{Float64Array, Int64Array, RecordBatch, StringArray};
+        use arrow::datatypes::{DataType, Field, Schema};
+
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let sequence = create_empty_sequence(&repo, &store, "test_sequence")
+            .await
+            .unwrap();
+        let topic = create_empty_topic(&repo, &store, &sequence, "test_sequence/test_topic")
+            .await
+            .unwrap();
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+                DataType::Int64,
+                false,
+            ),
+            Field::new("acc", DataType::Float64, false),
+            Field::new("label", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2, 3])),
+                Arc::new(Float64Array::from(vec![-1.0, 0.5, 2.0])),
+                Arc::new(StringArray::from(vec![Some("b"), None, Some("a")])),
+            ],
+        )
+        .unwrap();
+        let mut writer = rw::ChunkWriter::try_new(schema, rw::Format::Default).unwrap();
+        writer.write(&batch).unwrap();
+        let (buffer, _, metadata) = writer.finalize().unwrap();
+
+        let data_file = "test_sequence/test_topic/data-00000.parquet";
+        store.write_bytes(data_file, buffer).await.unwrap();
+        // The chunk is registered without statistics
+        repo::FacadeChunk::create(
+            topic.id,
+            data_file,
+            metadata.size_bytes as i64,
+            metadata.row_count as i64,
+            None,
+            Some(metadata.checksum),
+            &repo,
+        )
+        .await
+        .unwrap()
+        .finalize()
+        .await
+        .unwrap();
+
+        let run = async |name: &str| {
+            let body =
+                r#"{ "name": "test_sequence/test_topic", "topic": "test_sequence/test_topic" }"#;
+            let action = ActionRequest::try_new(name, body.as_bytes()).unwrap();
+            do_action((*store).clone(), repo.clone(), ts_engine.clone(), action)
+                .await
+                .unwrap()
+        };
+
+        let ActionResponse::TopicColumnStats(stats) = run("topic_column_stats").await else {
+            panic!("wrong response return")
+        };
+        assert!(stats.numeric.is_empty());
+
+        let ActionResponse::TopicRestat(restat) = run("topic_restat").await else {
+            panic!("wrong response return")
+        };
+        assert_eq!(restat.chunks, 1);
+
+        let ActionResponse::TopicColumnStats(stats) = run("topic_column_stats").await else {
+            panic!("wrong response return")
+        };
+        let acc = stats.numeric.iter().find(|s| s.field == "acc").unwrap();
+        assert_eq!((acc.min, acc.max), (Some(-1.0), Some(2.0)));
+        assert_eq!((acc.null_count, acc.row_count), (Some(0), 3));
+        assert_eq!(acc.completeness, Some(1.0));
+        let label = stats.literal.iter().find(|s| s.field == "label").unwrap();
+        assert_eq!(
+            (label.min.as_deref(), label.max.as_deref()),
+            (Some("a"), Some("b"))
+        );
+        assert_eq!(label.null_count, Some(1));
+
+        let mut cx = repo.connection();
+        let chunks = repo::topic_find_all_chunks(
+            &mut cx,
+            &types::TopicResourceLocator::from("test_sequence/test_topic"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            chunks[0].timestamp_bounds(),
+            Some((types::Timestamp::from(1), types::Timestamp::from(3)))
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    /// Test checking that the small chunks of an unlocked topic are merged in a single chunk,
+    /// keeping the column types of the merged chunks, and that locked topics are never
+    /// compacted under a concurrent read.
+    async fn topic_compact(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        use arrow::array::{Float64Array, Int64Array, RecordBatch, StringArray};
+        use arrow::datatypes::{DataType, Field, Schema};
+
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let sequence = create_empty_sequence(&repo, &store, "test_sequence")
+            .await
+            .unwrap();
+        let topic = create_empty_topic(&repo, &store, &sequence, "test_sequence/test_topic")
+            .await
+            .unwrap();
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+                DataType::Int64,
+                false,
+            ),
+            Field::new("acc", DataType::Float64, false),
+            Field::new("label", DataType::Utf8, true),
+        ]));
+        let batch = |idx: i64| {
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(Int64Array::from(vec![idx * 10, idx * 10 + 1])),
+                    Arc::new(Float64Array::from(vec![idx as f64, -(idx as f64)])),
+                    Arc::new(StringArray::from(vec![Some(format!("l{idx}")), None])),
+                ],
+            )
+            .unwrap()
+        };
+
+        let mut chunks = Vec::new();
+        for idx in 0..3 {
+            let mut writer = rw::ChunkWriter::try_new(schema.clone(), rw::Format::Default).unwrap();
+            writer.write(&batch(idx)).unwrap();
+            let (buffer, stats, metadata) = writer.finalize().unwrap();
+
+            let data_file = format!("test_sequence/test_topic/data-{idx:05}.parquet");
+            store.write_bytes(&data_file, buffer).await.unwrap();
+            chunks.push(repo::NewChunk::new(
+                types::TimestampUnit::default(),
+                data_file.into(),
+                stats,
+                metadata,
+            ));
+        }
+        repo::FacadeChunk::create_batch(topic.id, "test_tag", chunks, &repo)
+            .await
+            .unwrap();
+
+        let run = async |name: &str| {
+            let body =
+                r#"{ "name": "test_sequence/test_topic", "topic": "test_sequence/test_topic" }"#;
+            let action = ActionRequest::try_new(name, body.as_bytes()).unwrap();
+            do_action((*store).clone(), repo.clone(), ts_engine.clone(), action).await
+        };
+
+        let ActionResponse::TopicCompact(compaction) = run("topic_compact").await.unwrap() else {
+            panic!("wrong response return")
+        };
+        assert_eq!(compaction.merged_chunks, 3);
+        assert_eq!(compaction.created_chunks, 1);
+
+        let handle = FacadeTopic::new(
+            "test_sequence/test_topic".to_owned(),
+            (*store).clone(),
+            repo.clone(),
+        );
+        let info = handle.system_info().await.unwrap();
+        assert_eq!(info.chunks_number, 1);
+        assert!(
+            store
+                .read_bytes("test_sequence/test_topic/data-00000.parquet")
+                .await
+                .is_err()
+        );
+
+        // String columns are stored with their original type, so their statistics are kept
+        let stored = handle.arrow_schema(rw::Format::Default).await.unwrap();
+        assert_eq!(
+            stored.field_with_name("label").unwrap().data_type(),
+            &DataType::Utf8
+        );
+        let ActionResponse::TopicColumnStats(stats) = run("topic_column_stats").await.unwrap()
+        else {
+            panic!("wrong response return")
+        };
+        let label = stats.literal.iter().find(|s| s.field == "label").unwrap();
+        assert_eq!(
+            (label.min.as_deref(), label.max.as_deref()),
+            (Some("l0"), Some("l2"))
+        );
+        assert_eq!(label.null_count, Some(3));
+
+        let ActionResponse::TopicCompact(compaction) = run("topic_compact").await.unwrap() else {
+            panic!("wrong response return")
+        };
+        assert_eq!(compaction.merged_chunks, 0);
+
+        // A following write is appended to the compacted data
+        let cmd = serde_json::json!({
+            "topic": { "name": "test_sequence/test_topic", "key": topic.uuid.to_string() }
+        });
+        let mut decoder = super::super::do_put::testing::decoder(cmd, vec![batch(3)]);
+        super::super::do_put(
+            (*store).clone(),
+            repo.clone(),
+            ts_engine.clone(),
+            &mut decoder,
+        )
+        .await
+        .unwrap();
+        assert_eq!(handle.system_info().await.unwrap().chunks_number, 2);
+
+        // Topics are locked by the write, a read started before the compaction request still
+        // finds all the data files
+        let read = ts_engine
+            .read(
+                handle.path(),
+                rw::Format::Default,
+                None,
+                None,
+                None,
+                query::SortOrder::Asc,
+            )
+            .await
+            .unwrap();
+        assert!(matches!(
+            run("topic_compact").await,
+            Err(ServerError::FacadeError(FacadeError::TopicLocked))
+        ));
+        let rows: usize = read
+            .collect()
+            .await
+            .unwrap()
+            .iter()
+            .map(|batch| batch.num_rows())
+            .sum();
+        assert_eq!(rows, 8);
+        assert_eq!(handle.system_info().await.unwrap().chunks_number, 2);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn topic_notify_list_filters(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let seq_rid = create_empty_sequence(&repo, &store, "test_sequence")
+            .await
+            .unwrap();
+        create_empty_topic(&repo, &store, &seq_rid, "test_sequence/test_topic")
+            .await
+            .unwrap();
+
+        for (notify_type, msg) in [
+            ("info", "first"),
+            ("error", "second"),
+            ("warning", "third"),
+            ("error", "fourth"),
+        ] {
+            let body = format!(
+                r#"{{ "name": "test_sequence/test_topic", "notify_type": "{notify_type}", "msg": "{msg}" }}"#
+            );
+            let action = ActionRequest::try_new("topic_notify_create", body.as_bytes()).unwrap();
+            do_action((*store).clone(), repo.clone(), ts_engine.clone(), action)
+                .await
+                .unwrap();
+        }
+
+        let list = async |body: &str| {
+            let action = ActionRequest::try_new("topic_notify_list", body.as_bytes()).unwrap();
+            let response = do_action((*store).clone(), repo.clone(), ts_engine.clone(), action)
+                .await
+                .unwrap();
+            let ActionResponse::TopicNotifyList(list) = response else {
+                panic!("wrong response return")
+            };
+            list.notifies.into_iter().map(|n| n.msg).collect::<Vec<_>>()
+        };
+
+        // Most recent first
+        assert_eq!(
+            list(r#"{ "name": "test_sequence/test_topic" }"#).await,
+            ["fourth", "third", "second", "first"]
+        );
+        assert_eq!(
+            list(r#"{ "name": "test_sequence/test_topic", "min_severity": "warning" }"#).await,
+            ["fourth", "third", "second"]
+        );
+        assert_eq!(
+            list(r#"{ "name": "test_sequence/test_topic", "min_severity": "error", "limit": 1 }"#)
+                .await,
+            ["fourth"]
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    /// Test checking that notifications with a severity lower than the threshold are dropped.
+    async fn notify_severity_threshold(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+
+        let seq_rid = create_empty_sequence(&repo, &store, "test_sequence")
+            .await
+            .unwrap();
+        create_empty_topic(&repo, &store, &seq_rid, "test_sequence/test_topic")
+            .await
+            .unwrap();
+
+        let sequence =
+            FacadeSequence::new("test_sequence".to_owned(), (*store).clone(), repo.clone());
+        let topic = FacadeTopic::new(
+            "test_sequence/test_topic".to_owned(),
+            (*store).clone(),
+            repo.clone(),
+        );
+        let threshold = types::NotifyType::Warning;
+
+        for (ntype, msg) in [
+            (types::NotifyType::Info, "info"),
+            (types::NotifyType::Warning, "warning"),
+            (types::NotifyType::Error, "error"),
+        ] {
+            let kept = ntype >= threshold;
+            let notify = sequence
+                .notify(ntype, msg.to_owned(), threshold)
+                .await
+                .unwrap();
+            assert_eq!(notify.is_some(), kept);
+            let notify = topic
+                .notify(ntype, msg.to_owned(), threshold)
+                .await
+                .unwrap();
+            assert_eq!(notify.is_some(), kept);
+        }
+
+        let msgs = |notifies: Vec<types::Notify>| {
+            notifies
+                .into_iter()
+                .filter_map(|n| n.msg)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            msgs(sequence.notify_list(None, None).await.unwrap()),
+            ["error", "warning"]
+        );
+        assert_eq!(
+            msgs(topic.notify_list(None, None).await.unwrap()),
+            ["error", "warning"]
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn notify_delete(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        create_empty_sequence(&repo, &store, "test_sequence")
+            .await
+            .unwrap();
+
+        let run = async |name: &str, body: &str| {
+            let action = ActionRequest::try_new(name, body.as_bytes()).unwrap();
+            do_action((*store).clone(), repo.clone(), ts_engine.clone(), action).await
+        };
+
+        for msg in ["first", "second"] {
+            let body =
+                format!(r#"{{ "name": "test_sequence", "notify_type": "error", "msg": "{msg}" }}"#);
+            run("sequence_notify_create", &body).await.unwrap();
+        }
+
+        let list = async || {
+            let response = run("sequence_notify_list", r#"{ "name": "test_sequence" }"#)
+                .await
+                .unwrap();
+            let ActionResponse::SequenceNotifyList(list) = response else {
+                panic!("wrong response return")
+            };
+            list.notifies
+        };
+
+        let notifies = list().await;
+        assert_eq!(notifies.len(), 2);
+        assert_ne!(notifies[0].id, notifies[1].id);
+        assert!(notifies[0].created_unix_timestamp >= notifies[1].created_unix_timestamp);
+
+        let first = notifies.iter().find(|n| n.msg == "first").unwrap().id;
+        let body = format!(r#"{{ "name": "test_sequence", "id": {first} }}"#);
+        run("notify_delete", &body).await.unwrap();
+
+        let notifies = list().await;
+        assert_eq!(notifies.len(), 1);
+        assert_eq!(notifies[0].msg, "second");
+
+        // The notify does not exist anymore
+        assert!(run("notify_delete", &body).await.is_err());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn resource_exists(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let seq_rid = create_empty_sequence(&repo, &store, "test_sequence")
+            .await
+            .unwrap();
+        create_empty_topic(&repo, &store, &seq_rid, "test_sequence/test_topic")
+            .await
+            .unwrap();
+        FacadeLayer::new("test_layer".into(), (*store).clone(), (*repo).clone())
+            .create("layer description".to_owned())
+            .await
+            .unwrap();
+
+        let exists = async |name: &str, kind: &str| {
+            let body = format!(r#"{{ "name": "{name}", "kind": "{kind}" }}"#);
+            let action = ActionRequest::try_new("resource_exists", body.as_bytes()).unwrap();
+            let response = do_action((*store).clone(), repo.clone(), ts_engine.clone(), action)
+                .await
+                .unwrap();
+            let ActionResponse::ResourceExists(res) = response else {
+                panic!("wrong response return")
+            };
+            res.exists
+        };
+
+        assert!(exists("test_sequence", "sequence").await);
+        assert!(exists("test_sequence/test_topic", "topic").await);
+        assert!(exists("test_layer", "layer").await);
+
+        assert!(!exists("missing_sequence", "sequence").await);
+        assert!(!exists("test_sequence/missing_topic", "topic").await);
+        assert!(!exists("missing_layer", "layer").await);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn layer_sequences(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        create_empty_sequence(&repo, &store, "test_sequence")
+            .await
+            .unwrap();
+        for name in ["layer_b", "layer_a"] {
+            FacadeLayer::new(name.into(), (*store).clone(), (*repo).clone())
+                .create("layer description".to_owned())
+                .await
+                .unwrap();
+        }
+
+        let run = async |action: &str, layer: &str| {
+            let body = format!(r#"{{ "name": "{layer}", "sequence": "test_sequence" }}"#);
+            let action = ActionRequest::try_new(action, body.as_bytes()).unwrap();
+            do_action((*store).clone(), repo.clone(), ts_engine.clone(), action).await
+        };
+        let layers = async || {
+            FacadeSequence::new(
+                "test_sequence".to_owned(),
+                (*store).clone(),
+                (*repo).clone(),
+            )
+            .layers()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|l| l.locator.name().to_owned())
+            .collect::<Vec<_>>()
+        };
+
+        run("layer_add_sequence", "layer_b").await.unwrap();
+        run("layer_add_sequence", "layer_a").await.unwrap();
+        // Adding twice the same sequence has no effect
+        run("layer_add_sequence", "layer_a").await.unwrap();
+        assert_eq!(layers().await, vec!["layer_a", "layer_b"]);
+
+        run("layer_remove_sequence", "layer_b").await.unwrap();
+        assert_eq!(layers().await, vec!["layer_a"]);
+
+        // The sequence does not belong to the layer anymore
+        assert!(run("layer_remove_sequence", "layer_b").await.is_err());
+        assert!(run("layer_add_sequence", "missing_layer").await.is_err());
+
+        // Deleting a layer removes its memberships
+        FacadeLayer::new("layer_a".into(), (*store).clone(), (*repo).clone())
+            .delete()
+            .await
+            .unwrap();
+        assert!(layers().await.is_empty());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn ontology_contract(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let seq_rid = create_empty_sequence(&repo, &store, "test_sequence")
+            .await
+            .unwrap();
+
+        let run = async |action: &str, body: &str| {
+            let action = ActionRequest::try_new(action, body.as_bytes()).unwrap();
+            do_action((*store).clone(), repo.clone(), ts_engine.clone(), action).await
+        };
+
+        // Ranges are not allowed on text fields
+        let invalid = r#"{ "ontology_tag": "test_tag", "contract": { "metadata": { "test_field_1": { "type": "text", "range": [0, 1] } } } }"#;
+        assert!(run("ontology_contract_set", invalid).await.is_err());
+
+        let contract = r#"{ "ontology_tag": "test_tag", "contract": { "metadata": { "test_field_1": { "type": "text" }, "test_field_3": { "type": "integer" } } } }"#;
+        run("ontology_contract_set", contract).await.unwrap();
+
+        // The metadata of the test topic has no `test_field_3`
+        let err = create_empty_topic(&repo, &store, &seq_rid, "test_sequence/topic_a")
+            .await
+            .unwrap_err();
+        let FacadeError::ContractViolation { violations, .. } = err else {
+            panic!("wrong error returned")
+        };
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].field, "test_field_3");
+
+        run(
+            "ontology_contract_delete",
+            r#"{ "ontology_tag": "test_tag" }"#,
+        )
+        .await
+        .unwrap();
+        create_empty_topic(&repo, &store, &seq_rid, "test_sequence/topic_a")
+            .await
+            .unwrap();
+
+        // The contract does not exist anymore
+        assert!(
+            run(
+                "ontology_contract_delete",
+                r#"{ "ontology_tag": "test_tag" }"#
+            )
+            .await
+            .is_err()
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn system_health(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let action = ActionRequest::try_new("system_health", b"{}").unwrap();
+
+        let response = do_action((*store).clone(), repo.clone(), ts_engine, action)
+            .await
+            .unwrap();
+
+        if let ActionResponse::SystemHealth(health) = response {
+            assert!(health.healthy);
+            assert!(health.store.healthy);
+            assert!(health.repository.healthy);
+            assert!(health.store.error.is_none());
+            assert!(health.database_pool.size >= 1);
+            assert_eq!(
+                health.database_pool.size as usize,
+                health.database_pool.idle + health.database_pool.in_use
+            );
+            assert!(health.replica_pool.is_none());
+        } else {
+            panic!("wrong response return")
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn server_config(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let action = ActionRequest::try_new("server_config", b"{}").unwrap();
+
+        let response = do_action((*store).clone(), repo.clone(), ts_engine, action)
+            .await
+            .unwrap();
+
+        let ActionResponse::ServerConfig(config) = &response else {
+            panic!("wrong response return")
+        };
+        assert_eq!(config.store.backend, "filesystem");
+        assert_eq!(
+            config.max_chunk_bytes,
+            params::configurables().max_chunk_bytes
+        );
+
+        // Credentials never appear in the response
+        let body = String::from_utf8(response.bytes().unwrap()).unwrap();
+        assert!(!body.contains("secret"));
+        assert!(!body.contains("db_url"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn topic_list(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let sequence = create_empty_sequence(&repo, &store, "test_sequence")
+            .await
+            .unwrap();
+        for name in ["test_sequence/topic_b", "test_sequence/topic_a"] {
+            create_empty_topic(&repo, &store, &sequence, name)
+                .await
+                .unwrap();
+        }
+
+        // Only `topic_b` holds data
+        let topic_b = FacadeTopic::new(
+            "test_sequence/topic_b".to_owned(),
+            (*store).clone(),
+            repo.clone(),
+        )
+        .resource_id()
+        .await
+        .unwrap();
+        for (idx, rows) in [2, 3].into_iter().enumerate() {
+            repo::FacadeChunk::create(
+                topic_b.id,
+                format!("test_sequence/topic_b/data-{idx:05}.parquet"),
+                10,
+                rows,
+                None,
+                None,
+                &repo,
+            )
+            .await
+            .unwrap();
+        }
+
+        let list = async |body: &str| {
+            let action = ActionRequest::try_new("topic_list", body.as_bytes()).unwrap();
+            match do_action((*store).clone(), repo.clone(), ts_engine.clone(), action).await {
+                Ok(ActionResponse::TopicList(list)) => list.topics,
+                _ => panic!("wrong response return"),
+            }
+        };
+
+        let topics = list(r#"{ "name": "test_sequence" }"#).await;
+        let names: Vec<&str> = topics.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["test_sequence/topic_a", "test_sequence/topic_b"]
+        );
+        assert_eq!(topics[0].serialization_format.as_deref(), Some("default"));
+        assert_eq!(topics[0].ontology_tag.as_deref(), Some("test_tag"));
+        assert_eq!(topics[0].total_row_count, 0);
+        assert_eq!(topics[1].total_row_count, 5);
+        assert_eq!(topics[1].total_size_bytes, 20);
+
+        let topics = list(
+            r#"{ "name": "test_sequence", "filter": { "ontology_tag": { "$eq": "other_tag" } } }"#,
+        )
+        .await;
+        assert!(topics.is_empty());
+
+        Ok(())
+    }
 }
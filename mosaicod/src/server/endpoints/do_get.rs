@@ -1,60 +1,382 @@
 use arrow_flight::{
-    Ticket,
+    FlightData, Ticket,
     encode::{FlightDataEncoder, FlightDataEncoderBuilder},
     error::FlightError,
 };
 
-use futures::TryStreamExt;
-use log::{info, trace};
+use arrow::datatypes::{Fields, Schema};
+use datafusion::execution::SendableRecordBatchStream;
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use log::{info, trace, warn};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
 
-use crate::{marshal, params, query, repo, server::errors::ServerError, store, types::Resource};
+use crate::{
+    marshal, params, query, repo, server::errors::ServerError, store, types, types::Resource,
+};
+
+pub type FlightDataStream = BoxStream<'static, Result<FlightData, FlightError>>;
 
 pub async fn do_get(
     store: store::StoreRef,
     repo: repo::Repository,
     ts_engine: query::TimeseriesGwRef,
     ticket: Ticket,
-) -> Result<FlightDataEncoder, ServerError> {
-    let ticket = String::from_utf8(ticket.ticket.to_vec())
+) -> Result<FlightDataStream, ServerError> {
+    // The time budget of the read includes the time needed to plan it
+    let started = Instant::now();
+
+    if let Some(ticket) = marshal::MultiTopicTicket::try_from_bytes(&ticket.ticket) {
+        return Ok(do_get_many(store, repo, ts_engine, ticket).await?.boxed());
+    }
+
+    let ticket = marshal::TopicTicket::try_from_bytes(&ticket.ticket)
         .map_err(|e| ServerError::BadTicket(e.to_string()))?;
 
-    info!("requesting data for ticket `{}`", ticket);
+    info!("requesting data for ticket `{:?}`", ticket);
+
+    let time_range = ticket
+        .time_range()
+        .map_err(|e| ServerError::BadTicket(e.to_string()))?;
+
+    let timestamps = ticket.timestamps();
+    let projection = ticket.projection;
+    let order = ticket.order;
+    let order_by = ticket.order_by;
+    let decimate = ticket.decimate;
+    let aggregate = ticket.aggregate;
+    let row_number = ticket.row_number;
+    let page = ticket
+        .is_paginated()
+        .then(|| (ticket.offset.unwrap_or_default(), ticket.limit));
+    let deadline = ticket
+        .max_duration_ms
+        .map(|ms| started + Duration::from_millis(ms));
 
     // Create topic handle
-    let topic = ticket;
-    let tfacade = repo::FacadeTopic::new(topic, store, repo.clone());
+    let tfacade = repo::FacadeTopic::new(ticket.topic, store, repo.clone());
 
     // Read metadata from topic
     let metadata = tfacade.metadata().await?;
 
     trace!("{:?}", metadata);
 
+    let format = metadata.properties.serialization_format;
+    let declared_schema = metadata.properties.declared_schema.clone();
+
+    // Ticket timestamps are expressed in milliseconds, while the data is stored using
+    // the unit of the topic
+    let unit = metadata.properties.timestamp_unit;
+    let time_range = time_range.map(|range| query::Range {
+        min: unit.ceil_from(range.min).into(),
+        max: unit.floor_from(range.max).into(),
+    });
+    let timestamps = timestamps.map(|timestamps| {
+        timestamps
+            .into_iter()
+            .filter_map(|ts| unit.exact_from(ts).map(Into::into))
+            .collect::<Vec<query::Timestamp>>()
+    });
+
+    // Append JSON metadata to original data schema
+    let metadata = marshal::JsonTopicMetadata::from(metadata);
+    let mut flatten_mdata = metadata
+        .to_flat_hashmap()
+        .map_err(repo::FacadeError::from)?;
+
+    // Differences from the declared schema are checked on the whole topic, regardless of the
+    // requested projection
+    if let Some(declared) = declared_schema {
+        let schema = ts_engine
+            .read(
+                &tfacade.locator.name(),
+                format,
+                None,
+                None,
+                None,
+                query::SortOrder::Asc,
+            )
+            .await?
+            .schema_with_metadata(HashMap::new());
+
+        let violations = crate::arrow::declared_schema_violations(&schema, &declared.fields);
+        if !violations.is_empty() {
+            let violations = violations
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            if declared.strict {
+                return Err(ServerError::DeclaredSchemaMismatch(violations));
+            }
+
+            warn!(
+                "data of `{}` does not match the declared schema :: {}",
+                tfacade.locator, violations
+            );
+            flatten_mdata.insert(
+                params::SCHEMA_METADATA_SCHEMA_WARNING.to_owned(),
+                violations,
+            );
+        }
+    }
+
+    // Data of locked topics is immutable and can be served from the read cache,
+    // only whole topic reads in the default order are cached
+    let cache = ts_engine.read_cache();
+    // Taken before checking the lock, data rewritten from now on is not cached
+    let cache_generation = cache.generation();
+    let cacheable = cache.is_enabled()
+        && time_range.is_none()
+        && timestamps.is_none()
+        && projection.is_none()
+        && order.is_asc()
+        && order_by.is_none()
+        && decimate.is_none()
+        && aggregate.is_none()
+        && !row_number
+        && page.is_none()
+        && deadline.is_none()
+        && tfacade.is_locked().await?;
+    let cache_key = tfacade.locator.name().clone();
+
+    let cached = if cacheable {
+        cache.get(&cache_key)
+    } else {
+        None
+    };
+    if let Some(cached) = cached {
+        trace!("serving `{}` from read cache", cache_key);
+
+        let schema = Arc::new(Schema::new_with_metadata(
+            cached.schema.fields().clone(),
+            flatten_mdata,
+        ));
+
+        let batches = cached.batches.clone();
+        let stream = futures::stream::iter(batches.into_iter().map(Ok));
+
+        return Ok(FlightDataEncoderBuilder::new()
+            .with_schema(schema)
+            .build(stream)
+            .boxed());
+    }
+
+    // Corrupted data files of the chunks selected by the read are detected before the read,
+    // the read itself downloads them again
+    if params::configurables().verify_chunk_checksums {
+        let selected = time_range.clone().or_else(|| {
+            let timestamps = timestamps.as_ref()?;
+            Some(query::Range {
+                min: *timestamps.iter().min()?,
+                max: *timestamps.iter().max()?,
+            })
+        });
+        tfacade.verify_checksums(selected.as_ref()).await?;
+    }
+
+    // Reads returning all the rows of the topic report the total from the chunk records
+    let selects_all_rows =
+        time_range.is_none() && timestamps.is_none() && decimate.is_none() && aggregate.is_none();
+
     // Compute optimal batch size from database statistics
     let batch_size = compute_optimal_batch_size(&tfacade).await?;
 
     let query_result = ts_engine
         .read(
             &tfacade.locator.name(),
-            metadata.properties.serialization_format,
+            format,
             batch_size,
+            time_range,
+            projection,
+            order,
         )
         .await?;
 
-    // Append JSON metadata to original data schema
-    let metadata = marshal::JsonTopicMetadata::from(metadata);
-    let flatten_mdata = metadata
-        .to_flat_hashmap()
-        .map_err(repo::FacadeError::from)?;
+    let query_result = if let Some(timestamps) = timestamps {
+        query_result.at_timestamps(timestamps)?
+    } else {
+        query_result
+    };
+
+    let query_result = if let Some(n) = decimate {
+        query_result.decimate(n)?
+    } else {
+        query_result
+    };
+
+    let query_result = if let Some(aggregate) = aggregate {
+        query_result.aggregate(
+            Duration::from_millis(aggregate.window_ms),
+            unit,
+            aggregate
+                .fields
+                .into_iter()
+                .map(|f| (f.field, f.function))
+                .collect(),
+        )?
+    } else {
+        query_result
+    };
+
+    let query_result = if row_number {
+        query_result.with_row_number()?
+    } else {
+        query_result
+    };
+
+    let query_result = if let Some(field) = order_by {
+        query_result.order_by_field(&field, order)?
+    } else {
+        query_result
+    };
+
+    // The total is counted before paginating, so that clients know how many pages exist.
+    // Rows are only counted when some of them are filtered out by the read
+    let query_result = if let Some((offset, limit)) = page {
+        let total = if selects_all_rows {
+            tfacade.chunks_stats().await?.total_row_count as usize
+        } else {
+            query_result.clone().count().await?
+        };
+        flatten_mdata.insert(
+            params::SCHEMA_METADATA_TOTAL_ROWS.to_owned(),
+            total.to_string(),
+        );
+        query_result.paginate(offset, limit)?
+    } else {
+        query_result
+    };
+
     let schema = query_result.schema_with_metadata(flatten_mdata);
 
     trace!("{:?}", schema);
 
     // Get data stream from query result
     let stream = query_result.stream().await?;
+    let stream: SendableRecordBatchStream = if cacheable {
+        Box::pin(query::CachingStream::new(
+            stream,
+            cache.clone(),
+            cache_key,
+            cache_generation,
+        ))
+    } else {
+        stream
+    };
+
+    let (stream, truncated): (SendableRecordBatchStream, _) = if let Some(deadline) = deadline {
+        let stream = query::DeadlineStream::new(stream, deadline);
+        let truncated = stream.truncated();
+        (Box::pin(stream), Some(truncated))
+    } else {
+        (stream, None)
+    };
 
     // Convert the data stream to a flight stream casting the returned error
     let stream = stream.map_err(|e| FlightError::ExternalError(Box::new(e)));
 
+    let encoder = FlightDataEncoderBuilder::new()
+        .with_schema(schema)
+        .build(stream);
+
+    let Some(truncated) = truncated else {
+        return Ok(encoder.boxed());
+    };
+
+    // Once the data is over, a truncated read is reported with a trailing message
+    // carrying only application metadata
+    let trailer = futures::stream::once(async move { truncated.load(Ordering::Relaxed) })
+        .filter_map(|truncated| async move {
+            truncated.then(|| {
+                Ok(FlightData::new()
+                    .with_app_metadata(serde_json::json!({ "truncated": true }).to_string()))
+            })
+        });
+
+    Ok(encoder.chain(trailer).boxed())
+}
+
+/// Streams the rows of several topics merged by timestamp, the columns of each topic are
+/// held by a struct column named after the topic (see [`query::TimeseriesGw::read_many`]).
+///
+/// Empty topics are skipped (they have no column), the remaining ones need to share the same
+/// timestamp unit.
+async fn do_get_many(
+    store: store::StoreRef,
+    repo: repo::Repository,
+    ts_engine: query::TimeseriesGwRef,
+    ticket: marshal::MultiTopicTicket,
+) -> Result<FlightDataEncoder, ServerError> {
+    info!("requesting merged data for topics {:?}", ticket.topics);
+
+    let mut paths = Vec::new();
+    let mut unit: Option<types::TimestampUnit> = None;
+
+    for topic in &ticket.topics {
+        // Each topic has a single column
+        if paths.iter().any(|(name, _, _)| *name == topic.as_str()) {
+            continue;
+        }
+
+        let tfacade = repo::FacadeTopic::new(topic.clone(), store.clone(), repo.clone());
+        let metadata = tfacade.metadata().await?;
+
+        let topic_unit = metadata.properties.timestamp_unit;
+        match unit {
+            Some(unit) if unit != topic_unit => {
+                return Err(crate::arrow::SchemaError::TimestampUnitMismatch {
+                    expected: unit,
+                    found: topic_unit.to_string(),
+                }
+                .into());
+            }
+            _ => unit = Some(topic_unit),
+        }
+
+        if tfacade.chunks_stats().await?.total_row_count == 0 {
+            trace!("skipping empty topic `{}`", topic);
+            continue;
+        }
+
+        paths.push((
+            topic.as_str(),
+            tfacade.path().to_owned(),
+            metadata.properties.serialization_format,
+        ));
+    }
+
+    let metadata = HashMap::from([
+        ("mosaico:context".to_owned(), "topics".to_owned()),
+        (
+            "mosaico:topics".to_owned(),
+            serde_json::to_string(&ticket.topics)?,
+        ),
+        (
+            params::SCHEMA_METADATA_TIMESTAMP_UNIT.to_owned(),
+            unit.unwrap_or_default().to_string(),
+        ),
+    ]);
+
+    if paths.is_empty() {
+        let schema = Arc::new(Schema::new_with_metadata(Fields::empty(), metadata));
+        return Ok(FlightDataEncoderBuilder::new()
+            .with_schema(schema)
+            .build(futures::stream::empty()));
+    }
+
+    let query_result = ts_engine.read_many(&paths, None).await?;
+    let schema = query_result.schema_with_metadata(metadata);
+
+    let stream = query_result
+        .stream()
+        .await?
+        .map_err(|e| FlightError::ExternalError(Box::new(e)));
+
     Ok(FlightDataEncoderBuilder::new()
         .with_schema(schema)
         .build(stream))
@@ -78,3 +400,310 @@ async fn compute_optimal_batch_size(
 
     Ok(Some(batch_size as usize))
 }
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{Int64Array, RecordBatch, StringArray};
+    use arrow::datatypes::{DataType, Field};
+    use arrow_flight::decode::FlightRecordBatchStream;
+
+    use super::*;
+
+    use crate::rw;
+
+    /// Creates a topic declaring a nullable `label` field of type `label_type` and writes
+    /// some data with a `label` string column to it.
+    async fn create_topic_with_label(
+        repo: &repo::Repository,
+        store: &store::StoreRef,
+        ts_engine: &query::TimeseriesGwRef,
+        name: &str,
+        label_type: &str,
+        strict: bool,
+    ) {
+        let sequence = repo::FacadeSequence::new(name.to_owned(), store.clone(), repo.clone())
+            .create(None)
+            .await
+            .unwrap();
+
+        let topic_name = format!("{name}/topic");
+        let declared = types::DeclaredSchema {
+            fields: vec![types::DeclaredField {
+                name: "label".to_owned(),
+                data_type: label_type.to_owned(),
+                nullable: true,
+            }],
+            strict,
+        };
+        let props = types::TopicProperties::new(rw::Format::Default, Some("test_tag".to_owned()))
+            .with_declared_schema(Some(declared));
+        let metadata = types::TopicMetadata::new(
+            props,
+            marshal::JsonMetadataBlob::try_from_str("{}").unwrap(),
+        );
+        let topic = repo::FacadeTopic::new(topic_name.clone(), store.clone(), repo.clone())
+            .create(&sequence.uuid, Some(metadata))
+            .await
+            .unwrap();
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+                DataType::Int64,
+                false,
+            ),
+            Field::new("label", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(vec![0, 1])),
+                Arc::new(StringArray::from(vec![Some("a"), None])),
+            ],
+        )
+        .unwrap();
+
+        let cmd = serde_json::json!({
+            "topic": { "name": topic_name, "key": topic.uuid.to_string() }
+        });
+        let mut decoder = super::super::do_put::testing::decoder(cmd, vec![batch]);
+        super::super::do_put(store.clone(), repo.clone(), ts_engine.clone(), &mut decoder)
+            .await
+            .unwrap();
+    }
+
+    fn ticket(topic: &str) -> Ticket {
+        Ticket::new(
+            marshal::TopicTicket::new(topic.to_owned())
+                .to_bytes()
+                .unwrap(),
+        )
+    }
+
+    #[sqlx::test]
+    /// Test checking that string fields read through DataFusion (as views) match a declared
+    /// `utf8` type, both in warning and strict mode.
+    async fn declared_schema_utf8(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        create_topic_with_label(&repo, &store, &ts_engine, "warn", "utf8", false).await;
+        create_topic_with_label(&repo, &store, &ts_engine, "strict", "utf8", true).await;
+        create_topic_with_label(&repo, &store, &ts_engine, "wrong", "int64", true).await;
+
+        let stream = do_get(
+            (*store).clone(),
+            (*repo).clone(),
+            ts_engine.clone(),
+            ticket("warn/topic"),
+        )
+        .await
+        .unwrap();
+        let mut stream = FlightRecordBatchStream::new_from_flight_data(stream);
+        let batch = stream.try_next().await.unwrap().unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        let schema = stream.schema().unwrap();
+        assert!(
+            !schema
+                .metadata()
+                .contains_key(params::SCHEMA_METADATA_SCHEMA_WARNING)
+        );
+
+        assert!(
+            do_get(
+                (*store).clone(),
+                (*repo).clone(),
+                ts_engine.clone(),
+                ticket("strict/topic"),
+            )
+            .await
+            .is_ok()
+        );
+
+        assert!(matches!(
+            do_get(
+                (*store).clone(),
+                (*repo).clone(),
+                ts_engine.clone(),
+                ticket("wrong/topic"),
+            )
+            .await,
+            Err(ServerError::DeclaredSchemaMismatch(_))
+        ));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    /// Test checking that a data file modified in the store after being written is reported
+    /// as corrupt by the checksum verification.
+    async fn corrupt_data_file(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        create_topic_with_label(&repo, &store, &ts_engine, "corrupt", "utf8", false).await;
+
+        let tfacade = repo::FacadeTopic::new(
+            "corrupt/topic".to_owned(),
+            (*store).clone(),
+            (*repo).clone(),
+        );
+        tfacade.verify_checksums(None).await.unwrap();
+
+        let mut cx = repo.connection();
+        let chunks = repo::topic_find_all_chunks(
+            &mut cx,
+            &types::TopicResourceLocator::from("corrupt/topic"),
+        )
+        .await
+        .unwrap();
+        let data_file = chunks[0].data_file();
+        let mut bytes = store.read_bytes(data_file).await.unwrap();
+        let mid = bytes.len() / 2;
+        bytes[mid] ^= 0xff;
+        store.write_bytes(data_file, bytes).await.unwrap();
+
+        assert!(matches!(
+            tfacade.verify_checksums(None).await,
+            Err(repo::FacadeError::CorruptChunk { .. })
+        ));
+
+        // Only the chunks overlapping the time range of the read are verified
+        let (min, max) = chunks[0].timestamp_bounds().unwrap();
+        let after = query::Range {
+            min: (i64::from(max) + 1).into(),
+            max: (i64::from(max) + 1000).into(),
+        };
+        tfacade.verify_checksums(Some(&after)).await.unwrap();
+        let within = query::Range { min, max };
+        assert!(matches!(
+            tfacade.verify_checksums(Some(&within)).await,
+            Err(repo::FacadeError::CorruptChunk { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    /// Test checking that paginated reads report the total number of rows of the read, and
+    /// that rows sharing the same timestamp are paginated in a deterministic order.
+    async fn paginated_read(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let sequence =
+            repo::FacadeSequence::new("paged".to_owned(), (*store).clone(), (*repo).clone())
+                .create(None)
+                .await
+                .unwrap();
+        let metadata = types::TopicMetadata::new(
+            types::TopicProperties::new(rw::Format::Default, Some("test_tag".to_owned())),
+            marshal::JsonMetadataBlob::try_from_str("{}").unwrap(),
+        );
+        let topic =
+            repo::FacadeTopic::new("paged/topic".to_owned(), (*store).clone(), (*repo).clone())
+                .create(&sequence.uuid, Some(metadata))
+                .await
+                .unwrap();
+
+        let batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new(
+                    params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+                    DataType::Int64,
+                    false,
+                ),
+                Field::new("label", DataType::Utf8, false),
+            ])),
+            vec![
+                Arc::new(Int64Array::from(vec![0, 0, 1, 1, 2])),
+                Arc::new(StringArray::from(vec!["b", "a", "d", "c", "e"])),
+            ],
+        )
+        .unwrap();
+        let cmd = serde_json::json!({
+            "topic": { "name": "paged/topic", "key": topic.uuid.to_string() }
+        });
+        let mut decoder = super::super::do_put::testing::decoder(cmd, vec![batch]);
+        super::super::do_put(
+            (*store).clone(),
+            (*repo).clone(),
+            ts_engine.clone(),
+            &mut decoder,
+        )
+        .await
+        .unwrap();
+
+        // Returns the total reported for the read and the labels of the page
+        let read = async |ticket: marshal::TopicTicket| {
+            let stream = do_get(
+                (*store).clone(),
+                (*repo).clone(),
+                ts_engine.clone(),
+                Ticket::new(ticket.to_bytes().unwrap()),
+            )
+            .await
+            .unwrap();
+            let mut stream = FlightRecordBatchStream::new_from_flight_data(stream);
+
+            let mut labels = Vec::new();
+            while let Some(batch) = stream.try_next().await.unwrap() {
+                let column =
+                    arrow::compute::cast(batch.column_by_name("label").unwrap(), &DataType::Utf8)
+                        .unwrap();
+                labels.extend(
+                    column
+                        .as_any()
+                        .downcast_ref::<StringArray>()
+                        .unwrap()
+                        .iter()
+                        .map(|label| label.unwrap().to_owned()),
+                );
+            }
+            let total = stream
+                .schema()
+                .unwrap()
+                .metadata()
+                .get(params::SCHEMA_METADATA_TOTAL_ROWS)
+                .cloned();
+            (total, labels)
+        };
+
+        let page =
+            |offset| marshal::TopicTicket::new("paged/topic".to_owned()).with_page(offset, 2);
+
+        assert_eq!(
+            read(page(0)).await,
+            (Some("5".to_owned()), vec!["a".to_owned(), "b".to_owned()])
+        );
+        assert_eq!(
+            read(page(2)).await,
+            (Some("5".to_owned()), vec!["c".to_owned(), "d".to_owned()])
+        );
+        assert_eq!(
+            read(page(4)).await,
+            (Some("5".to_owned()), vec!["e".to_owned()])
+        );
+
+        // Reads selecting some of the rows count them
+        let (total, labels) = read(page(0).with_time_range(1.into(), 2.into())).await;
+        assert_eq!(total.as_deref(), Some("3"));
+        assert_eq!(labels, vec!["c".to_owned(), "d".to_owned()]);
+
+        // Reads of whole topics report no total
+        let (total, labels) = read(marshal::TopicTicket::new("paged/topic".to_owned())).await;
+        assert_eq!(total, None);
+        assert_eq!(labels.len(), 5);
+
+        Ok(())
+    }
+}
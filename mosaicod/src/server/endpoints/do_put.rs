@@ -1,18 +1,37 @@
+use std::sync::{Arc, Mutex};
+
 use arrow::datatypes::SchemaRef;
 use futures::TryStreamExt;
 
 use arrow_flight::decode::{DecodedFlightData, DecodedPayload, FlightDataDecoder};
 use arrow_flight::flight_descriptor::DescriptorType;
 
-use log::{debug, info, trace};
+use log::{debug, info, trace, warn};
 use serde::Deserialize;
 
-use crate::{repo, rw, server::errors::ServerError, store, types};
+use crate::{
+    marshal, params, query, repo, rw,
+    server::errors::ServerError,
+    store, traits, types,
+    types::{MetadataBlob, Resource},
+};
 
 #[derive(Deserialize, Debug)]
 struct DoPutTopic {
     name: String,
+    /// Key of the topic, or key of the parent sequence if the topic needs to be
+    /// created automatically (see [`params::MissingTopicPolicy`])
     key: String,
+    /// Ontology tag used when the topic is created automatically, or set to topics
+    /// created without a tag
+    #[serde(default)]
+    ontology_tag: Option<String>,
+}
+
+/// Encodes data for an existing topic without storing it, see [`do_put_dry_run`]
+#[derive(Deserialize, Debug)]
+struct DoPutDryRun {
+    name: String,
 }
 
 #[derive(Deserialize, Debug)]
@@ -20,18 +39,37 @@ struct DoPutTopic {
 #[serde(rename_all = "snake_case")]
 enum DoPutCommand {
     Topic(DoPutTopic),
+    DryRun(DoPutDryRun),
 }
 
+/// Handles a `do_put` call, returning the metadata (if any) to send back to the client:
+/// the report of a dry run, or the key of a topic created automatically by the write.
 pub async fn do_put(
     store: store::StoreRef,
     repo: repo::Repository,
+    ts_engine: query::TimeseriesGwRef,
     decoder: &mut FlightDataDecoder,
-) -> Result<(), ServerError> {
+) -> Result<Option<bytes::Bytes>, ServerError> {
     let (cmd, schema) = extract_command_and_schema_from_header_message(decoder).await?;
 
     match cmd {
         DoPutCommand::Topic(cmd) => {
-            return do_put_topic_data(store, repo, decoder, schema, cmd).await;
+            let policy = params::configurables().missing_topic_policy;
+            let created =
+                do_put_topic_data(store, repo, ts_engine, decoder, schema, cmd, policy).await?;
+
+            let Some(r_id) = created else {
+                return Ok(None);
+            };
+            let bytes = serde_json::to_vec(&marshal::ResourceKey::from(r_id))
+                .map_err(|e| marshal::ActionError::ResponseSerializationError(e.to_string()))?;
+            Ok(Some(bytes.into()))
+        }
+        DoPutCommand::DryRun(cmd) => {
+            let report = do_put_dry_run(store, repo, decoder, schema, cmd).await?;
+            let bytes = serde_json::to_vec(&report)
+                .map_err(|e| marshal::ActionError::ResponseSerializationError(e.to_string()))?;
+            Ok(Some(bytes.into()))
         }
     }
 }
@@ -75,13 +113,22 @@ fn extract_command_from_flight_data(data: &DecodedFlightData) -> Result<DoPutCom
     Ok(serde_json::from_slice::<DoPutCommand>(&desc.cmd)?)
 }
 
+/// Writes the data of a topic, a missing topic is handled according to `missing_topic_policy`.
+///
+/// A write is all-or-nothing: the records of the written chunks are created in a single
+/// batch once the whole stream has been written, and the data files of a failed write are
+/// deleted. The topic is left unlocked by a failed write, so that it can be retried.
+///
+/// Returns the id of the topic if it was created automatically.
 async fn do_put_topic_data(
     store: store::StoreRef,
     repo: repo::Repository,
+    ts_engine: query::TimeseriesGwRef,
     decoder: &mut FlightDataDecoder,
     schema: SchemaRef,
     cmd: DoPutTopic,
-) -> Result<(), ServerError> {
+    missing_topic_policy: params::MissingTopicPolicy,
+) -> Result<Option<types::ResourceId>, ServerError> {
     let name = cmd.name;
     let key = &cmd.key;
 
@@ -92,50 +139,237 @@ async fn do_put_topic_data(
 
     crate::arrow::check_schema(&schema)?;
 
-    let handle = repo::FacadeTopic::new(name, store.clone(), repo.clone());
+    let handle = repo::FacadeTopic::new(name, store, repo.clone());
 
-    // perform the match between received key and topic id
-    let r_id = handle.resource_id().await?;
     let received_uuid: uuid::Uuid = key.parse()?;
-    if received_uuid != r_id.uuid {
-        return Err(ServerError::BadKey);
-    }
+
+    let (r_id, created) = match handle.resource_id().await {
+        Ok(r_id) => {
+            // perform the match between received key and topic id
+            if received_uuid != r_id.uuid {
+                return Err(ServerError::BadKey);
+            }
+            (r_id, false)
+        }
+        Err(repo::FacadeError::RepositoryError(e)) if e.is_not_found() => {
+            let r_id = create_missing_topic(
+                &handle,
+                &received_uuid,
+                cmd.ontology_tag.clone(),
+                missing_topic_policy,
+            )
+            .await?;
+            (r_id, true)
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    // Data can't be added to locked topics or sequences
+    handle.ensure_writable().await?;
 
     let mdata = handle.metadata().await?;
 
-    // Setup the callback that will be used to create the repository record for the data catalog
-    // and prepare variables that will be moved in the closure
-    let ontology_tag = mdata.properties.ontology_tag;
+    crate::arrow::check_timestamp_unit(&schema, mdata.properties.timestamp_unit)?;
+
+    // Topics created without a tag are tagged by their first write
+    let ontology_tag = match &mdata.properties.ontology_tag {
+        Some(ontology_tag) => ontology_tag.clone(),
+        None => {
+            let ontology_tag = cmd.ontology_tag.ok_or(ServerError::MissingOntologyTag)?;
+            handle.set_ontology_tag(&ontology_tag).await?
+        }
+    };
+
+    handle.check_ontology_schema(&ontology_tag, &schema).await?;
+
+    // Chunks already registered for the topic are read together with the new ones
+    handle
+        .check_schema_evolution(mdata.properties.serialization_format, &schema)
+        .await?;
+
+    let contract = handle
+        .check_ontology_contract(&ontology_tag, &schema)
+        .await?;
+
+    let row_group_size = mdata.properties.row_group_size_limit();
+    let first_chunk_index = handle.next_chunk_index().await?;
+
+    // Setup the callback collecting the written chunks, their records for the data catalog are
+    // created all at once at the end of the write
     let serialization_format = mdata.properties.serialization_format;
+    let timestamp_unit = mdata.properties.timestamp_unit;
     let topic_id = r_id.id;
 
-    let mut writer = handle.writer(serialization_format).on_chunk_created(
-        move |target_path, cols_stats, chunk_metadata| {
-            let topic_id = topic_id;
-            let repo_clone = repo.clone();
-            let ontology_tag = ontology_tag.clone();
-
-            async move {
-                trace!(
-                    "calling chunk creation callback for `{}` {:?}",
-                    target_path.to_string_lossy(),
-                    cols_stats
-                );
+    let chunks = Arc::new(Mutex::new(Vec::new()));
+    let chunks_clbk = chunks.clone();
 
-                Ok(on_chunk_created(
-                    repo_clone,
-                    topic_id,
-                    &ontology_tag,
-                    target_path,
-                    cols_stats,
-                    chunk_metadata,
-                )
-                .await?)
-            }
-        },
-    );
+    let mut writer = handle
+        .writer(serialization_format)
+        .with_monotonic_timestamps(mdata.properties.monotonic_timestamps)
+        .with_max_chunk_bytes(params::configurables().max_chunk_bytes)
+        .with_max_row_group_size(row_group_size)
+        .with_first_chunk_index(first_chunk_index)
+        .on_chunk_created(move |target_path, cols_stats, chunk_metadata| {
+            trace!(
+                "calling chunk creation callback for `{}` {:?}",
+                target_path.to_string_lossy(),
+                cols_stats
+            );
+
+            chunks_clbk.lock().unwrap().push(repo::NewChunk::new(
+                timestamp_unit,
+                target_path,
+                cols_stats,
+                chunk_metadata,
+            ));
+            async { Ok(()) }
+        });
+
+    let written = async {
+        write_batches(decoder, &mut writer, contract.as_ref()).await?;
+
+        // If the finalize fails (e.g. problems during stats computation) the topic will not be
+        // locked, this allows the reindexing (currently not implemented) of
+        // the topic
+        trace!("finializing data write");
+        writer.finalize().await?;
+
+        Ok::<(), ServerError>(())
+    }
+    .await;
+
+    let chunks = std::mem::take(&mut *chunks.lock().unwrap());
+    let data_files: Vec<_> = chunks.iter().map(|chunk| chunk.datafile.clone()).collect();
+
+    let written = match written {
+        Ok(()) => {
+            trace!("creating #{} chunk records", chunks.len());
+            repo::FacadeChunk::create_batch(topic_id, &ontology_tag, chunks, &repo)
+                .await
+                .map(|_| ())
+                .map_err(ServerError::from)
+        }
+        Err(e) => Err(e),
+    };
+
+    if written.is_err() {
+        debug!("discarding #{} data files", data_files.len());
+        handle.discard_data_files(&data_files).await;
+    }
+
+    // The written chunks (also the ones of a failed write, if not discarded) may change the
+    // schema resolved for the topic
+    ts_engine.schema_cache().invalidate(handle.locator.name());
+    written?;
+
+    // Small chunks left by the previous writes of a re-opened topic are merged before the
+    // topic is locked, the data files of locked topics may be read concurrently
+    if handle.needs_compaction().await? {
+        compact_topic_data(&handle, &ts_engine).await;
+    }
+
+    trace!("resource {} locked", handle.locator);
+    handle.lock().await?;
+
+    Ok(created.then_some(r_id))
+}
+
+/// Compacts the chunks of the topic (see [`repo::FacadeTopic::compact`]), a failed
+/// compaction leaves the data as written.
+async fn compact_topic_data(handle: &repo::FacadeTopic, ts_engine: &query::TimeseriesGwRef) {
+    match handle.compact().await {
+        Ok(compaction) => debug!(
+            "[{}] {} chunks merged into {} chunks",
+            handle.locator, compaction.merged_chunks, compaction.created_chunks
+        ),
+        Err(e) => warn!("[{}] unable to compact chunks :: {}", handle.locator, e),
+    }
 
-    // Consume all batches
+    // A failed compaction may have replaced some of the chunks
+    ts_engine.read_cache().invalidate(handle.locator.name());
+    ts_engine.schema_cache().invalidate(handle.locator.name());
+}
+
+/// Encodes the data of a write to an existing topic exactly like [`do_put_topic_data`]
+/// (same serialization format and chunk splitting), but the chunks are discarded.
+///
+/// No file is written to the store and no record is created or updated, only the size
+/// and the statistics of the chunks that the write would produce are reported. Like a
+/// write, a dry run fails if the topic or its sequence are locked.
+async fn do_put_dry_run(
+    store: store::StoreRef,
+    repo: repo::Repository,
+    decoder: &mut FlightDataDecoder,
+    schema: SchemaRef,
+    cmd: DoPutDryRun,
+) -> Result<marshal::DryRunReport, ServerError> {
+    info!("client requested a dry run write on topic '{}'", cmd.name);
+
+    crate::arrow::check_schema(&schema)?;
+
+    let handle = repo::FacadeTopic::new(cmd.name, store, repo);
+
+    handle.ensure_writable().await?;
+
+    let mdata = handle.metadata().await?;
+
+    crate::arrow::check_timestamp_unit(&schema, mdata.properties.timestamp_unit)?;
+
+    // Topics without a tag have no registered fields nor contract to check
+    let ontology_tag = mdata.properties.ontology_tag.as_deref();
+
+    if let Some(ontology_tag) = ontology_tag {
+        handle.check_ontology_schema(ontology_tag, &schema).await?;
+    }
+
+    handle
+        .check_schema_evolution(mdata.properties.serialization_format, &schema)
+        .await?;
+
+    let contract = match ontology_tag {
+        Some(ontology_tag) => {
+            handle
+                .check_ontology_contract(ontology_tag, &schema)
+                .await?
+        }
+        None => None,
+    };
+
+    let chunks = Arc::new(Mutex::new(Vec::new()));
+    let chunks_clbk = chunks.clone();
+
+    let mut writer = handle
+        .dry_run_writer(mdata.properties.serialization_format)
+        .with_monotonic_timestamps(mdata.properties.monotonic_timestamps)
+        .with_max_chunk_bytes(params::configurables().max_chunk_bytes)
+        .with_max_row_group_size(mdata.properties.row_group_size_limit())
+        .on_chunk_created(move |_, cols_stats, chunk_metadata| {
+            chunks_clbk
+                .lock()
+                .unwrap()
+                .push(marshal::DryRunChunk::new(cols_stats, chunk_metadata));
+            async { Ok(()) }
+        });
+
+    write_batches(decoder, &mut writer, contract.as_ref()).await?;
+    writer.finalize().await?;
+
+    let chunks = std::mem::take(&mut *chunks.lock().unwrap());
+    Ok(marshal::DryRunReport { chunks })
+}
+
+/// Writes all the batches of the stream, the schema is expected to be already consumed.
+///
+/// If a `contract` is provided, batches holding null values in non nullable fields, or
+/// values outside the range of numeric fields, are rejected.
+async fn write_batches<W>(
+    decoder: &mut FlightDataDecoder,
+    writer: &mut rw::ChunkedWriter<'_, W>,
+    contract: Option<&types::OntologyContract>,
+) -> Result<(), ServerError>
+where
+    W: traits::AsyncWriteToPath,
+{
     while let Some(data) = decoder
         .try_next()
         .await
@@ -148,6 +382,23 @@ async fn do_put_topic_data(
                     batch.columns().len(),
                     batch.get_array_memory_size()
                 );
+
+                if let Some(contract) = contract {
+                    let mut violations =
+                        crate::arrow::contract_null_violations(&batch, &contract.data);
+                    violations.extend(crate::arrow::contract_range_violations(
+                        &batch,
+                        &contract.data,
+                    )?);
+                    if !violations.is_empty() {
+                        return Err(repo::FacadeError::ContractViolation {
+                            ontology_tag: contract.ontology_tag.clone(),
+                            violations,
+                        }
+                        .into());
+                    }
+                }
+
                 writer.write(&batch).await?;
             }
             DecodedPayload::Schema(_) => {
@@ -159,39 +410,726 @@ async fn do_put_topic_data(
         }
     }
 
-    // If the finalize fails (e.g. problems during stats computation) the topic will not be locked,
-    // this allows the reindexing (currently not implemented) of
-    // the topic
-    trace!("finializing data write");
-    writer.finalize().await?;
+    Ok(())
+}
 
-    trace!("resource {} locked", handle.locator);
-    handle.lock().await?;
+/// Handles a write to a non-existent topic according to `policy`.
+///
+/// When the topic is automatically created `sequence_key` is the key of the parent sequence,
+/// which needs to exist and be unlocked.
+async fn create_missing_topic(
+    handle: &repo::FacadeTopic,
+    sequence_key: &uuid::Uuid,
+    ontology_tag: Option<String>,
+    policy: params::MissingTopicPolicy,
+) -> Result<types::ResourceId, ServerError> {
+    match policy {
+        params::MissingTopicPolicy::Reject => {
+            Err(repo::FacadeError::NotFound(handle.locator.to_string()).into())
+        }
+        params::MissingTopicPolicy::AutoCreate => {
+            let ontology_tag = ontology_tag.ok_or(ServerError::MissingOntologyTag)?;
+            let format = params::configurables()
+                .default_formats
+                .format_for(&ontology_tag);
 
-    Ok(())
+            info!(
+                "automatically creating topic {} (ontology tag: `{}`, format: `{}`)",
+                handle.locator, ontology_tag, format
+            );
+
+            let user_mdata =
+                marshal::JsonMetadataBlob::try_from_str("{}").map_err(repo::FacadeError::from)?;
+            let mdata = types::TopicMetadata::new(
+                types::TopicProperties::new(format, Some(ontology_tag)),
+                user_mdata,
+            );
+
+            Ok(handle.create(sequence_key, Some(mdata)).await?)
+        }
+    }
 }
 
-async fn on_chunk_created(
-    repo: repo::Repository,
-    topic_id: i32,
-    ontology_tag: &str,
-    target_path: impl AsRef<std::path::Path>,
-    cstats: types::ColumnsStats,
-    chunk_metadata: rw::ChunkMetadata,
-) -> Result<(), ServerError> {
-    let mut handle = repo::FacadeChunk::create(
-        topic_id,
-        &target_path,
-        chunk_metadata.size_bytes as i64,
-        chunk_metadata.row_count as i64,
-        &repo,
-    )
-    .await?;
-
-    // Use batch insert for better performance (single INSERT per type instead of N)
-    handle.push_all_stats(ontology_tag, cstats).await?;
-
-    handle.finalize().await?;
+#[cfg(test)]
+pub(super) mod testing {
+    use arrow::array::RecordBatch;
+    use arrow_flight::FlightDescriptor;
+    use arrow_flight::decode::FlightDataDecoder;
+    use arrow_flight::encode::FlightDataEncoderBuilder;
 
-    Ok(())
+    /// Returns the flight data sent by a client putting `batches` with the command `cmd`
+    pub fn decoder(cmd: serde_json::Value, batches: Vec<RecordBatch>) -> FlightDataDecoder {
+        let stream = FlightDataEncoderBuilder::new()
+            .with_flight_descriptor(Some(FlightDescriptor::new_cmd(cmd.to_string())))
+            .build(futures::stream::iter(batches.into_iter().map(Ok)));
+        FlightDataDecoder::new(stream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{BinaryArray, Float64Array, Int64Array, RecordBatch, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    use super::*;
+
+    #[sqlx::test]
+    /// Test checking that a dry run rejects data that can't be read together with the chunks
+    /// already stored in the topic, and data for locked topics.
+    async fn dry_run_schema_evolution(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let sequence =
+            repo::FacadeSequence::new("sequence".to_owned(), (*store).clone(), (*repo).clone())
+                .create(None)
+                .await
+                .unwrap();
+        let mdata = types::TopicMetadata::new(
+            types::TopicProperties::new(rw::Format::Default, Some("imu".to_owned())),
+            marshal::JsonMetadataBlob::try_from_str("{}").unwrap(),
+        );
+        let topic =
+            repo::FacadeTopic::new("sequence/imu".to_owned(), (*store).clone(), (*repo).clone())
+                .create(&sequence.uuid, Some(mdata))
+                .await
+                .unwrap();
+
+        let timestamp = Field::new(
+            params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+            DataType::Int64,
+            false,
+        );
+        let schema = Arc::new(Schema::new(vec![
+            timestamp.clone(),
+            Field::new("acc", DataType::Float64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(vec![0, 1])),
+                Arc::new(Float64Array::from(vec![0.5, 1.5])),
+            ],
+        )
+        .unwrap();
+
+        // A chunk already stored in the (unlocked) topic
+        let mut writer = rw::ChunkWriter::try_new(schema, rw::Format::Default).unwrap();
+        writer.write(&batch).unwrap();
+        let (buffer, stats, metadata) = writer.finalize().unwrap();
+        let data_file = "sequence/imu/data-00000.parquet";
+        store.write_bytes(data_file, buffer).await.unwrap();
+        let chunk = repo::NewChunk::new(
+            types::TimestampUnit::default(),
+            data_file.into(),
+            stats,
+            metadata,
+        );
+        repo::FacadeChunk::create_batch(topic.id, "imu", vec![chunk], &repo)
+            .await
+            .unwrap();
+
+        let dry_run = async |batch: RecordBatch| {
+            let cmd = serde_json::json!({ "dry_run": { "name": "sequence/imu" } });
+            let mut decoder = testing::decoder(cmd, vec![batch]);
+            do_put(
+                (*store).clone(),
+                (*repo).clone(),
+                ts_engine.clone(),
+                &mut decoder,
+            )
+            .await
+        };
+
+        assert!(dry_run(batch).await.unwrap().is_some());
+
+        let changed = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                timestamp,
+                Field::new("acc", DataType::Utf8, false),
+            ])),
+            vec![
+                Arc::new(Int64Array::from(vec![2, 3])),
+                Arc::new(StringArray::from(vec!["a", "b"])),
+            ],
+        )
+        .unwrap();
+        assert!(matches!(
+            dry_run(changed).await,
+            Err(ServerError::FacadeError(
+                repo::FacadeError::IncompatibleField { .. }
+            ))
+        ));
+
+        repo::FacadeTopic::new("sequence/imu".to_owned(), (*store).clone(), (*repo).clone())
+            .lock()
+            .await
+            .unwrap();
+        let unchanged = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new(
+                    params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+                    DataType::Int64,
+                    false,
+                ),
+                Field::new("acc", DataType::Float64, false),
+            ])),
+            vec![
+                Arc::new(Int64Array::from(vec![2])),
+                Arc::new(Float64Array::from(vec![2.5])),
+            ],
+        )
+        .unwrap();
+        assert!(matches!(
+            dry_run(unchanged).await,
+            Err(ServerError::FacadeError(repo::FacadeError::TopicLocked))
+        ));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    /// Test checking that fields without statistics (e.g. binary fields) are registered for
+    /// the ontology tag, so that following writes with the same schema are accepted.
+    async fn ontology_with_binary_field(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let sequence =
+            repo::FacadeSequence::new("sequence".to_owned(), (*store).clone(), (*repo).clone())
+                .create(None)
+                .await
+                .unwrap();
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+                DataType::Int64,
+                false,
+            ),
+            Field::new("image", DataType::Binary, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(vec![0, 1])),
+                Arc::new(BinaryArray::from_vec(vec![&b"\x00\x01"[..], &b"\x02"[..]])),
+            ],
+        )
+        .unwrap();
+
+        for name in ["sequence/camera_a", "sequence/camera_b"] {
+            let mdata = types::TopicMetadata::new(
+                types::TopicProperties::new(rw::Format::Default, Some("image".to_owned())),
+                marshal::JsonMetadataBlob::try_from_str("{}").unwrap(),
+            );
+            let topic = repo::FacadeTopic::new(name.to_owned(), (*store).clone(), (*repo).clone())
+                .create(&sequence.uuid, Some(mdata))
+                .await
+                .unwrap();
+
+            let cmd = serde_json::json!({
+                "topic": { "name": name, "key": topic.uuid.to_string() }
+            });
+            let mut decoder = testing::decoder(cmd, vec![batch.clone()]);
+            do_put(
+                (*store).clone(),
+                (*repo).clone(),
+                ts_engine.clone(),
+                &mut decoder,
+            )
+            .await
+            .unwrap();
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    /// Test checking that the batches holding values outside the range of a numeric data
+    /// field of the ontology contract are rejected.
+    async fn contract_data_range(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        repo::FacadeOntologyContract::new("imu".to_owned(), (*repo).clone())
+            .set(types::OntologyContract {
+                ontology_tag: "imu".to_owned(),
+                metadata: vec![],
+                data: vec![types::ContractField {
+                    name: "acc".to_owned(),
+                    field_type: types::ContractFieldType::Float,
+                    nullable: true,
+                    range: Some((-10.0, 10.0)),
+                }],
+            })
+            .await
+            .unwrap();
+
+        let sequence =
+            repo::FacadeSequence::new("sequence".to_owned(), (*store).clone(), (*repo).clone())
+                .create(None)
+                .await
+                .unwrap();
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+                DataType::Int64,
+                false,
+            ),
+            Field::new("acc", DataType::Float64, true),
+        ]));
+        let batch = |acc: Vec<Option<f64>>| {
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(Int64Array::from_iter_values(0..acc.len() as i64)),
+                    Arc::new(Float64Array::from(acc)),
+                ],
+            )
+            .unwrap()
+        };
+
+        let put = async |name: &str, batch: RecordBatch| {
+            let mdata = types::TopicMetadata::new(
+                types::TopicProperties::new(rw::Format::Default, Some("imu".to_owned())),
+                marshal::JsonMetadataBlob::try_from_str("{}").unwrap(),
+            );
+            let topic = repo::FacadeTopic::new(name.to_owned(), (*store).clone(), (*repo).clone())
+                .create(&sequence.uuid, Some(mdata))
+                .await
+                .unwrap();
+
+            let cmd = serde_json::json!({
+                "topic": { "name": name, "key": topic.uuid.to_string() }
+            });
+            let mut decoder = testing::decoder(cmd, vec![batch]);
+            do_put(
+                (*store).clone(),
+                (*repo).clone(),
+                ts_engine.clone(),
+                &mut decoder,
+            )
+            .await
+        };
+
+        // Bounds are inclusive, null values are not checked
+        put("sequence/imu_a", batch(vec![Some(-10.0), None, Some(10.0)]))
+            .await
+            .unwrap();
+
+        let err = put("sequence/imu_b", batch(vec![Some(0.0), Some(10.5)]))
+            .await
+            .unwrap_err();
+        let ServerError::FacadeError(repo::FacadeError::ContractViolation { violations, .. }) = err
+        else {
+            panic!("wrong error returned")
+        };
+        assert_eq!(
+            violations,
+            vec![types::ContractViolation::new(
+                "acc",
+                "must be within [-10, 10]"
+            )]
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    /// Test checking that a failed write leaves no chunk nor data file in the topic, which
+    /// stays unlocked so that the write can be retried.
+    async fn failed_put_is_discarded(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        repo::FacadeOntologyContract::new("imu".to_owned(), (*repo).clone())
+            .set(types::OntologyContract {
+                ontology_tag: "imu".to_owned(),
+                metadata: vec![],
+                data: vec![types::ContractField {
+                    name: "acc".to_owned(),
+                    field_type: types::ContractFieldType::Float,
+                    nullable: false,
+                    range: Some((-10.0, 10.0)),
+                }],
+            })
+            .await
+            .unwrap();
+
+        let sequence =
+            repo::FacadeSequence::new("sequence".to_owned(), (*store).clone(), (*repo).clone())
+                .create(None)
+                .await
+                .unwrap();
+        let mdata = types::TopicMetadata::new(
+            types::TopicProperties::new(rw::Format::Default, Some("imu".to_owned())),
+            marshal::JsonMetadataBlob::try_from_str("{}").unwrap(),
+        );
+        let handle =
+            repo::FacadeTopic::new("sequence/imu".to_owned(), (*store).clone(), (*repo).clone());
+        let topic = handle.create(&sequence.uuid, Some(mdata)).await.unwrap();
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+                DataType::Int64,
+                false,
+            ),
+            Field::new("acc", DataType::Float64, false),
+        ]));
+        let batch = |timestamps: Vec<i64>, acc: Vec<f64>| {
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(Int64Array::from(timestamps)),
+                    Arc::new(Float64Array::from(acc)),
+                ],
+            )
+            .unwrap()
+        };
+
+        let put = async |batches: Vec<RecordBatch>| {
+            let cmd = serde_json::json!({
+                "topic": { "name": "sequence/imu", "key": topic.uuid.to_string() }
+            });
+            let mut decoder = testing::decoder(cmd, batches);
+            do_put(
+                (*store).clone(),
+                (*repo).clone(),
+                ts_engine.clone(),
+                &mut decoder,
+            )
+            .await
+        };
+
+        // The second batch of the stream is rejected
+        assert!(matches!(
+            put(vec![
+                batch(vec![0, 1], vec![0.5, 1.5]),
+                batch(vec![2], vec![20.0])
+            ])
+            .await,
+            Err(ServerError::FacadeError(
+                repo::FacadeError::ContractViolation { .. }
+            ))
+        ));
+
+        let stats = handle.chunks_stats().await.unwrap();
+        assert_eq!(stats.total_chunks, 0);
+        assert_eq!(stats.total_row_count, 0);
+        assert!(
+            store
+                .list(handle.path(), Some(params::ext::PARQUET))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(!handle.is_locked().await.unwrap());
+
+        // Only the data of the retried write is stored
+        put(vec![batch(vec![0, 1, 2], vec![0.5, 1.5, 2.5])])
+            .await
+            .unwrap();
+
+        let stats = handle.chunks_stats().await.unwrap();
+        assert_eq!(stats.total_chunks, 1);
+        assert_eq!(stats.total_row_count, 3);
+        assert!(handle.is_locked().await.unwrap());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    /// Test checking that the topics of an ontology tag can add nullable fields to the ones
+    /// registered for the tag, while new non nullable fields are rejected.
+    async fn ontology_additive_fields(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let sequence =
+            repo::FacadeSequence::new("sequence".to_owned(), (*store).clone(), (*repo).clone())
+                .create(None)
+                .await
+                .unwrap();
+
+        let put = async |name: &str, fields: Vec<Field>| {
+            let mdata = types::TopicMetadata::new(
+                types::TopicProperties::new(rw::Format::Default, Some("imu".to_owned())),
+                marshal::JsonMetadataBlob::try_from_str("{}").unwrap(),
+            );
+            let topic = repo::FacadeTopic::new(name.to_owned(), (*store).clone(), (*repo).clone())
+                .create(&sequence.uuid, Some(mdata))
+                .await
+                .unwrap();
+
+            let mut columns: Vec<arrow::array::ArrayRef> =
+                vec![Arc::new(Int64Array::from(vec![0, 1]))];
+            columns.extend(
+                fields
+                    .iter()
+                    .map(|_| Arc::new(Float64Array::from(vec![0.5, 1.5])) as _),
+            );
+            let mut schema = vec![Field::new(
+                params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+                DataType::Int64,
+                false,
+            )];
+            schema.extend(fields);
+            let batch = RecordBatch::try_new(Arc::new(Schema::new(schema)), columns).unwrap();
+
+            let cmd = serde_json::json!({
+                "topic": { "name": name, "key": topic.uuid.to_string() }
+            });
+            let mut decoder = testing::decoder(cmd, vec![batch]);
+            do_put(
+                (*store).clone(),
+                (*repo).clone(),
+                ts_engine.clone(),
+                &mut decoder,
+            )
+            .await
+        };
+
+        put(
+            "sequence/imu_a",
+            vec![Field::new("acc", DataType::Float64, false)],
+        )
+        .await
+        .unwrap();
+
+        // New nullable fields are accepted, and registered for the tag
+        put(
+            "sequence/imu_b",
+            vec![
+                Field::new("acc", DataType::Float64, false),
+                Field::new("gyro", DataType::Float64, true),
+            ],
+        )
+        .await
+        .unwrap();
+        put(
+            "sequence/imu_c",
+            vec![Field::new("gyro", DataType::Float64, false)],
+        )
+        .await
+        .unwrap();
+
+        let err = put(
+            "sequence/imu_d",
+            vec![
+                Field::new("acc", DataType::Float64, false),
+                Field::new("mag", DataType::Float64, false),
+            ],
+        )
+        .await
+        .unwrap_err();
+        let ServerError::FacadeError(repo::FacadeError::UnknownOntologyFields { fields, .. }) = err
+        else {
+            panic!("wrong error returned")
+        };
+        assert_eq!(fields, vec!["mag".to_owned()]);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    /// Test checking that topics created without an ontology tag are tagged by their first
+    /// write, which needs to provide the tag.
+    async fn untagged_topic(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let sequence =
+            repo::FacadeSequence::new("sequence".to_owned(), (*store).clone(), (*repo).clone())
+                .create(None)
+                .await
+                .unwrap();
+        let mdata = types::TopicMetadata::new(
+            types::TopicProperties::new(rw::Format::Default, None),
+            marshal::JsonMetadataBlob::try_from_str("{}").unwrap(),
+        );
+        let handle =
+            repo::FacadeTopic::new("sequence/imu".to_owned(), (*store).clone(), (*repo).clone());
+        let topic = handle.create(&sequence.uuid, Some(mdata)).await.unwrap();
+
+        let batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new(
+                    params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+                    DataType::Int64,
+                    false,
+                ),
+                Field::new("acc", DataType::Float64, false),
+            ])),
+            vec![
+                Arc::new(Int64Array::from(vec![0, 1])),
+                Arc::new(Float64Array::from(vec![0.5, 1.5])),
+            ],
+        )
+        .unwrap();
+
+        let put = async |cmd: serde_json::Value| {
+            let mut decoder = testing::decoder(cmd, vec![batch.clone()]);
+            do_put(
+                (*store).clone(),
+                (*repo).clone(),
+                ts_engine.clone(),
+                &mut decoder,
+            )
+            .await
+        };
+
+        assert!(matches!(
+            put(serde_json::json!({
+                "topic": { "name": "sequence/imu", "key": topic.uuid.to_string() }
+            }))
+            .await,
+            Err(ServerError::MissingOntologyTag)
+        ));
+        assert!(
+            handle
+                .metadata()
+                .await
+                .unwrap()
+                .properties
+                .ontology_tag
+                .is_none()
+        );
+
+        put(serde_json::json!({
+            "topic": {
+                "name": "sequence/imu",
+                "key": topic.uuid.to_string(),
+                "ontology_tag": "imu",
+            }
+        }))
+        .await
+        .unwrap();
+
+        assert_eq!(
+            handle
+                .metadata()
+                .await
+                .unwrap()
+                .properties
+                .ontology_tag
+                .as_deref(),
+            Some("imu")
+        );
+        let mut cx = repo.connection();
+        let record = repo::topic_find_by_locator(&mut cx, &handle.locator)
+            .await
+            .unwrap();
+        assert_eq!(record.ontology_tag.as_deref(), Some("imu"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    /// Test checking that writes to missing topics are rejected or create the topic,
+    /// depending on the [`params::MissingTopicPolicy`].
+    async fn missing_topic_policy(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let sequence =
+            repo::FacadeSequence::new("sequence".to_owned(), (*store).clone(), (*repo).clone())
+                .create(None)
+                .await
+                .unwrap();
+
+        let batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new(
+                    params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+                    DataType::Int64,
+                    false,
+                ),
+                Field::new("acc", DataType::Float64, false),
+            ])),
+            vec![
+                Arc::new(Int64Array::from(vec![0, 1])),
+                Arc::new(Float64Array::from(vec![0.5, 1.5])),
+            ],
+        )
+        .unwrap();
+
+        let put = async |policy: params::MissingTopicPolicy| {
+            let cmd = serde_json::json!({
+                "topic": {
+                    "name": "sequence/imu",
+                    "key": sequence.uuid.to_string(),
+                    "ontology_tag": "imu",
+                }
+            });
+            let mut decoder = testing::decoder(cmd, vec![batch.clone()]);
+            let (cmd, schema) = extract_command_and_schema_from_header_message(&mut decoder)
+                .await
+                .unwrap();
+            let DoPutCommand::Topic(cmd) = cmd else {
+                panic!("expecting a topic command");
+            };
+            do_put_topic_data(
+                (*store).clone(),
+                (*repo).clone(),
+                ts_engine.clone(),
+                &mut decoder,
+                schema,
+                cmd,
+                policy,
+            )
+            .await
+        };
+
+        let handle =
+            repo::FacadeTopic::new("sequence/imu".to_owned(), (*store).clone(), (*repo).clone());
+
+        assert!(matches!(
+            put(params::MissingTopicPolicy::Reject).await,
+            Err(ServerError::FacadeError(repo::FacadeError::NotFound(_)))
+        ));
+        assert!(handle.resource_id().await.is_err());
+
+        let created = put(params::MissingTopicPolicy::AutoCreate)
+            .await
+            .unwrap()
+            .expect("the key of the created topic should be returned");
+        assert_eq!(handle.resource_id().await.unwrap().uuid, created.uuid);
+        assert_eq!(
+            handle
+                .metadata()
+                .await
+                .unwrap()
+                .properties
+                .ontology_tag
+                .as_deref(),
+            Some("imu")
+        );
+        assert!(handle.is_locked().await.unwrap());
+
+        Ok(())
+    }
 }
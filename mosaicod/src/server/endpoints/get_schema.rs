@@ -0,0 +1,218 @@
+use arrow::datatypes::{Fields, Schema};
+use arrow::ipc::writer::IpcWriteOptions;
+use arrow_flight::{
+    FlightDescriptor, SchemaAsIpc, SchemaResult, flight_descriptor::DescriptorType,
+};
+use log::{info, trace};
+
+use crate::{
+    marshal, query,
+    repo::{self, FacadeError, FacadeTopic},
+    server::errors::ServerError,
+    store,
+};
+
+/// Returns the arrow schema of a topic, including the flattened topic metadata,
+/// without streaming any data.
+///
+/// Topics without chunks return the schema they declared at creation, if any (see
+/// [`crate::arrow::fields_from_declared_schema`]), otherwise a schema with no fields,
+/// carrying only the topic metadata (e.g. the ontology tag).
+pub async fn get_schema(
+    store: store::StoreRef,
+    repo: repo::Repository,
+    ts_engine: query::TimeseriesGwRef,
+    desc: FlightDescriptor,
+) -> Result<SchemaResult, ServerError> {
+    if desc.r#type() != DescriptorType::Path {
+        return Err(ServerError::UnsupportedDescriptor);
+    }
+    if desc.path.len() != 1 {
+        return Err(ServerError::MultiplePathUnsupported);
+    }
+
+    let topic_name = desc.path[0].clone();
+    info!("requesting schema for topic {}", topic_name);
+
+    let handle = FacadeTopic::new(topic_name, store, repo);
+    let metadata = handle.metadata().await?;
+    let format = metadata.properties.serialization_format;
+    let declared_fields = metadata
+        .properties
+        .declared_schema
+        .as_ref()
+        .and_then(|declared| crate::arrow::fields_from_declared_schema(&declared.fields));
+
+    let metadata = marshal::JsonTopicMetadata::from(metadata);
+    let flatten_metadata = metadata.to_flat_hashmap().map_err(FacadeError::from)?;
+
+    let stats = handle.chunks_stats().await?;
+    let schema = if stats.last_chunk_timestamp.is_none() {
+        trace!(
+            "{} has no chunks, building the declared schema",
+            handle.locator
+        );
+        Schema::new_with_metadata(
+            declared_fields.unwrap_or_else(Fields::empty),
+            flatten_metadata,
+        )
+    } else {
+        // Only the query plan is built, no data is read
+        let result = ts_engine
+            .read(
+                handle.path(),
+                format,
+                None,
+                None,
+                None,
+                query::SortOrder::Asc,
+            )
+            .await?;
+        result
+            .schema_with_metadata(flatten_metadata)
+            .as_ref()
+            .clone()
+    };
+
+    trace!("{:?}", schema);
+
+    let options = IpcWriteOptions::default();
+    Ok(SchemaAsIpc::new(&schema, &options).try_into()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::{Float64Array, Int64Array, RecordBatch};
+    use arrow::datatypes::{DataType, Field};
+
+    use super::*;
+    use crate::{params, rw, types};
+
+    /// Creates the topic `name` declaring a nullable `pose.x` float field, if `declared`
+    async fn create_topic(
+        repo: &repo::Repository,
+        store: &store::StoreRef,
+        name: &str,
+        declared: bool,
+    ) -> types::ResourceId {
+        let sequence = repo::FacadeSequence::new(name.to_owned(), store.clone(), repo.clone())
+            .create(None)
+            .await
+            .unwrap();
+
+        let declared = declared.then(|| types::DeclaredSchema {
+            fields: vec![types::DeclaredField {
+                name: "pose.x".to_owned(),
+                data_type: "float64".to_owned(),
+                nullable: true,
+            }],
+            strict: false,
+        });
+        let props = types::TopicProperties::new(rw::Format::Default, Some("test_tag".to_owned()))
+            .with_declared_schema(declared);
+        let metadata = types::TopicMetadata::new(
+            props,
+            marshal::JsonMetadataBlob::try_from_str("{}").unwrap(),
+        );
+        FacadeTopic::new(format!("{name}/topic"), store.clone(), repo.clone())
+            .create(&sequence.uuid, Some(metadata))
+            .await
+            .unwrap()
+    }
+
+    async fn schema(
+        repo: &repo::Repository,
+        store: &store::StoreRef,
+        ts_engine: &query::TimeseriesGwRef,
+        topic: &str,
+    ) -> Schema {
+        let desc = FlightDescriptor::new_path(vec![topic.to_owned()]);
+        let result = get_schema(store.clone(), repo.clone(), ts_engine.clone(), desc)
+            .await
+            .unwrap();
+        Schema::try_from(&result).unwrap()
+    }
+
+    #[sqlx::test]
+    /// Test checking that empty topics return the schema they declared, or no fields.
+    async fn empty_topic_schema(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        create_topic(&repo, &store, "declared", true).await;
+        let schema = schema(&repo, &store, &ts_engine, "declared/topic").await;
+        let pose = Field::new(
+            "pose",
+            DataType::Struct(vec![Field::new("x", DataType::Float64, true)].into()),
+            true,
+        );
+        assert_eq!(
+            schema.fields(),
+            &Fields::from(vec![
+                Field::new(
+                    params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+                    DataType::Int64,
+                    false
+                ),
+                pose,
+            ])
+        );
+        assert!(!schema.metadata().is_empty());
+
+        create_topic(&repo, &store, "undeclared", false).await;
+        let schema = schema(&repo, &store, &ts_engine, "undeclared/topic").await;
+        assert!(schema.fields().is_empty());
+        assert!(!schema.metadata().is_empty());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    /// Test checking that topics holding data return the schema of the data.
+    async fn topic_schema(pool: sqlx::Pool<repo::Database>) -> sqlx::Result<()> {
+        params::load_configurables_from_env();
+
+        let repo = repo::testing::Repository::new(pool);
+        let store = store::testing::Store::new_random_on_tmp().unwrap();
+        let ts_engine = Arc::new(query::TimeseriesGw::try_new(store.clone()).unwrap());
+
+        let topic = create_topic(&repo, &store, "sequence", false).await;
+
+        let data_schema = Arc::new(Schema::new(vec![
+            Field::new(
+                params::ARROW_SCHEMA_COLUMN_NAME_TIMESTAMP,
+                DataType::Int64,
+                false,
+            ),
+            Field::new("acc", DataType::Float64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            data_schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(vec![0, 1])),
+                Arc::new(Float64Array::from(vec![0.5, 1.5])),
+            ],
+        )
+        .unwrap();
+        let cmd = serde_json::json!({
+            "topic": { "name": "sequence/topic", "key": topic.uuid.to_string() }
+        });
+        let mut decoder = super::super::do_put::testing::decoder(cmd, vec![batch]);
+        super::super::do_put(
+            (*store).clone(),
+            (*repo).clone(),
+            ts_engine.clone(),
+            &mut decoder,
+        )
+        .await
+        .unwrap();
+
+        let schema = schema(&repo, &store, &ts_engine, "sequence/topic").await;
+        assert_eq!(schema.fields(), data_schema.fields());
+
+        Ok(())
+    }
+}
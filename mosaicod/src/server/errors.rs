@@ -1,6 +1,6 @@
 use thiserror::Error;
 
-use crate::{query, rw};
+use crate::{query, repo, rw};
 
 #[derive(Error, Debug)]
 pub enum ServerError {
@@ -53,12 +53,18 @@ pub enum ServerError {
     #[error("unimplemented")]
     Unimplemented,
 
-    #[error("bad ticket, unable to convert ticket to string (maybe not utf8?)")]
+    #[error("bad ticket :: {0}")]
     BadTicket(String),
 
     #[error("bad key")]
     BadKey,
 
+    #[error("re-opening sequences is disabled on this server")]
+    SequenceReopenDisabled,
+
+    #[error("data does not match the schema declared by the topic :: {0}")]
+    DeclaredSchemaMismatch(String),
+
     #[error("io error :: {0}")]
     IOError(#[from] std::io::Error),
 
@@ -100,8 +106,42 @@ impl From<ServerError> for tonic::Status {
             ServerError::MultiplePathUnsupported => Status::invalid_argument(value.to_string()),
             ServerError::MissingDescriptior => Status::invalid_argument(value.to_string()),
             ServerError::BadTicket(_) => Status::invalid_argument(value.to_string()),
+            ServerError::SequenceReopenDisabled => Status::permission_denied(value.to_string()),
+            ServerError::FacadeError(repo::FacadeError::InvalidName { .. }) => {
+                Status::invalid_argument(value.to_string())
+            }
+            ServerError::DeclaredSchemaMismatch(_) => {
+                Status::failed_precondition(value.to_string())
+            }
+            ServerError::ActionError(
+                crate::marshal::ActionError::UnsupportedResponseFormat(_)
+                | crate::marshal::ActionError::InvalidField { .. },
+            ) => Status::invalid_argument(value.to_string()),
+            // Clients are expected to back off and retry when the connection pool is exhausted
+            ServerError::RepositoryError(repo::Error::PoolTimeout)
+            | ServerError::FacadeError(repo::FacadeError::RepositoryError(
+                repo::Error::PoolTimeout,
+            )) => Status::unavailable(value.to_string()),
 
             _ => Status::internal(value.to_string()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pool_timeout_is_retryable() {
+        let err: repo::Error = sqlx::Error::PoolTimedOut.into();
+        assert!(matches!(err, repo::Error::PoolTimeout));
+
+        let status = tonic::Status::from(ServerError::from(repo::FacadeError::from(err)));
+        assert_eq!(status.code(), tonic::Code::Unavailable);
+
+        let err: repo::Error = sqlx::Error::RowNotFound.into();
+        let status = tonic::Status::from(ServerError::from(err));
+        assert_eq!(status.code(), tonic::Code::Internal);
+    }
+}
@@ -65,8 +65,14 @@ struct MosaicoFlightService {
 
 impl MosaicoFlightService {
     pub fn try_new(store: store::StoreRef, repo: repo::Repository) -> Result<Self, String> {
-        let ts_engine =
-            Arc::new(query::TimeseriesGw::try_new(store.clone()).map_err(|e| e.to_string())?);
+        let read_cache = query::ReadCache::new(params::configurables().read_cache_budget_in_bytes);
+        let schema_cache = query::SchemaCache::new(params::configurables().schema_cache_size);
+        let ts_engine = Arc::new(
+            query::TimeseriesGw::try_new(store.clone())
+                .map_err(|e| e.to_string())?
+                .with_read_cache(read_cache)
+                .with_schema_cache(schema_cache),
+        );
 
         Ok(MosaicoFlightService {
             store,
@@ -131,11 +137,20 @@ impl FlightService for MosaicoFlightService {
 
     async fn get_schema(
         &self,
-        _request: Request<FlightDescriptor>,
+        request: Request<FlightDescriptor>,
     ) -> Result<Response<SchemaResult>, Status> {
-        Err(Status::unimplemented(
-            "get_schema is currently unimplemented",
-        ))
+        let desc = request.into_inner();
+
+        let schema = endpoints::get_schema(
+            self.store.clone(),
+            self.repo.clone(),
+            self.ts_engine.clone(),
+            desc,
+        )
+        .await
+        .inspect_err(log_server_error)?;
+
+        Ok(Response::new(schema))
     }
 
     async fn do_get(
@@ -168,17 +183,34 @@ impl FlightService for MosaicoFlightService {
         let stream = request.into_inner();
         let mut decoder = FlightDataDecoder::new(stream.map_err(Into::into));
 
-        endpoints::do_put(self.store.clone(), self.repo.clone(), &mut decoder)
-            .await
-            .inspect_err(log_server_error)?;
+        let metadata = endpoints::do_put(
+            self.store.clone(),
+            self.repo.clone(),
+            self.ts_engine.clone(),
+            &mut decoder,
+        )
+        .await
+        .inspect_err(log_server_error)?;
 
-        Ok(Response::new(Box::pin(futures::stream::empty())))
+        // Metadata (e.g. the report of a dry run) is sent back in a single result
+        let results = metadata.map(|app_metadata| Ok(PutResult { app_metadata }));
+        Ok(Response::new(Box::pin(futures::stream::iter(results))))
     }
 
     async fn do_action(
         &self,
         request: Request<FlightAction>,
     ) -> Result<Response<Self::DoActionStream>, Status> {
+        let format = match request.metadata().get(marshal::ResponseFormat::HEADER) {
+            Some(value) => value
+                .to_str()
+                .map_err(|e| Status::invalid_argument(e.to_string()))?
+                .parse()
+                .map_err(ServerError::from)
+                .inspect_err(log_server_error)?,
+            None => marshal::ResponseFormat::default(),
+        };
+
         let action = request.into_inner();
         let action = marshal::ActionRequest::try_new(action.r#type.as_str(), &action.body)
             .map_err(ServerError::from)
@@ -193,14 +225,40 @@ impl FlightService for MosaicoFlightService {
         .await
         .inspect_err(log_server_error)?;
 
-        let bytes = response
-            .bytes()
+        // Streamed responses are sent as they are, in several results
+        if let marshal::ActionResponse::TopicExportCsv(csv) = response {
+            let stream = csv
+                .0
+                .map_ok(arrow_flight::Result::new)
+                .map_err(ServerError::from)
+                .inspect_err(log_server_error)
+                .map_err(Status::from);
+            return Ok(Response::new(Box::pin(stream)));
+        }
+
+        // Large responses are split in several results, to stay below the message size limits
+        let parts = response
+            .encode_parts(format, params::configurables().target_message_size_in_bytes)
             .map_err(ServerError::from)
             .inspect_err(log_server_error)?;
-
-        // Create the stream from the flight result
-        let stream = futures::stream::iter(vec![Ok(arrow_flight::Result::new(bytes))]);
-        Ok(Response::new(Box::pin(stream)))
+        let parts_count = parts.len();
+
+        // Create the stream from the flight results
+        let stream = futures::stream::iter(
+            parts
+                .into_iter()
+                .map(|part| Ok(arrow_flight::Result::new(part))),
+        );
+        let mut response = Response::new(Box::pin(stream) as Self::DoActionStream);
+        response.metadata_mut().insert(
+            marshal::ResponseFormat::HEADER,
+            tonic::metadata::MetadataValue::from_static(format.as_str()),
+        );
+        response.metadata_mut().insert(
+            marshal::ActionResponse::PARTS_HEADER,
+            tonic::metadata::MetadataValue::from(parts_count),
+        );
+        Ok(response)
     }
 
     async fn list_actions(
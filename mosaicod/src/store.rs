@@ -1,13 +1,17 @@
 //! This module provides the [`Store`], the application's core client for interacting
-//! with S3-compatible object storage services providing
+//! with S3-compatible and Azure Blob object storage services providing
 //! essential CRUD (Create, Read, Update, Delete) methods for byte-level data access.
 
 use futures::stream::TryStreamExt;
 use std::sync::Arc;
+use std::time::Duration;
 
 use datafusion::execution::object_store::{DefaultObjectStoreRegistry, ObjectStoreRegistry};
-use log::trace;
-use object_store::{ObjectStore, PutPayload, aws::AmazonS3Builder, local::LocalFileSystem};
+use log::{trace, warn};
+use object_store::{
+    ObjectStore, PutPayload, aws::AmazonS3Builder, azure::MicrosoftAzureBuilder,
+    local::LocalFileSystem,
+};
 use thiserror::Error;
 use url::Url;
 
@@ -29,6 +33,17 @@ pub struct S3Config {
     pub secret_key: params::Hidden,
 }
 
+#[derive(Debug, Clone)]
+pub struct AzureConfig {
+    /// Container name.
+    pub container: String,
+    /// Storage account name.
+    pub account: String,
+    pub access_key: params::Hidden,
+    /// Custom endpoint (e.g. an Azurite emulator), if missing the Azure public cloud is used
+    pub endpoint: Option<String>,
+}
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("storage backend error: {0}")]
@@ -37,12 +52,170 @@ pub enum Error {
     BadUrl(#[from] url::ParseError),
     #[error("io error :: {0}")]
     IoError(#[from] std::io::Error),
+    #[error("{source} (gave up after {attempts} attempts)")]
+    RetriesExhausted { attempts: u32, source: Box<Error> },
+}
+
+/// Returns `true` if the object store error may succeed if the operation is retried
+/// (e.g. throttling, service unavailable, connection errors).
+///
+/// Errors reporting a condition of the request or of the object (missing objects,
+/// authentication, bad paths, ...) are never transient.
+pub fn is_transient_backend_error(err: &object_store::Error) -> bool {
+    match err {
+        object_store::Error::JoinError { .. } => true,
+        // Http errors (even after the client retries) are reported as generic errors, they
+        // are classified by their cause
+        object_store::Error::Generic { source, .. } => is_transient_cause(source.as_ref()),
+        _ => false,
+    }
+}
+
+/// Walks the causes of a generic object store error, returning `true` on the first one
+/// reporting a transient condition: a `408`, `429` or `5xx` HTTP status, a connection error or
+/// timeout, or a transient io error (see [`is_transient_io_error`]).
+///
+/// Errors without any of these causes are not transient.
+fn is_transient_cause(err: &(dyn std::error::Error + 'static)) -> bool {
+    use object_store::client::{HttpError, HttpErrorKind, retry::RetryError};
+
+    let mut cause = Some(err);
+    while let Some(err) = cause {
+        if let Some(status) = err.downcast_ref::<RetryError>().and_then(|e| e.status()) {
+            let status = status.as_u16();
+            return status == 408 || status == 429 || (500..600).contains(&status);
+        }
+        if let Some(e) = err.downcast_ref::<HttpError>() {
+            return matches!(
+                e.kind(),
+                HttpErrorKind::Connect | HttpErrorKind::Timeout | HttpErrorKind::Interrupted
+            );
+        }
+        if let Some(e) = err.downcast_ref::<std::io::Error>() {
+            return is_transient_io_error(e);
+        }
+        cause = err.source();
+    }
+
+    false
+}
+
+/// Returns `true` for io errors caused by a temporary network or system condition.
+pub fn is_transient_io_error(err: &std::io::Error) -> bool {
+    use std::io::ErrorKind;
+    matches!(
+        err.kind(),
+        ErrorKind::TimedOut
+            | ErrorKind::Interrupted
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::ConnectionRefused
+            | ErrorKind::BrokenPipe
+            | ErrorKind::UnexpectedEof
+    )
+}
+
+/// Errors of operations that can be retried with [`retry`].
+pub trait Retryable: Sized {
+    /// Returns `true` if the operation failing with this error may succeed if retried
+    fn is_transient(&self) -> bool;
+
+    /// Wraps the error returned by the last attempt, reporting the number of attempts
+    fn exhausted(self, attempts: u32) -> Self;
+}
+
+impl Retryable for Error {
+    fn is_transient(&self) -> bool {
+        match self {
+            Self::BackendError(e) => is_transient_backend_error(e),
+            Self::IoError(e) => is_transient_io_error(e),
+            Self::BadUrl(_) | Self::RetriesExhausted { .. } => false,
+        }
+    }
+
+    fn exhausted(self, attempts: u32) -> Self {
+        Self::RetriesExhausted {
+            attempts,
+            source: Box::new(self),
+        }
+    }
+}
+
+/// Policy used to retry operations failing with transient errors.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, the first one included
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled at each following retry
+    pub base_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Policy performing a single attempt
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            base_delay: Duration::ZERO,
+        }
+    }
+
+    /// Builds the policy from the configurable parameters
+    pub fn from_configurables() -> Self {
+        Self {
+            max_attempts: params::configurables().store_retry_max_attempts.max(1),
+            base_delay: Duration::from_millis(params::configurables().store_retry_base_delay_in_ms),
+        }
+    }
+
+    /// Returns the delay before the retry following the given (1-based) attempt.
+    ///
+    /// The exponential delay is randomized in `[delay / 2, delay]`, so that concurrent
+    /// operations failing together do not retry all at the same time.
+    fn delay(&self, attempt: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(1 << attempt.saturating_sub(1).min(16));
+        let half = delay.as_micros() as u64 / 2;
+        Duration::from_micros(half + rand::random_range(0..=half))
+    }
+}
+
+/// Runs `op` until it succeeds, retrying transient errors with exponential backoff
+/// following the given `policy`.
+///
+/// Non-transient errors fail fast. Each retry is logged, and if the operation has been
+/// attempted more than once the returned error reports the number of attempts.
+pub async fn retry<T, E, F, Fut>(policy: RetryPolicy, what: &str, mut op: F) -> Result<T, E>
+where
+    E: Retryable + std::fmt::Display,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut attempt = 1;
+    loop {
+        match op().await {
+            Ok(v) => return Ok(v),
+            Err(e) if e.is_transient() && attempt < policy.max_attempts => {
+                let delay = policy.delay(attempt);
+                warn!(
+                    "{what} failed (attempt {attempt}/{}), retrying in {}ms: {e}",
+                    policy.max_attempts,
+                    delay.as_millis()
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) if attempt > 1 => return Err(e.exhausted(attempt)),
+            Err(e) => return Err(e),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum StoreTarget {
     Filesystem(String),
     S3Compatible(String),
+    Azure(String),
 }
 
 /// Implements the object storage client for the application.
@@ -55,6 +228,7 @@ pub struct Store {
     target: StoreTarget,
     driver: Arc<dyn ObjectStore>,
     registry: Arc<dyn ObjectStoreRegistry>,
+    retry_policy: RetryPolicy,
 }
 
 pub type StoreRef = Arc<Store>;
@@ -79,6 +253,7 @@ impl Store {
             target: StoreTarget::Filesystem(target),
             driver: storage.clone(),
             registry,
+            retry_policy: RetryPolicy::none(),
         })
     }
 
@@ -111,9 +286,59 @@ impl Store {
             target: StoreTarget::S3Compatible(config.bucket),
             driver: storage.clone(),
             registry: registry.clone(),
+            retry_policy: RetryPolicy::none(),
         })
     }
 
+    pub fn try_from_azure_store(config: AzureConfig) -> Result<Self, Error> {
+        trace!(
+            "creating object driver for an azure blob store, account: {}",
+            config.account
+        );
+
+        let bucket_url = Url::parse(&format!("az://{}", config.container))?;
+
+        let mut builder = MicrosoftAzureBuilder::new()
+            .with_account(config.account)
+            .with_container_name(&config.container)
+            .with_access_key(config.access_key.take());
+        if let Some(endpoint) = config.endpoint {
+            builder = builder.with_endpoint(endpoint).with_allow_http(true);
+        }
+        let storage = Arc::new(builder.build()?);
+
+        // Create object store registry (for datafusion support)
+        let registry = Arc::new(DefaultObjectStoreRegistry::default());
+        registry.register_store(&bucket_url, storage.clone());
+
+        Ok(Self {
+            url_schema: bucket_url,
+            target: StoreTarget::Azure(config.container),
+            driver: storage,
+            registry,
+            retry_policy: RetryPolicy::none(),
+        })
+    }
+
+    /// Returns the url of the object located at `path`, to be used by datafusion.
+    ///
+    /// The path is always resolved under the bucket (or container) root.
+    pub fn url(&self, path: impl AsRef<std::path::Path>) -> Result<Url, Error> {
+        let path = path.as_ref().to_string_lossy();
+        Ok(self.url_schema.join(path.trim_start_matches('/'))?)
+    }
+
+    /// Sets the policy used to retry reads failing with transient errors, by default
+    /// operations are attempted once.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+
     pub fn registry(&self) -> Arc<dyn ObjectStoreRegistry> {
         self.registry.clone()
     }
@@ -124,13 +349,36 @@ impl Store {
 
     pub async fn read_bytes(&self, path: impl AsRef<std::path::Path>) -> Result<Vec<u8>, Error> {
         trace!("reading bytes from {}", path.as_ref().display());
-        Ok(self
-            .driver
-            .get(&to_object_path(&path))
-            .await?
-            .bytes()
-            .await?
-            .into())
+        let location = to_object_path(&path);
+        retry(self.retry_policy, "store read", || async {
+            Ok::<Vec<u8>, Error>(self.driver.get(&location).await?.bytes().await?.into())
+        })
+        .await
+    }
+
+    /// Reads the bytes of the object at `path` in `range`
+    pub async fn read_range(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        range: std::ops::Range<usize>,
+    ) -> Result<Vec<u8>, Error> {
+        trace!(
+            "reading bytes {}..{} from {}",
+            range.start,
+            range.end,
+            path.as_ref().display()
+        );
+        let location = to_object_path(&path);
+        let range = range.start as u64..range.end as u64;
+        retry(self.retry_policy, "store read", || async {
+            Ok::<Vec<u8>, Error>(
+                self.driver
+                    .get_range(&location, range.clone())
+                    .await?
+                    .into(),
+            )
+        })
+        .await
     }
 
     pub async fn write_bytes(
@@ -181,8 +429,17 @@ impl Store {
         Ok(locations)
     }
 
+    /// Returns the location of `path` in the same form reported by [`Store::list`]
+    pub fn location(path: impl AsRef<std::path::Path>) -> String {
+        to_object_path(path).to_string()
+    }
+
     pub async fn size(&self, path: impl AsRef<std::path::Path>) -> Result<usize, Error> {
-        let head = self.driver.head(&to_object_path(&path)).await?;
+        let location = to_object_path(&path);
+        let head = retry(self.retry_policy, "store head", || async {
+            Ok::<_, Error>(self.driver.head(&location).await?)
+        })
+        .await?;
 
         Ok(head.size as usize)
     }
@@ -191,6 +448,34 @@ impl Store {
         Ok(self.driver.delete(&to_object_path(&path)).await?)
     }
 
+    /// Copies the object located at `from` to `to`, overwriting any existing object
+    pub async fn copy(
+        &self,
+        from: impl AsRef<std::path::Path>,
+        to: impl AsRef<std::path::Path>,
+    ) -> Result<(), Error> {
+        trace!(
+            "copying {} to {}",
+            from.as_ref().display(),
+            to.as_ref().display()
+        );
+        Ok(self
+            .driver
+            .copy(&to_object_path(&from), &to_object_path(&to))
+            .await?)
+    }
+
+    /// Checks that the store is reachable by fetching at most one entry from the
+    /// configured bucket (or filesystem root).
+    ///
+    /// The call is cheap, it does not read any object data.
+    pub async fn health_check(&self) -> Result<(), Error> {
+        trace!("checking store health");
+        let mut list_stream = self.driver.list(None);
+        list_stream.try_next().await?;
+        Ok(())
+    }
+
     /// Deletes recursively all objects under a given path
     pub async fn delete_recursive(&self, path: impl AsRef<std::path::Path>) -> Result<(), Error> {
         let mut list_stream = self.driver.list(Some(&to_object_path(&path)));
@@ -238,7 +523,8 @@ pub mod testing {
 
     pub struct Store {
         inner: super::StoreRef,
-        root: std::path::PathBuf,
+        /// Local directory removed on drop, if any
+        root: Option<std::path::PathBuf>,
     }
 
     impl Store {
@@ -255,7 +541,7 @@ pub mod testing {
             }
 
             Ok(Self {
-                root: root.as_ref().to_path_buf(),
+                root: Some(root.as_ref().to_path_buf()),
                 inner: Arc::new(super::Store::try_from_filesystem(root)?),
             })
         }
@@ -268,11 +554,55 @@ pub mod testing {
             let random_location = format!("/tmp/{}", crate::utils::random::random_string(10));
             Self::new(random_location)
         }
+
+        /// Creates a new [`Store`] backed by an Azurite emulator, using its well-known
+        /// development account.
+        ///
+        /// The emulator endpoint and the container (which must already exist) are read from
+        /// `MOSAICO_TEST_AZURITE_ENDPOINT` and `MOSAICO_TEST_AZURITE_CONTAINER`. All the
+        /// objects are written under a random prefix, which is not removed on drop.
+        #[cfg(feature = "azure-tests")]
+        pub fn new_random_on_azurite() -> Result<Self, Box<dyn std::error::Error>> {
+            use object_store::prefix::PrefixStore;
+
+            let endpoint = std::env::var("MOSAICO_TEST_AZURITE_ENDPOINT")
+                .unwrap_or_else(|_| "http://127.0.0.1:10000/devstoreaccount1".to_owned());
+            let container = std::env::var("MOSAICO_TEST_AZURITE_CONTAINER")
+                .unwrap_or_else(|_| "mosaico".to_owned());
+
+            let storage = MicrosoftAzureBuilder::new()
+                .with_use_emulator(true)
+                .with_endpoint(endpoint)
+                .with_allow_http(true)
+                .with_container_name(&container)
+                .build()?;
+            let storage = Arc::new(PrefixStore::new(
+                storage,
+                crate::utils::random::random_string(10),
+            ));
+
+            let bucket_url = Url::parse(&format!("az://{container}"))?;
+            let registry = Arc::new(DefaultObjectStoreRegistry::default());
+            registry.register_store(&bucket_url, storage.clone());
+
+            Ok(Self {
+                root: None,
+                inner: Arc::new(super::Store {
+                    url_schema: bucket_url,
+                    target: StoreTarget::Azure(container),
+                    driver: storage,
+                    registry,
+                    retry_policy: RetryPolicy::none(),
+                }),
+            })
+        }
     }
 
     impl Drop for Store {
         fn drop(&mut self) {
-            std::fs::remove_dir_all(&self.root).unwrap();
+            if let Some(root) = &self.root {
+                std::fs::remove_dir_all(root).unwrap();
+            }
         }
     }
 
@@ -313,4 +643,113 @@ mod test {
 
         assert_eq!(buffer, read_buffer);
     }
+
+    #[test]
+    fn store_url() {
+        let mut store =
+            Store::try_from_filesystem(format!("/tmp/{}", crate::utils::random::random_string(10)))
+                .unwrap();
+
+        for (schema, expected) in [
+            ("s3://bucket", "s3://bucket/sequence/topic/data.parquet"),
+            (
+                "az://container",
+                "az://container/sequence/topic/data.parquet",
+            ),
+        ] {
+            store.url_schema = Url::parse(schema).unwrap();
+            assert_eq!(
+                store.url("sequence/topic/data.parquet").unwrap().as_str(),
+                expected
+            );
+            // absolute paths are resolved under the container too
+            assert_eq!(
+                store.url("/sequence/topic/data.parquet").unwrap().as_str(),
+                expected
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn retry_transient_errors() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+        };
+        let generic = |kind: std::io::ErrorKind| {
+            Error::BackendError(object_store::Error::Generic {
+                store: "test",
+                source: Box::new(std::io::Error::new(kind, "test")),
+            })
+        };
+        let throttled = || generic(std::io::ErrorKind::ConnectionReset);
+
+        // fails twice, then succeeds
+        let calls = AtomicU32::new(0);
+        let res = retry(policy, "test", || async {
+            if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                Err(throttled())
+            } else {
+                Ok(())
+            }
+        })
+        .await;
+        assert!(res.is_ok());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // never succeeds, the error reports the attempts
+        let res: Result<(), Error> = retry(policy, "test", || async { Err(throttled()) }).await;
+        let err = res.unwrap_err();
+        assert!(matches!(err, Error::RetriesExhausted { attempts: 3, .. }));
+        assert!(err.to_string().contains("3 attempts"));
+
+        // non-transient errors fail fast
+        let calls = AtomicU32::new(0);
+        let res: Result<(), Error> = retry(policy, "test", || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(Error::BackendError(object_store::Error::NotFound {
+                path: "missing".to_owned(),
+                source: "not found".into(),
+            }))
+        })
+        .await;
+        assert!(matches!(res, Err(Error::BackendError(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // generic errors are transient only if their cause is
+        let calls = AtomicU32::new(0);
+        let res: Result<(), Error> = retry(policy, "test", || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(generic(std::io::ErrorKind::PermissionDenied))
+        })
+        .await;
+        assert!(matches!(res, Err(Error::BackendError(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let unknown = object_store::Error::Generic {
+            store: "test",
+            source: "invalid response".into(),
+        };
+        assert!(!is_transient_backend_error(&unknown));
+    }
+
+    /// Checks that the azure store works, writing and reading data on an Azurite emulator
+    #[cfg(feature = "azure-tests")]
+    #[tokio::test]
+    async fn test_azure_store() {
+        let store = testing::Store::new_random_on_azurite().unwrap();
+
+        store.health_check().await.unwrap();
+
+        let buffer = b"Some example text";
+        store
+            .write_to_path("write_text", &buffer[..])
+            .await
+            .unwrap();
+
+        assert_eq!(store.read_bytes("write_text").await.unwrap(), buffer);
+        assert_eq!(store.list("", None).await.unwrap(), vec!["write_text"]);
+    }
 }
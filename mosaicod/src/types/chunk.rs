@@ -1,6 +1,8 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 
+use crate::params;
+
 /// Placeholder indicating uninitialized minimum text statistic.
 /// Empty string compares less than any non-empty string.
 const TEXT_MIN_PLACEHOLDER: &str = "";
@@ -27,6 +29,7 @@ impl ColumnsStats {
 pub enum Stats {
     Numeric(NumericStats),
     Text(TextStats),
+    Boolean(BooleanStats),
     Unsupported,
 }
 
@@ -45,7 +48,15 @@ pub struct NumericStats {
     pub max: f64,
 
     pub has_null: bool,
+    /// Number of null values, tracked alongside `has_null` to measure the field completeness
+    pub null_count: u64,
     pub has_nan: bool,
+    /// Infinite values (`±inf`) are excluded from `min` and `max`, like NaN values,
+    /// so that a single infinite reading does not defeat range based pruning.
+    pub has_inf: bool,
+
+    /// Approximate distribution of the finite values, used to estimate quantiles
+    pub sketch: QuantileSketch,
 }
 
 impl Default for NumericStats {
@@ -61,17 +72,34 @@ impl NumericStats {
             max: NUMERIC_MAX_PLACEHOLDER,
 
             has_null: false,
+            null_count: 0,
             has_nan: false,
+            has_inf: false,
+
+            sketch: QuantileSketch::new(params::configurables().numeric_stats_sketch_size),
         }
     }
 
+    /// Approximate median of the finite values, [`None`] if no finite value was evaluated
+    pub fn p50(&self) -> Option<f64> {
+        self.sketch.quantile(0.5)
+    }
+
+    /// Approximate 95th percentile of the finite values, [`None`] if no finite value was evaluated
+    pub fn p95(&self) -> Option<f64> {
+        self.sketch.quantile(0.95)
+    }
+
     /// Evaluates a new numeric value and updates the column statistics.
-    /// If the provided value is [`None`], it is condered a null value.
+    /// If the provided value is [`None`], it is considered a null value.
+    /// Non-finite values are flagged and excluded from min/max.
     pub fn eval(&mut self, val: &Option<f64>) {
         if let Some(val) = val {
             let val = *val;
             if val.is_nan() {
                 self.has_nan = true;
+            } else if val.is_infinite() {
+                self.has_inf = true;
             } else {
                 if self.min > val {
                     self.min = val;
@@ -79,15 +107,28 @@ impl NumericStats {
                 if self.max < val {
                     self.max = val;
                 }
+                self.sketch.insert(val);
             }
         } else {
             self.has_null = true;
+            self.null_count += 1;
         }
     }
 
     /// Merges pre-computed statistics from an Arrow array.
     /// This is more efficient than calling `eval()` for each element.
-    pub fn merge(&mut self, min: Option<f64>, max: Option<f64>, has_null: bool, has_nan: bool) {
+    ///
+    /// The provided `min` and `max` are expected to be computed on finite values only,
+    /// the same values need to be provided as `finite` to keep the quantile sketch updated.
+    pub fn merge(
+        &mut self,
+        min: Option<f64>,
+        max: Option<f64>,
+        null_count: u64,
+        has_nan: bool,
+        has_inf: bool,
+        finite: impl IntoIterator<Item = f64>,
+    ) {
         if let Some(min_val) = min
             && self.min > min_val
         {
@@ -98,8 +139,68 @@ impl NumericStats {
         {
             self.max = max_val;
         }
-        self.has_null |= has_null;
+        self.has_null |= null_count > 0;
+        self.null_count += null_count;
         self.has_nan |= has_nan;
+        self.has_inf |= has_inf;
+        finite.into_iter().for_each(|v| self.sketch.insert(v));
+    }
+}
+
+/// Bounded size sample of a stream of values, used to estimate quantiles.
+///
+/// Values are sampled with a fixed stride; when the sample grows to twice the
+/// configured size, every other sample is dropped and the stride is doubled.
+/// The retained samples are therefore evenly spaced in the stream, so the quantile
+/// estimate gets coarser as more values are evaluated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantileSketch {
+    size: usize,
+    samples: Vec<f64>,
+    stride: u64,
+    seen: u64,
+}
+
+impl QuantileSketch {
+    pub fn new(size: usize) -> Self {
+        Self {
+            size: size.max(1),
+            samples: Vec::new(),
+            stride: 1,
+            seen: 0,
+        }
+    }
+
+    pub fn insert(&mut self, val: f64) {
+        self.seen += 1;
+        if self.seen % self.stride != 0 {
+            return;
+        }
+
+        self.samples.push(val);
+
+        if self.samples.len() >= 2 * self.size {
+            // Keeping the odd positions retains exactly one value every `2 * stride`
+            let mut idx = 0;
+            self.samples.retain(|_| {
+                idx += 1;
+                idx % 2 == 0
+            });
+            self.stride *= 2;
+        }
+    }
+
+    /// Returns the estimated `q` quantile (with `q` in `[0, 1]`) of the inserted values
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if self.samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted = self.samples.clone();
+        sorted.sort_by(f64::total_cmp);
+
+        let idx = (q.clamp(0.0, 1.0) * (sorted.len() - 1) as f64).round() as usize;
+        Some(sorted[idx])
     }
 }
 
@@ -109,6 +210,8 @@ pub struct TextStats {
     pub max: Cow<'static, str>,
 
     pub has_null: bool,
+    /// Number of null values, tracked alongside `has_null` to measure the field completeness
+    pub null_count: u64,
 }
 
 impl Default for TextStats {
@@ -124,11 +227,12 @@ impl TextStats {
             max: Cow::Borrowed(TEXT_MAX_PLACEHOLDER),
 
             has_null: false,
+            null_count: 0,
         }
     }
 
     /// Evaluates a new literal value and updates the column statistics.
-    /// If the provided value is [`None`], it is condered a null value.
+    /// If the provided value is [`None`], it is considered a null value.
     pub fn eval(&mut self, val: &Option<&str>) {
         if let Some(val) = val {
             let val = *val;
@@ -140,6 +244,7 @@ impl TextStats {
             }
         } else {
             self.has_null = true;
+            self.null_count += 1;
         }
     }
 
@@ -150,7 +255,7 @@ impl TextStats {
 
     /// Merges pre-computed statistics from an Arrow array.
     /// This is more efficient than calling `eval()` for each element.
-    pub fn merge(&mut self, min: Option<&str>, max: Option<&str>, has_null: bool) {
+    pub fn merge(&mut self, min: Option<&str>, max: Option<&str>, null_count: u64) {
         if let Some(min_val) = min
             && (self.min.as_ref() == TEXT_MIN_PLACEHOLDER || *self.min > *min_val)
         {
@@ -161,6 +266,117 @@ impl TextStats {
         {
             self.max = Cow::Owned(max_val.to_owned());
         }
+        self.has_null |= null_count > 0;
+        self.null_count += null_count;
+    }
+}
+
+/// Boolean values have no meaningful min/max, so the stats only track which
+/// values appear in the column.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BooleanStats {
+    pub has_true: bool,
+    pub has_false: bool,
+    pub has_null: bool,
+}
+
+impl BooleanStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Evaluates a new boolean value and updates the column statistics.
+    /// If the provided value is [`None`], it is considered a null value.
+    pub fn eval(&mut self, val: &Option<bool>) {
+        match val {
+            Some(true) => self.has_true = true,
+            Some(false) => self.has_false = true,
+            None => self.has_null = true,
+        }
+    }
+
+    /// Merges pre-computed statistics from an Arrow array.
+    pub fn merge(&mut self, has_true: bool, has_false: bool, has_null: bool) {
+        self.has_true |= has_true;
+        self.has_false |= has_false;
         self.has_null |= has_null;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numeric_stats_non_finite() {
+        params::load_configurables_from_env();
+
+        let mut stats = NumericStats::new();
+
+        for v in [
+            Some(1.0),
+            Some(f64::INFINITY),
+            Some(-5.0),
+            Some(f64::NEG_INFINITY),
+            Some(f64::NAN),
+            None,
+        ] {
+            stats.eval(&v);
+        }
+
+        assert_eq!(stats.min, -5.0);
+        assert_eq!(stats.max, 1.0);
+        assert!(stats.has_inf);
+        assert!(stats.has_nan);
+        assert!(stats.has_null);
+        assert_eq!(stats.null_count, 1);
+    }
+
+    #[test]
+    fn text_stats_null_count() {
+        let mut stats = TextStats::new();
+        stats.eval(&Some("a"));
+        stats.eval(&None);
+        stats.merge(Some("b"), Some("c"), 2);
+
+        assert!(stats.has_null);
+        assert_eq!(stats.null_count, 3);
+        assert_eq!(stats.into_owned(), ("a".to_owned(), "c".to_owned(), true));
+    }
+
+    #[test]
+    fn numeric_stats_only_infinite() {
+        params::load_configurables_from_env();
+
+        let mut stats = NumericStats::new();
+        stats.eval(&Some(f64::INFINITY));
+
+        // No finite value has been evaluated, so min/max are left untouched
+        assert_eq!(stats.min, NUMERIC_MIN_PLACEHOLDER);
+        assert_eq!(stats.max, NUMERIC_MAX_PLACEHOLDER);
+        assert!(stats.has_inf);
+        assert!(!stats.has_nan);
+        assert_eq!(stats.p95(), None);
+    }
+
+    #[test]
+    fn numeric_stats_quantiles() {
+        params::load_configurables_from_env();
+
+        let mut stats = NumericStats::new();
+
+        // Non-finite values are not part of the distribution
+        stats.eval(&Some(f64::INFINITY));
+        for v in 1..=10_000 {
+            stats.eval(&Some(v as f64));
+        }
+
+        let p50 = stats.p50().unwrap();
+        let p95 = stats.p95().unwrap();
+        assert!((p50 - 5_000.0).abs() < 200.0, "p50: {p50}");
+        assert!((p95 - 9_500.0).abs() < 200.0, "p95: {p95}");
+
+        // The sketch never holds more than twice the configured size
+        assert!(stats.sketch.samples.len() < 2 * params::configurables().numeric_stats_sketch_size);
+    }
+}
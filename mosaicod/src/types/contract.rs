@@ -0,0 +1,82 @@
+/// Type of a field constrained by an [`OntologyContract`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContractFieldType {
+    Boolean,
+    Integer,
+    Float,
+    Text,
+}
+
+impl ContractFieldType {
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, Self::Integer | Self::Float)
+    }
+}
+
+impl std::fmt::Display for ContractFieldType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Boolean => "boolean",
+            Self::Integer => "integer",
+            Self::Float => "float",
+            Self::Text => "text",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Constraints on a single field of an [`OntologyContract`]
+#[derive(Debug, Clone)]
+pub struct ContractField {
+    /// Full path of the field (e.g. `pose.position.x`)
+    pub name: String,
+    pub field_type: ContractFieldType,
+    /// If `false` the field is required and can't hold null values
+    pub nullable: bool,
+    /// Inclusive range of the allowed values, only for numeric fields
+    pub range: Option<(f64, f64)>,
+}
+
+impl ContractField {
+    /// Returns a violation if `value` is outside the range allowed for the field
+    pub fn check_range(&self, value: f64) -> Option<ContractViolation> {
+        let (min, max) = self.range?;
+        (value < min || value > max)
+            .then(|| ContractViolation::new(&self.name, format!("must be within [{min}, {max}]")))
+    }
+}
+
+/// Data contract associated with an ontology tag.
+///
+/// The contract constrains the user metadata of the topics tagged with the ontology tag,
+/// checked when a topic is created, and the data fields uploaded to such topics: types are
+/// checked on the schema of the uploads, nullability and ranges on each uploaded batch.
+#[derive(Debug, Clone)]
+pub struct OntologyContract {
+    pub ontology_tag: String,
+    pub metadata: Vec<ContractField>,
+    pub data: Vec<ContractField>,
+}
+
+/// A constraint of an [`OntologyContract`] not satisfied by a field
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContractViolation {
+    pub field: String,
+    pub constraint: String,
+}
+
+impl ContractViolation {
+    pub fn new(field: &str, constraint: impl Into<String>) -> Self {
+        Self {
+            field: field.to_owned(),
+            constraint: constraint.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ContractViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`{}` {}", self.field, self.constraint)
+    }
+}
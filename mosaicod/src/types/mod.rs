@@ -17,3 +17,6 @@ pub use layer::*;
 
 mod chunk;
 pub use chunk::*;
+
+mod contract;
+pub use contract::*;
@@ -1,10 +1,26 @@
+/// Kind of a notification, variants are ordered by increasing severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum NotifyType {
+    Info,
+    Warning,
     Error,
 }
 
+impl NotifyType {
+    /// All the notify types, by increasing severity
+    pub const ALL: [NotifyType; 3] = [Self::Info, Self::Warning, Self::Error];
+
+    /// Returns the notify types with a severity equal or greater than `self`
+    pub fn at_least(self) -> impl Iterator<Item = NotifyType> {
+        Self::ALL.into_iter().filter(move |t| *t >= self)
+    }
+}
+
 impl std::fmt::Display for NotifyType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            Self::Info => write!(f, "info"),
+            Self::Warning => write!(f, "warning"),
             Self::Error => write!(f, "error"),
         }
     }
@@ -15,6 +31,8 @@ impl std::str::FromStr for NotifyType {
 
     fn from_str(value: &str) -> Result<Self, Self::Err> {
         match value {
+            "info" => Ok(Self::Info),
+            "warning" => Ok(Self::Warning),
             "error" => Ok(Self::Error),
             _ => Err(std::io::Error::other(format!(
                 "unkwnown notify type `{}`",
@@ -68,22 +68,167 @@ impl<M> TopicMetadata<M> {
 pub struct TopicChunksStats {
     pub total_size_bytes: i64,
     pub total_row_count: i64,
+    pub total_chunks: i64,
+    /// Creation time of the most recent chunk, `None` if the topic has no chunks
+    pub last_chunk_timestamp: Option<super::Timestamp>,
+}
+
+/// A chunk holding NaN or null values for a field, as reported by the chunk statistics.
+#[derive(Debug, Clone)]
+pub struct ChunkQuality {
+    pub chunk_uuid: uuid::Uuid,
+    /// Timestamp bounds (in milliseconds) of the chunk data, `None` for chunks created before
+    /// bounds were tracked
+    pub timestamp_range: Option<(super::Timestamp, super::Timestamp)>,
+    pub has_nan: bool,
+    pub has_null: bool,
+}
+
+/// Statistics of a numeric field, aggregated across all the chunks of a topic.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumericFieldStats {
+    pub field: String,
+    /// Bounds of the finite values, `None` if the field holds no finite value
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub has_null: bool,
+    pub has_nan: bool,
+    pub has_inf: bool,
+    /// Number of null values, `None` if some chunk was created before null counts were tracked
+    pub null_count: Option<i64>,
+    /// Number of rows of the topic, the rows of the chunks not holding the field are counted
+    /// as null values
+    pub row_count: i64,
+}
+
+impl NumericFieldStats {
+    /// Fraction of non null values of the field (`1 - null_count / row_count`), [`None`] if
+    /// the null count is unknown or the field has no rows
+    pub fn completeness(&self) -> Option<f64> {
+        completeness(self.null_count, self.row_count)
+    }
+}
+
+/// Statistics of a literal field, of a single chunk or aggregated across all the chunks of
+/// a topic.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiteralFieldStats {
+    pub field: String,
+    /// Lexicographic bounds of the values, `None` if the field holds only null values
+    pub min: Option<String>,
+    pub max: Option<String>,
+    pub has_null: bool,
+    /// Number of null values, `None` if some chunk was created before null counts were tracked
+    pub null_count: Option<i64>,
+    /// Number of rows of the chunk, or of the whole topic when aggregated across the chunks of
+    /// a topic
+    pub row_count: i64,
+}
+
+impl LiteralFieldStats {
+    /// Fraction of non null values of the field (`1 - null_count / row_count`), [`None`] if
+    /// the null count is unknown or the field has no rows
+    pub fn completeness(&self) -> Option<f64> {
+        completeness(self.null_count, self.row_count)
+    }
+}
+
+fn completeness(null_count: Option<i64>, row_count: i64) -> Option<f64> {
+    let null_count = null_count?;
+    (row_count > 0).then(|| 1.0 - null_count as f64 / row_count as f64)
+}
+
+/// Per field statistics of a topic, computed from the chunk statistics (no data file is
+/// read). Fields are ordered by name.
+#[derive(Debug, Clone, Default)]
+pub struct TopicFieldsStats {
+    pub numeric: Vec<NumericFieldStats>,
+    pub literal: Vec<LiteralFieldStats>,
 }
 
 /// Configuration properties defining the data semantic and encoding for a topic.
 #[derive(Debug)]
 pub struct TopicProperties {
     pub serialization_format: rw::Format,
-    pub ontology_tag: String,
+    /// [`None`] for topics created without a tag, the tag is set by their first write
+    pub ontology_tag: Option<String>,
+    /// Unit of the values in the timestamp column
+    pub timestamp_unit: super::TimestampUnit,
+    /// If `true` writes with decreasing timestamps are rejected
+    pub monotonic_timestamps: bool,
+    /// Maximum number of rows of the row groups written for the topic, if [`None`] the server
+    /// default is used
+    pub max_row_group_size: Option<usize>,
+    /// Arrow schema the producer expects the topic to hold, checked when data is read
+    pub declared_schema: Option<DeclaredSchema>,
 }
 
 impl TopicProperties {
-    pub fn new(serialization_format: rw::Format, ontology_tag: String) -> Self {
+    pub fn new(serialization_format: rw::Format, ontology_tag: Option<String>) -> Self {
         Self {
             serialization_format,
             ontology_tag,
+            timestamp_unit: super::TimestampUnit::default(),
+            monotonic_timestamps: false,
+            max_row_group_size: None,
+            declared_schema: None,
         }
     }
+
+    pub fn with_timestamp_unit(mut self, timestamp_unit: super::TimestampUnit) -> Self {
+        self.timestamp_unit = timestamp_unit;
+        self
+    }
+
+    pub fn with_monotonic_timestamps(mut self, monotonic_timestamps: bool) -> Self {
+        self.monotonic_timestamps = monotonic_timestamps;
+        self
+    }
+
+    pub fn with_max_row_group_size(mut self, max_row_group_size: Option<usize>) -> Self {
+        self.max_row_group_size = max_row_group_size;
+        self
+    }
+
+    pub fn with_declared_schema(mut self, declared_schema: Option<DeclaredSchema>) -> Self {
+        self.declared_schema = declared_schema;
+        self
+    }
+
+    /// Maximum number of rows of the row groups written for the topic: the topic setting wins
+    /// over the server one, if none of them is set (or is zero) the serialization format
+    /// default is used ([`None`]).
+    pub fn row_group_size_limit(&self) -> Option<usize> {
+        self.max_row_group_size
+            .filter(|size| *size > 0)
+            .or_else(|| {
+                let configured = params::configurables().max_row_group_size;
+                (configured > 0).then_some(configured)
+            })
+    }
+}
+
+/// Field of a [`DeclaredSchema`]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DeclaredField {
+    /// Full path of the field (e.g. `pose.position.x`)
+    pub name: String,
+    /// Name of the type, as reported by the `topic_schema` action (e.g. `float64` or `list`)
+    pub data_type: String,
+    #[serde(default)]
+    pub nullable: bool,
+}
+
+/// Arrow schema declared by a topic at creation.
+///
+/// Only leaf fields are declared, nested fields are addressed by their full path.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DeclaredSchema {
+    pub fields: Vec<DeclaredField>,
+    /// If `true` reads of data not matching the schema fail, otherwise the mismatch is
+    /// reported as a warning along with the data
+    #[serde(default)]
+    pub strict: bool,
 }
 
 /// Represents system-level metadata and statistical information for a specific topic.
@@ -104,6 +249,18 @@ pub struct TopicSystemInfo {
     pub total_size_bytes: usize,
     /// Datetime of the topic creation
     pub created_datetime: super::DateTime,
+    /// Datetime of the creation of the most recent chunk, `None` if the topic has no chunks
+    pub last_chunk_datetime: Option<super::DateTime>,
+}
+
+/// Summary of a topic, reported when listing the topics of a sequence.
+#[derive(Debug)]
+pub struct TopicSummary {
+    pub locator: TopicResourceLocator,
+    pub serialization_format: Option<rw::Format>,
+    pub ontology_tag: Option<String>,
+    pub created_datetime: super::DateTime,
+    pub stats: TopicChunksStats,
 }
 
 #[derive(Debug, Clone)]
@@ -168,6 +325,36 @@ pub struct SequenceSystemInfo {
     pub created_datetime: super::DateTime,
 }
 
+/// Resources that would be removed by deleting a sequence.
+#[derive(Debug, Default)]
+pub struct SequenceDeleteSummary {
+    pub topics: Vec<TopicResourceLocator>,
+    /// Number of chunks across all the topics
+    pub total_chunks: i64,
+    /// Size in bytes of the chunks across all the topics
+    pub total_size_bytes: i64,
+}
+
+/// Outcome of the compaction of the chunks of a topic.
+#[derive(Debug, Default)]
+pub struct TopicCompaction {
+    /// Number of (small) chunks merged and removed
+    pub merged_chunks: usize,
+    /// Number of chunks created by merging them
+    pub created_chunks: usize,
+}
+
+/// Inconsistencies found between the chunks of a sequence and the objects in the store.
+#[derive(Debug, Default)]
+pub struct SequenceReconcileReport {
+    /// Data files referenced by a chunk but missing from the store
+    pub missing_data_files: Vec<String>,
+    /// Data files in the store not referenced by any chunk
+    pub orphan_data_files: Vec<String>,
+    /// `true` if the inconsistencies have been repaired
+    pub repaired: bool,
+}
+
 #[derive(Debug)]
 pub struct SequenceTopicGroup {
     pub sequence: SequenceResourceLocator,
@@ -216,6 +403,35 @@ impl SequenceTopicGroups {
 
         result
     }
+
+    /// Returns the number of topics across all the groups
+    pub fn topics_count(&self) -> usize {
+        self.0.iter().map(|grp| grp.topics.len()).sum()
+    }
+
+    /// Keeps at most `max_topics` topics, returns `true` if some topic has been discarded.
+    ///
+    /// Groups and topics are sorted by name before truncating, so that the retained topics
+    /// do not depend on the order in which the groups have been built. Groups left without
+    /// topics are removed.
+    pub fn truncate(&mut self, max_topics: usize) -> bool {
+        if self.topics_count() <= max_topics {
+            return false;
+        }
+
+        self.0
+            .sort_by(|a, b| a.sequence.name().cmp(b.sequence.name()));
+
+        let mut remaining = max_topics;
+        for grp in &mut self.0 {
+            grp.topics.sort_by(|a, b| a.name().cmp(b.name()));
+            grp.topics.truncate(remaining);
+            remaining -= grp.topics.len();
+        }
+        self.0.retain(|grp| !grp.topics.is_empty());
+
+        true
+    }
 }
 
 impl Default for SequenceTopicGroups {
@@ -278,10 +494,31 @@ fn sanitize_name(name: &str) -> String {
     }
 }
 
+/// A page of elements obtained from a paginated listing.
+#[derive(Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Total number of elements matching the listing, across all pages
+    pub total_count: usize,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn row_group_size_limit_ignores_zero() {
+        params::load_configurables_from_env();
+
+        let props =
+            |size| TopicProperties::new(rw::Format::Default, None).with_max_row_group_size(size);
+        assert_eq!(
+            props(Some(0)).row_group_size_limit(),
+            props(None).row_group_size_limit()
+        );
+        assert_eq!(props(Some(128)).row_group_size_limit(), Some(128));
+    }
+
     #[test]
     fn resource_name() {
         let target = "my/resource/name";
@@ -294,4 +531,33 @@ mod tests {
 
     #[test]
     fn merge_sequence_topic_groups() {}
+
+    #[test]
+    fn truncate_sequence_topic_groups() {
+        let group = |seq: &str, topics: &[&str]| {
+            SequenceTopicGroup::new(
+                SequenceResourceLocator::from(seq),
+                topics
+                    .iter()
+                    .map(|t| TopicResourceLocator::from(format!("{seq}/{t}")))
+                    .collect(),
+            )
+        };
+
+        let mut groups = SequenceTopicGroups::new(vec![
+            group("seq_c", &["a"]),
+            group("seq_b", &["b", "a"]),
+            group("seq_a", &["a"]),
+        ]);
+        assert!(!groups.truncate(4));
+        assert_eq!(groups.topics_count(), 4);
+
+        assert!(groups.truncate(2));
+        let groups: Vec<SequenceTopicGroup> = groups.into();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].sequence.name(), "seq_a");
+        assert_eq!(groups[1].sequence.name(), "seq_b");
+        assert_eq!(groups[1].topics.len(), 1);
+        assert_eq!(groups[1].topics[0].name(), "seq_b/a");
+    }
 }
@@ -59,6 +59,11 @@ impl DateTime {
         Self(chrono::Utc::now())
     }
 
+    /// Milliseconds elapsed since the UNIX epoch
+    pub fn unix_timestamp_ms(&self) -> i64 {
+        self.0.timestamp_millis()
+    }
+
     pub fn fmt_to_ms(&self) -> String {
         self.0.format("%Y%m%d%H%M%S%3f").to_string()
     }
@@ -69,3 +74,152 @@ impl std::fmt::Display for DateTime {
         write!(f, "{}", self.0)
     }
 }
+
+/// Unit of the timestamps stored in a topic, relative to the UNIX epoch.
+///
+/// Topics created before the unit was recorded use [`TimestampUnit::Milliseconds`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum TimestampUnit {
+    #[serde(rename = "s")]
+    Seconds,
+    #[default]
+    #[serde(rename = "ms")]
+    Milliseconds,
+    #[serde(rename = "us")]
+    Microseconds,
+    #[serde(rename = "ns")]
+    Nanoseconds,
+}
+
+impl TimestampUnit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Seconds => "s",
+            Self::Milliseconds => "ms",
+            Self::Microseconds => "us",
+            Self::Nanoseconds => "ns",
+        }
+    }
+
+    /// Converts a [`Timestamp`] (milliseconds) to this unit, rounding down
+    pub fn floor_from(&self, ts: Timestamp) -> i64 {
+        match self {
+            Self::Seconds => ts.0.div_euclid(1000),
+            _ => ts.0.saturating_mul(self.millis_factor()),
+        }
+    }
+
+    /// Converts a [`Timestamp`] (milliseconds) to this unit, rounding up
+    pub fn ceil_from(&self, ts: Timestamp) -> i64 {
+        match self {
+            Self::Seconds => ts.0.div_euclid(1000) + i64::from(ts.0.rem_euclid(1000) != 0),
+            _ => ts.0.saturating_mul(self.millis_factor()),
+        }
+    }
+
+    /// Converts a [`Timestamp`] (milliseconds) to this unit, returns [`None`] if
+    /// the timestamp can't be represented exactly
+    pub fn exact_from(&self, ts: Timestamp) -> Option<i64> {
+        match self {
+            Self::Seconds => (ts.0.rem_euclid(1000) == 0).then(|| ts.0.div_euclid(1000)),
+            _ => ts.0.checked_mul(self.millis_factor()),
+        }
+    }
+
+    /// Converts a value in this unit to a [`Timestamp`] (milliseconds), rounding down
+    pub fn floor_to_millis(&self, value: i64) -> Timestamp {
+        match self {
+            Self::Seconds => Timestamp(value.saturating_mul(1000)),
+            _ => Timestamp(value.div_euclid(self.millis_factor())),
+        }
+    }
+
+    /// Converts a value in this unit to a [`Timestamp`] (milliseconds), rounding up
+    pub fn ceil_to_millis(&self, value: i64) -> Timestamp {
+        match self {
+            Self::Seconds => Timestamp(value.saturating_mul(1000)),
+            _ => {
+                let factor = self.millis_factor();
+                Timestamp(value.div_euclid(factor) + i64::from(value.rem_euclid(factor) != 0))
+            }
+        }
+    }
+
+    /// Converts a duration to this unit, rounding down
+    pub fn duration_in_units(&self, duration: std::time::Duration) -> i64 {
+        let value = match self {
+            Self::Seconds => duration.as_secs() as u128,
+            Self::Milliseconds => duration.as_millis(),
+            Self::Microseconds => duration.as_micros(),
+            Self::Nanoseconds => duration.as_nanos(),
+        };
+        i64::try_from(value).unwrap_or(i64::MAX)
+    }
+
+    /// Number of units in a millisecond, not meaningful for [`TimestampUnit::Seconds`]
+    fn millis_factor(&self) -> i64 {
+        match self {
+            Self::Seconds | Self::Milliseconds => 1,
+            Self::Microseconds => 1_000,
+            Self::Nanoseconds => 1_000_000,
+        }
+    }
+}
+
+impl std::fmt::Display for TimestampUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for TimestampUnit {
+    type Err = std::io::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "s" => Ok(Self::Seconds),
+            "ms" => Ok(Self::Milliseconds),
+            "us" => Ok(Self::Microseconds),
+            "ns" => Ok(Self::Nanoseconds),
+            _ => Err(std::io::Error::other(format!(
+                "unknown timestamp unit `{}`",
+                value
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamp_unit_conversion() {
+        let ts = Timestamp::from(1_500);
+
+        assert_eq!(TimestampUnit::Milliseconds.floor_from(ts), 1_500);
+        assert_eq!(TimestampUnit::Nanoseconds.ceil_from(ts), 1_500_000_000);
+
+        assert_eq!(TimestampUnit::Seconds.floor_from(ts), 1);
+        assert_eq!(TimestampUnit::Seconds.ceil_from(ts), 2);
+        assert_eq!(TimestampUnit::Seconds.exact_from(ts), None);
+        assert_eq!(TimestampUnit::Seconds.exact_from(2_000.into()), Some(2));
+        assert_eq!(TimestampUnit::Seconds.floor_from((-1_500).into()), -2);
+
+        assert_eq!(TimestampUnit::Seconds.floor_to_millis(2), 2_000.into());
+        assert_eq!(TimestampUnit::Microseconds.floor_to_millis(1_500), 1.into());
+        assert_eq!(TimestampUnit::Microseconds.ceil_to_millis(1_500), 2.into());
+        assert_eq!(
+            TimestampUnit::Nanoseconds.ceil_to_millis(2_000_000),
+            2.into()
+        );
+        assert_eq!(TimestampUnit::Microseconds.floor_to_millis(-1), (-1).into());
+        assert_eq!(TimestampUnit::Microseconds.ceil_to_millis(-1), 0.into());
+
+        assert_eq!(
+            "us".parse::<TimestampUnit>().unwrap(),
+            TimestampUnit::Microseconds
+        );
+        assert!("minutes".parse::<TimestampUnit>().is_err());
+    }
+}
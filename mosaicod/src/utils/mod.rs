@@ -1,2 +1,3 @@
 pub mod print;
 pub mod random;
+pub mod size;
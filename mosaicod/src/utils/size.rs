@@ -0,0 +1,34 @@
+const UNITS: [&str; 6] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+/// Formats a byte count using binary units (e.g. `1.2 GiB`).
+///
+/// Sizes below 1 KiB are reported in bytes, larger sizes are rounded to one decimal.
+pub fn human_readable_bytes(bytes: usize) -> String {
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binary_units() {
+        assert_eq!(human_readable_bytes(0), "0 B");
+        assert_eq!(human_readable_bytes(1023), "1023 B");
+        assert_eq!(human_readable_bytes(1024), "1.0 KiB");
+        assert_eq!(human_readable_bytes(1536), "1.5 KiB");
+        assert_eq!(human_readable_bytes(5 * 1024 * 1024), "5.0 MiB");
+        assert_eq!(human_readable_bytes(1288490189), "1.2 GiB");
+    }
+}